@@ -0,0 +1,3 @@
+-- Reject follow-ups while the attempt worktree has uncommitted changes
+ALTER TABLE projects
+    ADD COLUMN require_clean_before_followup BOOLEAN NOT NULL DEFAULT FALSE;
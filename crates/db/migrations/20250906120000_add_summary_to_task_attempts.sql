@@ -0,0 +1,3 @@
+-- Concise summary of what the agent did, taken from its final message
+ALTER TABLE task_attempts
+    ADD COLUMN summary TEXT;
@@ -0,0 +1,3 @@
+-- Commit worktree changes made by the setup script before the coding agent runs
+ALTER TABLE projects
+    ADD COLUMN commit_setup_changes BOOLEAN NOT NULL DEFAULT FALSE;
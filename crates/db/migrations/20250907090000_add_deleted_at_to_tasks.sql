@@ -0,0 +1,2 @@
+-- Soft-delete marker for tasks; NULL means the task is live
+ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,2 @@
+-- Optional effort estimate for a task, in minutes
+ALTER TABLE tasks ADD COLUMN estimate_minutes INTEGER;
@@ -0,0 +1,3 @@
+-- Users that new tasks of the project are assigned to in turn
+ALTER TABLE projects
+    ADD COLUMN auto_assign_users TEXT;
@@ -0,0 +1,2 @@
+-- User a task is assigned to
+ALTER TABLE tasks ADD COLUMN assignee TEXT;
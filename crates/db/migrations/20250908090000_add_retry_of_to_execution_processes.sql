@@ -0,0 +1,2 @@
+-- Link a retried execution process to the failed process it re-runs
+ALTER TABLE execution_processes ADD COLUMN retry_of BLOB REFERENCES execution_processes(id);
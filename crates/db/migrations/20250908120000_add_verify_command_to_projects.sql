@@ -0,0 +1,3 @@
+-- Command run after the coding agent to verify its changes (tests/lint)
+ALTER TABLE projects
+    ADD COLUMN verify_command TEXT;
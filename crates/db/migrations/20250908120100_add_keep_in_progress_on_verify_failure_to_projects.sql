@@ -0,0 +1,3 @@
+-- Keep tasks in progress when the verify command fails
+ALTER TABLE projects
+    ADD COLUMN keep_in_progress_on_verify_failure BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+-- Result of the project's verify command for the attempt's latest run
+ALTER TABLE task_attempts
+    ADD COLUMN verify_passed BOOLEAN;
+ALTER TABLE task_attempts
+    ADD COLUMN verify_summary TEXT;
@@ -0,0 +1,3 @@
+-- Base branch used for new attempts when none is specified
+ALTER TABLE projects
+    ADD COLUMN default_base_branch TEXT;
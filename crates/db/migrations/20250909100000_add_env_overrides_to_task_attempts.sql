@@ -0,0 +1,3 @@
+-- JSON object of environment variables set for the attempt's agent and scripts
+ALTER TABLE task_attempts
+    ADD COLUMN env_overrides TEXT;
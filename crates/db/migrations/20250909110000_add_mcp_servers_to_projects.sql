@@ -0,0 +1,3 @@
+-- Extra MCP servers registered with the project's coding agents (JSON array)
+ALTER TABLE projects
+    ADD COLUMN mcp_servers TEXT;
@@ -0,0 +1,2 @@
+-- Why vibe-kanban failed a process itself, e.g. after an inactivity timeout
+ALTER TABLE execution_processes ADD COLUMN failure_reason TEXT;
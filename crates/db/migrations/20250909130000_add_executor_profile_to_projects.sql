@@ -0,0 +1,3 @@
+-- Executor profile used for the project's new attempts instead of the global default
+ALTER TABLE projects
+    ADD COLUMN executor_profile TEXT;
@@ -0,0 +1,5 @@
+-- Pinned tasks are listed before the others, in the order they were pinned
+ALTER TABLE tasks
+    ADD COLUMN is_pinned BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE tasks
+    ADD COLUMN pin_order INTEGER;
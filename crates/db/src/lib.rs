@@ -1,13 +1,89 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
 
 use sqlx::{
-    Error, Pool, Sqlite, SqlitePool,
-    sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions},
+    Error, Pool, Sqlite,
+    sqlite::{
+        SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions,
+        SqliteSynchronous,
+    },
 };
 use utils::assets::asset_dir;
 
 pub mod models;
 
+/// SQLite tuning so concurrent executions writing logs do not hit
+/// "database is locked"; each field can be overridden with the env var named
+/// on it
+#[derive(Debug, Clone)]
+pub struct PoolSettings {
+    /// `VIBE_KANBAN_SQLITE_JOURNAL_MODE`, e.g. `wal` or `delete`
+    pub journal_mode: SqliteJournalMode,
+    /// `VIBE_KANBAN_SQLITE_SYNCHRONOUS`, e.g. `normal` or `full`
+    pub synchronous: SqliteSynchronous,
+    /// `VIBE_KANBAN_SQLITE_BUSY_TIMEOUT_MS`
+    pub busy_timeout: Duration,
+    /// `VIBE_KANBAN_SQLITE_MAX_CONNECTIONS`
+    pub max_connections: u32,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_millis(5000),
+            max_connections: 16,
+        }
+    }
+}
+
+impl PoolSettings {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            journal_mode: env_setting("VIBE_KANBAN_SQLITE_JOURNAL_MODE", |v| {
+                SqliteJournalMode::from_str(v).ok()
+            })
+            .unwrap_or(defaults.journal_mode),
+            synchronous: env_setting("VIBE_KANBAN_SQLITE_SYNCHRONOUS", |v| {
+                SqliteSynchronous::from_str(v).ok()
+            })
+            .unwrap_or(defaults.synchronous),
+            busy_timeout: env_setting("VIBE_KANBAN_SQLITE_BUSY_TIMEOUT_MS", |v| {
+                v.parse().ok().map(Duration::from_millis)
+            })
+            .unwrap_or(defaults.busy_timeout),
+            max_connections: env_setting("VIBE_KANBAN_SQLITE_MAX_CONNECTIONS", |v| {
+                v.parse().ok().filter(|n| *n > 0)
+            })
+            .unwrap_or(defaults.max_connections),
+        }
+    }
+
+    pub fn connect_options(&self, db_path: &Path) -> SqliteConnectOptions {
+        SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+            .journal_mode(self.journal_mode)
+            .synchronous(self.synchronous)
+            .busy_timeout(self.busy_timeout)
+    }
+
+    pub fn pool_options(&self) -> SqlitePoolOptions {
+        SqlitePoolOptions::new().max_connections(self.max_connections)
+    }
+}
+
+/// Parse env var `name`, warning and returning `None` when it is set but invalid
+fn env_setting<T>(name: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        tracing::warn!("Ignoring invalid {}={:?}", name, value);
+    }
+    parsed
+}
+
 #[derive(Clone)]
 pub struct DBService {
     pub pool: Pool<Sqlite>,
@@ -15,12 +91,11 @@ pub struct DBService {
 
 impl DBService {
     pub async fn new() -> Result<DBService, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
-        let pool = SqlitePool::connect_with(options).await?;
+        let settings = PoolSettings::from_env();
+        let pool = settings
+            .pool_options()
+            .connect_with(settings.connect_options(&asset_dir().join("db.sqlite")))
+            .await?;
         sqlx::migrate!("./migrations").run(&pool).await?;
         Ok(DBService { pool })
     }
@@ -49,14 +124,12 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
+        let settings = PoolSettings::from_env();
+        let options = settings.connect_options(&asset_dir().join("db.sqlite"));
 
         let pool = if let Some(hook) = after_connect {
-            SqlitePoolOptions::new()
+            settings
+                .pool_options()
                 .after_connect(move |conn, _meta| {
                     let hook = hook.clone();
                     Box::pin(async move {
@@ -67,7 +140,7 @@ impl DBService {
                 .connect_with(options)
                 .await?
         } else {
-            SqlitePool::connect_with(options).await?
+            settings.pool_options().connect_with(options).await?
         };
 
         sqlx::migrate!("./migrations").run(&pool).await?;
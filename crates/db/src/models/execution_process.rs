@@ -12,6 +12,8 @@ use super::{task::Task, task_attempt::TaskAttempt};
 #[sqlx(type_name = "execution_process_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum ExecutionProcessStatus {
+    /// Waiting for a free slot under `max_concurrent_executions`
+    Queued,
     Running,
     Completed,
     Failed,
@@ -24,6 +26,7 @@ pub enum ExecutionProcessStatus {
 pub enum ExecutionProcessRunReason {
     SetupScript,
     CleanupScript,
+    VerifyScript,
     CodingAgent,
     DevServer,
 }
@@ -43,6 +46,11 @@ pub struct ExecutionProcess {
     /// history view (due to restore/trimming). Hidden from logs/timeline;
     /// still listed in the Processes tab.
     pub dropped: bool,
+    /// The failed process this one re-runs, when started as a retry
+    pub retry_of: Option<Uuid>,
+    /// Why vibe-kanban failed the process itself rather than it exiting on its
+    /// own, e.g. after an inactivity timeout
+    pub failure_reason: Option<String>,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -92,6 +100,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -136,6 +146,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -164,6 +176,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -177,6 +191,45 @@ impl ExecutionProcess {
         .await
     }
 
+    /// The attempt's latest process, if it failed. Dev servers are left out, and
+    /// an earlier failure no longer counts once a later run has finished.
+    pub async fn find_retryable_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT 
+                id as "id!: Uuid", 
+                task_attempt_id as "task_attempt_id!: Uuid", 
+                run_reason as "run_reason!: ExecutionProcessRunReason",
+                executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                after_head_commit,
+                status as "status!: ExecutionProcessStatus",
+                exit_code,
+                dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
+                started_at as "started_at!: DateTime<Utc>",
+                completed_at as "completed_at?: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>", 
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes 
+               WHERE id = (
+                   SELECT id FROM execution_processes
+                   WHERE task_attempt_id = $1
+                     AND dropped = FALSE
+                     AND run_reason != 'devserver'
+                   ORDER BY created_at DESC
+                   LIMIT 1
+               )
+                 AND status = 'failed'"#,
+            task_attempt_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
     /// Find running execution processes
     pub async fn find_running(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
@@ -190,6 +243,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -202,6 +257,33 @@ impl ExecutionProcess {
         .await
     }
 
+    /// Find queued execution processes, oldest first
+    pub async fn find_queued(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            ExecutionProcess,
+            r#"SELECT 
+                id as "id!: Uuid", 
+                task_attempt_id as "task_attempt_id!: Uuid", 
+                run_reason as "run_reason!: ExecutionProcessRunReason",
+                executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
+                after_head_commit,
+                status as "status!: ExecutionProcessStatus",
+                exit_code,
+                dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
+                started_at as "started_at!: DateTime<Utc>",
+                completed_at as "completed_at?: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>", 
+                updated_at as "updated_at!: DateTime<Utc>"
+               FROM execution_processes 
+               WHERE status = 'queued' 
+               ORDER BY created_at ASC"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
     /// Find running dev servers for a specific project
     pub async fn find_running_dev_servers_by_project(
         pool: &SqlitePool,
@@ -218,6 +300,8 @@ impl ExecutionProcess {
                 ep.status as "status!: ExecutionProcessStatus",
                 ep.exit_code,
                 ep.dropped as "dropped!: bool",
+                ep.retry_of as "retry_of: Uuid",
+                ep.failure_reason,
                 ep.started_at as "started_at!: DateTime<Utc>",
                 ep.completed_at as "completed_at?: DateTime<Utc>",
                 ep.created_at as "created_at!: DateTime<Utc>", 
@@ -281,6 +365,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -323,6 +409,8 @@ impl ExecutionProcess {
                 status as "status!: ExecutionProcessStatus",
                 exit_code,
                 dropped as "dropped!: bool",
+                retry_of as "retry_of: Uuid",
+                failure_reason,
                 started_at as "started_at!: DateTime<Utc>",
                 completed_at as "completed_at?: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>", 
@@ -357,7 +445,10 @@ impl ExecutionProcess {
         status: ExecutionProcessStatus,
         exit_code: Option<i64>,
     ) -> Result<(), sqlx::Error> {
-        let completed_at = if matches!(status, ExecutionProcessStatus::Running) {
+        let completed_at = if matches!(
+            status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        ) {
             None
         } else {
             Some(Utc::now())
@@ -378,6 +469,50 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Hold a just-created process until a slot frees up
+    pub async fn mark_queued(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET status = 'queued' WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Move a queued process to running; false if it is no longer queued, e.g.
+    /// because it was stopped while waiting
+    pub async fn mark_started(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let started_at = Utc::now();
+        let result = sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = 'running', started_at = $1
+               WHERE id = $2 AND status = 'queued'"#,
+            started_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Fail a running process for a reason of our own, such as an inactivity
+    /// timeout, before its exit is observed
+    pub async fn mark_failed(pool: &SqlitePool, id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+        let completed_at = Utc::now();
+        sqlx::query!(
+            r#"UPDATE execution_processes
+               SET status = 'failed', failure_reason = $1, completed_at = $2
+               WHERE id = $3"#,
+            reason,
+            completed_at,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Update the "after" commit oid for the process
     pub async fn update_after_head_commit(
         pool: &SqlitePool,
@@ -396,6 +531,22 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Record that `id` was started as a retry of the failed `retry_of` process
+    pub async fn set_retry_of(
+        pool: &SqlitePool,
+        id: Uuid,
+        retry_of: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE execution_processes SET retry_of = $1 WHERE id = $2",
+            retry_of,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn delete_by_task_attempt_id(
         pool: &SqlitePool,
         task_attempt_id: Uuid,
@@ -116,4 +116,98 @@ impl ExecutionProcessLogs {
 
         Ok(())
     }
+
+    /// Delete the persisted logs for an execution process
+    pub async fn delete_by_execution_id(
+        pool: &SqlitePool,
+        execution_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "DELETE FROM execution_process_logs WHERE execution_id = $1",
+            execution_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete persisted logs of attempts whose processes all finished before
+    /// `cutoff`. The attempt and process rows are kept. Attempts with a running
+    /// or queued process, an open pull request, or a task still in review keep
+    /// their logs. Returns the number of execution processes whose logs were
+    /// removed.
+    pub async fn delete_for_attempts_completed_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM execution_process_logs
+               WHERE execution_id IN (
+                   SELECT id FROM execution_processes
+                   WHERE task_attempt_id IN (
+                       SELECT ep.task_attempt_id
+                       FROM execution_processes ep
+                       JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+                       JOIN tasks t ON t.id = ta.task_id
+                       WHERE t.status != 'inreview'
+                         AND NOT EXISTS (
+                             SELECT 1 FROM merges m
+                             WHERE m.task_attempt_id = ep.task_attempt_id
+                               AND m.merge_type = 'pr'
+                               AND m.pr_status = 'open'
+                         )
+                       GROUP BY ep.task_attempt_id
+                       HAVING SUM(ep.status IN ('running', 'queued')) = 0
+                          AND datetime(MAX(COALESCE(ep.completed_at, ep.started_at))) < datetime($1)
+                   )
+               )"#,
+            cutoff
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete the oldest persisted logs until the total stored size is at most
+    /// `max_total_bytes`. Logs that [`Self::delete_for_attempts_completed_before`]
+    /// would keep are kept here too and do not count towards the limit.
+    /// Returns the number of bytes freed.
+    pub async fn prune_to_size(
+        pool: &SqlitePool,
+        max_total_bytes: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT epl.execution_id as "execution_id!: Uuid",
+                      epl.byte_size
+               FROM execution_process_logs epl
+               JOIN execution_processes ep ON ep.id = epl.execution_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE t.status != 'inreview'
+                 AND NOT EXISTS (
+                     SELECT 1 FROM merges m
+                     WHERE m.task_attempt_id = ep.task_attempt_id
+                       AND m.merge_type = 'pr'
+                       AND m.pr_status = 'open'
+                 )
+                 AND ep.task_attempt_id NOT IN (
+                     SELECT task_attempt_id FROM execution_processes
+                     WHERE status IN ('running', 'queued')
+                 )
+               ORDER BY datetime(epl.inserted_at) DESC"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut total = 0i64;
+        let mut freed = 0i64;
+        for row in rows {
+            total += row.byte_size;
+            if total > max_total_bytes {
+                Self::delete_by_execution_id(pool, row.execution_id).await?;
+                freed += row.byte_size;
+            }
+        }
+        Ok(freed)
+    }
 }
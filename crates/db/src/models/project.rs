@@ -1,8 +1,9 @@
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
+use executors::{mcp_config::McpServerDefinition, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqlitePool, types::Json};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -30,6 +31,29 @@ pub struct Project {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    /// Reject follow-ups while the attempt worktree has uncommitted changes
+    pub require_clean_before_followup: bool,
+    /// Commit worktree changes made by the setup script before the coding agent runs
+    pub commit_setup_changes: bool,
+    /// Comma-separated users that new tasks are assigned to in round-robin order;
+    /// unset or empty leaves new tasks unassigned
+    pub auto_assign_users: Option<String>,
+    /// Command run after the coding agent to verify its changes (e.g. tests or lint);
+    /// its result is recorded on the attempt
+    pub verify_command: Option<String>,
+    /// Keep the task in progress, needing a fix, instead of moving it to review
+    /// when the verify command fails
+    pub keep_in_progress_on_verify_failure: bool,
+    /// Base branch for new attempts that do not specify one; falls back to the
+    /// repository's default branch
+    pub default_base_branch: Option<String>,
+    /// Extra MCP servers written into the coding agent's MCP config, next to vibe-kanban
+    #[ts(type = "Array<McpServerDefinition> | null")]
+    pub mcp_servers: Option<Json<Vec<McpServerDefinition>>>,
+    /// Executor profile for new attempts that do not specify one, overriding the
+    /// global default
+    #[ts(type = "ExecutorProfileId | null")]
+    pub executor_profile: Option<Json<ExecutorProfileId>>,
 
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
@@ -46,6 +70,14 @@ pub struct CreateProject {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub require_clean_before_followup: Option<bool>,
+    pub commit_setup_changes: Option<bool>,
+    pub auto_assign_users: Option<String>,
+    pub verify_command: Option<String>,
+    pub keep_in_progress_on_verify_failure: Option<bool>,
+    pub default_base_branch: Option<String>,
+    pub mcp_servers: Option<Vec<McpServerDefinition>>,
+    pub executor_profile: Option<ExecutorProfileId>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -56,6 +88,14 @@ pub struct UpdateProject {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub require_clean_before_followup: Option<bool>,
+    pub commit_setup_changes: Option<bool>,
+    pub auto_assign_users: Option<String>,
+    pub verify_command: Option<String>,
+    pub keep_in_progress_on_verify_failure: Option<bool>,
+    pub default_base_branch: Option<String>,
+    pub mcp_servers: Option<Vec<McpServerDefinition>>,
+    pub executor_profile: Option<ExecutorProfileId>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -67,6 +107,16 @@ pub struct ProjectWithBranch {
     pub dev_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub copy_files: Option<String>,
+    pub require_clean_before_followup: bool,
+    pub commit_setup_changes: bool,
+    pub auto_assign_users: Option<String>,
+    pub verify_command: Option<String>,
+    pub keep_in_progress_on_verify_failure: bool,
+    pub default_base_branch: Option<String>,
+    #[ts(type = "Array<McpServerDefinition> | null")]
+    pub mcp_servers: Option<Json<Vec<McpServerDefinition>>>,
+    #[ts(type = "ExecutorProfileId | null")]
+    pub executor_profile: Option<Json<ExecutorProfileId>>,
     pub current_branch: Option<String>,
 
     #[ts(type = "Date")]
@@ -85,6 +135,14 @@ impl ProjectWithBranch {
             dev_script: project.dev_script,
             cleanup_script: project.cleanup_script,
             copy_files: project.copy_files,
+            require_clean_before_followup: project.require_clean_before_followup,
+            commit_setup_changes: project.commit_setup_changes,
+            auto_assign_users: project.auto_assign_users,
+            verify_command: project.verify_command,
+            keep_in_progress_on_verify_failure: project.keep_in_progress_on_verify_failure,
+            default_base_branch: project.default_base_branch,
+            mcp_servers: project.mcp_servers,
+            executor_profile: project.executor_profile,
             current_branch,
             created_at: project.created_at,
             updated_at: project.updated_at,
@@ -107,10 +165,46 @@ pub enum SearchMatchType {
 }
 
 impl Project {
+    /// Users from `auto_assign_users`, in order, without blanks
+    pub fn auto_assign_user_list(&self) -> Vec<String> {
+        self.auto_assign_users
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|user| !user.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Base branch for a new attempt: the requested one, else the project's
+    /// `default_base_branch`. `None` means the repository default should be used.
+    pub fn base_branch_for(&self, requested: Option<String>) -> Option<String> {
+        requested
+            .filter(|branch| !branch.trim().is_empty())
+            .or_else(|| self.default_base_branch.clone())
+    }
+
+    /// Executor profile for a new attempt: the requested one, else the project's
+    /// `executor_profile`, else `global_default` from the user config
+    pub fn executor_profile_for(
+        &self,
+        requested: Option<ExecutorProfileId>,
+        global_default: ExecutorProfileId,
+    ) -> ExecutorProfileId {
+        requested
+            .or_else(|| {
+                self.executor_profile
+                    .as_ref()
+                    .map(|profile| profile.0.clone())
+            })
+            .unwrap_or(global_default)
+    }
+
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects ORDER BY created_at DESC"#
         )
         .fetch_all(pool)
         .await
@@ -121,7 +215,7 @@ impl Project {
         sqlx::query_as!(
             Project,
             r#"
-            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, 
+            SELECT p.id as "id!: Uuid", p.name, p.git_repo_path, p.setup_script, p.dev_script, p.cleanup_script, p.copy_files, p.require_clean_before_followup as "require_clean_before_followup!: bool", p.commit_setup_changes as "commit_setup_changes!: bool", p.auto_assign_users, p.verify_command, p.keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", p.default_base_branch, p.mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", p.executor_profile as "executor_profile: Json<ExecutorProfileId>",
                    p.created_at as "created_at!: DateTime<Utc>", p.updated_at as "updated_at!: DateTime<Utc>"
             FROM projects p
             WHERE p.id IN (
@@ -141,7 +235,7 @@ impl Project {
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE id = $1"#,
             id
         )
         .fetch_optional(pool)
@@ -154,7 +248,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1"#,
             git_repo_path
         )
         .fetch_optional(pool)
@@ -168,7 +262,7 @@ impl Project {
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
+            r#"SELECT id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>" FROM projects WHERE git_repo_path = $1 AND id != $2"#,
             git_repo_path,
             exclude_id
         )
@@ -181,16 +275,30 @@ impl Project {
         data: &CreateProject,
         project_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
+        let require_clean_before_followup = data.require_clean_before_followup.unwrap_or(false);
+        let commit_setup_changes = data.commit_setup_changes.unwrap_or(false);
+        let keep_in_progress_on_verify_failure =
+            data.keep_in_progress_on_verify_failure.unwrap_or(false);
+        let mcp_servers = data.mcp_servers.clone().map(Json);
+        let executor_profile = data.executor_profile.clone().map(Json);
         sqlx::query_as!(
             Project,
-            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO projects (id, name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup, commit_setup_changes, auto_assign_users, verify_command, keep_in_progress_on_verify_failure, default_base_branch, mcp_servers, executor_profile) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             project_id,
             data.name,
             data.git_repo_path,
             data.setup_script,
             data.dev_script,
             data.cleanup_script,
-            data.copy_files
+            data.copy_files,
+            require_clean_before_followup,
+            commit_setup_changes,
+            data.auto_assign_users,
+            data.verify_command,
+            keep_in_progress_on_verify_failure,
+            data.default_base_branch,
+            mcp_servers,
+            executor_profile
         )
         .fetch_one(pool)
         .await
@@ -206,17 +314,33 @@ impl Project {
         dev_script: Option<String>,
         cleanup_script: Option<String>,
         copy_files: Option<String>,
+        require_clean_before_followup: bool,
+        commit_setup_changes: bool,
+        auto_assign_users: Option<String>,
+        verify_command: Option<String>,
+        keep_in_progress_on_verify_failure: bool,
+        default_base_branch: Option<String>,
+        mcp_servers: Option<Json<Vec<McpServerDefinition>>>,
+        executor_profile: Option<Json<ExecutorProfileId>>,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Project,
-            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"UPDATE projects SET name = $2, git_repo_path = $3, setup_script = $4, dev_script = $5, cleanup_script = $6, copy_files = $7, require_clean_before_followup = $8, commit_setup_changes = $9, auto_assign_users = $10, verify_command = $11, keep_in_progress_on_verify_failure = $12, default_base_branch = $13, mcp_servers = $14, executor_profile = $15 WHERE id = $1 RETURNING id as "id!: Uuid", name, git_repo_path, setup_script, dev_script, cleanup_script, copy_files, require_clean_before_followup as "require_clean_before_followup!: bool", commit_setup_changes as "commit_setup_changes!: bool", auto_assign_users, verify_command, keep_in_progress_on_verify_failure as "keep_in_progress_on_verify_failure!: bool", default_base_branch, mcp_servers as "mcp_servers: Json<Vec<McpServerDefinition>>", executor_profile as "executor_profile: Json<ExecutorProfileId>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             id,
             name,
             git_repo_path,
             setup_script,
             dev_script,
             cleanup_script,
-            copy_files
+            copy_files,
+            require_clean_before_followup,
+            commit_setup_changes,
+            auto_assign_users,
+            verify_command,
+            keep_in_progress_on_verify_failure,
+            default_base_branch,
+            mcp_servers,
+            executor_profile
         )
         .fetch_one(pool)
         .await
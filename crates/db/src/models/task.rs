@@ -1,11 +1,26 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool, Type};
+use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
 use super::project::Project;
 
+#[derive(Debug, Error)]
+pub enum TaskError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task not found")]
+    TaskNotFound,
+    #[error("Dependencies must be between tasks of the same project")]
+    CrossProjectDependency,
+    #[error("Dependency would create a cycle")]
+    DependencyCycle,
+}
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
 #[sqlx(type_name = "task_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -25,8 +40,15 @@ pub struct Task {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_task_attempt: Option<Uuid>, // Foreign key to parent TaskAttempt
+    pub estimate_minutes: Option<i64>,
+    pub assignee: Option<String>,
+    /// Pinned tasks are listed before the others
+    pub is_pinned: bool,
+    /// Position among the project's pinned tasks, lowest first; unset when unpinned
+    pub pin_order: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>, // Set while the task sits in the trash
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -37,12 +59,20 @@ pub struct TaskWithAttemptStatus {
     pub description: Option<String>,
     pub status: TaskStatus,
     pub parent_task_attempt: Option<Uuid>,
+    pub estimate_minutes: Option<i64>,
+    pub assignee: Option<String>,
+    pub is_pinned: bool,
+    pub pin_order: Option<i64>,
+    /// Wall-clock minutes spent in the task's setup, agent and cleanup processes
+    pub actual_minutes: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub has_in_progress_attempt: bool,
     pub has_merged_attempt: bool,
     pub last_attempt_failed: bool,
     pub executor: String,
+    /// Tasks that must be done before this one starts
+    pub dependency_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -52,6 +82,9 @@ pub struct CreateTask {
     pub description: Option<String>,
     pub parent_task_attempt: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
+    /// Left unset, the project's auto-assignment policy picks the assignee
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -61,6 +94,25 @@ pub struct UpdateTask {
     pub status: Option<TaskStatus>,
     pub parent_task_attempt: Option<Uuid>,
     pub image_ids: Option<Vec<Uuid>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_minutes: Option<i64>,
+    /// An empty string unassigns the task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+}
+
+/// The user after `last` in `users`, wrapping around; the first user when `last`
+/// is unset or no longer in the list.
+pub fn next_round_robin_assignee(users: &[String], last: Option<&str>) -> Option<String> {
+    let next = last
+        .and_then(|last| users.iter().position(|user| user == last))
+        .map_or(0, |i| (i + 1) % users.len());
+    users.get(next).cloned()
+}
+
+/// Title used to spot duplicate tasks: trimmed and case-folded
+pub fn normalize_title(title: &str) -> String {
+    title.trim().to_lowercase()
 }
 
 impl Task {
@@ -90,6 +142,10 @@ impl Task {
   t.parent_task_attempt           AS "parent_task_attempt: Uuid",
   t.created_at                    AS "created_at!: DateTime<Utc>",
   t.updated_at                    AS "updated_at!: DateTime<Utc>",
+  t.estimate_minutes,
+  t.assignee,
+  t.is_pinned                     AS "is_pinned!: bool",
+  t.pin_order,
 
   CASE WHEN EXISTS (
     SELECT 1
@@ -97,7 +153,7 @@ impl Task {
       JOIN execution_processes ep
         ON ep.task_attempt_id = ta.id
      WHERE ta.task_id       = t.id
-       AND ep.status        = 'running'
+       AND ep.status        IN ('queued','running')
        AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
      LIMIT 1
   ) THEN 1 ELSE 0 END            AS "has_in_progress_attempt!: i64",
@@ -119,16 +175,36 @@ impl Task {
       WHERE ta.task_id = t.id
      ORDER BY ta.created_at DESC
       LIMIT 1
-    )                               AS "executor!: String"
+    )                               AS "executor!: String",
+
+  ( SELECT CAST(COALESCE(SUM(
+             (julianday(COALESCE(ep.completed_at, datetime('now', 'subsec')))
+              - julianday(ep.started_at)) * 1440
+           ), 0) AS INTEGER)
+      FROM task_attempts ta
+      JOIN execution_processes ep
+        ON ep.task_attempt_id = ta.id
+     WHERE ta.task_id = t.id
+       AND ep.run_reason IN ('setupscript','cleanupscript','codingagent')
+  )                               AS "actual_minutes!: i64"
 
 FROM tasks t
-WHERE t.project_id = $1
-ORDER BY t.created_at DESC"#,
+WHERE t.project_id = $1 AND t.deleted_at IS NULL
+ORDER BY t.is_pinned DESC, t.pin_order ASC, t.created_at DESC"#,
             project_id
         )
         .fetch_all(pool)
         .await?;
 
+        // Dependencies on trashed tasks are kept, but not shown
+        let live: HashSet<Uuid> = records.iter().map(|rec| rec.id).collect();
+        let mut dependency_ids: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (task_id, depends_on) in Self::dependency_edges(pool, project_id).await? {
+            if live.contains(&depends_on) {
+                dependency_ids.entry(task_id).or_default().push(depends_on);
+            }
+        }
+
         let tasks = records
             .into_iter()
             .map(|rec| TaskWithAttemptStatus {
@@ -138,34 +214,99 @@ ORDER BY t.created_at DESC"#,
                 description: rec.description,
                 status: rec.status,
                 parent_task_attempt: rec.parent_task_attempt,
+                estimate_minutes: rec.estimate_minutes,
+                assignee: rec.assignee,
+                is_pinned: rec.is_pinned,
+                pin_order: rec.pin_order,
+                actual_minutes: rec.actual_minutes,
                 created_at: rec.created_at,
                 updated_at: rec.updated_at,
                 has_in_progress_attempt: rec.has_in_progress_attempt != 0,
                 has_merged_attempt: false, // TODO use merges table
                 last_attempt_failed: rec.last_attempt_failed != 0,
                 executor: rec.executor,
+                dependency_ids: dependency_ids.remove(&rec.id).unwrap_or_default(),
             })
             .collect();
 
         Ok(tasks)
     }
 
+    /// Number of tasks in each status, keyed by the stored status name
+    pub async fn count_by_status(pool: &SqlitePool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT status, COUNT(*) as "count!: i64"
+               FROM tasks
+               WHERE deleted_at IS NULL
+               GROUP BY status"#
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| (r.status, r.count)).collect())
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
                FROM tasks 
-               WHERE id = $1"#,
+               WHERE id = $1 AND deleted_at IS NULL"#,
             id
         )
         .fetch_optional(pool)
         .await
     }
 
+    /// Tasks of a project outside the trash, oldest first
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
+               FROM tasks 
+               WHERE project_id = $1 AND deleted_at IS NULL
+               ORDER BY created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The newest task of the project created within `window` whose title matches
+    /// `title` once both are trimmed and case-folded
+    pub async fn find_recent_by_title(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        title: &str,
+        window: std::time::Duration,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let since = format!("-{} seconds", window.as_secs());
+        let recent = sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
+               FROM tasks 
+               WHERE project_id = $1 AND deleted_at IS NULL
+                 AND datetime(created_at) >= datetime('now', $2)
+               ORDER BY created_at DESC"#,
+            project_id,
+            since
+        )
+        .fetch_all(pool)
+        .await?;
+        let title = normalize_title(title);
+        Ok(recent
+            .into_iter()
+            .find(|task| normalize_title(&task.title) == title))
+    }
+
+    /// Unlike the other lookups this also returns soft-deleted tasks, so that
+    /// the change hook can tell when a task moved to or from the trash.
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
                FROM tasks 
                WHERE rowid = $1"#,
             rowid
@@ -181,9 +322,9 @@ ORDER BY t.created_at DESC"#,
     ) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
-            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
                FROM tasks 
-               WHERE id = $1 AND project_id = $2"#,
+               WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL"#,
             id,
             project_id
         )
@@ -191,27 +332,91 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    /// Next assignee from the project's round-robin list, if it has one
+    async fn auto_assignee(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let Some(project) = Project::find_by_id(pool, project_id).await? else {
+            return Ok(None);
+        };
+        let users = project.auto_assign_user_list();
+        if users.is_empty() {
+            return Ok(None);
+        }
+
+        let last = sqlx::query_scalar!(
+            r#"SELECT assignee FROM tasks
+               WHERE project_id = $1 AND assignee IS NOT NULL
+               ORDER BY created_at DESC, rowid DESC
+               LIMIT 1"#,
+            project_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        Ok(next_round_robin_assignee(&users, last.as_deref()))
+    }
+
+    /// Create a task, assigning it through the project's auto-assignment policy
+    /// when `data` names no assignee
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateTask,
         task_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
+        let assignee = match &data.assignee {
+            Some(assignee) => Some(assignee.clone()),
+            None => Self::auto_assignee(pool, data.project_id).await?,
+        };
         sqlx::query_as!(
             Task,
-            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt) 
-               VALUES ($1, $2, $3, $4, $5, $6) 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, parent_task_attempt, assignee) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7) 
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order"#,
             task_id,
             data.project_id,
             data.title,
             data.description,
             TaskStatus::Todo as TaskStatus,
-            data.parent_task_attempt
+            data.parent_task_attempt,
+            assignee
         )
         .fetch_one(pool)
         .await
     }
 
+    /// Insert a copy of `task` under `project_id` with a fresh id, keeping its
+    /// status, estimate, assignee and timestamps. Links to attempts are dropped.
+    pub async fn import<'e, E>(
+        executor: E,
+        task: &Task,
+        project_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query_as!(
+            Task,
+            r#"INSERT INTO tasks (id, project_id, title, description, status, estimate_minutes, assignee, created_at, updated_at) 
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order"#,
+            task_id,
+            project_id,
+            task.title,
+            task.description,
+            task.status,
+            task.estimate_minutes,
+            task.assignee,
+            task.created_at,
+            task.updated_at
+        )
+        .fetch_one(executor)
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn update(
         pool: &SqlitePool,
         id: Uuid,
@@ -220,19 +425,23 @@ ORDER BY t.created_at DESC"#,
         description: Option<String>,
         status: TaskStatus,
         parent_task_attempt: Option<Uuid>,
+        estimate_minutes: Option<i64>,
+        assignee: Option<String>,
     ) -> Result<Self, sqlx::Error> {
         sqlx::query_as!(
             Task,
             r#"UPDATE tasks 
-               SET title = $3, description = $4, status = $5, parent_task_attempt = $6 
+               SET title = $3, description = $4, status = $5, parent_task_attempt = $6, estimate_minutes = $7, assignee = $8 
                WHERE id = $1 AND project_id = $2 
-               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+               RETURNING id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order"#,
             id,
             project_id,
             title,
             description,
             status,
-            parent_task_attempt
+            parent_task_attempt,
+            estimate_minutes,
+            assignee
         )
         .fetch_one(pool)
         .await
@@ -253,10 +462,93 @@ ORDER BY t.created_at DESC"#,
         Ok(())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query!("DELETE FROM tasks WHERE id = $1", id)
+    /// Pin a task above the unpinned ones, after the project's other pinned
+    /// tasks, or unpin it. Pinning an already pinned task keeps its place.
+    pub async fn set_pinned(pool: &SqlitePool, id: Uuid, pinned: bool) -> Result<(), sqlx::Error> {
+        if pinned {
+            sqlx::query!(
+                r#"UPDATE tasks
+               SET is_pinned = TRUE,
+                   pin_order = (SELECT COALESCE(MAX(pin_order), 0) + 1
+                                  FROM tasks
+                                 WHERE is_pinned
+                                   AND project_id = (SELECT project_id FROM tasks WHERE id = $1)),
+                   updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1 AND NOT is_pinned"#,
+                id
+            )
+            .execute(pool)
+            .await?;
+        } else {
+            sqlx::query!(
+                r#"UPDATE tasks
+               SET is_pinned = FALSE, pin_order = NULL, updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1"#,
+                id
+            )
             .execute(pool)
             .await?;
+        }
+        Ok(())
+    }
+
+    /// Move a task to the trash. The row is kept until it is restored or
+    /// purged by [`Task::purge_deleted_before`].
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE tasks
+               SET deleted_at = datetime('now', 'subsec'), updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Take a task back out of the trash
+    pub async fn restore(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"UPDATE tasks
+               SET deleted_at = NULL, updated_at = CURRENT_TIMESTAMP
+               WHERE id = $1 AND deleted_at IS NOT NULL"#,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Soft-deleted tasks of a project, most recently deleted first
+    pub async fn find_deleted_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT id as "id!: Uuid", project_id as "project_id!: Uuid", title, description, status as "status!: TaskStatus", parent_task_attempt as "parent_task_attempt: Uuid", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>", deleted_at as "deleted_at: DateTime<Utc>", estimate_minutes, assignee, is_pinned as "is_pinned!: bool", pin_order
+               FROM tasks
+               WHERE project_id = $1 AND deleted_at IS NOT NULL
+               ORDER BY deleted_at DESC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Permanently remove tasks that were moved to the trash before `cutoff`.
+    /// Their attempts, processes and logs go with them through the cascades.
+    pub async fn purge_deleted_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM tasks
+               WHERE deleted_at IS NOT NULL AND datetime(deleted_at) < datetime($1)"#,
+            cutoff
+        )
+        .execute(pool)
+        .await?;
         Ok(result.rows_affected())
     }
 
@@ -266,7 +558,7 @@ ORDER BY t.created_at DESC"#,
         project_id: Uuid,
     ) -> Result<bool, sqlx::Error> {
         let result = sqlx::query!(
-            "SELECT id as \"id!: Uuid\" FROM tasks WHERE id = $1 AND project_id = $2",
+            r#"SELECT id as "id!: Uuid" FROM tasks WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL"#,
             id,
             project_id
         )
@@ -282,9 +574,9 @@ ORDER BY t.created_at DESC"#,
         // Find both children and parent for this attempt
         sqlx::query_as!(
             Task,
-            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>"
+            r#"SELECT DISTINCT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.deleted_at as "deleted_at: DateTime<Utc>", t.estimate_minutes, t.assignee, t.is_pinned as "is_pinned!: bool", t.pin_order
                FROM tasks t
-               WHERE (
+               WHERE t.deleted_at IS NULL AND ((
                    -- Find children: tasks that have this attempt as parent
                    t.parent_task_attempt = $1
                ) OR (
@@ -296,11 +588,138 @@ ORDER BY t.created_at DESC"#,
                    )
                )
                -- Exclude the current task itself to prevent circular references
-               AND t.id != (SELECT task_id FROM task_attempts WHERE id = $1)
+               AND t.id != (SELECT task_id FROM task_attempts WHERE id = $1))
                ORDER BY t.created_at DESC"#,
             attempt_id,
         )
         .fetch_all(pool)
         .await
     }
+
+    /// Tasks that must be done before this one starts, oldest dependency first
+    pub async fn dependencies(&self, pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            Task,
+            r#"SELECT t.id as "id!: Uuid", t.project_id as "project_id!: Uuid", t.title, t.description, t.status as "status!: TaskStatus", t.parent_task_attempt as "parent_task_attempt: Uuid", t.created_at as "created_at!: DateTime<Utc>", t.updated_at as "updated_at!: DateTime<Utc>", t.deleted_at as "deleted_at: DateTime<Utc>", t.estimate_minutes, t.assignee, t.is_pinned as "is_pinned!: bool", t.pin_order
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.depends_on_task_id
+               WHERE td.task_id = $1 AND t.deleted_at IS NULL
+               ORDER BY td.created_at ASC"#,
+            self.id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record that `task_id` cannot start before `depends_on_task_id` is done.
+    /// Both tasks must be in the same project, and the new edge must not close
+    /// a cycle in the project's dependency graph.
+    pub async fn add_dependency(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<(), TaskError> {
+        let task = Self::find_by_id(pool, task_id)
+            .await?
+            .ok_or(TaskError::TaskNotFound)?;
+        let depends_on = Self::find_by_id(pool, depends_on_task_id)
+            .await?
+            .ok_or(TaskError::TaskNotFound)?;
+        if task.project_id != depends_on.project_id {
+            return Err(TaskError::CrossProjectDependency);
+        }
+
+        let edges = Self::dependency_edges(pool, task.project_id).await?;
+        if creates_dependency_cycle(&edges, task_id, depends_on_task_id) {
+            return Err(TaskError::DependencyCycle);
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id)
+               VALUES ($1, $2)
+               ON CONFLICT DO NOTHING"#,
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        Self::touch(pool, task_id).await?;
+        Ok(())
+    }
+
+    /// Drop a dependency; returns the number of rows removed
+    pub async fn remove_dependency(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND depends_on_task_id = $2",
+            task_id,
+            depends_on_task_id
+        )
+        .execute(pool)
+        .await?;
+        if result.rows_affected() > 0 {
+            Self::touch(pool, task_id).await?;
+        }
+        Ok(result.rows_affected())
+    }
+
+    /// (task, depends on) pairs for every task of a project, trashed ones included
+    async fn dependency_edges(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"SELECT td.task_id as "task_id!: Uuid", td.depends_on_task_id as "depends_on_task_id!: Uuid"
+               FROM task_dependencies td
+               JOIN tasks t ON t.id = td.task_id
+               WHERE t.project_id = $1
+               ORDER BY td.created_at ASC"#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.task_id, row.depends_on_task_id))
+            .collect())
+    }
+
+    /// Bump `updated_at` so task streams pick up the changed dependencies
+    async fn touch(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE tasks SET updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Whether making `task_id` depend on `depends_on_task_id` closes a cycle, i.e.
+/// `task_id` is already reachable from `depends_on_task_id` through `edges`
+fn creates_dependency_cycle(
+    edges: &[(Uuid, Uuid)],
+    task_id: Uuid,
+    depends_on_task_id: Uuid,
+) -> bool {
+    let mut seen = HashSet::new();
+    let mut pending = vec![depends_on_task_id];
+    while let Some(current) = pending.pop() {
+        if current == task_id {
+            return true;
+        }
+        if seen.insert(current) {
+            pending.extend(
+                edges
+                    .iter()
+                    .filter(|(from, _)| *from == current)
+                    .map(|(_, to)| *to),
+            );
+        }
+    }
+    false
 }
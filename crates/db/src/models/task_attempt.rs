@@ -1,7 +1,9 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
-use executors::executors::BaseCodingAgent;
-use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool, Type};
+use executors::{command::ExecutionEnv, executors::BaseCodingAgent};
+use serde::{Deserialize, Serialize, Serializer};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
 use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
@@ -45,10 +47,35 @@ pub struct TaskAttempt {
     // "GEMINI", etc.)
     pub worktree_deleted: bool, // Flag indicating if worktree has been cleaned up
     pub setup_completed_at: Option<DateTime<Utc>>, // When setup script was last completed
+    pub summary: Option<String>, // Final agent message, when attempt summaries are enabled
+    pub verify_passed: Option<bool>, // Whether the project's verify command last passed
+    pub verify_summary: Option<String>, // Test counts or exit status of the last verify run
+    /// Environment variables set for the attempt's coding agent and scripts.
+    /// Only their names are serialized, since the values may be secrets.
+    #[serde(
+        rename(serialize = "env_override_names"),
+        serialize_with = "serialize_env_names",
+        skip_deserializing
+    )]
+    #[ts(rename = "env_override_names", type = "Array<string> | null")]
+    pub env_overrides: Option<Json<HashMap<String, String>>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Serialize env overrides as their sorted names, leaving out the values
+fn serialize_env_names<S: Serializer>(
+    env_overrides: &Option<Json<HashMap<String, String>>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let names = env_overrides.as_ref().map(|vars| {
+        let mut names: Vec<&String> = vars.keys().collect();
+        names.sort();
+        names
+    });
+    names.serialize(serializer)
+}
+
 /// GitHub PR creation parameters
 pub struct CreatePrParams<'a> {
     pub attempt_id: Uuid,
@@ -83,6 +110,7 @@ pub struct TaskAttemptContext {
 pub struct CreateTaskAttempt {
     pub executor: BaseCodingAgent,
     pub base_branch: String,
+    pub env_overrides: Option<HashMap<String, String>>,
 }
 
 impl TaskAttempt {
@@ -90,6 +118,16 @@ impl TaskAttempt {
         Task::find_by_id(pool, self.task_id).await
     }
 
+    /// The attempt's env overrides, to set on every process spawned for it
+    pub fn execution_env(&self) -> ExecutionEnv {
+        ExecutionEnv::new(
+            self.env_overrides
+                .as_ref()
+                .map(|vars| vars.0.clone())
+                .unwrap_or_default(),
+        )
+    }
+
     /// Fetch all task attempts, optionally filtered by task_id. Newest first.
     pub async fn fetch_all(
         pool: &SqlitePool,
@@ -106,6 +144,10 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              summary,
+                              verify_passed AS "verify_passed: bool",
+                              verify_summary,
+                              env_overrides AS "env_overrides: Json<HashMap<String, String>>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -126,6 +168,10 @@ impl TaskAttempt {
                               executor AS "executor!",
                               worktree_deleted AS "worktree_deleted!: bool",
                               setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                              summary,
+                              verify_passed AS "verify_passed: bool",
+                              verify_summary,
+                              env_overrides AS "env_overrides: Json<HashMap<String, String>>",
                               created_at AS "created_at!: DateTime<Utc>",
                               updated_at AS "updated_at!: DateTime<Utc>"
                        FROM task_attempts
@@ -157,6 +203,10 @@ impl TaskAttempt {
                        ta.executor AS "executor!",
                        ta.worktree_deleted  AS "worktree_deleted!: bool",
                        ta.setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       ta.summary,
+                       ta.verify_passed AS "verify_passed: bool",
+                       ta.verify_summary,
+                       ta.env_overrides AS "env_overrides: Json<HashMap<String, String>>",
                        ta.created_at        AS "created_at!: DateTime<Utc>",
                        ta.updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts ta
@@ -222,6 +272,42 @@ impl TaskAttempt {
         Ok(())
     }
 
+    pub async fn update_summary(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        summary: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "UPDATE task_attempts SET summary = $1, updated_at = $2 WHERE id = $3",
+            summary,
+            now,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_verification(
+        pool: &SqlitePool,
+        attempt_id: Uuid,
+        passed: bool,
+        summary: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        sqlx::query!(
+            "UPDATE task_attempts SET verify_passed = $1, verify_summary = $2, updated_at = $3 WHERE id = $4",
+            passed,
+            summary,
+            now,
+            attempt_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Helper function to mark a worktree as deleted in the database
     pub async fn mark_worktree_deleted(
         pool: &SqlitePool,
@@ -247,6 +333,10 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       summary,
+                       verify_passed AS "verify_passed: bool",
+                       verify_summary,
+                       env_overrides AS "env_overrides: Json<HashMap<String, String>>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -268,6 +358,10 @@ impl TaskAttempt {
                        executor AS "executor!",
                        worktree_deleted  AS "worktree_deleted!: bool",
                        setup_completed_at AS "setup_completed_at: DateTime<Utc>",
+                       summary,
+                       verify_passed AS "verify_passed: bool",
+                       verify_summary,
+                       env_overrides AS "env_overrides: Json<HashMap<String, String>>",
                        created_at        AS "created_at!: DateTime<Utc>",
                        updated_at        AS "updated_at!: DateTime<Utc>"
                FROM    task_attempts
@@ -378,19 +472,70 @@ impl TaskAttempt {
             .collect())
     }
 
+    /// Worktrees of attempts without running processes, least recently active first,
+    /// with the time of their last activity
+    pub async fn find_prunable_worktrees(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, String, String, DateTime<Utc>)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"
+            SELECT ta.id as "attempt_id!: Uuid", ta.container_ref, p.git_repo_path as "git_repo_path!",
+                   MAX(
+                       CASE
+                           WHEN ep.completed_at IS NOT NULL THEN ep.completed_at
+                           ELSE ta.updated_at
+                       END
+                   ) as "last_activity!: DateTime<Utc>"
+            FROM task_attempts ta
+            LEFT JOIN execution_processes ep ON ta.id = ep.task_attempt_id AND ep.completed_at IS NOT NULL
+            JOIN tasks t ON ta.task_id = t.id
+            JOIN projects p ON t.project_id = p.id
+            WHERE ta.worktree_deleted = FALSE
+                -- Exclude attempts with any running processes (in progress)
+                AND ta.id NOT IN (
+                    SELECT DISTINCT ep2.task_attempt_id
+                    FROM execution_processes ep2
+                    WHERE ep2.completed_at IS NULL
+                )
+            GROUP BY ta.id, ta.container_ref, p.git_repo_path, ta.updated_at
+            ORDER BY datetime(last_activity) ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|r| {
+                r.container_ref
+                    .map(|path| (r.attempt_id, path, r.git_repo_path, r.last_activity))
+            })
+            .collect())
+    }
+
+    /// Number of attempts whose worktree is still on disk
+    pub async fn count_live_worktrees(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM task_attempts WHERE worktree_deleted = FALSE AND container_ref IS NOT NULL"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
     pub async fn create(
         pool: &SqlitePool,
         data: &CreateTaskAttempt,
         task_id: Uuid,
     ) -> Result<Self, TaskAttemptError> {
         let attempt_id = Uuid::new_v4();
+        let env_overrides = data.env_overrides.as_ref().map(Json);
         // let prefixed_id = format!("vibe-kanban-{}", attempt_id);
         // Insert the record into the database
         Ok(sqlx::query_as!(
             TaskAttempt,
-            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, base_branch, executor, worktree_deleted, setup_completed_at)
-               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, base_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+            r#"INSERT INTO task_attempts (id, task_id, container_ref, branch, base_branch, executor, worktree_deleted, setup_completed_at, env_overrides)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING id as "id!: Uuid", task_id as "task_id!: Uuid", container_ref, branch, base_branch, executor as "executor!",  worktree_deleted as "worktree_deleted!: bool", setup_completed_at as "setup_completed_at: DateTime<Utc>", summary, verify_passed as "verify_passed: bool", verify_summary, env_overrides as "env_overrides: Json<HashMap<String, String>>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             attempt_id,
             task_id,
             Option::<String>::None, // Container isn't known yet
@@ -398,7 +543,8 @@ impl TaskAttempt {
             data.base_branch,
             data.executor,
             false, // worktree_deleted is false during creation
-            Option::<DateTime<Utc>>::None // setup_completed_at is None during creation
+            Option::<DateTime<Utc>>::None, // setup_completed_at is None during creation
+            env_overrides
         )
         .fetch_one(pool)
         .await?)
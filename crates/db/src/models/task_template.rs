@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -42,10 +42,13 @@ impl TaskTemplate {
         .await
     }
 
-    pub async fn find_by_project_id(
-        pool: &SqlitePool,
+    pub async fn find_by_project_id<'e, E>(
+        executor: E,
         project_id: Option<Uuid>,
-    ) -> Result<Vec<Self>, sqlx::Error> {
+    ) -> Result<Vec<Self>, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         if let Some(pid) = project_id {
             // Return only project-specific templates
             sqlx::query_as::<_, TaskTemplate>(
@@ -55,7 +58,7 @@ impl TaskTemplate {
                    ORDER BY template_name ASC"#,
             )
             .bind(pid)
-            .fetch_all(pool)
+            .fetch_all(executor)
             .await
         } else {
             // Return only global templates
@@ -66,7 +69,7 @@ impl TaskTemplate {
                    WHERE project_id IS NULL
                    ORDER BY template_name ASC"#
             )
-            .fetch_all(pool)
+            .fetch_all(executor)
             .await
         }
     }
@@ -83,7 +86,10 @@ impl TaskTemplate {
         .await
     }
 
-    pub async fn create(pool: &SqlitePool, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error> {
+    pub async fn create<'e, E>(executor: E, data: &CreateTaskTemplate) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let id = Uuid::new_v4();
         sqlx::query_as!(
             TaskTemplate,
@@ -96,7 +102,7 @@ impl TaskTemplate {
             data.description,
             data.template_name
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await
     }
 
@@ -0,0 +1,117 @@
+//! Fixtures shared by the db integration tests. Each test binary only uses a
+//! subset of them.
+#![allow(dead_code)]
+
+use db::models::{
+    project::CreateProject,
+    task::{CreateTask, Task},
+    task_attempt::{CreateTaskAttempt, TaskAttempt},
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    executors::BaseCodingAgent,
+};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+/// Open a migrated in-memory database
+pub async fn setup_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Insert a bare project row and return its id
+pub async fn create_project(pool: &SqlitePool) -> Uuid {
+    let project_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, $2, $3)")
+        .bind(project_id)
+        .bind("test project")
+        .bind(format!("/tmp/test-project-{project_id}"))
+        .execute(pool)
+        .await
+        .expect("insert project");
+    project_id
+}
+
+/// Project creation data with every optional setting left unset
+pub fn project_data(id: Uuid) -> CreateProject {
+    CreateProject {
+        name: "test project".to_string(),
+        git_repo_path: format!("/tmp/test-project-{id}"),
+        use_existing_repo: true,
+        setup_script: None,
+        dev_script: None,
+        cleanup_script: None,
+        copy_files: None,
+        require_clean_before_followup: None,
+        commit_setup_changes: None,
+        auto_assign_users: None,
+        verify_command: None,
+        keep_in_progress_on_verify_failure: None,
+        default_base_branch: None,
+        mcp_servers: None,
+        executor_profile: None,
+    }
+}
+
+pub async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+    let data = CreateTask {
+        project_id,
+        title: title.to_string(),
+        description: None,
+        parent_task_attempt: None,
+        image_ids: None,
+        assignee: None,
+    };
+    Task::create(pool, &data, Uuid::new_v4())
+        .await
+        .expect("create task")
+}
+
+pub async fn create_attempt_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    base_branch: &str,
+) -> TaskAttempt {
+    TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: base_branch.to_string(),
+            env_overrides: None,
+        },
+        task_id,
+    )
+    .await
+    .expect("create attempt")
+}
+
+/// Create a project with a single task and return an attempt on `main`
+pub async fn create_attempt(pool: &SqlitePool) -> TaskAttempt {
+    let project_id = create_project(pool).await;
+    let task = create_task(pool, project_id, "task").await;
+    create_attempt_for_task(pool, task.id, "main").await
+}
+
+pub fn setup_script_action(script: &str) -> ExecutorAction {
+    ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: script.to_string(),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::SetupScript,
+            dry_run: false,
+        }),
+        None,
+    )
+}
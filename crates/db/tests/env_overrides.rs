@@ -0,0 +1,46 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{create_project, create_task, setup_pool};
+use db::models::task_attempt::{CreateTaskAttempt, TaskAttempt};
+use executors::executors::BaseCodingAgent;
+
+#[tokio::test]
+async fn serialized_attempt_lists_env_names_without_values() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let task = create_task(&pool, project_id, "task").await;
+    let attempt = TaskAttempt::create(
+        &pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            env_overrides: Some(HashMap::from([
+                ("OPENAI_API_KEY".to_string(), "sk-secret".to_string()),
+                ("FEATURE_FLAG".to_string(), "on".to_string()),
+            ])),
+        },
+        task.id,
+    )
+    .await
+    .unwrap();
+    let attempt = TaskAttempt::find_by_id(&pool, attempt.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let json = serde_json::to_value(&attempt).unwrap();
+    assert_eq!(
+        json["env_override_names"],
+        serde_json::json!(["FEATURE_FLAG", "OPENAI_API_KEY"])
+    );
+    assert!(json.get("env_overrides").is_none());
+    assert!(!json.to_string().contains("sk-secret"));
+
+    // The values are still there for the processes spawned for the attempt
+    assert_eq!(
+        attempt.env_overrides.unwrap().0["OPENAI_API_KEY"],
+        "sk-secret"
+    );
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{create_attempt, setup_pool, setup_script_action};
+use db::models::{
+    execution_process::{
+        CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    task::Task,
+    task_attempt::TaskAttempt,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+async fn create_process(pool: &SqlitePool, attempt: &TaskAttempt) -> ExecutionProcess {
+    let data = CreateExecutionProcess {
+        task_attempt_id: attempt.id,
+        executor_action: setup_script_action("cargo build"),
+        run_reason: ExecutionProcessRunReason::SetupScript,
+    };
+    ExecutionProcess::create(pool, &data, Uuid::new_v4())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn queued_process_counts_as_an_attempt_in_progress() {
+    let pool = setup_pool().await;
+    let attempt = create_attempt(&pool).await;
+    let task = attempt.parent_task(&pool).await.unwrap().unwrap();
+
+    let process = create_process(&pool, &attempt).await;
+    ExecutionProcess::mark_queued(&pool, process.id)
+        .await
+        .unwrap();
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, task.project_id)
+        .await
+        .unwrap();
+    assert!(tasks[0].has_in_progress_attempt);
+
+    ExecutionProcess::update_completion(&pool, process.id, ExecutionProcessStatus::Killed, None)
+        .await
+        .unwrap();
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, task.project_id)
+        .await
+        .unwrap();
+    assert!(!tasks[0].has_in_progress_attempt);
+}
+
+#[tokio::test]
+async fn queue_is_first_in_first_out_and_skips_stopped_processes() {
+    let pool = setup_pool().await;
+    let attempt = create_attempt(&pool).await;
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let process = create_process(&pool, &attempt).await;
+        ExecutionProcess::mark_queued(&pool, process.id)
+            .await
+            .unwrap();
+        ids.push(process.id);
+    }
+    let queued = ExecutionProcess::find_queued(&pool).await.unwrap();
+    assert_eq!(queued.iter().map(|p| p.id).collect::<Vec<_>>(), ids);
+
+    // Stopping a queued process takes it out of the queue for good
+    ExecutionProcess::update_completion(&pool, ids[0], ExecutionProcessStatus::Killed, None)
+        .await
+        .unwrap();
+    assert!(!ExecutionProcess::mark_started(&pool, ids[0]).await.unwrap());
+    let queued = ExecutionProcess::find_queued(&pool).await.unwrap();
+    assert_eq!(queued.iter().map(|p| p.id).collect::<Vec<_>>(), ids[1..]);
+}
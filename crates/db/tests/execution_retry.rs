@@ -0,0 +1,81 @@
+mod common;
+
+use common::{create_attempt, setup_pool, setup_script_action};
+use db::models::execution_process::{
+    CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+async fn finished_process(
+    pool: &SqlitePool,
+    task_attempt_id: Uuid,
+    run_reason: ExecutionProcessRunReason,
+    status: ExecutionProcessStatus,
+) -> ExecutionProcess {
+    let data = CreateExecutionProcess {
+        task_attempt_id,
+        executor_action: setup_script_action("npm install"),
+        run_reason,
+    };
+    let process = ExecutionProcess::create(pool, &data, Uuid::new_v4())
+        .await
+        .unwrap();
+    ExecutionProcess::update_completion(pool, process.id, status, Some(1))
+        .await
+        .unwrap();
+    process
+}
+
+async fn retryable(pool: &SqlitePool, task_attempt_id: Uuid) -> Option<Uuid> {
+    ExecutionProcess::find_retryable_by_task_attempt_id(pool, task_attempt_id)
+        .await
+        .unwrap()
+        .map(|process| process.id)
+}
+
+#[tokio::test]
+async fn only_a_failed_latest_process_is_retryable() {
+    let pool = setup_pool().await;
+    let attempt = create_attempt(&pool).await;
+    assert_eq!(retryable(&pool, attempt.id).await, None);
+
+    let failed = finished_process(
+        &pool,
+        attempt.id,
+        ExecutionProcessRunReason::SetupScript,
+        ExecutionProcessStatus::Failed,
+    )
+    .await;
+    assert_eq!(retryable(&pool, attempt.id).await, Some(failed.id));
+
+    // A later successful run supersedes the failure
+    finished_process(
+        &pool,
+        attempt.id,
+        ExecutionProcessRunReason::SetupScript,
+        ExecutionProcessStatus::Completed,
+    )
+    .await;
+    assert_eq!(retryable(&pool, attempt.id).await, None);
+
+    let failed_again = finished_process(
+        &pool,
+        attempt.id,
+        ExecutionProcessRunReason::CodingAgent,
+        ExecutionProcessStatus::Failed,
+    )
+    .await;
+    // Dev servers are started on their own and don't hide the failure
+    finished_process(
+        &pool,
+        attempt.id,
+        ExecutionProcessRunReason::DevServer,
+        ExecutionProcessStatus::Killed,
+    )
+    .await;
+    assert_eq!(retryable(&pool, attempt.id).await, Some(failed_again.id));
+
+    let other_attempt = create_attempt(&pool).await;
+    assert_eq!(retryable(&pool, other_attempt.id).await, None);
+}
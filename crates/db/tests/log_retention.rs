@@ -0,0 +1,182 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{
+    create_attempt_for_task, create_project, create_task, setup_pool, setup_script_action,
+};
+use db::models::{
+    execution_process::{
+        CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    execution_process_logs::{CreateExecutionProcessLogs, ExecutionProcessLogs},
+    merge::Merge,
+    task::{Task, TaskStatus},
+    task_attempt::TaskAttempt,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+const RETENTION_DAYS: i64 = 30;
+
+/// Create an attempt with one process that finished `days_ago` days ago and
+/// has persisted logs
+async fn seed_attempt(pool: &SqlitePool, days_ago: i64) -> (Task, TaskAttempt, ExecutionProcess) {
+    let project_id = create_project(pool).await;
+    let task = create_task(pool, project_id, "retention").await;
+    let attempt = create_attempt_for_task(pool, task.id, "main").await;
+
+    let process = add_process(pool, &attempt).await;
+    ExecutionProcess::update_completion(
+        pool,
+        process.id,
+        ExecutionProcessStatus::Completed,
+        Some(0),
+    )
+    .await
+    .unwrap();
+    let finished_at = Utc::now() - Duration::days(days_ago);
+    sqlx::query("UPDATE execution_processes SET started_at = $1, completed_at = $1 WHERE id = $2")
+        .bind(finished_at)
+        .bind(process.id)
+        .execute(pool)
+        .await
+        .expect("backdate process");
+    (task, attempt, process)
+}
+
+async fn add_process(pool: &SqlitePool, attempt: &TaskAttempt) -> ExecutionProcess {
+    let data = CreateExecutionProcess {
+        task_attempt_id: attempt.id,
+        executor_action: setup_script_action("cargo build"),
+        run_reason: ExecutionProcessRunReason::SetupScript,
+    };
+    let process = ExecutionProcess::create(pool, &data, Uuid::new_v4())
+        .await
+        .unwrap();
+    ExecutionProcessLogs::upsert(
+        pool,
+        &CreateExecutionProcessLogs {
+            execution_id: process.id,
+            logs: "{\"Stdout\":\"building\"}\n".to_string(),
+            byte_size: 23,
+        },
+    )
+    .await
+    .unwrap();
+    process
+}
+
+async fn has_logs(pool: &SqlitePool, process: &ExecutionProcess) -> bool {
+    ExecutionProcessLogs::find_by_execution_id(pool, process.id)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+async fn purge(pool: &SqlitePool) -> u64 {
+    let cutoff = Utc::now() - Duration::days(RETENTION_DAYS);
+    ExecutionProcessLogs::delete_for_attempts_completed_before(pool, cutoff)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn only_logs_of_attempts_finished_before_the_window_are_purged() {
+    let pool = setup_pool().await;
+    let (_, old_attempt, old) = seed_attempt(&pool, RETENTION_DAYS + 5).await;
+    let (_, _, recent) = seed_attempt(&pool, 1).await;
+
+    assert_eq!(purge(&pool).await, 1);
+    assert!(!has_logs(&pool, &old).await);
+    assert!(has_logs(&pool, &recent).await);
+
+    // The attempt and process rows survive the purge
+    assert!(
+        TaskAttempt::find_by_id(&pool, old_attempt.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        ExecutionProcess::find_by_id(&pool, old.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn age_is_measured_from_the_last_process_of_the_attempt() {
+    let pool = setup_pool().await;
+    let (_, attempt, old) = seed_attempt(&pool, RETENTION_DAYS + 5).await;
+    // A follow-up that is still running keeps the whole attempt
+    let follow_up = add_process(&pool, &attempt).await;
+
+    assert_eq!(purge(&pool).await, 0);
+    assert!(has_logs(&pool, &old).await);
+
+    // Once the follow-up finishes recently, the attempt is no longer old
+    ExecutionProcess::update_completion(
+        &pool,
+        follow_up.id,
+        ExecutionProcessStatus::Completed,
+        Some(0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(purge(&pool).await, 0);
+    assert!(has_logs(&pool, &old).await);
+    assert!(has_logs(&pool, &follow_up).await);
+}
+
+#[tokio::test]
+async fn attempts_under_review_keep_their_logs() {
+    let pool = setup_pool().await;
+    let (_, pr_attempt, with_open_pr) = seed_attempt(&pool, RETENTION_DAYS + 5).await;
+    Merge::create_pr(&pool, pr_attempt.id, "main", 7, "https://example.com/pr/7")
+        .await
+        .unwrap();
+    let (in_review_task, _, in_review) = seed_attempt(&pool, RETENTION_DAYS + 5).await;
+    Task::update_status(&pool, in_review_task.id, TaskStatus::InReview)
+        .await
+        .unwrap();
+
+    assert_eq!(purge(&pool).await, 0);
+    assert!(has_logs(&pool, &with_open_pr).await);
+    assert!(has_logs(&pool, &in_review).await);
+
+    // Closing the review makes the logs eligible again
+    Task::update_status(&pool, in_review_task.id, TaskStatus::Done)
+        .await
+        .unwrap();
+    assert_eq!(purge(&pool).await, 1);
+    assert!(!has_logs(&pool, &in_review).await);
+    assert!(has_logs(&pool, &with_open_pr).await);
+}
+
+#[tokio::test]
+async fn size_pruning_keeps_the_same_attempts_as_age_pruning() {
+    let pool = setup_pool().await;
+    let (_, pr_attempt, with_open_pr) = seed_attempt(&pool, 3).await;
+    Merge::create_pr(&pool, pr_attempt.id, "main", 7, "https://example.com/pr/7")
+        .await
+        .unwrap();
+    let (in_review_task, _, in_review) = seed_attempt(&pool, 3).await;
+    Task::update_status(&pool, in_review_task.id, TaskStatus::InReview)
+        .await
+        .unwrap();
+    let (_, queued_attempt, waiting) = seed_attempt(&pool, 3).await;
+    let queued = add_process(&pool, &queued_attempt).await;
+    ExecutionProcess::mark_queued(&pool, queued.id)
+        .await
+        .unwrap();
+    let (_, _, idle) = seed_attempt(&pool, 3).await;
+
+    let freed = ExecutionProcessLogs::prune_to_size(&pool, 0).await.unwrap();
+
+    assert_eq!(freed, 23);
+    assert!(!has_logs(&pool, &idle).await);
+    for kept in [&with_open_pr, &in_review, &waiting, &queued] {
+        assert!(has_logs(&pool, kept).await);
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{create_attempt_for_task, create_task, project_data, setup_pool};
+use db::models::{
+    project::{CreateProject, Project},
+    task_attempt::TaskAttempt,
+};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+async fn create_project(pool: &SqlitePool, default_base_branch: Option<&str>) -> Project {
+    let id = Uuid::new_v4();
+    let data = CreateProject {
+        default_base_branch: default_base_branch.map(str::to_string),
+        ..project_data(id)
+    };
+    Project::create(pool, &data, id)
+        .await
+        .expect("create project")
+}
+
+async fn create_attempt(
+    pool: &SqlitePool,
+    project: &Project,
+    requested: Option<&str>,
+) -> TaskAttempt {
+    let task = create_task(pool, project.id, "attempt").await;
+    let base_branch = project
+        .base_branch_for(requested.map(str::to_string))
+        .unwrap_or_else(|| "main".to_string());
+    create_attempt_for_task(pool, task.id, &base_branch).await
+}
+
+#[tokio::test]
+async fn attempts_use_project_default_base_branch_when_unspecified() {
+    let pool = setup_pool().await;
+    let project = create_project(&pool, Some("develop")).await;
+    assert_eq!(project.default_base_branch.as_deref(), Some("develop"));
+
+    let attempt = create_attempt(&pool, &project, None).await;
+    assert_eq!(attempt.base_branch, "develop");
+
+    // Blank requests are treated as unspecified
+    let attempt = create_attempt(&pool, &project, Some("  ")).await;
+    assert_eq!(attempt.base_branch, "develop");
+}
+
+#[tokio::test]
+async fn explicit_base_branch_overrides_project_default() {
+    let pool = setup_pool().await;
+    let project = create_project(&pool, Some("develop")).await;
+    let attempt = create_attempt(&pool, &project, Some("release/1.0")).await;
+    assert_eq!(attempt.base_branch, "release/1.0");
+}
+
+#[tokio::test]
+async fn attempts_fall_back_to_repo_default_without_project_default() {
+    let pool = setup_pool().await;
+    let project = create_project(&pool, None).await;
+    assert_eq!(project.base_branch_for(None), None);
+
+    let attempt = create_attempt(&pool, &project, None).await;
+    assert_eq!(attempt.base_branch, "main");
+    let attempt = create_attempt(&pool, &project, Some("trunk")).await;
+    assert_eq!(attempt.base_branch, "trunk");
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use db::PoolSettings;
+use sqlx::sqlite::SqliteJournalMode;
+
+#[tokio::test]
+async fn pool_uses_wal_and_busy_timeout() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let settings = PoolSettings::default();
+    let pool = settings
+        .pool_options()
+        .connect_with(settings.connect_options(&dir.path().join("db.sqlite")))
+        .await
+        .expect("open database");
+
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(journal_mode, "wal");
+
+    let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(busy_timeout, 5000);
+
+    // NORMAL is 1
+    let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(synchronous, 1);
+}
+
+#[tokio::test]
+async fn journal_mode_can_be_overridden() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let settings = PoolSettings {
+        journal_mode: SqliteJournalMode::Delete,
+        busy_timeout: Duration::from_millis(250),
+        ..PoolSettings::default()
+    };
+    let pool = settings
+        .pool_options()
+        .connect_with(settings.connect_options(&dir.path().join("db.sqlite")))
+        .await
+        .expect("open database");
+
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(journal_mode, "delete");
+}
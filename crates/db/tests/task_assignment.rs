@@ -0,0 +1,97 @@
+mod common;
+
+use common::setup_pool;
+use db::models::task::{CreateTask, Task, next_round_robin_assignee};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+async fn create_project(pool: &SqlitePool, auto_assign_users: Option<&str>) -> Uuid {
+    let project_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO projects (id, name, git_repo_path, auto_assign_users) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(project_id)
+    .bind("assignment test")
+    .bind(format!("/tmp/assignment-test-{project_id}"))
+    .bind(auto_assign_users)
+    .execute(pool)
+    .await
+    .expect("insert project");
+    project_id
+}
+
+async fn create_task(pool: &SqlitePool, project_id: Uuid, assignee: Option<&str>) -> Task {
+    let data = CreateTask {
+        project_id,
+        title: "task".to_string(),
+        description: None,
+        parent_task_attempt: None,
+        image_ids: None,
+        assignee: assignee.map(str::to_string),
+    };
+    Task::create(pool, &data, Uuid::new_v4())
+        .await
+        .expect("create task")
+}
+
+#[test]
+fn round_robin_wraps_and_restarts_on_unknown_user() {
+    let users = vec!["alice".to_string(), "bob".to_string()];
+    assert_eq!(
+        next_round_robin_assignee(&users, None).as_deref(),
+        Some("alice")
+    );
+    assert_eq!(
+        next_round_robin_assignee(&users, Some("alice")).as_deref(),
+        Some("bob")
+    );
+    assert_eq!(
+        next_round_robin_assignee(&users, Some("bob")).as_deref(),
+        Some("alice")
+    );
+    assert_eq!(
+        next_round_robin_assignee(&users, Some("carol")).as_deref(),
+        Some("alice")
+    );
+    assert_eq!(next_round_robin_assignee(&[], Some("alice")), None);
+}
+
+#[tokio::test]
+async fn tasks_rotate_through_project_users() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool, Some("alice, bob,")).await;
+
+    let mut assignees = Vec::new();
+    for _ in 0..3 {
+        assignees.push(create_task(&pool, project_id, None).await.assignee);
+    }
+    assert_eq!(
+        assignees,
+        vec![
+            Some("alice".to_string()),
+            Some("bob".to_string()),
+            Some("alice".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn explicit_assignee_is_kept() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool, Some("alice,bob")).await;
+
+    let task = create_task(&pool, project_id, Some("carol")).await;
+    assert_eq!(task.assignee.as_deref(), Some("carol"));
+    // An assignee outside the rotation restarts it
+    let next = create_task(&pool, project_id, None).await;
+    assert_eq!(next.assignee.as_deref(), Some("alice"));
+}
+
+#[tokio::test]
+async fn projects_without_users_leave_tasks_unassigned() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool, None).await;
+
+    let task = create_task(&pool, project_id, None).await;
+    assert!(task.assignee.is_none());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{create_project, create_task, setup_pool};
+use db::models::task::{Task, normalize_title};
+
+const WINDOW: Duration = Duration::from_secs(5 * 60);
+
+#[test]
+fn titles_are_trimmed_and_case_folded() {
+    assert_eq!(
+        normalize_title("  Fix the Login BUG \n"),
+        "fix the login bug"
+    );
+}
+
+#[tokio::test]
+async fn recent_task_with_the_same_title_is_found() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let task = create_task(&pool, project_id, "Fix the login bug").await;
+
+    let found = Task::find_recent_by_title(&pool, project_id, "  fix THE login bug ", WINDOW)
+        .await
+        .unwrap();
+    assert_eq!(found.map(|t| t.id), Some(task.id));
+
+    // Other projects are not searched
+    let other_project = create_project(&pool).await;
+    let found = Task::find_recent_by_title(&pool, other_project, "Fix the login bug", WINDOW)
+        .await
+        .unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+async fn different_or_old_titles_are_not_duplicates() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let task = create_task(&pool, project_id, "Fix the login bug").await;
+
+    let found = Task::find_recent_by_title(&pool, project_id, "Fix the logout bug", WINDOW)
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    // Outside the window the same title counts as a new task
+    sqlx::query("UPDATE tasks SET created_at = datetime('now', '-1 hour') WHERE id = $1")
+        .bind(task.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let found = Task::find_recent_by_title(&pool, project_id, "Fix the login bug", WINDOW)
+        .await
+        .unwrap();
+    assert!(found.is_none());
+}
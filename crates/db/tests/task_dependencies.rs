@@ -0,0 +1,82 @@
+mod common;
+
+use common::{create_project, create_task, setup_pool};
+use db::models::task::{Task, TaskError};
+
+#[tokio::test]
+async fn add_dependency_is_listed_with_the_task() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let schema = create_task(&pool, project_id, "schema").await;
+    let api = create_task(&pool, project_id, "api").await;
+
+    Task::add_dependency(&pool, api.id, schema.id)
+        .await
+        .unwrap();
+    // Adding the same dependency twice is a no-op
+    Task::add_dependency(&pool, api.id, schema.id)
+        .await
+        .unwrap();
+
+    let dependencies = api.dependencies(&pool).await.unwrap();
+    assert_eq!(
+        dependencies.iter().map(|t| t.id).collect::<Vec<_>>(),
+        vec![schema.id]
+    );
+    assert!(schema.dependencies(&pool).await.unwrap().is_empty());
+
+    let tasks = Task::find_by_project_id_with_attempt_status(&pool, project_id)
+        .await
+        .unwrap();
+    let listed = |id: Uuid| tasks.iter().find(|t| t.id == id).unwrap();
+    assert_eq!(listed(api.id).dependency_ids, vec![schema.id]);
+    assert!(listed(schema.id).dependency_ids.is_empty());
+
+    assert_eq!(
+        Task::remove_dependency(&pool, api.id, schema.id)
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(api.dependencies(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn add_dependency_rejects_cycles() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let a = create_task(&pool, project_id, "a").await;
+    let b = create_task(&pool, project_id, "b").await;
+    let c = create_task(&pool, project_id, "c").await;
+
+    // a -> b -> c
+    Task::add_dependency(&pool, a.id, b.id).await.unwrap();
+    Task::add_dependency(&pool, b.id, c.id).await.unwrap();
+
+    for (task, depends_on) in [(c.id, a.id), (b.id, a.id), (a.id, a.id)] {
+        assert!(matches!(
+            Task::add_dependency(&pool, task, depends_on).await,
+            Err(TaskError::DependencyCycle)
+        ));
+    }
+    assert!(c.dependencies(&pool).await.unwrap().is_empty());
+
+    // A shortcut that keeps the graph acyclic is fine
+    Task::add_dependency(&pool, a.id, c.id).await.unwrap();
+}
+
+#[tokio::test]
+async fn add_dependency_rejects_tasks_of_other_projects() {
+    let pool = setup_pool().await;
+    let task = create_task(&pool, create_project(&pool).await, "here").await;
+    let other = create_task(&pool, create_project(&pool).await, "there").await;
+
+    assert!(matches!(
+        Task::add_dependency(&pool, task.id, other.id).await,
+        Err(TaskError::CrossProjectDependency)
+    ));
+    assert!(matches!(
+        Task::add_dependency(&pool, task.id, Uuid::new_v4()).await,
+        Err(TaskError::TaskNotFound)
+    ));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::{create_project, create_task, setup_pool};
+use db::models::task::Task;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Create a project with tasks "a", "b", "c", created in that order
+async fn create_tasks(pool: &SqlitePool) -> (Uuid, Vec<Task>) {
+    let project_id = create_project(pool).await;
+
+    let mut tasks = Vec::new();
+    for (minute, title) in ["a", "b", "c"].into_iter().enumerate() {
+        let task = create_task(pool, project_id, title).await;
+        // Spread creation times so the newest-first order is deterministic
+        sqlx::query("UPDATE tasks SET created_at = $1 WHERE id = $2")
+            .bind(format!("2025-01-01 00:0{minute}:00"))
+            .bind(task.id)
+            .execute(pool)
+            .await
+            .expect("set created_at");
+        tasks.push(task);
+    }
+    (project_id, tasks)
+}
+
+async fn listed_titles(pool: &SqlitePool, project_id: Uuid) -> Vec<String> {
+    Task::find_by_project_id_with_attempt_status(pool, project_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|task| task.title)
+        .collect()
+}
+
+#[tokio::test]
+async fn pinned_tasks_are_listed_first_in_pin_order() {
+    let pool = setup_pool().await;
+    let (project_id, tasks) = create_tasks(&pool).await;
+    assert_eq!(listed_titles(&pool, project_id).await, ["c", "b", "a"]);
+
+    Task::set_pinned(&pool, tasks[0].id, true).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["a", "c", "b"]);
+
+    Task::set_pinned(&pool, tasks[1].id, true).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["a", "b", "c"]);
+
+    // Pinning an already pinned task keeps its place
+    Task::set_pinned(&pool, tasks[0].id, true).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["a", "b", "c"]);
+
+    let first = Task::find_by_id(&pool, tasks[0].id).await.unwrap().unwrap();
+    let second = Task::find_by_id(&pool, tasks[1].id).await.unwrap().unwrap();
+    assert!(first.is_pinned && second.is_pinned);
+    assert!(first.pin_order < second.pin_order);
+}
+
+#[tokio::test]
+async fn unpinning_restores_creation_order() {
+    let pool = setup_pool().await;
+    let (project_id, tasks) = create_tasks(&pool).await;
+    Task::set_pinned(&pool, tasks[0].id, true).await.unwrap();
+    Task::set_pinned(&pool, tasks[1].id, true).await.unwrap();
+
+    Task::set_pinned(&pool, tasks[0].id, false).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["b", "c", "a"]);
+    let unpinned = Task::find_by_id(&pool, tasks[0].id).await.unwrap().unwrap();
+    assert!(!unpinned.is_pinned);
+    assert_eq!(unpinned.pin_order, None);
+
+    // Pinning again puts it after the tasks that stayed pinned
+    Task::set_pinned(&pool, tasks[0].id, true).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["b", "a", "c"]);
+
+    Task::set_pinned(&pool, tasks[0].id, false).await.unwrap();
+    Task::set_pinned(&pool, tasks[1].id, false).await.unwrap();
+    assert_eq!(listed_titles(&pool, project_id).await, ["c", "b", "a"]);
+}
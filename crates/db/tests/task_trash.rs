@@ -0,0 +1,79 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{create_project, create_task, setup_pool};
+use db::models::task::Task;
+
+#[tokio::test]
+async fn delete_moves_task_to_trash() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let kept = create_task(&pool, project_id, "kept").await;
+    let deleted = create_task(&pool, project_id, "deleted").await;
+
+    assert_eq!(Task::delete(&pool, deleted.id).await.unwrap(), 1);
+    // Deleting again is a no-op
+    assert_eq!(Task::delete(&pool, deleted.id).await.unwrap(), 0);
+
+    assert!(Task::find_by_id(&pool, deleted.id).await.unwrap().is_none());
+    assert!(!Task::exists(&pool, deleted.id, project_id).await.unwrap());
+    let live = Task::find_by_project_id_with_attempt_status(&pool, project_id)
+        .await
+        .unwrap();
+    assert_eq!(live.iter().map(|t| t.id).collect::<Vec<_>>(), vec![kept.id]);
+
+    let trash = Task::find_deleted_by_project_id(&pool, project_id)
+        .await
+        .unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].id, deleted.id);
+    assert!(trash[0].deleted_at.is_some());
+}
+
+#[tokio::test]
+async fn restore_brings_task_back() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let task = create_task(&pool, project_id, "restore me").await;
+
+    Task::delete(&pool, task.id).await.unwrap();
+    assert_eq!(Task::restore(&pool, task.id).await.unwrap(), 1);
+    // Restoring a live task does nothing
+    assert_eq!(Task::restore(&pool, task.id).await.unwrap(), 0);
+
+    let restored = Task::find_by_id(&pool, task.id)
+        .await
+        .unwrap()
+        .expect("task is live again");
+    assert!(restored.deleted_at.is_none());
+    assert!(
+        Task::find_deleted_by_project_id(&pool, project_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn purge_only_removes_tasks_deleted_before_cutoff() {
+    let pool = setup_pool().await;
+    let project_id = create_project(&pool).await;
+    let live = create_task(&pool, project_id, "live").await;
+    let deleted = create_task(&pool, project_id, "deleted").await;
+    Task::delete(&pool, deleted.id).await.unwrap();
+
+    let past = Utc::now() - Duration::days(30);
+    assert_eq!(Task::purge_deleted_before(&pool, past).await.unwrap(), 0);
+
+    let future = Utc::now() + Duration::minutes(1);
+    assert_eq!(Task::purge_deleted_before(&pool, future).await.unwrap(), 1);
+
+    assert!(Task::find_by_id(&pool, live.id).await.unwrap().is_some());
+    assert!(
+        Task::find_deleted_by_project_id(&pool, project_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(Task::restore(&pool, deleted.id).await.unwrap(), 0);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{create_attempt, setup_pool};
+use db::models::{
+    execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+    task_attempt::TaskAttempt,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType,
+    script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn verify_process_result_is_recorded_on_attempt() {
+    let pool = setup_pool().await;
+    let attempt = create_attempt(&pool).await;
+    assert_eq!(attempt.verify_passed, None);
+
+    let data = CreateExecutionProcess {
+        task_attempt_id: attempt.id,
+        executor_action: ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "cargo test".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::VerifyScript,
+                dry_run: false,
+            }),
+            None,
+        ),
+        run_reason: ExecutionProcessRunReason::VerifyScript,
+    };
+    let process = ExecutionProcess::create(&pool, &data, Uuid::new_v4())
+        .await
+        .expect("verifyscript is an allowed run reason");
+    assert_eq!(process.run_reason, ExecutionProcessRunReason::VerifyScript);
+
+    TaskAttempt::update_verification(&pool, attempt.id, false, "3 passed, 1 failed")
+        .await
+        .unwrap();
+    let attempt = TaskAttempt::find_by_id(&pool, attempt.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(attempt.verify_passed, Some(false));
+    assert_eq!(
+        attempt.verify_summary.as_deref(),
+        Some("3 passed, 1 failed")
+    );
+}
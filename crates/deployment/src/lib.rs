@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::Error as AnyhowError;
 use async_trait::async_trait;
@@ -12,11 +12,11 @@ use db::{
     },
 };
 use executors::executors::ExecutorError;
-use futures::{StreamExt, TryStreamExt};
+use futures::{StreamExt, TryStreamExt, stream::BoxStream};
 use git2::Error as Git2Error;
 use serde_json::Value;
 use services::services::{
-    analytics::AnalyticsService,
+    analytics::{AnalyticsService, analytics_allowed},
     auth::{AuthError, AuthService},
     config::{Config, ConfigError},
     container::{ContainerError, ContainerService},
@@ -28,6 +28,7 @@ use services::services::{
     image::{ImageError, ImageService},
     pr_monitor::PrMonitorService,
     sentry::SentryService,
+    webhook::WebhookService,
     worktree_manager::WorktreeError,
 };
 use sqlx::{Error as SqlxError, types::Uuid};
@@ -35,6 +36,9 @@ use thiserror::Error;
 use tokio::sync::RwLock;
 use utils::msg_store::MsgStore;
 
+/// Seconds between SSE heartbeats unless `SSE_HEARTBEAT_SECS` overrides it
+const DEFAULT_SSE_HEARTBEAT_SECS: u64 = 15;
+
 #[derive(Debug, Error)]
 pub enum DeploymentError {
     #[error(transparent)]
@@ -118,8 +122,14 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         PrMonitorService::spawn(db, config).await
     }
 
+    async fn spawn_webhook_service(&self) -> tokio::task::JoinHandle<()> {
+        let db = self.db().clone();
+        let config = self.config().clone();
+        WebhookService::spawn(db, config, self.events()).await
+    }
+
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
-        if let Some(true) = self.config().read().await.analytics_enabled {
+        if analytics_allowed(self.config().read().await.analytics_enabled) {
             // Does the user allow analytics?
             if let Some(analytics) = self.analytics() {
                 // Is analytics setup?
@@ -132,6 +142,16 @@ pub trait Deployment: Clone + Send + Sync + 'static {
     async fn cleanup_orphan_executions(&self) -> Result<(), DeploymentError> {
         let running_processes = ExecutionProcess::find_running(&self.db().pool).await?;
         for process in running_processes {
+            // Detached agents may have kept running while we were down
+            match self.container().try_reattach(&process).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => tracing::warn!(
+                    "Failed to reattach to execution process {}: {}",
+                    process.id,
+                    e
+                ),
+            }
             tracing::info!(
                 "Found orphaned execution process {} for task attempt {}",
                 process.id,
@@ -176,6 +196,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 ExecutionProcessRunReason::CodingAgent
                     | ExecutionProcessRunReason::SetupScript
                     | ExecutionProcessRunReason::CleanupScript
+                    | ExecutionProcessRunReason::VerifyScript
             ) && let Ok(Some(task_attempt)) =
                 TaskAttempt::find_by_id(&self.db().pool, process.task_attempt_id).await
                 && let Ok(Some(task)) = task_attempt.parent_task(&self.db().pool).await
@@ -188,16 +209,74 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 );
             }
         }
+
+        // Processes still queued when we shut down wait for the freed slots
+        self.container().start_queued_executions().await?;
         Ok(())
     }
 
     async fn stream_events(
         &self,
     ) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
-        self.events()
+        let events = self
+            .events()
             .msg_store()
             .history_plus_stream()
             .map_ok(|m| m.to_sse_event())
-            .boxed()
+            .boxed();
+        with_heartbeat(events, sse_heartbeat_interval())
+    }
+}
+
+fn sse_heartbeat_interval() -> Duration {
+    let secs = std::env::var("SSE_HEARTBEAT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_SSE_HEARTBEAT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Interleave a heartbeat comment into `stream` every `period` so proxies do not
+/// drop idle connections. Comments are ignored by `EventSource` clients.
+pub fn with_heartbeat(
+    stream: BoxStream<'static, Result<Event, std::io::Error>>,
+    period: Duration,
+) -> BoxStream<'static, Result<Event, std::io::Error>> {
+    let interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    let heartbeats = futures::stream::unfold(interval, |mut interval| async move {
+        interval.tick().await;
+        Some((Ok(Event::default().comment("heartbeat")), interval))
+    });
+    futures::stream::select(stream, heartbeats).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn idle_stream_receives_heartbeats() {
+        let idle = futures::stream::pending().boxed();
+        let mut stream = with_heartbeat(idle, Duration::from_millis(50));
+
+        let first = tokio::time::timeout(Duration::from_millis(500), stream.next())
+            .await
+            .expect("heartbeat arrives after the interval");
+        assert!(matches!(first, Some(Ok(_))));
+    }
+
+    #[tokio::test]
+    async fn heartbeats_do_not_replace_real_events() {
+        let events = futures::stream::iter([Ok(Event::default().data("real"))])
+            .chain(futures::stream::pending())
+            .boxed();
+        let mut stream = with_heartbeat(events, Duration::from_millis(50));
+
+        // The real event is available immediately, before any heartbeat is due
+        let first = tokio::time::timeout(Duration::from_millis(10), stream.next()).await;
+        assert!(matches!(first, Ok(Some(Ok(_)))));
+        let second = tokio::time::timeout(Duration::from_millis(500), stream.next()).await;
+        assert!(matches!(second, Ok(Some(Ok(_)))));
     }
 }
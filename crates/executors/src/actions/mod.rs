@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -11,7 +11,9 @@ use crate::{
         coding_agent_follow_up::CodingAgentFollowUpRequest,
         coding_agent_initial::CodingAgentInitialRequest, script::ScriptRequest,
     },
-    executors::ExecutorError,
+    command::ExecutionEnv,
+    executors::{CodingAgent, ExecutorError},
+    profile::ExecutorConfigs,
 };
 pub mod coding_agent_follow_up;
 pub mod coding_agent_initial;
@@ -30,11 +32,26 @@ pub enum ExecutorActionType {
 pub struct ExecutorAction {
     pub typ: ExecutorActionType,
     pub next_action: Option<Box<ExecutorAction>>,
+    /// Directory relative to the worktree to run this action in, instead of the
+    /// worktree root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_subdir: Option<PathBuf>,
 }
 
 impl ExecutorAction {
     pub fn new(typ: ExecutorActionType, next_action: Option<Box<ExecutorAction>>) -> Self {
-        Self { typ, next_action }
+        Self {
+            typ,
+            next_action,
+            working_subdir: None,
+        }
+    }
+
+    /// Run this action in `working_subdir` of the worktree; chained actions
+    /// keep their own directory
+    pub fn with_working_subdir(mut self, working_subdir: Option<PathBuf>) -> Self {
+        self.working_subdir = working_subdir;
+        self
     }
 
     pub fn typ(&self) -> &ExecutorActionType {
@@ -44,17 +61,171 @@ impl ExecutorAction {
     pub fn next_action(&self) -> Option<&ExecutorAction> {
         self.next_action.as_deref()
     }
+
+    /// The coding agent this action spawns, if it is a coding agent request
+    pub fn coding_agent(&self) -> Option<CodingAgent> {
+        let executor_profile_id = match self.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::ScriptRequest(_) => return None,
+        };
+        ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
+    }
+
+    /// True if this action spawns a coding agent configured to run detached
+    pub fn is_detached(&self) -> bool {
+        self.coding_agent().is_some_and(|agent| agent.is_detached())
+    }
+}
+
+/// Reject a working subdirectory that is absolute or climbs out with `..`
+pub fn validate_working_subdir(subdir: &Path) -> Result<(), ExecutorError> {
+    if subdir
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(())
+    } else {
+        Err(ExecutorError::InvalidWorkingDir(format!(
+            "{} is outside the worktree",
+            subdir.display()
+        )))
+    }
+}
+
+/// The directory to run in: `worktree`, or `subdir` inside it. The subdirectory
+/// must exist and, after resolving symlinks, stay within the worktree.
+pub fn resolve_working_dir(
+    worktree: &Path,
+    subdir: Option<&Path>,
+) -> Result<PathBuf, ExecutorError> {
+    let Some(subdir) = subdir else {
+        return Ok(worktree.to_path_buf());
+    };
+    validate_working_subdir(subdir)?;
+    let dir = worktree.join(subdir);
+    let resolved = std::fs::canonicalize(&dir).map_err(|_| {
+        ExecutorError::InvalidWorkingDir(format!("{} does not exist", subdir.display()))
+    })?;
+    if !resolved.is_dir() {
+        return Err(ExecutorError::InvalidWorkingDir(format!(
+            "{} is not a directory",
+            subdir.display()
+        )));
+    }
+    if !resolved.starts_with(std::fs::canonicalize(worktree).map_err(ExecutorError::Io)?) {
+        return Err(ExecutorError::InvalidWorkingDir(format!(
+            "{} is outside the worktree",
+            subdir.display()
+        )));
+    }
+    Ok(dir)
 }
 
 #[async_trait]
 #[enum_dispatch(ExecutorActionType)]
 pub trait Executable {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError>;
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError>;
 }
 
 #[async_trait]
 impl Executable for ExecutorAction {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
-        self.typ.spawn(current_dir).await
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let working_dir = resolve_working_dir(current_dir, self.working_subdir.as_deref())?;
+        self.typ.spawn(&working_dir, env).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::script::{ScriptContext, ScriptRequestLanguage};
+
+    fn temp_worktree() -> PathBuf {
+        let worktree = std::env::temp_dir().join(format!("vk-subdir-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(worktree.join("packages/app")).unwrap();
+        worktree
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn action_runs_in_its_working_subdir() {
+        let worktree = temp_worktree();
+        let action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: "pwd -P".to_string(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+                dry_run: false,
+            }),
+            None,
+        )
+        .with_working_subdir(Some(PathBuf::from("packages/app")));
+
+        let child = action
+            .spawn(&worktree, &ExecutionEnv::default())
+            .await
+            .unwrap();
+        let output = child.wait_with_output().await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()),
+            std::fs::canonicalize(worktree.join("packages/app")).unwrap()
+        );
+        std::fs::remove_dir_all(&worktree).unwrap();
+    }
+
+    #[test]
+    fn subdirs_outside_the_worktree_are_rejected() {
+        let worktree = temp_worktree();
+        assert_eq!(
+            resolve_working_dir(&worktree, None).unwrap(),
+            worktree.clone()
+        );
+        assert_eq!(
+            resolve_working_dir(&worktree, Some(Path::new("packages/app"))).unwrap(),
+            worktree.join("packages/app")
+        );
+        for subdir in ["../other", "packages/../../other", "/etc", "missing"] {
+            assert!(
+                matches!(
+                    resolve_working_dir(&worktree, Some(Path::new(subdir))),
+                    Err(ExecutorError::InvalidWorkingDir(_))
+                ),
+                "{subdir} should be rejected"
+            );
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), worktree.join("escape")).unwrap();
+            assert!(matches!(
+                resolve_working_dir(&worktree, Some(Path::new("escape"))),
+                Err(ExecutorError::InvalidWorkingDir(_))
+            ));
+        }
+        std::fs::remove_dir_all(&worktree).unwrap();
+    }
+
+    #[test]
+    fn actions_stored_without_a_subdir_still_load() {
+        let action: ExecutorAction = serde_json::from_value(serde_json::json!({
+            "typ": {
+                "type": "ScriptRequest",
+                "script": "true",
+                "language": "Bash",
+                "context": "SetupScript",
+            },
+            "next_action": null,
+        }))
+        .unwrap();
+        assert_eq!(action.working_subdir, None);
     }
 }
@@ -5,9 +5,14 @@ use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 use ts_rs::TS;
-use utils::shell::get_shell_command;
+use utils::{msg_store::MsgStore, shell::get_shell_command};
 
-use crate::{actions::Executable, executors::ExecutorError};
+use crate::{
+    actions::Executable,
+    command::ExecutionEnv,
+    executors::ExecutorError,
+    logs::{NormalizedEntry, NormalizedEntryType, utils::patch::ConversationPatch},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub enum ScriptRequestLanguage {
@@ -18,6 +23,7 @@ pub enum ScriptRequestLanguage {
 pub enum ScriptContext {
     SetupScript,
     CleanupScript,
+    VerifyScript,
     DevServer,
 }
 
@@ -26,23 +32,107 @@ pub struct ScriptRequest {
     pub script: String,
     pub language: ScriptRequestLanguage,
     pub context: ScriptContext,
+    /// Log the script instead of running it, so it can be previewed
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl ScriptRequest {
+    /// Push the script that would run to `msg_store` as a system message and
+    /// finish the store, without spawning anything
+    pub fn record_dry_run(&self, msg_store: &MsgStore) {
+        msg_store.push_stdout(format!("{}\n", self.script));
+        msg_store.push_patch(ConversationPatch::add_normalized_entry(
+            0,
+            NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::SystemMessage,
+                content: format!("Dry run, the script was not executed:\n{}", self.script),
+                metadata: None,
+            },
+        ));
+        msg_store.push_finished();
+    }
 }
 
 #[async_trait]
 impl Executable for ScriptRequest {
-    async fn spawn(&self, current_dir: &Path) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let (shell_cmd, shell_args) = get_shell_command();
         let mut command = Command::new(shell_cmd);
         command
             .kill_on_drop(true)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
-            .arg(shell_arg)
+            .args(shell_args)
             .arg(&self.script)
             .current_dir(current_dir);
+        env.apply_to_command(&mut command);
 
         let child = command.group_spawn()?;
 
         Ok(child)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use utils::log_msg::LogMsg;
+
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_passes_env_overrides_to_child() {
+        let request = ScriptRequest {
+            script: r#"echo "$VK_TEST_FLAG""#.to_string(),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::SetupScript,
+            dry_run: false,
+        };
+        let env = ExecutionEnv::new([("VK_TEST_FLAG".to_string(), "enabled".to_string())]);
+
+        let child = request.spawn(&std::env::temp_dir(), &env).await.unwrap();
+        let output = child.wait_with_output().await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "enabled");
+    }
+
+    #[test]
+    fn dry_run_records_script_without_running_it() {
+        let marker = std::env::temp_dir().join(format!("vk-dry-run-{}", uuid::Uuid::new_v4()));
+        let request = ScriptRequest {
+            script: format!("touch {}", marker.display()),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::SetupScript,
+            dry_run: true,
+        };
+        let msg_store = MsgStore::new();
+
+        request.record_dry_run(&msg_store);
+
+        assert!(!marker.exists());
+        let mut conversation = serde_json::json!({ "entries": [] });
+        for msg in msg_store.get_history() {
+            if let LogMsg::JsonPatch(patch) = msg {
+                json_patch::patch(&mut conversation, &patch).unwrap();
+            }
+        }
+        let entries = conversation["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0]["content"]["entry_type"]["type"],
+            "system_message"
+        );
+        let content = entries[0]["content"]["content"].as_str().unwrap();
+        assert!(content.contains(&request.script));
+        assert!(matches!(
+            msg_store.get_history().last(),
+            Some(LogMsg::Finished)
+        ));
+    }
+}
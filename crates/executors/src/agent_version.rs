@@ -0,0 +1,158 @@
+//! Detection of outdated agent CLIs.
+//!
+//! Agents launched through `npx ...@latest` can still resolve to an old cached
+//! build, so we probe the installed version and compare it against a known
+//! minimum. Probing is best-effort: a missing binary, a slow `npx` or output we
+//! cannot parse all yield `detected: None` rather than an error.
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use ts_rs::TS;
+use utils::shell::get_shell_command;
+
+/// Minimum recommended versions per agent CLI. Bump these when an executor
+/// starts relying on newer CLI flags or output formats.
+pub mod min_versions {
+    pub const CLAUDE_CODE: &str = "1.0.90";
+    pub const AMP: &str = "0.0.1755000000";
+    pub const GEMINI: &str = "0.2.0";
+    pub const CODEX: &str = "0.29.0";
+    pub const OPENCODE: &str = "0.6.0";
+    pub const CURSOR: &str = "2025.08.25";
+    pub const QWEN_CODE: &str = "0.0.9";
+}
+
+/// How long a single `--version` probe may take before we give up.
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct AgentVersionInfo {
+    /// Version reported by the installed CLI, if it could be determined
+    pub detected: Option<String>,
+    /// Minimum version we recommend
+    pub recommended: String,
+    /// True when the detected version is older than the recommended one
+    pub update_recommended: bool,
+}
+
+impl AgentVersionInfo {
+    pub fn new(detected: Option<String>, recommended: &str) -> Self {
+        let update_recommended = detected
+            .as_deref()
+            .is_some_and(|d| is_older_than(d, recommended));
+        Self {
+            detected,
+            recommended: recommended.to_string(),
+            update_recommended,
+        }
+    }
+}
+
+/// Probe an npm package that is normally run through `npx`. Uses `--no-install`
+/// so that the probe never triggers a download; only an already installed or
+/// cached copy is reported.
+pub async fn check_npx_package_version(package: &str, recommended: &str) -> AgentVersionInfo {
+    let detected = probe_version(&format!("npx --no-install {package} --version")).await;
+    AgentVersionInfo::new(detected, recommended)
+}
+
+/// Probe a binary available on PATH by running `<binary> --version`.
+pub async fn check_binary_version(binary: &str, recommended: &str) -> AgentVersionInfo {
+    let detected = probe_version(&format!("{binary} --version")).await;
+    AgentVersionInfo::new(detected, recommended)
+}
+
+async fn probe_version(command: &str) -> Option<String> {
+    let (shell_cmd, shell_args) = get_shell_command();
+    let output = Command::new(shell_cmd)
+        .kill_on_drop(true)
+        .args(shell_args)
+        .arg(command)
+        .output();
+
+    let output = match tokio::time::timeout(VERSION_PROBE_TIMEOUT, output).await {
+        Ok(Ok(output)) if output.status.success() => output,
+        Ok(Ok(_)) => return None,
+        Ok(Err(e)) => {
+            tracing::debug!("Failed to run version probe `{}`: {}", command, e);
+            return None;
+        }
+        Err(_) => {
+            tracing::debug!("Version probe `{}` timed out", command);
+            return None;
+        }
+    };
+
+    extract_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Find the first dotted numeric version (e.g. `1.0.98` in `1.0.98 (Claude Code)`
+/// or `0.29.0` in `codex-cli 0.29.0`) in CLI output.
+pub fn extract_version(output: &str) -> Option<String> {
+    output
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ',')
+        .map(|token| token.trim_start_matches('v'))
+        .find_map(|token| {
+            let version: String = token
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            let version = version.trim_end_matches('.');
+            (version.contains('.') && parse_version(version).is_some()).then(|| version.to_string())
+        })
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect()
+}
+
+/// Compare two dotted numeric versions; missing components count as zero.
+/// Unparseable versions are never considered older.
+pub fn is_older_than(version: &str, minimum: &str) -> bool {
+    let (Some(mut version), Some(mut minimum)) = (parse_version(version), parse_version(minimum))
+    else {
+        return false;
+    };
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    version < minimum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_version() {
+        assert_eq!(
+            extract_version("1.0.98 (Claude Code)\n"),
+            Some("1.0.98".to_string())
+        );
+        assert_eq!(
+            extract_version("codex-cli 0.29.0"),
+            Some("0.29.0".to_string())
+        );
+        assert_eq!(extract_version("v0.6.3"), Some("0.6.3".to_string()));
+        assert_eq!(extract_version("no version here"), None);
+    }
+
+    #[test]
+    fn test_is_older_than() {
+        assert!(is_older_than("1.0.9", "1.0.90"));
+        assert!(is_older_than("0.28", "0.29.0"));
+        assert!(!is_older_than("0.29.0", "0.29"));
+        assert!(!is_older_than("2025.09.01", "2025.08.25"));
+        assert!(!is_older_than("garbage", "1.0.0"));
+    }
+
+    #[test]
+    fn test_update_recommended_requires_detected_version() {
+        assert!(!AgentVersionInfo::new(None, "1.0.0").update_recommended);
+        assert!(AgentVersionInfo::new(Some("0.9.0".to_string()), "1.0.0").update_recommended);
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Answer to an agent's request to run a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approved,
+    Denied,
+}
+
+/// Approval requests running agents are blocked on, keyed by execution process
+/// and then by the agent's call id. Call ids are only unique within a run.
+#[derive(Debug, Clone, Default)]
+pub struct PendingApprovals(Arc<Mutex<HashMap<Uuid, HashSet<String>>>>);
+
+impl PendingApprovals {
+    /// Record that execution `exec_id` is waiting on a decision for `call_id`
+    pub fn register(&self, exec_id: Uuid, call_id: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .entry(exec_id)
+            .or_default()
+            .insert(call_id.to_string());
+    }
+
+    /// Stop tracking `call_id` for `exec_id`; false if it was not pending
+    pub fn take(&self, exec_id: &Uuid, call_id: &str) -> bool {
+        let mut pending = self.0.lock().unwrap();
+        let Some(calls) = pending.get_mut(exec_id) else {
+            return false;
+        };
+        let taken = calls.remove(call_id);
+        if calls.is_empty() {
+            pending.remove(exec_id);
+        }
+        taken
+    }
+
+    /// Forget every request of an execution that has exited
+    pub fn clear(&self, exec_id: &Uuid) {
+        self.0.lock().unwrap().remove(exec_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_ids_are_scoped_to_their_execution() {
+        let pending = PendingApprovals::default();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        pending.register(first, "call_1");
+        pending.register(second, "call_1");
+
+        assert!(pending.take(&first, "call_1"));
+        assert!(!pending.take(&first, "call_1"));
+        assert!(pending.take(&second, "call_1"));
+    }
+
+    #[test]
+    fn clear_drops_an_exited_execution() {
+        let pending = PendingApprovals::default();
+        let exec_id = Uuid::new_v4();
+        pending.register(exec_id, "call_1");
+        pending.clear(&exec_id);
+
+        assert!(!pending.take(&exec_id, "call_1"));
+    }
+}
@@ -1,6 +1,28 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::process::Command;
 use ts_rs::TS;
+use utils::{
+    msg_store::MsgStore,
+    shell::{get_shell_command, quote_shell_arg},
+};
+
+use crate::{
+    detached,
+    logs::{
+        NormalizedEntry, NormalizedEntryType,
+        utils::{EntryIndexProvider, patch::ConversationPatch},
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
 pub struct CmdOverrides {
@@ -16,6 +38,168 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub additional_params: Option<Vec<String>>,
+    #[schemars(
+        title = "Raw Additional Parameters",
+        description = "Append additional parameters verbatim instead of quoting them, allowing shell syntax such as pipes or variables"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_additional_params: Option<bool>,
+    #[schemars(
+        title = "Detached",
+        description = "Keep the agent running if the server stops; output is written to log files and reattached on restart (Unix only)"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detached: Option<bool>,
+    #[schemars(
+        title = "MCP Config Path Override",
+        description = "Write MCP servers to this config file instead of the agent's global one; relative paths resolve against the task worktree and are kept out of its commits"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_config_path_override: Option<String>,
+}
+
+impl CmdOverrides {
+    pub fn is_detached(&self) -> bool {
+        cfg!(unix) && self.detached.unwrap_or(false)
+    }
+
+    /// Check that every additional parameter can be quoted safely. Always
+    /// passes when `raw_additional_params` is set.
+    pub fn validate(&self) -> Result<(), CommandBuildError> {
+        if self.raw_additional_params.unwrap_or(false) {
+            return Ok(());
+        }
+        match self
+            .additional_params
+            .iter()
+            .flatten()
+            .find(|param| is_unsafe_param(param))
+        {
+            Some(param) => Err(CommandBuildError::UnsafeParam(param.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Quoted additional parameters holding several words. They reach the
+    /// agent as one argument, where older versions let the shell split them.
+    pub fn multi_word_params(&self) -> Vec<&str> {
+        if self.raw_additional_params.unwrap_or(false) {
+            return Vec::new();
+        }
+        self.additional_params
+            .iter()
+            .flatten()
+            .filter(|param| param.trim().contains(char::is_whitespace))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Shell command that runs `command` in `current_dir` with a piped stdin.
+    /// Attached agents also get piped stdout/stderr and die with their handle;
+    /// detached agents write their output to log files instead.
+    pub fn shell_command(&self, current_dir: &Path, command: &str) -> Command {
+        let (shell_cmd, shell_args) = get_shell_command();
+        let mut cmd = Command::new(shell_cmd);
+        cmd.stdin(Stdio::piped())
+            .current_dir(current_dir)
+            .args(shell_args);
+        if self.is_detached() {
+            cmd.kill_on_drop(false)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .arg(detached::wrap_command(command));
+        } else {
+            cmd.kill_on_drop(true)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .arg(command);
+        }
+        cmd
+    }
+}
+
+/// Resolve `working_dir_env` entries (variable name to a path relative to the
+/// worktree, empty for the worktree root) against `current_dir`. Entries that
+/// would point outside the worktree are skipped.
+pub fn working_dir_env_values(
+    current_dir: &Path,
+    working_dir_env: Option<&HashMap<String, String>>,
+) -> Vec<(String, PathBuf)> {
+    let Some(vars) = working_dir_env else {
+        return Vec::new();
+    };
+    let mut values: Vec<(String, PathBuf)> = vars
+        .iter()
+        .filter_map(|(name, subdir)| {
+            let subdir = Path::new(subdir.trim());
+            if subdir.is_absolute()
+                || subdir
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                tracing::warn!(
+                    "Ignoring working directory env var {name}: {} is outside the worktree",
+                    subdir.display()
+                );
+                return None;
+            }
+            Some((name.clone(), current_dir.join(subdir)))
+        })
+        .collect();
+    values.sort();
+    values
+}
+
+/// Set the configured working directory env vars on `cmd`
+pub fn apply_working_dir_env(
+    cmd: &mut Command,
+    current_dir: &Path,
+    working_dir_env: Option<&HashMap<String, String>>,
+) {
+    for (name, path) in working_dir_env_values(current_dir, working_dir_env) {
+        cmd.env(name, path);
+    }
+}
+
+/// Extra environment variables for a spawned agent or script, such as a task
+/// attempt's overrides. `Debug` redacts the values so the env is safe to log.
+#[derive(Clone, Default, PartialEq)]
+pub struct ExecutionEnv {
+    vars: BTreeMap<String, String>,
+}
+
+impl ExecutionEnv {
+    pub fn new(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            vars: vars.into_iter().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Set the variables on `cmd`, overriding any inherited values
+    pub fn apply_to_command(&self, cmd: &mut Command) {
+        cmd.envs(&self.vars);
+    }
+
+    /// Whether `name` is a valid override name: `[A-Z_][A-Z0-9_]*`
+    pub fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+            && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    }
+}
+
+impl fmt::Debug for ExecutionEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.vars.keys().map(|name| (name, "<redacted>")))
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -78,15 +262,277 @@ impl CommandBuilder {
     }
 }
 
-pub fn apply_overrides(builder: CommandBuilder, overrides: &CmdOverrides) -> CommandBuilder {
+#[derive(Debug, Error)]
+pub enum CommandBuildError {
+    #[error(
+        "Additional parameter {0:?} contains shell metacharacters; enable raw additional parameters to pass it verbatim"
+    )]
+    UnsafeParam(String),
+}
+
+/// Characters that would let an additional parameter end the command, chain
+/// another one or substitute output, even inside quotes
+const SHELL_METACHARACTERS: &[char] = &[';', '&', '|', '$', '`', '<', '>', '(', ')', '\n', '\r'];
+
+/// Whether `param` has characters quoting cannot make safe for the platform
+/// shell; `cmd` expands `%VAR%` even inside quotes
+fn is_unsafe_param(param: &str) -> bool {
+    param.contains(SHELL_METACHARACTERS) || (cfg!(windows) && param.contains('%'))
+}
+
+/// Quote each additional parameter for the shell so it reaches the agent as a
+/// single argument. Parameters with shell metacharacters are rejected unless
+/// `raw_additional_params` is set, in which case all of them are appended as is.
+pub fn apply_overrides(
+    builder: CommandBuilder,
+    overrides: &CmdOverrides,
+) -> Result<CommandBuilder, CommandBuildError> {
     let builder = if let Some(ref base) = overrides.base_command_override {
         builder.override_base(base.clone())
     } else {
         builder
     };
-    if let Some(ref extra) = overrides.additional_params {
-        builder.extend_params(extra.clone())
-    } else {
-        builder
+    let Some(ref extra) = overrides.additional_params else {
+        return Ok(builder);
+    };
+    if overrides.raw_additional_params.unwrap_or(false) {
+        return Ok(builder.extend_params(extra.clone()));
+    }
+    overrides.validate()?;
+    Ok(builder.extend_params(extra.iter().map(|param| quote_shell_arg(param))))
+}
+
+/// Readable reason why `program` could not be started in `current_dir`
+pub fn describe_spawn_error(program: &str, current_dir: &Path, err: &std::io::Error) -> String {
+    match err.kind() {
+        ErrorKind::NotFound if !current_dir.is_dir() => {
+            format!("working directory not found: {}", current_dir.display())
+        }
+        ErrorKind::NotFound => format!("command not found: {program}"),
+        ErrorKind::PermissionDenied => format!("permission denied: {program}"),
+        _ => format!("failed to start {program}: {err}"),
+    }
+}
+
+/// The program a shell reports missing on one line of its error output
+fn missing_program(line: &str) -> Option<&str> {
+    let line = line.trim();
+    // zsh: `zsh:1: command not found: npx`
+    if let Some((_, program)) = line.split_once("command not found: ") {
+        return Some(program.trim());
+    }
+    // bash: `bash: line 1: npx: command not found`, dash: `sh: 1: npx: not found`
+    if let Some(prefix) = line
+        .strip_suffix(": command not found")
+        .or_else(|| line.strip_suffix(": not found"))
+    {
+        return prefix.rsplit(": ").next();
+    }
+    // cmd and PowerShell: `'npx' is not recognized as ...`
+    if line.contains("is not recognized as") {
+        return line.split('\'').nth(1);
+    }
+    None
+}
+
+/// Readable reason why a process that failed with `exit_code` never ran its
+/// command. Commands run through the shell, so a missing program is an exit
+/// code of 127 and a complaint on stderr rather than a spawn error.
+pub fn describe_missing_command(exit_code: Option<i64>, stderr: &str) -> Option<String> {
+    match stderr.lines().find_map(missing_program) {
+        Some(program) => Some(format!("command not found: {program}")),
+        None if exit_code == Some(127) => Some("command not found (exit code 127)".to_string()),
+        None => None,
+    }
+}
+
+/// Record that an execution could not be started, as stderr and as an error
+/// entry in its conversation, then finish the store
+pub fn record_spawn_error(msg_store: &MsgStore, message: &str) {
+    msg_store.push_stderr(format!("{message}\n"));
+    push_error_entry(msg_store, message);
+    msg_store.push_finished();
+}
+
+/// Add an error entry saying `message` to the conversation in `msg_store`
+pub fn push_error_entry(msg_store: &MsgStore, message: &str) {
+    let index = EntryIndexProvider::start_from(msg_store).next();
+    msg_store.push_patch(ConversationPatch::add_normalized_entry(
+        index,
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ErrorMessage,
+            content: message.to_string(),
+            metadata: None,
+        },
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_working_dir_env_values() {
+        let vars = HashMap::from([
+            ("PROJECT_ROOT".to_string(), String::new()),
+            ("APP_DIR".to_string(), "packages/app".to_string()),
+            ("ESCAPE".to_string(), "../other".to_string()),
+            ("ABSOLUTE".to_string(), "/etc".to_string()),
+        ]);
+        let values = working_dir_env_values(Path::new("/tmp/wt"), Some(&vars));
+        assert_eq!(
+            values,
+            vec![
+                ("APP_DIR".to_string(), PathBuf::from("/tmp/wt/packages/app")),
+                ("PROJECT_ROOT".to_string(), PathBuf::from("/tmp/wt")),
+            ]
+        );
+        assert!(working_dir_env_values(Path::new("/tmp/wt"), None).is_empty());
+    }
+
+    fn overrides(params: &[&str], raw: bool) -> CmdOverrides {
+        CmdOverrides {
+            additional_params: Some(params.iter().map(|p| p.to_string()).collect()),
+            raw_additional_params: raw.then_some(true),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_additional_params_are_quoted() {
+        let builder = apply_overrides(
+            CommandBuilder::new("agent").params(["-p"]),
+            &overrides(
+                &["--model=sonnet", "--append", "be brief, it's fine"],
+                false,
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            builder.build_initial(),
+            "agent -p --model=sonnet --append 'be brief, it'\\''s fine'"
+        );
+    }
+
+    #[test]
+    fn test_additional_params_with_metacharacters_are_rejected() {
+        for param in [
+            "--flag; rm -rf ~",
+            "$(whoami)",
+            "`id`",
+            "a && b",
+            "x\ny",
+            "> out",
+        ] {
+            let result = apply_overrides(CommandBuilder::new("agent"), &overrides(&[param], false));
+            assert!(
+                matches!(result, Err(CommandBuildError::UnsafeParam(ref p)) if p == param),
+                "{param:?} should be rejected"
+            );
+        }
+
+        // The escape hatch passes them through untouched
+        let builder = apply_overrides(
+            CommandBuilder::new("agent"),
+            &overrides(&["--verbose", "2>&1", "| tee out.log"], true),
+        )
+        .unwrap();
+        assert_eq!(
+            builder.build_initial(),
+            "agent --verbose 2>&1 | tee out.log"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_params_with_variables_are_rejected() {
+        assert!(matches!(
+            apply_overrides(
+                CommandBuilder::new("agent"),
+                &overrides(&["%USERPROFILE%"], false)
+            ),
+            Err(CommandBuildError::UnsafeParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_multi_word_params_are_reported() {
+        let cmd = overrides(&["--model opus", "--verbose", " --trailing "], false);
+        assert!(cmd.validate().is_ok());
+        assert_eq!(cmd.multi_word_params(), ["--model opus"]);
+        assert!(
+            overrides(&["--model opus"], true)
+                .multi_word_params()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_execution_env_names_and_redaction() {
+        assert!(ExecutionEnv::is_valid_name("API_KEY"));
+        assert!(ExecutionEnv::is_valid_name("_FLAG2"));
+        assert!(!ExecutionEnv::is_valid_name("2FA"));
+        assert!(!ExecutionEnv::is_valid_name("api_key"));
+        assert!(!ExecutionEnv::is_valid_name(""));
+
+        let env = ExecutionEnv::new([("API_KEY".to_string(), "sk-secret".to_string())]);
+        let logged = format!("{env:?}");
+        assert!(logged.contains("API_KEY"));
+        assert!(!logged.contains("sk-secret"));
+    }
+
+    #[tokio::test]
+    async fn spawn_failure_becomes_error_entry() {
+        let program = "vk-no-such-command";
+        let current_dir = std::env::temp_dir();
+        let err = Command::new(program)
+            .current_dir(&current_dir)
+            .spawn()
+            .expect_err("bogus command should not spawn");
+        let message = describe_spawn_error(program, &current_dir, &err);
+        assert_eq!(message, "command not found: vk-no-such-command");
+
+        let msg_store = MsgStore::new();
+        record_spawn_error(&msg_store, &message);
+
+        let mut conversation = serde_json::json!({ "entries": [] });
+        for msg in msg_store.get_history() {
+            if let utils::log_msg::LogMsg::JsonPatch(patch) = msg {
+                json_patch::patch(&mut conversation, &patch).unwrap();
+            }
+        }
+        let entries = conversation["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["content"]["entry_type"]["type"], "error_message");
+        assert_eq!(entries[0]["content"]["content"], message);
+    }
+
+    #[tokio::test]
+    async fn missing_program_is_reported_from_the_shell() {
+        let output = CmdOverrides::default()
+            .shell_command(&std::env::temp_dir(), "vk-no-such-command --version")
+            .output()
+            .await
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(
+            describe_missing_command(output.status.code().map(i64::from), &stderr).as_deref(),
+            Some("command not found: vk-no-such-command")
+        );
+
+        for line in [
+            "bash: line 1: npx: command not found",
+            "sh: 1: npx: not found",
+            "zsh:1: command not found: npx",
+            "'npx' is not recognized as an internal or external command,",
+        ] {
+            assert_eq!(
+                describe_missing_command(Some(1), line).as_deref(),
+                Some("command not found: npx")
+            );
+        }
+        assert!(describe_missing_command(Some(1), "error: tests failed").is_none());
     }
 }
@@ -0,0 +1,104 @@
+//! Support for agents that keep running after the server stops.
+//!
+//! A detached agent is not killed when its handle is dropped and does not write
+//! to pipes owned by the server. Instead the shell wrapper redirects stdout and
+//! stderr into per-PID log files and records the exit code when the agent
+//! finishes, so a restarted server can reattach by tailing those files.
+use std::path::PathBuf;
+
+use utils::path::get_vibe_kanban_temp_dir;
+use uuid::Uuid;
+
+pub fn detached_logs_dir() -> PathBuf {
+    get_vibe_kanban_temp_dir().join("detached")
+}
+
+/// Files written by a detached agent, keyed by the PID of its wrapper shell
+#[derive(Debug, Clone)]
+pub struct DetachedOutput {
+    pub stdout: PathBuf,
+    pub stderr: PathBuf,
+    pub exit_code: PathBuf,
+}
+
+impl DetachedOutput {
+    pub fn for_pid(pid: u32) -> Self {
+        let dir = detached_logs_dir();
+        Self {
+            stdout: dir.join(format!("{pid}.stdout")),
+            stderr: dir.join(format!("{pid}.stderr")),
+            exit_code: dir.join(format!("{pid}.exit")),
+        }
+    }
+
+    /// Exit code recorded by the wrapper, or None if the agent has not finished
+    /// (or was killed before it could record one)
+    pub fn read_exit_code(&self) -> Option<i64> {
+        std::fs::read_to_string(&self.exit_code)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+    }
+
+    pub fn remove(&self) {
+        for path in [&self.stdout, &self.stderr, &self.exit_code] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Wrap a shell command so that it ignores SIGHUP, appends its output to the
+/// files from [`DetachedOutput::for_pid`] and records its exit code. `$$` is the
+/// PID of the wrapper shell, which is the PID of the spawned child.
+pub fn wrap_command(command: &str) -> String {
+    let dir = detached_logs_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::warn!(
+            "Failed to create detached logs dir {}: {}",
+            dir.display(),
+            e
+        );
+    }
+    let dir = dir.to_string_lossy();
+    format!(
+        "trap '' HUP\nexec >>\"{dir}/$$.stdout\" 2>>\"{dir}/$$.stderr\"\n{command}\nvk_exit_code=$?\necho $vk_exit_code >\"{dir}/$$.exit\"\nexit $vk_exit_code"
+    )
+}
+
+fn pid_file(execution_id: &Uuid) -> PathBuf {
+    detached_logs_dir().join(format!("{execution_id}.pid"))
+}
+
+/// Persist the PID of a detached execution so it can be found after a restart
+pub fn record_pid(execution_id: &Uuid, pid: u32) -> std::io::Result<()> {
+    std::fs::create_dir_all(detached_logs_dir())?;
+    std::fs::write(pid_file(execution_id), pid.to_string())
+}
+
+pub fn read_pid(execution_id: &Uuid) -> Option<u32> {
+    std::fs::read_to_string(pid_file(execution_id))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// Remove the PID file and log files of a finished detached execution
+pub fn cleanup(execution_id: &Uuid) {
+    if let Some(pid) = read_pid(execution_id) {
+        DetachedOutput::for_pid(pid).remove();
+    }
+    let _ = std::fs::remove_file(pid_file(execution_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_command_redirects_output_and_records_exit_code() {
+        let wrapped = wrap_command("echo hi");
+        assert!(wrapped.starts_with("trap '' HUP\n"));
+        assert!(wrapped.contains("$$.stdout"));
+        assert!(wrapped.contains("$$.stderr"));
+        assert!(wrapped.contains("\necho hi\n"));
+        assert!(wrapped.ends_with("exit $vk_exit_code"));
+    }
+}
@@ -0,0 +1,273 @@
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::{AsyncCommandGroup, AsyncGroupChild};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use ts_rs::TS;
+use utils::{msg_store::MsgStore, path::make_path_relative, shell::resolve_executable_path};
+
+use crate::{
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides},
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
+    logs::{
+        ActionType, NormalizedEntry, NormalizedEntryType,
+        plain_text_processor::{MessageBoundary, PlainTextLogProcessor},
+        stderr_processor::normalize_stderr_logs,
+        utils::EntryIndexProvider,
+    },
+};
+
+/// Line Aider prints after writing changes to a file
+const APPLIED_EDIT_PREFIX: &str = "Applied edit to ";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Aider {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+impl Aider {
+    /// Aider commits on its own by default; leave that to the task attempt flow
+    /// and keep the output free of colors so it can be parsed.
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        let mut builder =
+            CommandBuilder::new("aider").params(["--yes", "--no-auto-commits", "--no-pretty"]);
+
+        if let Some(model) = &self.model {
+            builder = builder.extend_params(["--model", model]);
+        }
+
+        apply_overrides(builder, &self.cmd)
+    }
+
+    async fn spawn_with_command(
+        &self,
+        current_dir: &Path,
+        aider_command: &str,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
+
+        let mut command = self.cmd.shell_command(current_dir, aider_command);
+        env.apply_to_command(&mut command);
+
+        let mut child = command.group_spawn()?;
+
+        // Feed the prompt in, then close the pipe so Aider exits when done
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(combined_prompt.as_bytes()).await?;
+            stdin.shutdown().await?;
+        }
+
+        Ok(child)
+    }
+
+    /// File path from an `Applied edit to <file>` line
+    fn parse_applied_edit(line: &str) -> Option<&str> {
+        line.trim()
+            .strip_prefix(APPLIED_EDIT_PREFIX)
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Keep each applied edit line in an entry of its own
+    fn detect_applied_edit(lines: &[String]) -> Option<MessageBoundary> {
+        let i = lines
+            .iter()
+            .position(|line| line.ends_with('\n') && Self::parse_applied_edit(line).is_some())?;
+        if i == 0 {
+            // separate the edit from subsequent content
+            Some(MessageBoundary::Split(1))
+        } else {
+            // separate the edit from previous content
+            Some(MessageBoundary::Split(i))
+        }
+    }
+
+    fn create_normalized_entry(content: String, worktree_path: &Path) -> NormalizedEntry {
+        if let Some(path) = Self::parse_applied_edit(&content) {
+            let path = make_path_relative(path, &worktree_path.to_string_lossy());
+            return NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::ToolUse {
+                    tool_name: "edit".to_string(),
+                    action_type: ActionType::FileEdit {
+                        path: path.clone(),
+                        changes: vec![],
+                    },
+                },
+                content: format!("`{path}`"),
+                metadata: None,
+            };
+        }
+
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content,
+            metadata: None,
+        }
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Aider {
+    async fn spawn(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        let aider_command = self.build_command_builder()?.build_initial();
+        self.spawn_with_command(current_dir, &aider_command, prompt, env)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        _session_id: &str,
+        env: &ExecutionEnv,
+    ) -> Result<AsyncGroupChild, ExecutorError> {
+        // Aider keeps its chat history in the worktree, so resuming only needs it restored
+        let aider_command = self
+            .build_command_builder()?
+            .build_follow_up(&["--restore-chat-history".to_string()]);
+        self.spawn_with_command(current_dir, &aider_command, prompt, env)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index_provider.clone());
+
+        // Send session ID to msg_store to enable follow-ups
+        msg_store.push_session_id(
+            current_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        );
+
+        let worktree_path = current_dir.to_path_buf();
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            let mut stdout = msg_store.stdout_chunked_stream();
+            let mut processor = PlainTextLogProcessor::builder()
+                .normalized_entry_producer(Box::new(move |content: String| {
+                    Self::create_normalized_entry(content, &worktree_path)
+                }))
+                .message_boundary_predicate(Box::new(|lines: &[String]| {
+                    Self::detect_applied_edit(lines)
+                }))
+                .index_provider(entry_index_provider)
+                .build();
+
+            while let Some(Ok(chunk)) = stdout.next().await {
+                for patch in processor.process(chunk) {
+                    msg_store.push_patch(patch);
+                }
+            }
+        });
+    }
+
+    // Aider has no MCP support
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    async fn check_availability(&self) -> bool {
+        resolve_executable_path("aider").is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aider(model: Option<&str>) -> Aider {
+        Aider {
+            append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
+            model: model.map(str::to_string),
+            cmd: CmdOverrides::default(),
+        }
+    }
+
+    #[test]
+    fn test_command_construction() {
+        assert_eq!(
+            aider(None).build_command_builder().unwrap().build_initial(),
+            "aider --yes --no-auto-commits --no-pretty"
+        );
+        assert_eq!(
+            aider(Some("sonnet"))
+                .build_command_builder()
+                .unwrap()
+                .build_initial(),
+            "aider --yes --no-auto-commits --no-pretty --model sonnet"
+        );
+        assert_eq!(
+            aider(None)
+                .build_command_builder()
+                .unwrap()
+                .build_follow_up(&["--restore-chat-history".to_string()]),
+            "aider --yes --no-auto-commits --no-pretty --restore-chat-history"
+        );
+    }
+
+    #[test]
+    fn test_applied_edit_detection() {
+        assert_eq!(
+            Aider::parse_applied_edit("Applied edit to src/main.rs\n"),
+            Some("src/main.rs")
+        );
+        assert_eq!(Aider::parse_applied_edit("Applied edit to \n"), None);
+        assert_eq!(Aider::parse_applied_edit("I will edit src/main.rs\n"), None);
+
+        let lines = vec![
+            "Sure, updating the file.\n".to_string(),
+            "Applied edit to src/lib.rs\n".to_string(),
+            "Done.\n".to_string(),
+        ];
+        assert_eq!(
+            Aider::detect_applied_edit(&lines),
+            Some(MessageBoundary::Split(1))
+        );
+        assert_eq!(
+            Aider::detect_applied_edit(&lines[1..]),
+            Some(MessageBoundary::Split(1))
+        );
+        // An incomplete line is not split off yet
+        assert_eq!(
+            Aider::detect_applied_edit(&["Applied edit to src/li".to_string()]),
+            None
+        );
+
+        let entry = Aider::create_normalized_entry(
+            "Applied edit to /tmp/worktree/src/lib.rs\n".to_string(),
+            Path::new("/tmp/worktree"),
+        );
+        assert_eq!(entry.content, "`src/lib.rs`");
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::FileEdit { ref path, .. },
+                ..
+            } if path == "src/lib.rs"
+        ));
+    }
+}
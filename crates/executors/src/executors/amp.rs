@@ -9,9 +9,10 @@ use ts_rs::TS;
 use utils::{msg_store::MsgStore, shell::get_shell_command};
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    agent_version::{AgentVersionInfo, check_npx_package_version, min_versions},
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides},
     executors::{
-        AppendPrompt, ExecutorError, StandardCodingAgentExecutor,
+        AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor,
         claude::{ClaudeLogProcessor, HistoryStrategy},
     },
     logs::{stderr_processor::normalize_stderr_logs, utils::EntryIndexProvider},
@@ -21,6 +22,8 @@ use crate::{
 pub struct Amp {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[schemars(
         title = "Dangerously Allow All",
@@ -32,7 +35,7 @@ pub struct Amp {
 }
 
 impl Amp {
-    fn build_command_builder(&self) -> CommandBuilder {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
         let mut builder = CommandBuilder::new("npx -y @sourcegraph/amp@latest")
             .params(["--execute", "--stream-json"]);
         if self.dangerously_allow_all.unwrap_or(false) {
@@ -40,6 +43,50 @@ impl Amp {
         }
         apply_overrides(builder, &self.cmd)
     }
+
+    fn fork_command(&self, thread_id: &str) -> Result<String, CommandBuildError> {
+        Ok(self.build_command_builder()?.build_follow_up(&[
+            "threads".to_string(),
+            "fork".to_string(),
+            thread_id.to_string(),
+        ]))
+    }
+
+    fn continue_command(&self, thread_id: &str) -> Result<String, CommandBuildError> {
+        Ok(self.build_command_builder()?.build_follow_up(&[
+            "threads".to_string(),
+            "continue".to_string(),
+            thread_id.to_string(),
+        ]))
+    }
+}
+
+/// The last Amp thread id (`T-<uuid>`) in `text`, which may be a bare id, a
+/// thread URL or CLI output mentioning one
+pub(crate) fn extract_thread_id(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .rev()
+        .find(|token| {
+            token.strip_prefix("T-").is_some_and(|id| {
+                !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+            })
+        })
+        .map(str::to_string)
+}
+
+fn normalize_amp_logs(msg_store: Arc<MsgStore>, current_dir: &Path, strategy: HistoryStrategy) {
+    let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
+
+    // Process stdout logs (Amp's stream JSON output) using Claude's log processor
+    ClaudeLogProcessor::process_logs(
+        msg_store.clone(),
+        current_dir,
+        entry_index_provider.clone(),
+        strategy,
+    );
+
+    // Process stderr logs using the standard stderr processor
+    normalize_stderr_logs(msg_store, entry_index_provider);
 }
 
 #[async_trait]
@@ -48,21 +95,16 @@ impl StandardCodingAgentExecutor for Amp {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let amp_command = self.build_command_builder().build_initial();
+        let amp_command = self.build_command_builder()?.build_initial();
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&amp_command);
+        let mut command = self.cmd.shell_command(current_dir, &amp_command);
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -80,59 +122,52 @@ impl StandardCodingAgentExecutor for Amp {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         // Use shell command for cross-platform compatibility
-        let (shell_cmd, shell_arg) = get_shell_command();
+        let (shell_cmd, shell_args) = get_shell_command();
+        let thread_id = extract_thread_id(session_id).unwrap_or_else(|| session_id.to_string());
 
         // 1) Fork the thread synchronously to obtain new thread id
-        let fork_cmd = self.build_command_builder().build_follow_up(&[
-            "threads".to_string(),
-            "fork".to_string(),
-            session_id.to_string(),
-        ]);
-        let fork_output = Command::new(shell_cmd)
-            .kill_on_drop(true)
+        let fork_cmd = self.fork_command(&thread_id)?;
+        let mut fork = Command::new(shell_cmd);
+        fork.kill_on_drop(true)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&fork_cmd)
-            .output()
-            .await?;
+            .args(shell_args)
+            .arg(&fork_cmd);
+        env.apply_to_command(&mut fork);
+        let fork_output = fork.output().await?;
         let stdout_str = String::from_utf8_lossy(&fork_output.stdout);
-        let new_thread_id = stdout_str
-            .lines()
-            .rev()
-            .find(|l| !l.trim().is_empty())
-            .unwrap_or("")
-            .trim()
-            .to_string();
-        if new_thread_id.is_empty() {
-            return Err(ExecutorError::Io(std::io::Error::other(
-                "AMP threads fork did not return a thread id",
-            )));
-        }
+        let new_thread_id = extract_thread_id(&stdout_str)
+            .or_else(|| {
+                // Otherwise take the last line, as Amp prints the new id last
+                stdout_str
+                    .lines()
+                    .rev()
+                    .map(str::trim)
+                    .find(|l| !l.is_empty())
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| {
+                ExecutorError::Io(std::io::Error::other(
+                    "AMP threads fork did not return a thread id",
+                ))
+            })?;
 
         tracing::debug!("AMP threads fork -> new thread id: {}", new_thread_id);
 
-        // 2) Continue using the new thread id
-        let continue_cmd = self.build_command_builder().build_follow_up(&[
-            "threads".to_string(),
-            "continue".to_string(),
-            new_thread_id.clone(),
-        ]);
+        // 2) Continue using the new thread id; its stream reports that id as the
+        // session id, so the next follow-up forks from it
+        let continue_cmd = self.continue_command(&new_thread_id)?;
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&continue_cmd);
+        let mut command = self.cmd.shell_command(current_dir, &continue_cmd);
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -146,22 +181,149 @@ impl StandardCodingAgentExecutor for Amp {
     }
 
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
-        let entry_index_provider = EntryIndexProvider::start_from(&msg_store);
-
-        // Process stdout logs (Amp's stream JSON output) using Claude's log processor
-        ClaudeLogProcessor::process_logs(
-            msg_store.clone(),
-            current_dir,
-            entry_index_provider.clone(),
-            HistoryStrategy::AmpResume,
-        );
+        normalize_amp_logs(msg_store, current_dir, HistoryStrategy::Amp);
+    }
 
-        // Process stderr logs using the standard stderr processor
-        normalize_stderr_logs(msg_store, entry_index_provider);
+    fn normalize_follow_up_logs(&self, msg_store: Arc<MsgStore>, current_dir: &Path) {
+        normalize_amp_logs(msg_store, current_dir, HistoryStrategy::AmpResume);
     }
 
     // MCP configuration methods
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
         dirs::home_dir().map(|home| home.join(".config").join("amp").join("settings.json"))
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(check_npx_package_version("@sourcegraph/amp", min_versions::AMP).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::log_msg::LogMsg;
+
+    use super::*;
+    use crate::logs::{
+        NormalizedEntry, NormalizedEntryType,
+        utils::replay::{collect_entries, raw_log_store},
+    };
+
+    const THREAD_ID: &str = "T-5928a90d-d53b-488f-a829-4e36442142ee";
+
+    fn amp() -> Amp {
+        Amp {
+            append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
+            dangerously_allow_all: None,
+            cmd: CmdOverrides::default(),
+        }
+    }
+
+    #[test]
+    fn extracts_thread_ids_from_ids_urls_and_cli_output() {
+        assert_eq!(extract_thread_id(THREAD_ID).as_deref(), Some(THREAD_ID));
+        assert_eq!(
+            extract_thread_id(&format!("https://ampcode.com/threads/{THREAD_ID}?tab=1")).as_deref(),
+            Some(THREAD_ID)
+        );
+        assert_eq!(
+            extract_thread_id(&format!("Forked T-1234 into new thread {THREAD_ID}.\n")).as_deref(),
+            Some(THREAD_ID)
+        );
+        assert_eq!(extract_thread_id("T-"), None);
+        assert_eq!(extract_thread_id("T-not-a-thread"), None);
+        assert_eq!(extract_thread_id("session-123"), None);
+    }
+
+    #[test]
+    fn follow_up_forks_then_continues_the_thread() {
+        let executor = amp();
+        assert_eq!(
+            executor.fork_command(THREAD_ID).unwrap(),
+            format!(
+                "npx -y @sourcegraph/amp@latest --execute --stream-json threads fork {THREAD_ID}"
+            )
+        );
+        assert_eq!(
+            executor.continue_command(THREAD_ID).unwrap(),
+            format!(
+                "npx -y @sourcegraph/amp@latest --execute --stream-json threads continue {THREAD_ID}"
+            )
+        );
+
+        let executor = Amp {
+            dangerously_allow_all: Some(true),
+            ..amp()
+        };
+        assert!(
+            executor
+                .continue_command(THREAD_ID)
+                .unwrap()
+                .ends_with(&format!(
+                    "--dangerously-allow-all threads continue {THREAD_ID}"
+                ))
+        );
+    }
+
+    /// Replayed turns of a resumed thread, then a new prompt and its answer
+    fn resumed_thread_logs() -> Arc<MsgStore> {
+        let lines = [
+            format!(r#"{{"type":"system","subtype":"init","session_id":"{THREAD_ID}"}}"#),
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"first"}]}}"#.to_string(),
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"old answer"}]}}"#.to_string(),
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"second"}]}}"#.to_string(),
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"new answer"}]}}"#.to_string(),
+            // Tool results alongside text are not a new prompt
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok","is_error":false},{"type":"text","text":"note"}]}}"#.to_string(),
+        ];
+        raw_log_store(
+            lines
+                .iter()
+                .map(|line| LogMsg::Stdout(format!("{line}\n")))
+                .collect(),
+        )
+    }
+
+    fn contents(entries: &[NormalizedEntry]) -> Vec<(bool, &str)> {
+        entries
+            .iter()
+            .map(|entry| {
+                (
+                    matches!(entry.entry_type, NormalizedEntryType::UserMessage),
+                    entry.content.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn resumed_thread_shows_only_the_latest_turn() {
+        let store = resumed_thread_logs();
+        amp().normalize_follow_up_logs(store.clone(), Path::new("/tmp"));
+        let history_store = store.clone();
+        let entries = collect_entries(store).await;
+
+        assert_eq!(
+            contents(&entries),
+            [(true, "second"), (false, "new answer")]
+        );
+        assert!(
+            history_store
+                .get_history()
+                .iter()
+                .any(|msg| matches!(msg, LogMsg::SessionId(id) if id == THREAD_ID))
+        );
+    }
+
+    #[tokio::test]
+    async fn new_thread_keeps_every_turn() {
+        let store = resumed_thread_logs();
+        amp().normalize_logs(store.clone(), Path::new("/tmp"));
+        let entries = collect_entries(store).await;
+
+        let contents = contents(&entries);
+        assert!(contents.contains(&(true, "first")));
+        assert!(contents.contains(&(false, "old answer")));
+        assert!(contents.ends_with(&[(true, "second"), (false, "new answer")]));
+    }
 }
@@ -1,27 +1,36 @@
-use std::{path::Path, process::Stdio, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use utils::{
-    diff::{concatenate_diff_hunks, create_unified_diff, create_unified_diff_hunk},
+    diff::{
+        DEFAULT_CONTEXT_LINES, concatenate_diff_hunks, create_unified_diff,
+        create_unified_diff_hunk,
+    },
     log_msg::LogMsg,
     msg_store::MsgStore,
     path::make_path_relative,
-    shell::get_shell_command,
 };
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    agent_version::{AgentVersionInfo, check_npx_package_version, min_versions},
+    command::{
+        CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides,
+        apply_working_dir_env,
+    },
+    executors::{
+        AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor,
+        amp::extract_thread_id,
+    },
     logs::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryType, TodoItem,
         stderr_processor::normalize_stderr_logs,
-        utils::{EntryIndexProvider, patch::ConversationPatch},
+        utils::{EntryIndexProvider, output::output_to_string_lossy, patch::ConversationPatch},
     },
 };
 
@@ -37,18 +46,24 @@ fn base_command(claude_code_router: bool) -> &'static str {
 pub struct ClaudeCode {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub claude_code_router: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub plan: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dangerously_skip_permissions: Option<bool>,
+    /// Environment variables set to the worktree path at spawn, keyed by name with a
+    /// subdirectory relative to the worktree as value (empty for the worktree root)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir_env: Option<HashMap<String, String>>,
     #[serde(flatten)]
     pub cmd: CmdOverrides,
 }
 
 impl ClaudeCode {
-    fn build_command_builder(&self) -> CommandBuilder {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
         // If base_command_override is provided and claude_code_router is also set, log a warning
         if self.cmd.base_command_override.is_some() && self.claude_code_router.is_some() {
             tracing::warn!(
@@ -78,9 +93,9 @@ impl StandardCodingAgentExecutor for ClaudeCode {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let command_builder = self.build_command_builder();
+        let command_builder = self.build_command_builder()?;
         let base_command = command_builder.build_initial();
         let claude_command = if self.plan.unwrap_or(false) {
             create_watchkill_script(&base_command)
@@ -88,17 +103,13 @@ impl StandardCodingAgentExecutor for ClaudeCode {
             base_command
         };
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&claude_command);
+        let mut command = self.cmd.shell_command(current_dir, &claude_command);
+        apply_working_dir_env(&mut command, current_dir, self.working_dir_env.as_ref());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -116,9 +127,9 @@ impl StandardCodingAgentExecutor for ClaudeCode {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let command_builder = self.build_command_builder();
+        let command_builder = self.build_command_builder()?;
         // Build follow-up command with --resume {session_id}
         let base_command =
             command_builder.build_follow_up(&["--resume".to_string(), session_id.to_string()]);
@@ -128,17 +139,13 @@ impl StandardCodingAgentExecutor for ClaudeCode {
             base_command
         };
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&claude_command);
+        let mut command = self.cmd.shell_command(current_dir, &claude_command);
+        apply_working_dir_env(&mut command, current_dir, self.working_dir_env.as_ref());
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -170,6 +177,12 @@ impl StandardCodingAgentExecutor for ClaudeCode {
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
         dirs::home_dir().map(|home| home.join(".claude.json"))
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(
+            check_npx_package_version("@anthropic-ai/claude-code", min_versions::CLAUDE_CODE).await,
+        )
+    }
 }
 
 fn create_watchkill_script(command: &str) -> String {
@@ -199,7 +212,9 @@ exit "$exit_code"
 pub enum HistoryStrategy {
     // Claude-code format
     Default,
-    // Amp threads format which includes logs from previous executions
+    // Amp threads format, for a thread started by this execution
+    Amp,
+    // Amp threads format of a resumed thread, which includes logs from previous executions
     AmpResume,
 }
 
@@ -276,6 +291,13 @@ impl ClaudeLogProcessor {
                             if !session_id_extracted
                                 && let Some(session_id) = Self::extract_session_id(&claude_json)
                             {
+                                // Amp reports its thread id, which follow-ups continue from
+                                let session_id = match processor.strategy {
+                                    HistoryStrategy::Amp | HistoryStrategy::AmpResume => {
+                                        extract_thread_id(&session_id).unwrap_or(session_id)
+                                    }
+                                    HistoryStrategy::Default => session_id,
+                                };
                                 msg_store.push_session_id(session_id);
                                 session_id_extracted = true;
                             }
@@ -367,16 +389,22 @@ impl ClaudeLogProcessor {
                                     }
                                 }
                                 ClaudeJson::User { message, .. } => {
-                                    // Amp resume hack: if AmpResume and the user message contains plain text,
-                                    // clear all previous entries so UI shows only fresh context, and emit user text.
-                                    if matches!(processor.strategy, HistoryStrategy::AmpResume)
+                                    // Amp emits user prompts (text only, no tool results) as user text.
+                                    // Resume hack: a resumed thread replays its history, so each prompt
+                                    // clears all previous entries so UI shows only fresh context.
+                                    if matches!(
+                                        processor.strategy,
+                                        HistoryStrategy::Amp | HistoryStrategy::AmpResume
+                                    ) && !message.content.is_empty()
                                         && message
                                             .content
                                             .iter()
-                                            .any(|c| matches!(c, ClaudeContentItem::Text { .. }))
+                                            .all(|c| matches!(c, ClaudeContentItem::Text { .. }))
                                     {
                                         let cur = entry_index_provider.current();
-                                        if cur > 0 {
+                                        if matches!(processor.strategy, HistoryStrategy::AmpResume)
+                                            && cur > 0
+                                        {
                                             for _ in 0..cur {
                                                 msg_store.push_patch(
                                                     ConversationPatch::remove_diff(0.to_string()),
@@ -423,33 +451,31 @@ impl ClaudeLogProcessor {
                                             if is_command {
                                                 // For bash commands, attach result as CommandRun output where possible
                                                 // Prefer parsing Amp's claude-compatible Bash format: {"output":"...","exitCode":0}
-                                                let content_str = if let Some(s) = content.as_str()
-                                                {
-                                                    s.to_string()
-                                                } else {
-                                                    content.to_string()
-                                                };
+                                                let content_str = output_to_string_lossy(content);
 
                                                 let result = if let Ok(result) =
                                                     serde_json::from_str::<AmpBashResult>(
                                                         &content_str,
                                                     ) {
-                                                    Some(crate::logs::CommandRunResult {
-
-                                                        exit_status : Some(
+                                                    Some(crate::logs::CommandRunResult::truncated(
+                                                        &msg_store,
+                                                        tool_use_id,
+                                                        Some(
                                                             crate::logs::CommandExitStatus::ExitCode {
                                                                 code: result.exit_code,
                                                             },
                                                         ),
-                                                        output: Some(result.output)
-                                                    })
+                                                        Some(result.output),
+                                                    ))
                                                 } else {
-                                                    Some(crate::logs::CommandRunResult {
-                                                        exit_status: (*is_error).map(|is_error| {
+                                                    Some(crate::logs::CommandRunResult::truncated(
+                                                        &msg_store,
+                                                        tool_use_id,
+                                                        (*is_error).map(|is_error| {
                                                             crate::logs::CommandExitStatus::Success { success: !is_error }
                                                         }),
-                                                        output: Some(content_str)
-                                                    })
+                                                        Some(content_str),
+                                                    ))
                                                 };
 
                                                 let entry = NormalizedEntry {
@@ -468,6 +494,31 @@ impl ClaudeLogProcessor {
                                                     info.entry_index,
                                                     entry,
                                                 ));
+                                            } else if let ClaudeToolData::WebSearch {
+                                                query, ..
+                                            } = &info.tool_data
+                                            {
+                                                let entry = NormalizedEntry {
+                                                    timestamp: None,
+                                                    entry_type: NormalizedEntryType::ToolUse {
+                                                        tool_name: info.tool_name.clone(),
+                                                        action_type: ActionType::Tool {
+                                                            tool_name: info.tool_name.clone(),
+                                                            arguments: Some(
+                                                                serde_json::json!({ "query": query }),
+                                                            ),
+                                                            result: Some(Self::web_search_result(
+                                                                content,
+                                                            )),
+                                                        },
+                                                    },
+                                                    content: info.content.clone(),
+                                                    metadata: None,
+                                                };
+                                                msg_store.push_patch(ConversationPatch::replace(
+                                                    info.entry_index,
+                                                    entry,
+                                                ));
                                             } else {
                                                 // Show args and results for NotebookEdit and MCP tools
                                                 let tool_name =
@@ -706,6 +757,30 @@ impl ClaudeLogProcessor {
         }
     }
 
+    /// Extract the returned links from a WebSearch tool_result. Claude reports them as
+    /// a JSON array following a `Links:` label; anything else is normalized as usual.
+    fn web_search_result(content: &serde_json::Value) -> crate::logs::ToolResult {
+        let (r#type, value) = Self::normalize_claude_tool_result_value(content);
+        let links = value
+            .as_str()
+            .and_then(|text| text.split_once("Links:"))
+            .and_then(|(_, rest)| {
+                serde_json::Deserializer::from_str(rest.trim_start())
+                    .into_iter::<serde_json::Value>()
+                    .next()
+                    .and_then(Result::ok)
+            })
+            .filter(serde_json::Value::is_array);
+
+        match links {
+            Some(links) => crate::logs::ToolResult {
+                r#type: crate::logs::ToolResultValueType::Json,
+                value: links,
+            },
+            None => crate::logs::ToolResult { r#type, value },
+        }
+    }
+
     /// Normalize Claude tool_result content to either Markdown string or parsed JSON.
     /// - If content is a string that parses as JSON, return Json with parsed value.
     /// - If content is a string (non-JSON), return Markdown with the raw string.
@@ -815,6 +890,7 @@ impl ClaudeLogProcessor {
                             file_path,
                             &old_string.clone().unwrap_or_default(),
                             &new_string.clone().unwrap_or_default(),
+                            DEFAULT_CONTEXT_LINES,
                         ),
                         has_line_numbers: false,
                     }]
@@ -834,6 +910,7 @@ impl ClaudeLogProcessor {
                             Some(create_unified_diff_hunk(
                                 &edit.old_string.clone().unwrap_or_default(),
                                 &edit.new_string.clone().unwrap_or_default(),
+                                DEFAULT_CONTEXT_LINES,
                             ))
                         } else {
                             None
@@ -1503,10 +1580,15 @@ mod tests {
             claude_code_router: Some(false),
             plan: None,
             append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
             dangerously_skip_permissions: None,
+            working_dir_env: None,
             cmd: crate::command::CmdOverrides {
                 base_command_override: None,
                 additional_params: None,
+                raw_additional_params: None,
+                detached: None,
+                mcp_config_path_override: None,
             },
         };
         let msg_store = Arc::new(MsgStore::new());
@@ -1739,6 +1821,107 @@ mod tests {
         assert_eq!(entries[0].content, "Task: `Only prompt`");
     }
 
+    #[tokio::test]
+    async fn test_web_search_tool_result_attaches_links() {
+        let msg_store = Arc::new(MsgStore::new());
+        msg_store.push_stdout(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"ws1","name":"WebSearch","input":{"query":"rust axum sse"}}]}}"#.to_string() + "\n",
+        );
+        msg_store.push_stdout(
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"ws1","content":"Web search results for query: \"rust axum sse\"\n\nLinks: [{\"title\":\"Axum SSE\",\"url\":\"https://docs.rs/axum/latest/axum/response/sse/\"},{\"title\":\"Example\",\"url\":\"https://example.com/sse\"}]\n\nSummary follows."}]}}"#.to_string() + "\n",
+        );
+        msg_store.push_finished();
+
+        ClaudeLogProcessor::process_logs(
+            msg_store.clone(),
+            Path::new("/tmp/test-worktree"),
+            EntryIndexProvider::test_new(),
+            HistoryStrategy::Default,
+        );
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let replaced = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::JsonPatch(patch) => serde_json::to_value(patch).ok(),
+                _ => None,
+            })
+            .flat_map(|ops| ops.as_array().cloned().unwrap_or_default())
+            .rfind(|op| op["op"] == "replace")
+            .expect("tool_result should replace the tool_use entry");
+        let entry: NormalizedEntry =
+            serde_json::from_value(replaced["value"]["content"].clone()).unwrap();
+
+        assert_eq!(entry.content, "`rust axum sse`");
+        let NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type: ActionType::Tool {
+                arguments, result, ..
+            },
+        } = entry.entry_type
+        else {
+            panic!("expected a WebSearch tool entry");
+        };
+        assert_eq!(tool_name, "WebSearch");
+        assert_eq!(
+            arguments,
+            Some(serde_json::json!({ "query": "rust axum sse" }))
+        );
+        let result = result.expect("result attached");
+        assert!(matches!(
+            result.r#type,
+            crate::logs::ToolResultValueType::Json
+        ));
+        let links = result.value.as_array().expect("links array");
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[1]["url"], "https://example.com/sse");
+    }
+
+    #[tokio::test]
+    async fn test_bash_tool_result_decodes_non_utf8_output() {
+        let msg_store = Arc::new(MsgStore::new());
+        msg_store.push_stdout(
+            r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"tool_use","id":"b1","name":"Bash","input":{"command":"cat notes.txt"}}]}}"#.to_string() + "\n",
+        );
+        // "caf\xe9\n" from a Latin-1 locale, reported as bytes
+        msg_store.push_stdout(
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"b1","content":[99,97,102,233,10],"is_error":false}]}}"#.to_string() + "\n",
+        );
+        msg_store.push_finished();
+
+        ClaudeLogProcessor::process_logs(
+            msg_store.clone(),
+            Path::new("/tmp/test-worktree"),
+            EntryIndexProvider::test_new(),
+            HistoryStrategy::Default,
+        );
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let replaced = msg_store
+            .get_history()
+            .into_iter()
+            .filter_map(|msg| match msg {
+                LogMsg::JsonPatch(patch) => serde_json::to_value(patch).ok(),
+                _ => None,
+            })
+            .flat_map(|ops| ops.as_array().cloned().unwrap_or_default())
+            .rfind(|op| op["op"] == "replace")
+            .expect("tool_result should replace the tool_use entry");
+        let entry: NormalizedEntry =
+            serde_json::from_value(replaced["value"]["content"].clone()).unwrap();
+
+        let NormalizedEntryType::ToolUse {
+            action_type: ActionType::CommandRun { result, .. },
+            ..
+        } = entry.entry_type
+        else {
+            panic!("expected a Bash command entry");
+        };
+        let output = result.and_then(|r| r.output);
+        assert_eq!(output.as_deref(), Some("caf\u{FFFD}\n"));
+    }
+
     #[test]
     fn test_tool_result_parsing_ignored() {
         let tool_result_json = r#"{"type":"tool_result","result":"File content here","is_error":false,"session_id":"test123"}"#;
@@ -1,31 +1,36 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
-    process::Stdio,
     sync::Arc,
 };
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
+use json_patch::Patch;
 use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum_macros::AsRefStr;
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use utils::{
     diff::{concatenate_diff_hunks, extract_unified_diff_hunks},
     msg_store::MsgStore,
     path::make_path_relative,
-    shell::get_shell_command,
 };
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    agent_version::{AgentVersionInfo, check_npx_package_version, min_versions},
+    approvals::ApprovalDecision,
+    command::{
+        CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides,
+        apply_working_dir_env,
+    },
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
     logs::{
-        ActionType, FileChange, NormalizedEntry, NormalizedEntryType,
-        utils::{EntryIndexProvider, patch::ConversationPatch},
+        ActionType, FileChange, NormalizedEntry, NormalizedEntryType, TodoItem, TokenUsage,
+        utils::{EntryIndexProvider, output::deserialize_output_lossy, patch::ConversationPatch},
     },
 };
 
@@ -50,6 +55,16 @@ pub enum ApprovalPolicy {
     Never,
 }
 
+/// How much reasoning Codex asks the model for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, AsRefStr, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
 /// Handles session management for Codex executor
 pub struct SessionHandler;
 
@@ -206,6 +221,8 @@ impl SessionHandler {
 pub struct Codex {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sandbox: Option<SandboxMode>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -214,12 +231,49 @@ pub struct Codex {
     pub oss: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// Hide background events containing any of these substrings (defaults to retry notices)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignored_background_events: Option<Vec<String>>,
+    /// Environment variables set to the worktree path at spawn, keyed by name with a
+    /// subdirectory relative to the worktree as value (empty for the worktree root)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir_env: Option<HashMap<String, String>>,
     #[serde(flatten)]
     pub cmd: CmdOverrides,
 }
 
+/// Background events hidden unless overridden by `ignored_background_events`
+const DEFAULT_IGNORED_BACKGROUND_EVENTS: &[&str] = &["stream error", "retrying"];
+
+fn is_ignored_background_event(message: &str, ignored: &[String]) -> bool {
+    ignored
+        .iter()
+        .any(|substring| !substring.is_empty() && message.contains(substring.as_str()))
+}
+
+/// A Codex protocol event the container acts on while the run is live
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtoEvent {
+    /// Codex is blocked until the command with this call id is approved or denied
+    ApprovalRequested(String),
+    /// The turn is over; codex waits for another submission
+    TaskComplete,
+}
+
 impl Codex {
-    fn build_command_builder(&self) -> CommandBuilder {
+    /// On-request approvals need answers on stdin, which `codex exec` never
+    /// reads, so those runs use the `codex proto` protocol instead
+    pub fn uses_proto(&self) -> bool {
+        self.approval == Some(ApprovalPolicy::OnRequest)
+    }
+
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        if self.uses_proto() {
+            return self.build_proto_command_builder();
+        }
+
         let mut builder = CommandBuilder::new("npx -y @openai/codex exec")
             .params(["--json", "--skip-git-repo-check"]);
 
@@ -242,8 +296,127 @@ impl Codex {
             builder = builder.extend_params(["--model", model]);
         }
 
+        if let Some(effort) = self.reasoning_effort {
+            builder = builder.extend_params([
+                "-c".to_string(),
+                format!("model_reasoning_effort={}", effort.as_ref()),
+            ]);
+        }
+
+        apply_overrides(builder, &self.cmd)
+    }
+
+    /// `codex proto` only takes `-c` config overrides
+    fn build_proto_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        let mut overrides = vec!["approval_policy=on-request".to_string()];
+        if let Some(sandbox) = &self.sandbox {
+            overrides.push(format!("sandbox_mode={}", sandbox.as_ref()));
+        }
+        if self.oss.unwrap_or(false) {
+            overrides.push("model_provider=oss".to_string());
+        }
+        if let Some(model) = &self.model {
+            overrides.push(format!("model={model}"));
+        }
+        if let Some(effort) = self.reasoning_effort {
+            overrides.push(format!("model_reasoning_effort={}", effort.as_ref()));
+        }
+
+        let builder = CommandBuilder::new("npx -y @openai/codex proto").params(
+            overrides
+                .into_iter()
+                .flat_map(|value| ["-c".to_string(), value]),
+        );
         apply_overrides(builder, &self.cmd)
     }
+
+    /// Feed the prompt in, then close the pipe so codex sees EOF. In proto mode
+    /// the prompt is a submission and the pipe stays open for
+    /// [`Codex::approval_response`] and [`Codex::shutdown_submission`].
+    async fn feed_prompt(
+        &self,
+        child: &mut AsyncGroupChild,
+        prompt: &str,
+    ) -> Result<(), ExecutorError> {
+        if self.uses_proto() {
+            if let Some(stdin) = child.inner().stdin.as_mut() {
+                stdin
+                    .write_all(Self::user_input_submission(prompt).as_bytes())
+                    .await?;
+                stdin.flush().await?;
+            }
+            return Ok(());
+        }
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            stdin.write_all(prompt.as_bytes()).await?;
+            stdin.shutdown().await?;
+        }
+        Ok(())
+    }
+
+    /// The submission answering an `exec_approval_request`, as a JSON line for
+    /// codex's stdin
+    pub fn approval_response(call_id: &str, decision: ApprovalDecision) -> String {
+        let submission = serde_json::json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "op": {
+                "type": "exec_approval",
+                "id": call_id,
+                "decision": decision,
+            },
+        });
+        format!("{submission}\n")
+    }
+
+    /// The submission starting a proto turn with `prompt`
+    fn user_input_submission(prompt: &str) -> String {
+        let submission = serde_json::json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "op": {
+                "type": "user_input",
+                "items": [{ "type": "text", "text": prompt }],
+            },
+        });
+        format!("{submission}\n")
+    }
+
+    /// The submission asking `codex proto` to exit, which it only does on
+    /// request
+    pub fn shutdown_submission() -> String {
+        let submission = serde_json::json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "op": { "type": "shutdown" },
+        });
+        format!("{submission}\n")
+    }
+
+    /// The event on a proto stdout line that needs an answer from the container
+    pub fn proto_event(line: &str) -> Option<ProtoEvent> {
+        match serde_json::from_str::<CodexJson>(line.trim()).ok()? {
+            CodexJson::StructuredMessage {
+                msg:
+                    CodexMsgContent::ExecApprovalRequest {
+                        call_id: Some(call_id),
+                        ..
+                    },
+                ..
+            } => Some(ProtoEvent::ApprovalRequested(call_id)),
+            CodexJson::StructuredMessage {
+                msg: CodexMsgContent::TaskComplete { .. },
+                ..
+            } => Some(ProtoEvent::TaskComplete),
+            _ => None,
+        }
+    }
+
+    fn ignored_background_events(&self) -> Vec<String> {
+        self.ignored_background_events.clone().unwrap_or_else(|| {
+            DEFAULT_IGNORED_BACKGROUND_EVENTS
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        })
+    }
 }
 
 #[async_trait]
@@ -252,31 +425,21 @@ impl StandardCodingAgentExecutor for Codex {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let codex_command = self.build_command_builder().build_initial();
-
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&codex_command)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("RUST_LOG", "info");
+        let codex_command = self.build_command_builder()?.build_initial();
 
-        let mut child = command.group_spawn()?;
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        // Feed the prompt in, then close the pipe so codex sees EOF
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        let mut command = self.cmd.shell_command(current_dir, &codex_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1").env("RUST_LOG", "info");
+        apply_working_dir_env(&mut command, current_dir, self.working_dir_env.as_ref());
+
+        let mut child = command.group_spawn()?;
+        self.feed_prompt(&mut child, &combined_prompt).await?;
 
         Ok(child)
     }
@@ -286,38 +449,28 @@ impl StandardCodingAgentExecutor for Codex {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         // Fork rollout: copy and assign a new session id so each execution has a unique session
         let (rollout_file_path, _new_session_id) = SessionHandler::fork_rollout_file(session_id)
             .map_err(|e| ExecutorError::SpawnError(std::io::Error::other(e)))?;
 
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let codex_command = self.build_command_builder().build_follow_up(&[
+        let codex_command = self.build_command_builder()?.build_follow_up(&[
             "-c".to_string(),
             format!("experimental_resume={}", rollout_file_path.display()),
         ]);
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&codex_command)
-            .env("NODE_NO_WARNINGS", "1")
-            .env("RUST_LOG", "info");
+        let mut command = self.cmd.shell_command(current_dir, &codex_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1").env("RUST_LOG", "info");
+        apply_working_dir_env(&mut command, current_dir, self.working_dir_env.as_ref());
 
         let mut child = command.group_spawn()?;
-
-        // Feed the prompt in, then close the pipe so codex sees EOF
-        if let Some(mut stdin) = child.inner().stdin.take() {
-            stdin.write_all(combined_prompt.as_bytes()).await?;
-            stdin.shutdown().await?;
-        }
+        self.feed_prompt(&mut child, &combined_prompt).await?;
 
         Ok(child)
     }
@@ -330,6 +483,8 @@ impl StandardCodingAgentExecutor for Codex {
 
         // Process stdout logs (Codex's JSONL output)
         let current_dir = current_dir.to_path_buf();
+        let ignored_background_events = self.ignored_background_events();
+        let reasoning_effort = self.reasoning_effort;
         tokio::spawn(async move {
             let mut stream = msg_store.stdout_lines_stream();
             use std::collections::HashMap;
@@ -341,6 +496,7 @@ impl StandardCodingAgentExecutor for Codex {
                 String,
                 (usize, String, Option<serde_json::Value>, String),
             > = HashMap::new();
+            let mut reasoning = ReasoningStream::default();
 
             while let Some(Ok(line)) = stream.next().await {
                 let trimmed = line.trim();
@@ -348,10 +504,35 @@ impl StandardCodingAgentExecutor for Codex {
                     continue;
                 }
 
-                if let Ok(cj) = serde_json::from_str::<CodexJson>(trimmed) {
+                if let Ok(mut cj) = serde_json::from_str::<CodexJson>(trimmed) {
+                    cj.fill_reasoning_effort(reasoning_effort);
+                    if let Some(usage) = cj.token_usage() {
+                        msg_store.push_patch(ConversationPatch::token_usage(usage));
+                    }
+                    // Proto runs report their session here rather than on stderr
+                    if let CodexJson::StructuredMessage {
+                        msg: CodexMsgContent::SessionConfigured { session_id },
+                        ..
+                    } = &cj
+                    {
+                        msg_store.push_session_id(session_id.clone());
+                    }
+
                     // Handle result-carrying events that require replacement
                     match &cj {
                         CodexJson::StructuredMessage { msg, .. } => match msg {
+                            CodexMsgContent::BackgroundEvent { message }
+                                if is_ignored_background_event(
+                                    message,
+                                    &ignored_background_events,
+                                ) => {}
+                            CodexMsgContent::AgentReasoningRawContentDelta { delta } => {
+                                msg_store
+                                    .push_patch(reasoning.push_delta(delta, &entry_index_provider));
+                            }
+                            CodexMsgContent::AgentReasoning { text } => {
+                                msg_store.push_patch(reasoning.finish(text, &entry_index_provider));
+                            }
                             CodexMsgContent::ExecCommandBegin {
                                 call_id, command, ..
                             } => {
@@ -446,10 +627,14 @@ impl StandardCodingAgentExecutor for Codex {
                                             tool_name,
                                             action_type: ActionType::CommandRun {
                                                 command: prev_command,
-                                                result: Some(crate::logs::CommandRunResult {
-                                                    exit_status,
-                                                    output,
-                                                }),
+                                                result: Some(
+                                                    crate::logs::CommandRunResult::truncated(
+                                                        &msg_store,
+                                                        cid,
+                                                        exit_status,
+                                                        output,
+                                                    ),
+                                                ),
                                             },
                                         },
                                         content: prev_content,
@@ -559,6 +744,55 @@ impl StandardCodingAgentExecutor for Codex {
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
         dirs::home_dir().map(|home| home.join(".codex").join("config.toml"))
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(check_npx_package_version("@openai/codex", min_versions::CODEX).await)
+    }
+}
+
+/// Coalesces `agent_reasoning_raw_content_delta` events into a single Thinking
+/// entry that is replaced as deltas arrive and finalized by `agent_reasoning`.
+#[derive(Debug, Default)]
+struct ReasoningStream {
+    /// Entry index and text accumulated so far for the reasoning being streamed
+    in_progress: Option<(usize, String)>,
+}
+
+impl ReasoningStream {
+    fn thinking_entry(content: String) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::Thinking,
+            content,
+            metadata: None,
+        }
+    }
+
+    fn push_delta(&mut self, delta: &str, entry_index_provider: &EntryIndexProvider) -> Patch {
+        match &mut self.in_progress {
+            Some((idx, text)) => {
+                text.push_str(delta);
+                ConversationPatch::replace(*idx, Self::thinking_entry(text.clone()))
+            }
+            None => {
+                let idx = entry_index_provider.next();
+                self.in_progress = Some((idx, delta.to_string()));
+                ConversationPatch::add_normalized_entry(
+                    idx,
+                    Self::thinking_entry(delta.to_string()),
+                )
+            }
+        }
+    }
+
+    /// The complete reasoning replaces the streamed entry, so the text is not duplicated
+    fn finish(&mut self, text: &str, entry_index_provider: &EntryIndexProvider) -> Patch {
+        let entry = Self::thinking_entry(text.to_string());
+        match self.in_progress.take() {
+            Some((idx, _)) => ConversationPatch::replace(idx, entry),
+            None => ConversationPatch::add_normalized_entry(entry_index_provider.next(), entry),
+        }
+    }
 }
 
 // Data structures for parsing Codex's JSON output format
@@ -650,7 +884,10 @@ pub enum CodexMsgContent {
     #[serde(rename = "exec_command_end")]
     ExecCommandEnd {
         call_id: Option<String>,
+        // Output may arrive as raw bytes when it is not valid UTF-8
+        #[serde(default, deserialize_with = "deserialize_output_lossy")]
         stdout: Option<String>,
+        #[serde(default, deserialize_with = "deserialize_output_lossy")]
         stderr: Option<String>,
         // Codex protocol has exit_code + duration; CLI may provide success; keep optional
         success: Option<bool>,
@@ -708,6 +945,9 @@ pub enum CodexMsgContent {
         value: serde_json::Value,
     },
 
+    #[serde(rename = "session_configured")]
+    SessionConfigured { session_id: String },
+
     #[serde(rename = "task_started")]
     TaskStarted,
     #[serde(rename = "task_complete")]
@@ -739,7 +979,74 @@ pub enum CodexFileChange {
     },
 }
 
+/// Payload of a `plan_update` event
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CodexPlan {
+    #[serde(default)]
+    pub explanation: Option<String>,
+    pub plan: Vec<CodexPlanStep>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct CodexPlanStep {
+    pub step: String,
+    pub status: String,
+}
+
+impl CodexPlanStep {
+    /// Map Codex step statuses onto the ones used by `TodoItem`
+    fn todo_status(&self) -> String {
+        match self.status.to_lowercase().as_str() {
+            "in_progress" | "in-progress" | "inprogress" => "in_progress".to_string(),
+            "completed" | "complete" | "done" => "completed".to_string(),
+            "pending" | "" => "pending".to_string(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl CodexPlan {
+    fn to_todos(&self) -> Vec<TodoItem> {
+        self.plan
+            .iter()
+            .map(|step| TodoItem {
+                content: step.step.clone(),
+                status: step.todo_status(),
+                priority: None,
+            })
+            .collect()
+    }
+}
+
 impl CodexJson {
+    /// Token usage to report after this event: reset when a new turn starts and
+    /// replaced by the latest `token_count` event
+    pub fn token_usage(&self) -> Option<TokenUsage> {
+        match self {
+            CodexJson::StructuredMessage {
+                msg: CodexMsgContent::TaskStarted,
+                ..
+            } => Some(TokenUsage::default()),
+            CodexJson::StructuredMessage {
+                msg:
+                    CodexMsgContent::TokenCount {
+                        input_tokens,
+                        cached_input_tokens,
+                        output_tokens,
+                        total_tokens,
+                        ..
+                    },
+                ..
+            } => Some(TokenUsage {
+                input_tokens: input_tokens.unwrap_or_default(),
+                output_tokens: output_tokens.unwrap_or_default(),
+                cached_input_tokens: cached_input_tokens.unwrap_or_default(),
+                total_tokens: total_tokens.unwrap_or_default(),
+            }),
+            _ => None,
+        }
+    }
+
     /// Convert to normalized entries
     pub fn to_normalized_entries(&self, current_dir: &Path) -> Option<Vec<NormalizedEntry>> {
         match self {
@@ -843,10 +1150,10 @@ impl CodexJson {
                     }
                     CodexMsgContent::McpToolCallBegin { .. } => None,
                     CodexMsgContent::ExecApprovalRequest {
+                        call_id,
                         command,
                         cwd,
                         reason,
-                        ..
                     } => {
                         let command_str = command.join(" ");
                         let mut parts = vec![format!("command: `{}`", command_str)];
@@ -858,9 +1165,16 @@ impl CodexJson {
                         }
                         let content =
                             format!("Execution approval requested — {}", parts.join("  "));
+                        // Without a call id there is nothing to answer, so only report it
+                        let entry_type = match call_id {
+                            Some(call_id) => NormalizedEntryType::PendingApproval {
+                                call_id: call_id.clone(),
+                            },
+                            None => NormalizedEntryType::SystemMessage,
+                        };
                         Some(vec![NormalizedEntry {
                             timestamp: None,
-                            entry_type: NormalizedEntryType::SystemMessage,
+                            entry_type,
                             content,
                             metadata: None,
                         }])
@@ -886,12 +1200,39 @@ impl CodexJson {
                             metadata: None,
                         }])
                     }
-                    CodexMsgContent::PlanUpdate { value } => Some(vec![NormalizedEntry {
+                    CodexMsgContent::BackgroundEvent { message } => Some(vec![NormalizedEntry {
                         timestamp: None,
                         entry_type: NormalizedEntryType::SystemMessage,
-                        content: "Plan update".to_string(),
-                        metadata: Some(value.clone()),
+                        content: message.clone(),
+                        metadata: None,
                     }]),
+                    CodexMsgContent::PlanUpdate { value } => {
+                        let entry = match serde_json::from_value::<CodexPlan>(value.clone()) {
+                            Ok(plan) => NormalizedEntry {
+                                timestamp: None,
+                                entry_type: NormalizedEntryType::ToolUse {
+                                    tool_name: "plan".to_string(),
+                                    action_type: ActionType::TodoManagement {
+                                        todos: plan.to_todos(),
+                                        operation: "update".to_string(),
+                                    },
+                                },
+                                content: plan
+                                    .explanation
+                                    .filter(|e| !e.trim().is_empty())
+                                    .unwrap_or_else(|| "Plan updated".to_string()),
+                                metadata: Some(value.clone()),
+                            },
+                            // Unrecognised plan schema; keep it visible as before
+                            Err(_) => NormalizedEntry {
+                                timestamp: None,
+                                entry_type: NormalizedEntryType::SystemMessage,
+                                content: "Plan update".to_string(),
+                                metadata: Some(value.clone()),
+                            },
+                        };
+                        Some(vec![entry])
+                    }
 
                     // Ignored message types
                     CodexMsgContent::AgentReasoningRawContent { .. }
@@ -901,17 +1242,28 @@ impl CodexJson {
                     | CodexMsgContent::ExecCommandEnd { .. }
                     | CodexMsgContent::PatchApplyEnd { .. }
                     | CodexMsgContent::McpToolCallEnd { .. }
+                    | CodexMsgContent::SessionConfigured { .. }
                     | CodexMsgContent::TaskStarted
                     | CodexMsgContent::TaskComplete { .. }
                     | CodexMsgContent::TokenCount { .. }
                     | CodexMsgContent::TurnDiff { .. }
-                    | CodexMsgContent::BackgroundEvent { .. }
                     | CodexMsgContent::Unknown => None,
                 }
             }
         }
     }
 
+    /// Show the configured reasoning effort when Codex's config message omits it
+    fn fill_reasoning_effort(&mut self, configured: Option<ReasoningEffort>) {
+        if let CodexJson::SystemConfig {
+            reasoning_effort: reasoning_effort @ None,
+            ..
+        } = self
+        {
+            *reasoning_effort = configured.map(|effort| effort.as_ref().to_string());
+        }
+    }
+
     /// Format system configuration message for display
     fn format_config_message(&self) -> Option<String> {
         if let CodexJson::SystemConfig {
@@ -951,7 +1303,9 @@ impl CodexJson {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::logs::{ActionType, NormalizedEntry, NormalizedEntryType};
+    use crate::logs::{
+        ActionType, NormalizedEntry, NormalizedEntryType, utils::output::DEFAULT_OUTPUT_LIMIT_BYTES,
+    };
 
     /// Test helper that directly tests the JSON parsing functions
     fn parse_test_json_lines(input: &str) -> Vec<NormalizedEntry> {
@@ -1009,6 +1363,47 @@ mod tests {
         assert_eq!(session_id, None);
     }
 
+    #[test]
+    fn test_reasoning_deltas_coalesce_into_one_entry() {
+        let provider = EntryIndexProvider::test_new();
+        let mut reasoning = ReasoningStream::default();
+        let mut doc = serde_json::json!({ "entries": [] });
+        let lines = [
+            r#"{"id":"1","msg":{"type":"agent_reasoning_raw_content_delta","delta":"Checking "}}"#,
+            r#"{"id":"1","msg":{"type":"agent_reasoning_raw_content_delta","delta":"the "}}"#,
+            r#"{"id":"1","msg":{"type":"agent_reasoning_raw_content_delta","delta":"tree"}}"#,
+            r#"{"id":"1","msg":{"type":"agent_reasoning","text":"Checking the tree"}}"#,
+        ];
+
+        for (i, line) in lines.iter().enumerate() {
+            let patch = match test_codex_json_parsing(line).unwrap() {
+                CodexJson::StructuredMessage {
+                    msg: CodexMsgContent::AgentReasoningRawContentDelta { delta },
+                    ..
+                } => reasoning.push_delta(&delta, &provider),
+                CodexJson::StructuredMessage {
+                    msg: CodexMsgContent::AgentReasoning { text },
+                    ..
+                } => reasoning.finish(&text, &provider),
+                other => panic!("unexpected event {other:?}"),
+            };
+            json_patch::patch(&mut doc, &patch).unwrap();
+
+            let entries = doc["entries"].as_array().unwrap();
+            assert_eq!(entries.len(), 1, "after event {i}");
+        }
+
+        let entry = &doc["entries"][0]["content"];
+        assert_eq!(entry["entry_type"]["type"], "thinking");
+        assert_eq!(entry["content"], "Checking the tree");
+        assert_eq!(provider.current(), 1);
+
+        // A later reasoning without deltas gets its own entry
+        let patch = reasoning.finish("Next step", &provider);
+        json_patch::patch(&mut doc, &patch).unwrap();
+        assert_eq!(doc["entries"].as_array().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_normalize_logs_basic() {
         let logs = r#"{"id":"1","msg":{"type":"task_started"}}
@@ -1060,6 +1455,36 @@ mod tests {
         assert_eq!(entries.len(), 0);
     }
 
+    #[test]
+    fn test_token_usage_tracks_latest_count() {
+        let logs = r#"{"id":"1","msg":{"type":"task_started"}}
+{"id":"1","msg":{"type":"token_count","input_tokens":1674,"cached_input_tokens":1627,"output_tokens":384,"reasoning_output_tokens":384,"total_tokens":2058}}
+{"id":"1","msg":{"type":"agent_message","message":"Working on it"}}
+{"id":"1","msg":{"type":"token_count","input_tokens":3120,"cached_input_tokens":2944,"output_tokens":512,"reasoning_output_tokens":256,"total_tokens":3632}}
+{"id":"1","msg":{"type":"task_complete","last_agent_message":"Done!"}}"#;
+
+        let latest_usage = |logs: &str| {
+            logs.lines()
+                .filter_map(|line| serde_json::from_str::<CodexJson>(line).ok())
+                .filter_map(|cj| cj.token_usage())
+                .last()
+        };
+
+        assert_eq!(
+            latest_usage(logs),
+            Some(TokenUsage {
+                input_tokens: 3120,
+                output_tokens: 512,
+                cached_input_tokens: 2944,
+                total_tokens: 3632,
+            })
+        );
+
+        // A follow-up turn starts from zero until its first token count
+        let follow_up = format!("{logs}\n{}", r#"{"id":"2","msg":{"type":"task_started"}}"#);
+        assert_eq!(latest_usage(&follow_up), Some(TokenUsage::default()));
+    }
+
     #[test]
     fn test_normalize_logs_malformed_json() {
         let logs = r#"{"id":"1","msg":{"type":"task_started"}}
@@ -1228,6 +1653,113 @@ invalid json line here
         assert!(entries[0].content.contains("README.md"));
     }
 
+    #[test]
+    fn test_exec_command_end_decodes_non_utf8_output() {
+        // stdout is "caf\xe9\n" from a Latin-1 locale, reported as bytes
+        let json = r#"{"id":"1","msg":{"type":"exec_command_end","call_id":"call_1","stdout":[99,97,102,233,10],"stderr":"warning\n","exit_code":0}}"#;
+
+        let parsed = test_codex_json_parsing(json).unwrap();
+        let CodexJson::StructuredMessage {
+            msg: CodexMsgContent::ExecCommandEnd { stdout, stderr, .. },
+            ..
+        } = parsed
+        else {
+            panic!("expected exec_command_end");
+        };
+        assert_eq!(stdout.as_deref(), Some("caf\u{FFFD}\n"));
+        assert_eq!(stderr.as_deref(), Some("warning\n"));
+    }
+
+    #[tokio::test]
+    async fn test_exec_command_end_truncates_long_output() {
+        let executor: Codex = serde_json::from_str("{}").unwrap();
+        let stdout = "0123456789".repeat(DEFAULT_OUTPUT_LIMIT_BYTES / 5);
+        let end = serde_json::json!({
+            "id": "1",
+            "msg": {
+                "type": "exec_command_end",
+                "call_id": "call_long_output",
+                "stdout": stdout,
+                "stderr": "",
+                "exit_code": 0,
+            },
+        });
+        let raw_logs = [
+            r#"{"id":"1","msg":{"type":"exec_command_begin","call_id":"call_long_output","command":["bash","-lc","cat big.log"],"cwd":"/tmp"}}"#.to_string(),
+            end.to_string(),
+        ]
+        .into_iter()
+        .map(|line| utils::log_msg::LogMsg::Stdout(format!("{line}\n")))
+        .collect();
+        let store = crate::logs::utils::replay::raw_log_store(raw_logs);
+        let full_outputs = store.full_outputs();
+        executor.normalize_logs(store.clone(), Path::new("/tmp"));
+        let entries = crate::logs::utils::replay::collect_entries(store).await;
+
+        let Some(NormalizedEntryType::ToolUse {
+            action_type:
+                ActionType::CommandRun {
+                    result: Some(result),
+                    ..
+                },
+            ..
+        }) = entries.first().map(|entry| &entry.entry_type)
+        else {
+            panic!("expected a command entry, got {entries:?}");
+        };
+        let output = result.output.as_deref().unwrap();
+        assert!(output.len() < stdout.len());
+        assert!(output.starts_with(&stdout[..DEFAULT_OUTPUT_LIMIT_BYTES]));
+        assert!(output.contains("output truncated"));
+        assert_eq!(result.full_output_id.as_deref(), Some("call_long_output"));
+        assert_eq!(
+            full_outputs.get("call_long_output").as_deref(),
+            Some(stdout.as_str())
+        );
+    }
+
+    #[test]
+    fn test_plan_update_becomes_todo_entry() {
+        let logs = r#"{"id":"1","msg":{"type":"plan_update","explanation":null,"plan":[{"step":"Inspect the repository layout","status":"completed"},{"step":"Add the retry endpoint","status":"in_progress"},{"step":"Write tests","status":"pending"}]}}"#;
+
+        let entries = parse_test_json_lines(logs);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "Plan updated");
+        assert!(entries[0].metadata.as_ref().unwrap()["plan"].is_array());
+
+        let NormalizedEntryType::ToolUse {
+            tool_name,
+            action_type: ActionType::TodoManagement { todos, operation },
+        } = &entries[0].entry_type
+        else {
+            panic!("expected a todo entry");
+        };
+        assert_eq!(tool_name, "plan");
+        assert_eq!(operation, "update");
+        let items: Vec<(&str, &str)> = todos
+            .iter()
+            .map(|t| (t.content.as_str(), t.status.as_str()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Inspect the repository layout", "completed"),
+                ("Add the retry endpoint", "in_progress"),
+                ("Write tests", "pending"),
+            ]
+        );
+
+        // An unknown payload shape still shows up as a system message
+        let entries = parse_test_json_lines(
+            r#"{"id":"1","msg":{"type":"plan_update","steps":"unexpected"}}"#,
+        );
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+    }
+
     #[test]
     fn test_normalize_logs_skip_task_messages() {
         let logs = r#"{"id":"1","msg":{"type":"task_started"}}
@@ -1305,4 +1837,141 @@ invalid json line here
         let entries = parsed.to_normalized_entries(&current_dir);
         assert!(entries.is_none()); // Should return None
     }
+
+    #[test]
+    fn test_background_event_surfaces_as_system_message() {
+        let logs = r#"{"id":"1","msg":{"type":"background_event","message":"Installing ripgrep for faster search"}}"#;
+
+        let entries = parse_test_json_lines(logs);
+
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert_eq!(entries[0].content, "Installing ripgrep for faster search");
+    }
+
+    #[test]
+    fn test_exec_approval_request_becomes_pending_approval() {
+        let logs = r#"{"id":"1","msg":{"type":"exec_approval_request","call_id":"call_42","command":["rm","-rf","target"],"cwd":"/tmp","reason":"clean build"}}"#;
+
+        let entries = parse_test_json_lines(logs);
+
+        assert_eq!(entries.len(), 1);
+        match &entries[0].entry_type {
+            NormalizedEntryType::PendingApproval { call_id } => assert_eq!(call_id, "call_42"),
+            other => panic!("expected a pending approval, got {other:?}"),
+        }
+        assert!(entries[0].content.contains("`rm -rf target`"));
+
+        let response: serde_json::Value = serde_json::from_str(&Codex::approval_response(
+            "call_42",
+            ApprovalDecision::Denied,
+        ))
+        .unwrap();
+        assert_eq!(response["op"]["type"], "exec_approval");
+        assert_eq!(response["op"]["id"], "call_42");
+        assert_eq!(response["op"]["decision"], "denied");
+    }
+
+    #[test]
+    fn test_ignored_background_events() {
+        let ignored: Vec<String> = DEFAULT_IGNORED_BACKGROUND_EVENTS
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(is_ignored_background_event(
+            "stream error: connection reset; retrying 1/5 in 200ms",
+            &ignored
+        ));
+        assert!(!is_ignored_background_event(
+            "Installing ripgrep for faster search",
+            &ignored
+        ));
+        assert!(!is_ignored_background_event("anything", &[String::new()]));
+    }
+
+    #[test]
+    fn test_reasoning_effort_command_construction() {
+        let base = "npx -y @openai/codex exec --json --skip-git-repo-check";
+        let codex: Codex = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(codex.build_command_builder().unwrap().build_initial(), base);
+
+        for effort in ["low", "medium", "high"] {
+            let codex: Codex =
+                serde_json::from_value(serde_json::json!({ "reasoning_effort": effort })).unwrap();
+            assert_eq!(
+                codex.build_command_builder().unwrap().build_initial(),
+                format!("{base} -c model_reasoning_effort={effort}")
+            );
+        }
+    }
+
+    #[test]
+    fn test_on_request_approvals_use_proto() {
+        let codex: Codex = serde_json::from_value(serde_json::json!({
+            "approval": "on-request",
+            "sandbox": "workspace-write",
+            "model": "o3",
+        }))
+        .unwrap();
+        assert_eq!(
+            codex.build_command_builder().unwrap().build_initial(),
+            "npx -y @openai/codex proto -c approval_policy=on-request \
+             -c sandbox_mode=workspace-write -c model=o3"
+        );
+
+        let submission: serde_json::Value =
+            serde_json::from_str(&Codex::user_input_submission("fix the build")).unwrap();
+        assert_eq!(submission["op"]["type"], "user_input");
+        assert_eq!(submission["op"]["items"][0]["text"], "fix the build");
+    }
+
+    #[test]
+    fn test_proto_events() {
+        assert_eq!(
+            Codex::proto_event(
+                r#"{"id":"1","msg":{"type":"exec_approval_request","call_id":"call_42","command":["ls"],"cwd":"/tmp"}}"#
+            ),
+            Some(ProtoEvent::ApprovalRequested("call_42".to_string()))
+        );
+        assert_eq!(
+            Codex::proto_event(
+                r#"{"id":"1","msg":{"type":"task_complete","last_agent_message":"done"}}"#
+            ),
+            Some(ProtoEvent::TaskComplete)
+        );
+        assert_eq!(
+            Codex::proto_event(r#"{"id":"1","msg":{"type":"agent_message","message":"hi"}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_reasoning_effort_is_rejected() {
+        let result =
+            serde_json::from_value::<Codex>(serde_json::json!({ "reasoning_effort": "maximum" }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_config_message_falls_back_to_configured_reasoning_effort() {
+        let current_dir = PathBuf::from("/tmp");
+        let mut parsed = test_codex_json_parsing(r#"{"model":"codex-mini-latest"}"#).unwrap();
+        parsed.fill_reasoning_effort(Some(ReasoningEffort::High));
+        let entries = parsed.to_normalized_entries(&current_dir).unwrap();
+        assert_eq!(
+            entries[0].content,
+            "model: codex-mini-latest  reasoning effort: high"
+        );
+
+        // The effort Codex reports wins over the configured one
+        let mut parsed =
+            test_codex_json_parsing(r#"{"model":"codex-mini-latest","reasoning effort":"medium"}"#)
+                .unwrap();
+        parsed.fill_reasoning_effort(Some(ReasoningEffort::High));
+        let entries = parsed.to_normalized_entries(&current_dir).unwrap();
+        assert!(entries[0].content.contains("reasoning effort: medium"));
+    }
 }
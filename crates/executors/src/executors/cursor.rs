@@ -1,26 +1,27 @@
 use core::str;
-use std::{path::Path, process::Stdio, sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use utils::{
     diff::{
-        concatenate_diff_hunks, create_unified_diff, create_unified_diff_hunk,
-        extract_unified_diff_hunks,
+        DEFAULT_CONTEXT_LINES, concatenate_diff_hunks, create_unified_diff,
+        create_unified_diff_hunk, extract_unified_diff_hunks,
     },
     msg_store::MsgStore,
     path::make_path_relative,
-    shell::{get_shell_command, resolve_executable_path},
+    shell::resolve_executable_path,
 };
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    agent_version::{AgentVersionInfo, check_binary_version, min_versions},
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides},
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
     logs::{
         ActionType, FileChange, NormalizedEntry, NormalizedEntryType, TodoItem,
         plain_text_processor::PlainTextLogProcessor,
@@ -32,6 +33,8 @@ use crate::{
 pub struct Cursor {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub force: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -41,7 +44,7 @@ pub struct Cursor {
 }
 
 impl Cursor {
-    fn build_command_builder(&self) -> CommandBuilder {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
         let mut builder =
             CommandBuilder::new("cursor-agent").params(["-p", "--output-format=stream-json"]);
 
@@ -63,21 +66,16 @@ impl StandardCodingAgentExecutor for Cursor {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let agent_cmd = self.build_command_builder().build_initial();
+        let agent_cmd = self.build_command_builder()?.build_initial();
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&agent_cmd);
+        let mut command = self.cmd.shell_command(current_dir, &agent_cmd);
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -94,23 +92,18 @@ impl StandardCodingAgentExecutor for Cursor {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
         let agent_cmd = self
-            .build_command_builder()
+            .build_command_builder()?
             .build_follow_up(&["--resume".to_string(), session_id.to_string()]);
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&agent_cmd);
+        let mut command = self.cmd.shell_command(current_dir, &agent_cmd);
+        env.apply_to_command(&mut command);
 
         let mut child = command.group_spawn()?;
 
@@ -329,6 +322,7 @@ impl StandardCodingAgentExecutor for Cursor {
                                     result: Some(crate::logs::CommandRunResult {
                                         exit_status,
                                         output,
+                                        full_output_id: None,
                                     }),
                                 };
                             } else if let CursorToolCall::Mcp { args, result } = &tool_call {
@@ -382,8 +376,28 @@ impl StandardCodingAgentExecutor for Cursor {
                         }
                     }
 
+                    CursorJson::Result {
+                        is_error: Some(true),
+                        result,
+                        ..
+                    } => {
+                        let content = match result {
+                            Some(serde_json::Value::String(text)) => text.clone(),
+                            Some(value) => value.to_string(),
+                            None => "Cursor agent reported an error".to_string(),
+                        };
+                        let entry = NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::ErrorMessage,
+                            content,
+                            metadata: None,
+                        };
+                        let id = entry_index_provider.next();
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(id, entry));
+                    }
+
                     CursorJson::Result { .. } => {
-                        // no-op; metadata-only events not surfaced
+                        // no-op; successful run metadata is not surfaced
                     }
 
                     CursorJson::Unknown => {
@@ -409,6 +423,10 @@ impl StandardCodingAgentExecutor for Cursor {
     async fn check_availability(&self) -> bool {
         resolve_executable_path("cursor-agent").is_some()
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(check_binary_version("cursor-agent", min_versions::CURSOR).await)
+    }
 }
 
 fn strip_cursor_ascii_art_banner(line: String) -> String {
@@ -496,6 +514,8 @@ pub enum CursorJson {
         duration_ms: Option<u64>,
         #[serde(default)]
         result: Option<serde_json::Value>,
+        #[serde(default)]
+        session_id: Option<String>,
     },
     #[serde(other)]
     Unknown,
@@ -508,7 +528,7 @@ impl CursorJson {
             CursorJson::User { session_id, .. } => session_id.clone(),
             CursorJson::Assistant { session_id, .. } => session_id.clone(),
             CursorJson::ToolCall { session_id, .. } => session_id.clone(),
-            CursorJson::Result { .. } => None,
+            CursorJson::Result { session_id, .. } => session_id.clone(),
             CursorJson::Unknown => None,
         }
     }
@@ -667,6 +687,7 @@ impl CursorToolCall {
                             &path,
                             &str_replace.old_text,
                             &str_replace.new_text,
+                            DEFAULT_CONTEXT_LINES,
                         ),
                         has_line_numbers: false,
                     });
@@ -676,7 +697,13 @@ impl CursorToolCall {
                     let hunks: Vec<String> = multi_str_replace
                         .edits
                         .iter()
-                        .map(|edit| create_unified_diff_hunk(&edit.old_text, &edit.new_text))
+                        .map(|edit| {
+                            create_unified_diff_hunk(
+                                &edit.old_text,
+                                &edit.new_text,
+                                DEFAULT_CONTEXT_LINES,
+                            )
+                        })
                         .collect();
                     changes.push(FileChange::Edit {
                         unified_diff: concatenate_diff_hunks(&path, &hunks),
@@ -1073,6 +1100,7 @@ mod tests {
         let executor = Cursor {
             // No command field needed anymore
             append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
             force: None,
             model: None,
             cmd: Default::default(),
@@ -1111,12 +1139,101 @@ mod tests {
         );
     }
 
+    fn normalize(executor: &Cursor, lines: &[&str]) -> Arc<MsgStore> {
+        let raw_logs = lines
+            .iter()
+            .map(|line| utils::log_msg::LogMsg::Stdout(format!("{line}\n")))
+            .collect();
+        let store = crate::logs::utils::replay::raw_log_store(raw_logs);
+        executor.normalize_logs(store.clone(), Path::new("/tmp/test-worktree"));
+        store
+    }
+
+    fn executor() -> Cursor {
+        Cursor {
+            append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
+            force: None,
+            model: None,
+            cmd: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assistant_chunks_coalesce_into_one_message() {
+        let store = normalize(
+            &executor(),
+            &[
+                r#"{"type":"system","subtype":"init","session_id":"sess-1","model":"GPT-5"}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Hello"}]},"session_id":"sess-1"}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":" world"}]},"session_id":"sess-1"}"#,
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"next"}]},"session_id":"sess-1"}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done"}]},"session_id":"sess-1"}"#,
+            ],
+        );
+        let entries = crate::logs::utils::replay::collect_entries(store).await;
+
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(
+            entries[0].entry_type,
+            NormalizedEntryType::SystemMessage
+        ));
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[1].content, "Hello world");
+        assert_eq!(entries[2].content, "Done");
+    }
+
+    #[tokio::test]
+    async fn test_shell_tool_call_completion_carries_output() {
+        let store = normalize(
+            &executor(),
+            &[
+                r#"{"type":"tool_call","subtype":"started","call_id":"c1","tool_call":{"shellToolCall":{"args":{"command":"wc -l drill.md"}}}}"#,
+                r#"{"type":"tool_call","subtype":"completed","call_id":"c1","tool_call":{"shellToolCall":{"args":{"command":"wc -l drill.md"},"result":{"success":{"stdout":"3 drill.md\n","stderr":"","exitCode":0}}}}}"#,
+                r#"{"type":"result","subtype":"error","is_error":true,"result":"Usage limit reached","session_id":"sess-1"}"#,
+            ],
+        );
+        let entries = crate::logs::utils::replay::collect_entries(store).await;
+
+        assert_eq!(entries.len(), 2);
+        match &entries[0].entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::CommandRun { command, result },
+            } => {
+                assert_eq!(tool_name, "shell");
+                assert_eq!(command, "wc -l drill.md");
+                let result = result.as_ref().expect("completed call has a result");
+                assert_eq!(result.output.as_deref(), Some("3 drill.md\n"));
+                assert!(matches!(
+                    result.exit_status,
+                    Some(crate::logs::CommandExitStatus::ExitCode { code: 0 })
+                ));
+            }
+            other => panic!("expected a command run, got {other:?}"),
+        }
+        assert!(matches!(
+            entries[1].entry_type,
+            NormalizedEntryType::ErrorMessage
+        ));
+        assert_eq!(entries[1].content, "Usage limit reached");
+    }
+
     #[test]
     fn test_session_id_extraction_from_system_line() {
         // Ensure we can parse and find session_id from a system JSON line
         let system_line = r#"{"type":"system","subtype":"init","session_id":"abc-xyz","model":"Claude 4 Sonnet"}"#;
         let parsed: CursorJson = serde_json::from_str(system_line).unwrap();
         assert_eq!(parsed.extract_session_id().as_deref(), Some("abc-xyz"));
+
+        // Result lines carry it too, so a run that fails early can still be resumed
+        let result_line =
+            r#"{"type":"result","subtype":"error","is_error":true,"session_id":"abc-xyz"}"#;
+        let parsed: CursorJson = serde_json::from_str(result_line).unwrap();
+        assert_eq!(parsed.extract_session_id().as_deref(), Some("abc-xyz"));
     }
 
     #[test]
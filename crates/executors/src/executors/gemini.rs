@@ -1,28 +1,36 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
-    process::Stdio,
     sync::Arc,
 };
 
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::{StreamExt, stream::BoxStream};
+use json_patch::Patch;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::{
     fs::{self, OpenOptions},
     io::AsyncWriteExt,
-    process::Command,
 };
 use ts_rs::TS;
-use utils::{msg_store::MsgStore, shell::get_shell_command};
+use utils::{
+    diff::{DEFAULT_CONTEXT_LINES, create_unified_diff},
+    msg_store::MsgStore,
+    path::make_path_relative,
+};
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    agent_version::{AgentVersionInfo, check_npx_package_version, min_versions},
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides},
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
     logs::{
-        NormalizedEntry, NormalizedEntryType, plain_text_processor::PlainTextLogProcessor,
-        stderr_processor::normalize_stderr_logs, utils::EntryIndexProvider,
+        ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
+        NormalizedEntryType, ToolResult, ToolResultValueType,
+        plain_text_processor::PlainTextLogProcessor,
+        stderr_processor::normalize_stderr_logs,
+        utils::{EntryIndexProvider, patch::ConversationPatch},
     },
     stdout_dup,
 };
@@ -54,23 +62,36 @@ impl GeminiModel {
 pub struct Gemini {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     pub model: GeminiModel,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub yolo: Option<bool>,
+    /// Request `--output-format stream-json` and parse tool calls from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_json: Option<bool>,
     #[serde(flatten)]
     pub cmd: CmdOverrides,
 }
 
 impl Gemini {
-    fn build_command_builder(&self) -> CommandBuilder {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
         let mut builder = self.model.build_command_builder();
 
         if self.yolo.unwrap_or(false) {
             builder = builder.extend_params(["--yolo"]);
         }
 
+        if self.stream_json() {
+            builder = builder.extend_params(["--output-format", "stream-json"]);
+        }
+
         apply_overrides(builder, &self.cmd)
     }
+
+    fn stream_json(&self) -> bool {
+        self.stream_json.unwrap_or(false)
+    }
 }
 
 #[async_trait]
@@ -79,22 +100,17 @@ impl StandardCodingAgentExecutor for Gemini {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let gemini_command = self.build_command_builder().build_initial();
-
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(gemini_command)
-            .env("NODE_NO_WARNINGS", "1");
+        let gemini_command = self.build_command_builder()?.build_initial();
+
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
+
+        let mut command = self.cmd.shell_command(current_dir, &gemini_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
@@ -105,13 +121,17 @@ impl StandardCodingAgentExecutor for Gemini {
         }
 
         // Duplicate stdout for session logging
-        let duplicate_stdout = stdout_dup::duplicate_stdout(&mut child)?;
-        tokio::spawn(Self::record_session(
-            duplicate_stdout,
-            current_dir.to_path_buf(),
-            prompt.to_string(),
-            false,
-        ));
+        // (detached agents write stdout to a log file, so there is nothing to duplicate)
+        if !self.cmd.is_detached() {
+            let duplicate_stdout = stdout_dup::duplicate_stdout(&mut child)?;
+            tokio::spawn(Self::record_session(
+                duplicate_stdout,
+                current_dir.to_path_buf(),
+                prompt.to_string(),
+                false,
+                self.stream_json(),
+            ));
+        }
 
         Ok(child)
     }
@@ -121,24 +141,16 @@ impl StandardCodingAgentExecutor for Gemini {
         current_dir: &Path,
         prompt: &str,
         _session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         // Build comprehensive prompt with session context
         let followup_prompt = self.build_followup_prompt(current_dir, prompt).await?;
 
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let gemini_command = self.build_command_builder().build_follow_up(&[]);
+        let gemini_command = self.build_command_builder()?.build_follow_up(&[]);
 
-        let mut command = Command::new(shell_cmd);
-
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(gemini_command)
-            .env("NODE_NO_WARNINGS", "1");
+        let mut command = self.cmd.shell_command(current_dir, &gemini_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
@@ -149,13 +161,17 @@ impl StandardCodingAgentExecutor for Gemini {
         }
 
         // Duplicate stdout for session logging (resume existing session)
-        let duplicate_stdout = stdout_dup::duplicate_stdout(&mut child)?;
-        tokio::spawn(Self::record_session(
-            duplicate_stdout,
-            current_dir.to_path_buf(),
-            prompt.to_string(),
-            true,
-        ));
+        // (detached agents write stdout to a log file, so there is nothing to duplicate)
+        if !self.cmd.is_detached() {
+            let duplicate_stdout = stdout_dup::duplicate_stdout(&mut child)?;
+            tokio::spawn(Self::record_session(
+                duplicate_stdout,
+                current_dir.to_path_buf(),
+                prompt.to_string(),
+                true,
+                self.stream_json(),
+            ));
+        }
 
         Ok(child)
     }
@@ -177,11 +193,18 @@ impl StandardCodingAgentExecutor for Gemini {
     /// Subsequent queries to `msg_store` will receive JSON patches representing parsed log entries.
     /// Sets up log normalization for the Gemini executor:
     /// - stderr via [`normalize_stderr_logs`]
-    /// - stdout via [`PlainTextLogProcessor`] with Gemini-specific formatting and default heuristics
+    /// - stdout via [`PlainTextLogProcessor`] with Gemini-specific formatting and default heuristics,
+    ///   or via [`GeminiLogProcessor`] when `stream_json` is enabled
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let entry_index_counter = EntryIndexProvider::start_from(&msg_store);
         normalize_stderr_logs(msg_store.clone(), entry_index_counter.clone());
 
+        if self.stream_json() {
+            // Session ID comes from the init event
+            GeminiLogProcessor::process_logs(msg_store, worktree_path, entry_index_counter);
+            return;
+        }
+
         // Send session ID to msg_store to enable follow-ups
         msg_store.push_session_id(
             worktree_path
@@ -210,6 +233,10 @@ impl StandardCodingAgentExecutor for Gemini {
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
         dirs::home_dir().map(|home| home.join(".gemini").join("settings.json"))
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(check_npx_package_version("@google/gemini-cli", min_versions::GEMINI).await)
+    }
 }
 
 impl Gemini {
@@ -278,6 +305,7 @@ impl Gemini {
         current_dir: PathBuf,
         prompt: String,
         resume_session: bool,
+        stream_json: bool,
     ) {
         let file_path = Self::get_session_file_path(&current_dir).await;
 
@@ -324,6 +352,11 @@ impl Gemini {
             stdout_content.push_str(&chunk);
         }
 
+        // Keep only the assistant's text so the follow-up context stays readable
+        if stream_json {
+            stdout_content = GeminiLogProcessor::assistant_text(&stdout_content);
+        }
+
         let mut assistant_message_json = serde_json::to_string(&NormalizedEntry {
             timestamp: None,
             entry_type: NormalizedEntryType::AssistantMessage,
@@ -350,6 +383,10 @@ impl Gemini {
             ))
         })?;
 
+        let append_prompt =
+            self.append_prompt
+                .combine_prompt("", &self.append_prompt_file, current_dir)?;
+
         Ok(format!(
             r#"RESUME CONTEXT FOR CONTINUING TASK
 
@@ -363,7 +400,7 @@ The following is the conversation history from this session:
 === INSTRUCTIONS ===
 You are continuing work on the above task. The execution history shows the previous conversation in this session. Please continue from where the previous execution left off, taking into account all the context provided above.{}
 "#,
-            self.append_prompt.get().unwrap_or_default(),
+            append_prompt,
         ))
     }
 
@@ -418,3 +455,447 @@ You are continuing work on the above task. The execution history shows the previ
         new_path
     }
 }
+
+/// Events emitted by `gemini --output-format stream-json`, one per line
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeminiJson {
+    Init {
+        #[serde(default)]
+        session_id: Option<String>,
+        #[serde(default)]
+        model: Option<String>,
+    },
+    Message {
+        role: String,
+        #[serde(default)]
+        content: String,
+        #[serde(default)]
+        delta: bool,
+    },
+    ToolUse {
+        tool_name: String,
+        tool_id: String,
+        #[serde(default)]
+        parameters: serde_json::Value,
+    },
+    ToolResult {
+        tool_id: String,
+        status: String,
+        #[serde(default)]
+        output: Option<String>,
+        #[serde(default)]
+        error: Option<GeminiErrorInfo>,
+    },
+    Error {
+        #[serde(default)]
+        message: String,
+    },
+    Result {
+        #[serde(default)]
+        status: Option<String>,
+        #[serde(default)]
+        error: Option<GeminiErrorInfo>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeminiErrorInfo {
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Handles log processing and interpretation for Gemini's stream-json output
+pub struct GeminiLogProcessor {
+    worktree_path: String,
+    entry_index_provider: EntryIndexProvider,
+    // Index and text of the assistant message currently being streamed
+    assistant_message: Option<(usize, String)>,
+    // Map tool_id -> entry index and entry, for replacement once the result arrives
+    tool_map: HashMap<String, (usize, NormalizedEntry)>,
+}
+
+impl GeminiLogProcessor {
+    fn new(worktree_path: &Path, entry_index_provider: EntryIndexProvider) -> Self {
+        Self {
+            worktree_path: worktree_path.to_string_lossy().to_string(),
+            entry_index_provider,
+            assistant_message: None,
+            tool_map: HashMap::new(),
+        }
+    }
+
+    /// Process stdout lines into normalized entries and push them as patches
+    pub fn process_logs(
+        msg_store: Arc<MsgStore>,
+        worktree_path: &Path,
+        entry_index_provider: EntryIndexProvider,
+    ) {
+        let mut processor = Self::new(worktree_path, entry_index_provider);
+        tokio::spawn(async move {
+            let mut lines = msg_store.stdout_lines_stream();
+            let mut session_id_extracted = false;
+
+            while let Some(Ok(line)) = lines.next().await {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+
+                match serde_json::from_str::<GeminiJson>(trimmed) {
+                    Ok(event) => {
+                        if !session_id_extracted
+                            && let GeminiJson::Init {
+                                session_id: Some(session_id),
+                                ..
+                            } = &event
+                        {
+                            msg_store.push_session_id(session_id.clone());
+                            session_id_extracted = true;
+                        }
+                        for patch in processor.process_event(event) {
+                            msg_store.push_patch(patch);
+                        }
+                    }
+                    Err(_) => {
+                        if let Some(patch) = processor.process_text_line(trimmed) {
+                            msg_store.push_patch(patch);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Non-JSON output (CLI notices) becomes a system message
+    fn process_text_line(&mut self, line: &str) -> Option<Patch> {
+        if line == "Data collection is disabled." {
+            return None;
+        }
+        self.assistant_message = None;
+        Some(self.add_entry(NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::SystemMessage,
+            content: line.to_string(),
+            metadata: None,
+        }))
+    }
+
+    fn process_event(&mut self, event: GeminiJson) -> Vec<Patch> {
+        // Streamed assistant deltas are merged into one entry until something else arrives
+        if let GeminiJson::Message {
+            role,
+            content,
+            delta,
+        } = &event
+            && role == "assistant"
+        {
+            return vec![self.push_assistant_text(content, *delta)];
+        }
+        self.assistant_message = None;
+
+        match event {
+            GeminiJson::Init { model, .. } => model
+                .map(|model| {
+                    self.add_entry(NormalizedEntry {
+                        timestamp: None,
+                        entry_type: NormalizedEntryType::SystemMessage,
+                        content: format!("System initialized with model: {model}"),
+                        metadata: None,
+                    })
+                })
+                .into_iter()
+                .collect(),
+            GeminiJson::Message { .. } | GeminiJson::Unknown => vec![],
+            GeminiJson::ToolUse {
+                tool_name,
+                tool_id,
+                parameters,
+            } => {
+                let action_type =
+                    Self::extract_action_type(&tool_name, &parameters, &self.worktree_path);
+                let content = Self::generate_concise_content(&tool_name, &action_type);
+                let entry = NormalizedEntry {
+                    timestamp: None,
+                    entry_type: NormalizedEntryType::ToolUse {
+                        tool_name,
+                        action_type,
+                    },
+                    content,
+                    metadata: None,
+                };
+                let id = self.entry_index_provider.next();
+                self.tool_map.insert(tool_id, (id, entry.clone()));
+                vec![ConversationPatch::add_normalized_entry(id, entry)]
+            }
+            GeminiJson::ToolResult {
+                tool_id,
+                status,
+                output,
+                error,
+            } => {
+                let Some((id, mut entry)) = self.tool_map.remove(&tool_id) else {
+                    return vec![];
+                };
+                let success = status == "success";
+                let failure_message = format!("{} failed", entry.content);
+                let output = output
+                    .filter(|o| !o.trim().is_empty())
+                    .or_else(|| error.as_ref().map(|e| e.message.clone()));
+
+                let NormalizedEntryType::ToolUse { action_type, .. } = &mut entry.entry_type else {
+                    return vec![];
+                };
+                match action_type {
+                    ActionType::CommandRun { result, .. } => {
+                        *result = Some(CommandRunResult {
+                            exit_status: Some(CommandExitStatus::Success { success }),
+                            output,
+                            full_output_id: None,
+                        });
+                        vec![ConversationPatch::replace(id, entry)]
+                    }
+                    ActionType::Tool { result, .. } => {
+                        *result = output.map(|output| ToolResult {
+                            r#type: ToolResultValueType::Markdown,
+                            value: serde_json::Value::String(output),
+                        });
+                        vec![ConversationPatch::replace(id, entry)]
+                    }
+                    _ if !success => {
+                        let message = error
+                            .map(|e| e.message)
+                            .filter(|m| !m.is_empty())
+                            .unwrap_or(failure_message);
+                        vec![self.add_entry(NormalizedEntry {
+                            timestamp: None,
+                            entry_type: NormalizedEntryType::ErrorMessage,
+                            content: message,
+                            metadata: None,
+                        })]
+                    }
+                    _ => vec![],
+                }
+            }
+            GeminiJson::Error { message } => vec![self.add_entry(NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::ErrorMessage,
+                content: message,
+                metadata: None,
+            })],
+            GeminiJson::Result { status, error } => match (status.as_deref(), error) {
+                (Some("error"), error) => vec![
+                    self.add_entry(NormalizedEntry {
+                        timestamp: None,
+                        entry_type: NormalizedEntryType::ErrorMessage,
+                        content: error
+                            .map(|e| e.message)
+                            .unwrap_or_else(|| "Gemini finished with an error".to_string()),
+                        metadata: None,
+                    }),
+                ],
+                _ => vec![],
+            },
+        }
+    }
+
+    fn push_assistant_text(&mut self, content: &str, delta: bool) -> Patch {
+        match &mut self.assistant_message {
+            Some((id, text)) if delta => {
+                text.push_str(content);
+                ConversationPatch::replace(*id, Self::assistant_entry(text.clone()))
+            }
+            _ => {
+                let id = self.entry_index_provider.next();
+                self.assistant_message = Some((id, content.to_string()));
+                ConversationPatch::add_normalized_entry(
+                    id,
+                    Self::assistant_entry(content.to_string()),
+                )
+            }
+        }
+    }
+
+    fn assistant_entry(content: String) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content,
+            metadata: None,
+        }
+    }
+
+    fn add_entry(&mut self, entry: NormalizedEntry) -> Patch {
+        ConversationPatch::add_normalized_entry(self.entry_index_provider.next(), entry)
+    }
+
+    fn extract_action_type(
+        tool_name: &str,
+        parameters: &serde_json::Value,
+        worktree_path: &str,
+    ) -> ActionType {
+        let param = |key: &str| parameters.get(key).and_then(|v| v.as_str());
+        let path = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| param(key))
+                .map(|p| make_path_relative(p, worktree_path))
+        };
+
+        match tool_name {
+            "run_shell_command" => ActionType::CommandRun {
+                command: param("command").unwrap_or_default().to_string(),
+                result: None,
+            },
+            "write_file" => ActionType::FileEdit {
+                path: path(&["file_path", "absolute_path"]).unwrap_or_default(),
+                changes: vec![FileChange::Write {
+                    content: param("content").unwrap_or_default().to_string(),
+                }],
+            },
+            "replace" => {
+                let file_path = param("file_path").unwrap_or_default();
+                ActionType::FileEdit {
+                    path: make_path_relative(file_path, worktree_path),
+                    changes: vec![FileChange::Edit {
+                        unified_diff: create_unified_diff(
+                            file_path,
+                            param("old_string").unwrap_or_default(),
+                            param("new_string").unwrap_or_default(),
+                            DEFAULT_CONTEXT_LINES,
+                        ),
+                        has_line_numbers: false,
+                    }],
+                }
+            }
+            "read_file" => ActionType::FileRead {
+                path: path(&["absolute_path", "file_path"]).unwrap_or_default(),
+            },
+            "search_file_content" | "glob" => ActionType::Search {
+                query: param("pattern").unwrap_or_default().to_string(),
+            },
+            "google_web_search" => ActionType::Search {
+                query: param("query").unwrap_or_default().to_string(),
+            },
+            "web_fetch" => ActionType::WebFetch {
+                url: param("prompt").unwrap_or_default().to_string(),
+            },
+            _ => ActionType::Tool {
+                tool_name: tool_name.to_string(),
+                arguments: Some(parameters.clone()),
+                result: None,
+            },
+        }
+    }
+
+    fn generate_concise_content(tool_name: &str, action_type: &ActionType) -> String {
+        match action_type {
+            ActionType::CommandRun { command, .. } => format!("`{command}`"),
+            ActionType::FileEdit { path, .. } | ActionType::FileRead { path } => {
+                format!("`{path}`")
+            }
+            ActionType::Search { query } => format!("`{query}`"),
+            ActionType::WebFetch { url } => format!("`{url}`"),
+            _ => tool_name.to_string(),
+        }
+    }
+
+    /// Concatenate the assistant text from stream-json output
+    fn assistant_text(output: &str) -> String {
+        output
+            .lines()
+            .filter_map(|line| serde_json::from_str::<GeminiJson>(line.trim()).ok())
+            .filter_map(|event| match event {
+                GeminiJson::Message { role, content, .. } if role == "assistant" => Some(content),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn normalize(lines: &[&str]) -> Vec<serde_json::Value> {
+        let mut processor =
+            GeminiLogProcessor::new(Path::new("/tmp/wt"), EntryIndexProvider::test_new());
+        let mut doc = json!({ "entries": [] });
+        for line in lines {
+            let event = serde_json::from_str::<GeminiJson>(line).unwrap();
+            for patch in processor.process_event(event) {
+                json_patch::patch(&mut doc, &patch).unwrap();
+            }
+        }
+        doc["entries"].as_array().unwrap().clone()
+    }
+
+    fn entry_type(entry: &serde_json::Value) -> &str {
+        entry["content"]["entry_type"]["type"].as_str().unwrap()
+    }
+
+    #[test]
+    fn test_stream_json_entries() {
+        let entries = normalize(&[
+            r#"{"type":"init","session_id":"abc","model":"gemini-2.5-pro"}"#,
+            r#"{"type":"message","role":"user","content":"fix it"}"#,
+            r#"{"type":"message","role":"assistant","content":"Looking ","delta":true}"#,
+            r#"{"type":"message","role":"assistant","content":"now.","delta":true}"#,
+            r#"{"type":"tool_use","tool_name":"run_shell_command","tool_id":"t1","parameters":{"command":"ls"}}"#,
+            r#"{"type":"tool_use","tool_name":"replace","tool_id":"t2","parameters":{"file_path":"/tmp/wt/src/a.rs","old_string":"a","new_string":"b"}}"#,
+            r#"{"type":"tool_result","tool_id":"t1","status":"success","output":"a.rs"}"#,
+            r#"{"type":"tool_result","tool_id":"t2","status":"success"}"#,
+            r#"{"type":"message","role":"assistant","content":"Done.","delta":true}"#,
+            r#"{"type":"result","status":"success","stats":{}}"#,
+        ]);
+
+        let types: Vec<&str> = entries.iter().map(entry_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                "system_message",
+                "assistant_message",
+                "tool_use",
+                "tool_use",
+                "assistant_message"
+            ]
+        );
+        assert_eq!(entries[1]["content"]["content"], "Looking now.");
+
+        let command = &entries[2]["content"]["entry_type"]["action_type"];
+        assert_eq!(command["action"], "command_run");
+        assert_eq!(command["result"]["output"], "a.rs");
+        assert_eq!(command["result"]["exit_status"]["success"], true);
+
+        let edit = &entries[3]["content"]["entry_type"]["action_type"];
+        assert_eq!(edit["action"], "file_edit");
+        assert_eq!(edit["path"], "src/a.rs");
+    }
+
+    #[test]
+    fn test_failed_tool_and_error_events() {
+        let entries = normalize(&[
+            r#"{"type":"tool_use","tool_name":"write_file","tool_id":"t1","parameters":{"file_path":"/tmp/wt/b.txt","content":"x"}}"#,
+            r#"{"type":"tool_result","tool_id":"t1","status":"error","error":{"type":"io","message":"permission denied"}}"#,
+            r#"{"type":"error","severity":"error","message":"quota exceeded"}"#,
+        ]);
+
+        let types: Vec<&str> = entries.iter().map(entry_type).collect();
+        assert_eq!(types, vec!["tool_use", "error_message", "error_message"]);
+        assert_eq!(entries[1]["content"]["content"], "permission denied");
+    }
+
+    #[test]
+    fn test_assistant_text_for_session_context() {
+        let output = [
+            r#"{"type":"init","session_id":"abc"}"#,
+            r#"{"type":"message","role":"assistant","content":"Hello ","delta":true}"#,
+            r#"{"type":"message","role":"assistant","content":"there","delta":true}"#,
+        ]
+        .join("\n");
+        assert_eq!(GeminiLogProcessor::assistant_text(&output), "Hello there");
+    }
+}
@@ -1,4 +1,7 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use command_group::AsyncGroupChild;
@@ -13,13 +16,16 @@ use ts_rs::TS;
 use utils::msg_store::MsgStore;
 
 use crate::{
+    agent_version::AgentVersionInfo,
+    command::{CmdOverrides, CommandBuildError, ExecutionEnv},
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, cursor::Cursor, gemini::Gemini,
+        aider::Aider, amp::Amp, claude::ClaudeCode, codex::Codex, cursor::Cursor, gemini::Gemini,
         opencode::Opencode, qwen::QwenCode, warp_cli::WarpCli,
     },
-    mcp_config::McpConfig,
+    mcp_config::{McpConfig, McpRegistration, McpServerDefinition, VIBE_KANBAN_MCP_SERVER},
 };
 
+pub mod aider;
 pub mod amp;
 pub mod claude;
 pub mod codex;
@@ -51,6 +57,12 @@ pub enum ExecutorError {
     TomlSerialize(#[from] toml::ser::Error),
     #[error(transparent)]
     TomlDeserialize(#[from] toml::de::Error),
+    #[error("Failed to read append prompt file {0}: {1}")]
+    AppendPromptFile(String, std::io::Error),
+    #[error("Invalid working directory: {0}")]
+    InvalidWorkingDir(String),
+    #[error(transparent)]
+    CommandBuild(#[from] CommandBuildError),
 }
 
 #[enum_dispatch]
@@ -62,7 +74,7 @@ pub enum ExecutorError {
 #[strum_discriminants(
     name(BaseCodingAgent),
     // Only add Hash; Eq/PartialEq are already provided by EnumDiscriminants.
-    derive(EnumString, Hash, strum_macros::Display, Serialize, Deserialize, TS, Type),
+    derive(EnumString, Hash, strum_macros::Display, Serialize, Deserialize, TS, JsonSchema, Type),
     strum(serialize_all = "SCREAMING_SNAKE_CASE"),
     ts(use_ts_enum),
     serde(rename_all = "SCREAMING_SNAKE_CASE"),
@@ -77,6 +89,7 @@ pub enum CodingAgent {
     Cursor,
     QwenCode,
     WarpCli,
+    Aider,
 }
 
 impl CodingAgent {
@@ -108,7 +121,7 @@ impl CodingAgent {
                 vec!["mcp".to_string()],
                 serde_json::json!({
                     "mcp": {},
-                    "$schema": "https://opencode.ai/config.json"
+                    "$schema": opencode::CONFIG_SCHEMA
                 }),
                 serde_json::json!({
                     "type": "local",
@@ -135,12 +148,118 @@ impl CodingAgent {
         self.default_mcp_config_path().is_some()
     }
 
+    /// A project MCP server in the entry shape of this agent's config file
+    pub fn mcp_server_entry(&self, server: &McpServerDefinition) -> serde_json::Value {
+        match self {
+            Self::Opencode(_) => {
+                let command: Vec<&str> = std::iter::once(server.command.as_str())
+                    .chain(server.args.iter().map(String::as_str))
+                    .collect();
+                serde_json::json!({
+                    "type": "local",
+                    "command": command,
+                    "environment": server.env,
+                    "enabled": true
+                })
+            }
+            _ => serde_json::json!({
+                "command": server.command,
+                "args": server.args,
+                "env": server.env,
+            }),
+        }
+    }
+
+    /// Merge the vibe-kanban entry and a project's MCP `servers` into an agent config
+    pub fn merge_mcp_servers(
+        &self,
+        config: &mut serde_json::Value,
+        servers: &[McpServerDefinition],
+    ) {
+        let mcp_config = self.get_mcp_config();
+        let entries = std::iter::once((
+            VIBE_KANBAN_MCP_SERVER.to_string(),
+            mcp_config.vibe_kanban.clone(),
+        ))
+        .chain(
+            servers
+                .iter()
+                .map(|server| (server.name.clone(), self.mcp_server_entry(server))),
+        );
+        mcp_config.merge_servers(config, entries);
+        // Opencode validates its config file against the schema it names
+        if let Self::Opencode(_) = self
+            && let Some(config) = config.as_object_mut()
+        {
+            config
+                .entry("$schema")
+                .or_insert_with(|| opencode::CONFIG_SCHEMA.into());
+        }
+    }
+
+    /// Config file MCP servers are written to for a run in `current_dir`: the
+    /// profile's `mcp_config_path_override`, resolved against the worktree when
+    /// relative, or else the agent's global config file
+    pub fn mcp_config_path(&self, current_dir: &Path) -> Option<PathBuf> {
+        match self.cmd_overrides().mcp_config_path_override.as_deref() {
+            Some(path) if !path.trim().is_empty() => Some(current_dir.join(path.trim())),
+            _ => self.default_mcp_config_path(),
+        }
+    }
+
+    /// Register a project's MCP servers in this agent's config file before it
+    /// runs in `current_dir`; the file is left alone when the project defines none.
+    /// Release the returned registration when the run ends.
+    pub async fn register_mcp_servers(
+        &self,
+        current_dir: &Path,
+        servers: &[McpServerDefinition],
+    ) -> Result<Option<McpRegistration>, ExecutorError> {
+        let Some(config_path) = self.mcp_config_path(current_dir) else {
+            return Ok(None);
+        };
+        if servers.is_empty() {
+            return Ok(None);
+        }
+        let names = servers.iter().map(|server| server.name.clone()).collect();
+        McpRegistration::register(config_path, self.get_mcp_config(), names, |config| {
+            self.merge_mcp_servers(config, servers)
+        })
+        .await
+        .map(Some)
+    }
+
+    /// Command overrides from this agent's profile
+    pub fn cmd_overrides(&self) -> &CmdOverrides {
+        match self {
+            Self::ClaudeCode(agent) => &agent.cmd,
+            Self::Amp(agent) => &agent.cmd,
+            Self::Gemini(agent) => &agent.cmd,
+            Self::Codex(agent) => &agent.cmd,
+            Self::Opencode(agent) => &agent.cmd,
+            Self::Cursor(agent) => &agent.cmd,
+            Self::QwenCode(agent) => &agent.cmd,
+            Self::WarpCli(agent) => &agent.cmd,
+            Self::Aider(agent) => &agent.cmd,
+        }
+    }
+
+    /// Whether this agent is configured to keep running when the server stops
+    pub fn is_detached(&self) -> bool {
+        self.cmd_overrides().is_detached()
+    }
+
     pub fn capabilities(&self) -> Vec<BaseAgentCapability> {
         match self {
             Self::ClaudeCode(_) => vec![BaseAgentCapability::RestoreCheckpoint],
             Self::Amp(_) => vec![BaseAgentCapability::RestoreCheckpoint],
             Self::Codex(_) => vec![BaseAgentCapability::RestoreCheckpoint],
-            Self::Gemini(_) | Self::Opencode(_) | Self::Cursor(_) | Self::QwenCode(_) | Self::WarpCli(_) => vec![],
+            Self::Gemini(_)
+            | Self::Opencode(_)
+            | Self::Cursor(_)
+            | Self::QwenCode(_)
+            | Self::WarpCli(_)
+            | Self::Aider(_) => vec![],
         }
     }
 }
@@ -152,14 +271,20 @@ pub trait StandardCodingAgentExecutor {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError>;
     async fn spawn_follow_up(
         &self,
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError>;
     fn normalize_logs(&self, _raw_logs_event_store: Arc<MsgStore>, _worktree_path: &Path);
+    /// Like `normalize_logs`, for a run that resumed an earlier session
+    fn normalize_follow_up_logs(&self, raw_logs_event_store: Arc<MsgStore>, worktree_path: &Path) {
+        self.normalize_logs(raw_logs_event_store, worktree_path)
+    }
 
     // MCP configuration methods
     fn default_mcp_config_path(&self) -> Option<std::path::PathBuf>;
@@ -169,6 +294,12 @@ pub trait StandardCodingAgentExecutor {
             .map(|path| path.exists())
             .unwrap_or(false)
     }
+
+    /// Best-effort probe of the installed CLI version against the recommended
+    /// minimum. Returns `None` for executors that are not version-checked.
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -186,10 +317,73 @@ impl AppendPrompt {
         self.0.clone()
     }
 
-    pub fn combine_prompt(&self, prompt: &str) -> String {
-        match self {
+    /// `prompt` followed by the inline append prompt, then the contents of
+    /// `file` (resolved against `current_dir` when relative)
+    pub fn combine_prompt(
+        &self,
+        prompt: &str,
+        file: &AppendPromptFile,
+        current_dir: &Path,
+    ) -> Result<String, ExecutorError> {
+        let mut combined = match self {
             AppendPrompt(Some(value)) => format!("{prompt}{value}"),
             AppendPrompt(None) => prompt.to_string(),
+        };
+        if let AppendPromptFile(Some(path)) = file {
+            let path = current_dir.join(path);
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| ExecutorError::AppendPromptFile(path.display().to_string(), e))?;
+            combined.push_str(&contents);
         }
+        Ok(combined)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
+#[serde(transparent)]
+#[schemars(
+    title = "Append Prompt File",
+    description = "File whose contents are appended to the prompt after the append prompt; relative paths resolve against the task worktree"
+)]
+pub struct AppendPromptFile(pub Option<PathBuf>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_prompt_appends_inline_text_then_file_contents() {
+        let dir = std::env::temp_dir().join(format!("vk-append-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("CONVENTIONS.md"), "\nUse tabs.").unwrap();
+
+        let inline = AppendPrompt(Some("\nBe brief.".to_string()));
+        let relative = AppendPromptFile(Some(PathBuf::from("CONVENTIONS.md")));
+        assert_eq!(
+            inline.combine_prompt("Fix it", &relative, &dir).unwrap(),
+            "Fix it\nBe brief.\nUse tabs."
+        );
+
+        let absolute = AppendPromptFile(Some(dir.join("CONVENTIONS.md")));
+        assert_eq!(
+            AppendPrompt::default()
+                .combine_prompt("Fix it", &absolute, Path::new("/elsewhere"))
+                .unwrap(),
+            "Fix it\nUse tabs."
+        );
+        assert_eq!(
+            inline
+                .combine_prompt("Fix it", &AppendPromptFile::default(), &dir)
+                .unwrap(),
+            "Fix it\nBe brief."
+        );
+
+        let missing = AppendPromptFile(Some(PathBuf::from("MISSING.md")));
+        let err = inline.combine_prompt("Fix it", &missing, &dir).unwrap_err();
+        assert!(
+            matches!(&err, ExecutorError::AppendPromptFile(path, _) if path.ends_with("MISSING.md"))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
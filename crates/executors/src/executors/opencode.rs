@@ -1,8 +1,8 @@
 use std::{
     fmt,
     path::{Path, PathBuf},
-    process::Stdio,
     sync::Arc,
+    time::Duration,
 };
 
 use async_trait::async_trait;
@@ -13,27 +13,43 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::io::AsyncWriteExt;
 use ts_rs::TS;
 use utils::{
-    diff::create_unified_diff, msg_store::MsgStore, path::make_path_relative,
-    shell::get_shell_command,
+    diff::{DEFAULT_CONTEXT_LINES, create_unified_diff},
+    msg_store::MsgStore,
+    path::make_path_relative,
+    stream_lines::LineBufferConfig,
 };
 
 use crate::{
-    command::{CmdOverrides, CommandBuilder, apply_overrides},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    agent_version::{AgentVersionInfo, check_npx_package_version, min_versions},
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv, apply_overrides},
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
     logs::{
-        ActionType, FileChange, NormalizedEntry, NormalizedEntryType, TodoItem,
+        ActionType, CommandExitStatus, CommandRunResult, FileChange, NormalizedEntry,
+        NormalizedEntryType, TodoItem,
         plain_text_processor::{MessageBoundary, PlainTextLogProcessor},
         utils::EntryIndexProvider,
     },
 };
 
+/// `$schema` that tags Opencode's `opencode.json` config
+pub const CONFIG_SCHEMA: &str = "https://opencode.ai/config.json";
+
+/// Opencode streams the agent's text on stderr and can pause mid-line while the
+/// model is thinking; surface what has arrived instead of waiting for the newline
+const STDERR_LINE_BUFFER: LineBufferConfig = LineBufferConfig {
+    max_line_length: Some(64 * 1024),
+    flush_timeout: Some(Duration::from_secs(2)),
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
 pub struct Opencode {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -43,9 +59,13 @@ pub struct Opencode {
 }
 
 impl Opencode {
-    fn build_command_builder(&self) -> CommandBuilder {
-        let mut builder =
-            CommandBuilder::new("npx -y opencode-ai@latest run").params(["--print-logs"]);
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
+        // Structured events go to stdout; logs (with the session id) to stderr
+        let mut builder = CommandBuilder::new("npx -y opencode-ai@latest run").params([
+            "--print-logs",
+            "--format",
+            "json",
+        ]);
 
         if let Some(model) = &self.model {
             builder = builder.extend_params(["--model", model]);
@@ -65,22 +85,17 @@ impl StandardCodingAgentExecutor for Opencode {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let opencode_command = self.build_command_builder().build_initial();
-
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
-
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped()) // Keep stdout but we won't use it
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(opencode_command)
-            .env("NODE_NO_WARNINGS", "1");
+        let opencode_command = self.build_command_builder()?.build_initial();
+
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
+
+        let mut command = self.cmd.shell_command(current_dir, &opencode_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
@@ -98,24 +113,19 @@ impl StandardCodingAgentExecutor for Opencode {
         current_dir: &Path,
         prompt: &str,
         session_id: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
         let opencode_command = self
-            .build_command_builder()
+            .build_command_builder()?
             .build_follow_up(&["--session".to_string(), session_id.to_string()]);
 
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped()) // Keep stdout but we won't use it
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&opencode_command)
-            .env("NODE_NO_WARNINGS", "1");
+        let mut command = self.cmd.shell_command(current_dir, &opencode_command);
+        env.apply_to_command(&mut command);
+        command.env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
 
@@ -130,16 +140,23 @@ impl StandardCodingAgentExecutor for Opencode {
 
     /// Normalize logs for OpenCode executor
     ///
-    /// This implementation uses three separate threads:
+    /// This implementation uses four separate threads:
     /// 1. Session ID thread: read by line, search for session ID format, store it.
     /// 2. Error log recognition thread: read by line, identify error log lines, store them as error messages.
     /// 3. Main normalizer thread: read stderr by line, filter out log lines, send lines (with '\n' appended) to plain text normalizer,
     ///    then define predicate for split and create appropriate normalized entry (either assistant or tool call).
+    /// 4. JSON event thread: read stdout by line and map `--format json` events to normalized entries.
     fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
         let entry_index_counter = EntryIndexProvider::start_from(&msg_store);
 
+        tokio::spawn(Self::process_json_events(
+            msg_store.clone(),
+            worktree_path.to_path_buf(),
+            entry_index_counter.clone(),
+        ));
+
         let stderr_lines = msg_store
-            .stderr_lines_stream()
+            .stderr_lines_stream_with_config(STDERR_LINE_BUFFER)
             .filter_map(|res| ready(res.ok()))
             .map(|line| strip_ansi_escapes::strip_str(&line))
             .fork();
@@ -190,6 +207,10 @@ impl StandardCodingAgentExecutor for Opencode {
             dirs::config_dir().map(|config| config.join("opencode").join("opencode.json"))
         }
     }
+
+    async fn check_version(&self) -> Option<AgentVersionInfo> {
+        Some(check_npx_package_version("opencode-ai", min_versions::OPENCODE).await)
+    }
 }
 impl Opencode {
     async fn process_opencode_log_lines(
@@ -225,6 +246,31 @@ impl Opencode {
         }
     }
 
+    async fn process_json_events(
+        msg_store: Arc<MsgStore>,
+        worktree_path: PathBuf,
+        entry_index_counter: EntryIndexProvider,
+    ) {
+        let mut stdout_lines = msg_store.stdout_lines_stream();
+        let mut session_id_extracted = false;
+        while let Some(Ok(line)) = stdout_lines.next().await {
+            let Some(event) = JsonEvent::parse(&line) else {
+                continue;
+            };
+            if !session_id_extracted && let Some(session_id) = event.session_id() {
+                msg_store.push_session_id(session_id.to_string());
+                session_id_extracted = true;
+            }
+            if let Some(entry) = event.to_normalized_entry(&worktree_path) {
+                let patch = crate::logs::utils::ConversationPatch::add_normalized_entry(
+                    entry_index_counter.next(),
+                    entry,
+                );
+                msg_store.push_patch(patch);
+            }
+        }
+    }
+
     async fn process_agent_logs(
         mut agent_logs: BoxStream<'_, String>,
         worktree_path: PathBuf,
@@ -297,6 +343,153 @@ impl Opencode {
     }
 }
 
+// =============================================================================
+// JSON EVENTS
+// =============================================================================
+
+/// One line of `opencode run --format json` output
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonEvent {
+    StepStart {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+    },
+    StepFinish {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+    },
+    Text {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: TextPart,
+    },
+    ToolUse {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: ToolPart,
+    },
+    Error {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        error: serde_json::Value,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TextPart {
+    pub text: String,
+}
+
+/// A finished tool call; its `input` has the same shape as the legacy tool arguments
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolPart {
+    pub tool: String,
+    pub state: ToolState,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolState {
+    pub status: String,
+    #[serde(default)]
+    pub input: serde_json::Value,
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl JsonEvent {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            JsonEvent::StepStart { session_id }
+            | JsonEvent::StepFinish { session_id }
+            | JsonEvent::Text { session_id, .. }
+            | JsonEvent::ToolUse { session_id, .. }
+            | JsonEvent::Error { session_id, .. } => session_id.as_deref(),
+            JsonEvent::Unknown => None,
+        }
+    }
+
+    /// The conversation entry for this event; step markers produce none
+    pub fn to_normalized_entry(&self, worktree_path: &Path) -> Option<NormalizedEntry> {
+        let (entry_type, content) = match self {
+            JsonEvent::Text { part, .. } if !part.text.trim().is_empty() => {
+                (NormalizedEntryType::AssistantMessage, part.text.clone())
+            }
+            JsonEvent::ToolUse { part, .. } => {
+                return Some(part.to_normalized_entry(worktree_path));
+            }
+            JsonEvent::Error { error, .. } => {
+                let message = error
+                    .pointer("/data/message")
+                    .or_else(|| error.get("message"))
+                    .and_then(|message| message.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                (NormalizedEntryType::ErrorMessage, message)
+            }
+            _ => return None,
+        };
+        Some(NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content,
+            metadata: None,
+        })
+    }
+}
+
+impl ToolPart {
+    fn to_normalized_entry(&self, worktree_path: &Path) -> NormalizedEntry {
+        let tool_name = ToolUtils::normalize_tool_name(&self.tool);
+        let tool = serde_json::from_value::<Tool>(serde_json::json!({
+            "tool_name": tool_name,
+            "arguments": self.state.input,
+        }))
+        .unwrap_or_else(|_| Tool::Other {
+            tool_name: tool_name.clone(),
+            arguments: self.state.input.clone(),
+        });
+        let worktree_path = worktree_path.to_string_lossy();
+
+        let mut action_type = ToolUtils::determine_action_type(&tool, &worktree_path);
+        if let ActionType::CommandRun { result, .. } = &mut action_type {
+            *result = Some(CommandRunResult {
+                exit_status: Some(CommandExitStatus::Success {
+                    success: self.state.status == "completed",
+                }),
+                output: self
+                    .state
+                    .output
+                    .clone()
+                    .or_else(|| self.state.error.clone()),
+                full_output_id: None,
+            });
+        }
+
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: tool.name(),
+                action_type,
+            },
+            content: ToolUtils::generate_tool_content(&tool, &worktree_path),
+            metadata: None,
+        }
+    }
+}
+
 // =============================================================================
 // TOOL DEFINITIONS
 // =============================================================================
@@ -744,7 +937,12 @@ impl ToolUtils {
             } => {
                 let changes = match (old_string, new_string) {
                     (Some(old), Some(new)) => vec![FileChange::Edit {
-                        unified_diff: create_unified_diff(file_path, old, new),
+                        unified_diff: create_unified_diff(
+                            file_path,
+                            old,
+                            new,
+                            DEFAULT_CONTEXT_LINES,
+                        ),
                         has_line_numbers: false,
                     }],
                     _ => Vec::new(),
@@ -958,3 +1156,99 @@ impl LogUtils {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS: &str = r#"{"type":"step_start","timestamp":1757000000000,"sessionID":"ses_6a1f2b","part":{"type":"step-start"}}
+{"type":"tool_use","timestamp":1757000000100,"sessionID":"ses_6a1f2b","part":{"type":"tool","callID":"call_1","tool":"read","state":{"status":"completed","input":{"filePath":"/repo/src/main.rs"},"output":"fn main() {}","title":"src/main.rs"}}}
+{"type":"tool_use","timestamp":1757000000200,"sessionID":"ses_6a1f2b","part":{"type":"tool","callID":"call_2","tool":"edit","state":{"status":"completed","input":{"filePath":"/repo/src/main.rs","oldString":"fn main() {}","newString":"fn main() {\n    println!(\"hi\");\n}"},"output":""}}}
+{"type":"tool_use","timestamp":1757000000300,"sessionID":"ses_6a1f2b","part":{"type":"tool","callID":"call_3","tool":"bash","state":{"status":"error","input":{"command":"cargo test"},"error":"exit code 101"}}}
+{"type":"text","timestamp":1757000000400,"sessionID":"ses_6a1f2b","part":{"type":"text","text":"Added a greeting to main."}}
+{"type":"step_finish","timestamp":1757000000500,"sessionID":"ses_6a1f2b","part":{"type":"step-finish"}}
+{"type":"error","timestamp":1757000000600,"sessionID":"ses_6a1f2b","error":{"name":"APIError","data":{"message":"rate limited"}}}
+INFO  2025-09-04T10:17:26 +1ms service=session id=ses_6a1f2b"#;
+
+    #[test]
+    fn json_events_map_to_entry_types() {
+        let worktree = Path::new("/repo");
+        let events: Vec<JsonEvent> = EVENTS.lines().filter_map(JsonEvent::parse).collect();
+        assert_eq!(events.len(), 7, "the log line is not an event");
+        assert!(
+            events
+                .iter()
+                .all(|event| event.session_id() == Some("ses_6a1f2b"))
+        );
+
+        let entries: Vec<NormalizedEntry> = events
+            .iter()
+            .filter_map(|event| event.to_normalized_entry(worktree))
+            .collect();
+        assert_eq!(entries.len(), 5, "step markers produce no entries");
+
+        assert!(matches!(
+            &entries[0].entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::FileRead { path },
+                ..
+            } if path == "src/main.rs"
+        ));
+        match &entries[1].entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name,
+                action_type: ActionType::FileEdit { path, changes },
+            } => {
+                assert_eq!(tool_name, "edit");
+                assert_eq!(path, "src/main.rs");
+                assert!(matches!(
+                    changes.as_slice(),
+                    [FileChange::Edit { unified_diff, .. }] if unified_diff.contains("+    println!(\"hi\");")
+                ));
+            }
+            other => panic!("expected file edit, got {other:?}"),
+        }
+        match &entries[2].entry_type {
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::CommandRun { command, result },
+                ..
+            } => {
+                assert_eq!(command, "cargo test");
+                let result = result.as_ref().unwrap();
+                assert!(matches!(
+                    result.exit_status,
+                    Some(CommandExitStatus::Success { success: false })
+                ));
+                assert_eq!(result.output.as_deref(), Some("exit code 101"));
+            }
+            other => panic!("expected command run, got {other:?}"),
+        }
+        assert!(matches!(
+            entries[3].entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(entries[3].content, "Added a greeting to main.");
+        assert!(matches!(
+            entries[4].entry_type,
+            NormalizedEntryType::ErrorMessage
+        ));
+        assert_eq!(entries[4].content, "rate limited");
+    }
+
+    #[test]
+    fn unknown_tools_and_events_are_tolerated() {
+        let line = r#"{"type":"tool_use","sessionID":"ses_1","part":{"tool":"github_create_issue","state":{"status":"completed","input":{"title":"Bug"}}}}"#;
+        let entry = JsonEvent::parse(line)
+            .unwrap()
+            .to_normalized_entry(Path::new("/repo"))
+            .unwrap();
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse { ref tool_name, .. } if tool_name == "github_create_issue"
+        ));
+
+        let future = JsonEvent::parse(r#"{"type":"reasoning","sessionID":"ses_1"}"#).unwrap();
+        assert!(matches!(future, JsonEvent::Unknown));
+        assert!(future.to_normalized_entry(Path::new("/repo")).is_none());
+    }
+}
@@ -4,13 +4,12 @@ use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
-use utils::{msg_store::MsgStore, shell::get_shell_command};
+use utils::msg_store::MsgStore;
 
 use crate::{
-    command::{apply_overrides, CmdOverrides, CommandBuilder},
-    executors::{AppendPrompt, ExecutorError, StandardCodingAgentExecutor},
+    command::{apply_overrides, CmdOverrides, CommandBuildError, CommandBuilder, ExecutionEnv},
+    executors::{AppendPrompt, AppendPromptFile, ExecutorError, StandardCodingAgentExecutor},
     logs::{
         stderr_processor::normalize_stderr_logs,
         utils::EntryIndexProvider,
@@ -24,6 +23,8 @@ use crate::{
 pub struct WarpCli {
     #[serde(default)]
     pub append_prompt: AppendPrompt,
+    #[serde(default)]
+    pub append_prompt_file: AppendPromptFile,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub profile: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -37,7 +38,7 @@ pub struct WarpCli {
 }
 
 impl WarpCli {
-    fn build_command_builder(&self) -> CommandBuilder {
+    fn build_command_builder(&self) -> Result<CommandBuilder, CommandBuildError> {
         let mut builder = CommandBuilder::new(self.binary.clone().unwrap_or_else(|| "warp".to_string()))
             .params(["agent", "run"]);
 
@@ -69,25 +70,21 @@ impl StandardCodingAgentExecutor for WarpCli {
         &self,
         current_dir: &Path,
         prompt: &str,
+        env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
-        let (shell_cmd, shell_arg) = get_shell_command();
-        let mut builder = self.build_command_builder();
-        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let mut builder = self.build_command_builder()?;
+        let combined_prompt =
+            self.append_prompt
+                .combine_prompt(prompt, &self.append_prompt_file, current_dir)?;
         builder = builder.extend_params([
             "--prompt".to_string(),
             Self::shell_escape(&combined_prompt),
         ]);
         let warp_command = builder.build_initial();
 
-        let mut command = Command::new(shell_cmd);
-        command
-            .kill_on_drop(true)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .arg(shell_arg)
-            .arg(&warp_command);
+        let mut command = self.cmd.shell_command(current_dir, &warp_command);
+        env.apply_to_command(&mut command);
+        command.stdin(Stdio::null());
 
         let child = command.group_spawn()?;
         Ok(child)
@@ -98,6 +95,7 @@ impl StandardCodingAgentExecutor for WarpCli {
         _current_dir: &Path,
         _prompt: &str,
         _session_id: &str,
+        _env: &ExecutionEnv,
     ) -> Result<AsyncGroupChild, ExecutorError> {
         Err(ExecutorError::FollowUpNotSupported(
             "Warp CLI does not support follow-up sessions".to_string(),
@@ -1,5 +1,8 @@
 pub mod actions;
+pub mod agent_version;
+pub mod approvals;
 pub mod command;
+pub mod detached;
 pub mod executors;
 pub mod logs;
 pub mod mcp_config;
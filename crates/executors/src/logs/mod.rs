@@ -1,3 +1,4 @@
+use ::utils::msg_store::MsgStore;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -34,6 +35,37 @@ pub enum CommandExitStatus {
 pub struct CommandRunResult {
     pub exit_status: Option<CommandExitStatus>,
     pub output: Option<String>,
+    /// Tool call id to fetch the complete output with when `output` was
+    /// truncated
+    #[serde(default)]
+    pub full_output_id: Option<String>,
+}
+
+impl CommandRunResult {
+    /// Result whose output is cut to the configured limit; the full output
+    /// stays retrievable from `msg_store` by `call_id`
+    pub fn truncated(
+        msg_store: &MsgStore,
+        call_id: &str,
+        exit_status: Option<CommandExitStatus>,
+        output: Option<String>,
+    ) -> Self {
+        match output
+            .as_deref()
+            .and_then(|output| utils::output::truncate_output(msg_store, call_id, output))
+        {
+            Some(truncated) => Self {
+                exit_status,
+                output: Some(truncated),
+                full_output_id: Some(call_id.to_string()),
+            },
+            None => Self {
+                exit_status,
+                output,
+                full_output_id: None,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -43,6 +75,18 @@ pub struct NormalizedConversation {
     pub executor_type: String,
     pub prompt: Option<String>,
     pub summary: Option<String>,
+    /// Latest token usage reported by the agent for the current turn
+    pub token_usage: Option<TokenUsage>,
+}
+
+/// Token counts reported by an agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub total_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -56,7 +100,13 @@ pub enum NormalizedEntryType {
     },
     SystemMessage,
     ErrorMessage,
+    /// Non-fatal diagnostics, e.g. warning or deprecation lines on stderr
+    Warning,
     Thinking,
+    /// The agent is blocked until the command for `call_id` is approved or denied
+    PendingApproval {
+        call_id: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
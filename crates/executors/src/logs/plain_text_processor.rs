@@ -7,7 +7,7 @@
 //! with custom split predicates to detect embedded markers and emit separate entries.
 //!
 //! ## Use cases
-//! - **stderr_processor**: Cluster stderr lines by time gap and format as `ErrorMessage` log entries.
+//! - **stderr_processor**: Cluster stderr lines by time gap and format as `Warning` or `ErrorMessage` log entries.
 //!   See [`stderr_processor::normalize_stderr_logs`].
 //! - **Gemini executor**: Post-process Gemini CLI output to make it prettier, then format it as assistant messages clustered by size.
 //!   See [`crate::executors::gemini::Gemini::format_stdout_chunk`].
@@ -1,7 +1,8 @@
 //! Standard stderr log processor for executors
 //!
 //! Uses `PlainTextLogProcessor` with a 2-second `latency_threshold` to split stderr streams into entries.
-//! Each entry is normalized as `ErrorMessage` and emitted as JSON patches to the message store.
+//! Lines mentioning a warning or deprecation are normalized as `Warning`, everything else as
+//! `ErrorMessage`, and emitted as JSON patches to the message store.
 //!
 //! Example:
 //! ```rust,ignore
@@ -13,18 +14,24 @@ use std::{sync::Arc, time::Duration};
 use futures::StreamExt;
 use utils::msg_store::MsgStore;
 
-use super::{NormalizedEntry, NormalizedEntryType, plain_text_processor::PlainTextLogProcessor};
+use super::{
+    NormalizedEntry, NormalizedEntryType,
+    plain_text_processor::{MessageBoundary, PlainTextLogProcessor},
+};
 use crate::logs::utils::EntryIndexProvider;
 
 /// Standard stderr log normalizer that uses PlainTextLogProcessor to stream error logs.
 ///
 /// Splits stderr output into discrete entries based on a latency threshold (2s) to group
-/// related lines into a single error entry. Each entry is normalized as an `ErrorMessage`
+/// related lines into a single error entry. Entries are split where lines switch between
+/// warnings and errors, normalized as `Warning` or `ErrorMessage` (see [`stderr_entry_type`]),
 /// and emitted as JSON patches for downstream consumption (e.g., UI or log aggregation).
 ///
 /// # Options
 /// - `latency_threshold`: 2 seconds to separate error messages based on time gaps.
-/// - `normalized_entry_producer`: maps each chunk into an `ErrorMessage` entry.
+/// - `message_boundary_predicate`: splits where a line is classified differently from the
+///   entry it would join.
+/// - `normalized_entry_producer`: maps each chunk into a `Warning` or `ErrorMessage` entry.
 ///
 /// # Use case
 /// Intended for executor stderr streams, grouping multi-line errors into cohesive entries
@@ -38,16 +45,7 @@ pub fn normalize_stderr_logs(msg_store: Arc<MsgStore>, entry_index_provider: Ent
         let mut stderr = msg_store.stderr_chunked_stream();
 
         // Create a processor with time-based emission for stderr
-        let mut processor = PlainTextLogProcessor::builder()
-            .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
-                timestamp: None,
-                entry_type: NormalizedEntryType::ErrorMessage,
-                content,
-                metadata: None,
-            }))
-            .time_gap(Duration::from_secs(2)) // Break messages if they are 2 seconds apart
-            .index_provider(entry_index_provider)
-            .build();
+        let mut processor = stderr_processor(entry_index_provider);
 
         while let Some(Ok(chunk)) = stderr.next().await {
             for patch in processor.process(chunk) {
@@ -56,3 +54,130 @@ pub fn normalize_stderr_logs(msg_store: Arc<MsgStore>, entry_index_provider: Ent
         }
     });
 }
+
+fn stderr_processor(entry_index_provider: EntryIndexProvider) -> PlainTextLogProcessor {
+    PlainTextLogProcessor::builder()
+        .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
+            timestamp: None,
+            entry_type: stderr_entry_type(content.lines().next().unwrap_or_default()),
+            content,
+            metadata: None,
+        }))
+        .message_boundary_predicate(Box::new(split_on_kind_change))
+        .time_gap(Duration::from_secs(2)) // Break messages if they are 2 seconds apart
+        .index_provider(entry_index_provider)
+        .build()
+}
+
+/// Classify a stderr line: warnings and deprecation notices are `Warning`,
+/// anything else is treated as an `ErrorMessage`
+pub fn stderr_entry_type(line: &str) -> NormalizedEntryType {
+    let line = line.to_lowercase();
+    if line.contains("warning") || line.contains("deprecated") {
+        NormalizedEntryType::Warning
+    } else {
+        NormalizedEntryType::ErrorMessage
+    }
+}
+
+fn is_warning(line: &str) -> bool {
+    matches!(stderr_entry_type(line), NormalizedEntryType::Warning)
+}
+
+/// Split before the first complete line whose kind differs from the first
+/// buffered line, so every entry holds only warnings or only errors
+fn split_on_kind_change(lines: &[String]) -> Option<MessageBoundary> {
+    let first = is_warning(lines.first()?);
+    lines
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, line)| line.ends_with('\n') && is_warning(line) != first)
+        .map(|(idx, _)| MessageBoundary::Split(idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entry types and contents after feeding `chunks` through the processor
+    fn classify(chunks: &[&str]) -> Vec<(String, String)> {
+        let mut processor = stderr_processor(EntryIndexProvider::test_new());
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for chunk in chunks {
+            for patch in processor.process(chunk.to_string()) {
+                let patch = serde_json::to_value(patch).unwrap();
+                let op = &patch[0];
+                let index: usize = op["path"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("/entries/")
+                    .parse()
+                    .unwrap();
+                let content = &op["value"]["content"];
+                let entry = (
+                    content["entry_type"]["type"].as_str().unwrap().to_string(),
+                    content["content"].as_str().unwrap().to_string(),
+                );
+                if index < entries.len() {
+                    entries[index] = entry;
+                } else {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries
+    }
+
+    #[test]
+    fn test_warning_and_deprecation_lines_are_classified() {
+        assert!(matches!(
+            stderr_entry_type("npm WARN deprecated inflight@1.0.6: not supported"),
+            NormalizedEntryType::Warning
+        ));
+        assert!(matches!(
+            stderr_entry_type("Warning: no config file found, using defaults"),
+            NormalizedEntryType::Warning
+        ));
+        assert!(matches!(
+            stderr_entry_type("Error: ENOENT: no such file or directory"),
+            NormalizedEntryType::ErrorMessage
+        ));
+    }
+
+    #[test]
+    fn test_mixed_stderr_is_split_by_kind() {
+        let entries = classify(&[
+            "(node:1) DeprecationWarning: Buffer() is deprecated\n",
+            "warning: unused variable `x`\n",
+            "Error: request failed\n    at fetch (index.js:10)\n",
+            "warning: retrying\n",
+        ]);
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "warning".to_string(),
+                    "(node:1) DeprecationWarning: Buffer() is deprecated\n\
+                     warning: unused variable `x`\n"
+                        .to_string()
+                ),
+                (
+                    "error_message".to_string(),
+                    "Error: request failed\n    at fetch (index.js:10)\n".to_string()
+                ),
+                ("warning".to_string(), "warning: retrying\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plain_stderr_stays_a_single_error() {
+        let entries = classify(&[
+            "thread 'main' panicked\n",
+            "note: run with RUST_BACKTRACE=1\n",
+        ]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "error_message");
+    }
+}
@@ -1,7 +1,9 @@
 //! Utility modules for executor framework
 
 pub mod entry_index;
+pub mod output;
 pub mod patch;
+pub mod replay;
 
 pub use entry_index::EntryIndexProvider;
 pub use patch::ConversationPatch;
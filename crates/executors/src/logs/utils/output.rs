@@ -0,0 +1,134 @@
+//! Decoding and truncation of command output reported by agents
+//!
+//! Commands run on non-UTF-8 locales can produce output that is not valid
+//! UTF-8. Agents then report it as raw bytes rather than a string; decode
+//! those lossily so the output survives with replacement characters.
+//!
+//! Long output is cut down before it goes into the conversation. The full text
+//! is kept in the execution's store by tool call id so it can still be fetched
+//! on demand.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use utils::msg_store::MsgStore;
+
+pub const DEFAULT_OUTPUT_LIMIT_BYTES: usize = 16 * 1024;
+
+static OUTPUT_LIMIT_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_OUTPUT_LIMIT_BYTES);
+
+/// Set how many bytes of command output go into the conversation; 0 keeps
+/// all of it
+pub fn set_output_limit_bytes(limit: usize) {
+    OUTPUT_LIMIT_BYTES.store(limit, Ordering::Relaxed);
+}
+
+/// Cut `output` to the configured limit, ending it with a marker. The full
+/// text stays available from `msg_store`'s full outputs under `call_id`.
+/// Returns `None` when the output is within the limit.
+pub fn truncate_output(msg_store: &MsgStore, call_id: &str, output: &str) -> Option<String> {
+    let truncated = truncate_to(output, OUTPUT_LIMIT_BYTES.load(Ordering::Relaxed))?;
+    msg_store.full_outputs().insert(call_id, output);
+    Some(truncated)
+}
+
+fn truncate_to(output: &str, limit: usize) -> Option<String> {
+    if limit == 0 || output.len() <= limit {
+        return None;
+    }
+    let mut end = limit;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(format!(
+        "{}\n… output truncated, showing {} of {} bytes",
+        &output[..end],
+        end,
+        output.len()
+    ))
+}
+
+/// Decode a JSON array of bytes, replacing invalid UTF-8 sequences.
+/// Returns `None` if `value` is not a byte array.
+pub fn bytes_to_string_lossy(value: &Value) -> Option<String> {
+    let bytes = value
+        .as_array()?
+        .iter()
+        .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Render command output as text: strings as-is, byte arrays decoded
+/// lossily, and any other JSON as its serialized form.
+pub fn output_to_string_lossy(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => bytes_to_string_lossy(other).unwrap_or_else(|| other.to_string()),
+    }
+}
+
+/// Deserialize optional command output given either as a string or as raw bytes
+pub fn deserialize_output_lossy<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Value>::deserialize(deserializer)?
+        .filter(|value| !value.is_null())
+        .map(|value| output_to_string_lossy(&value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_invalid_utf8_bytes_are_replaced() {
+        // "caf\xe9" as emitted under a Latin-1 locale
+        let value = json!([99, 97, 102, 233, 10]);
+        assert_eq!(
+            bytes_to_string_lossy(&value).as_deref(),
+            Some("caf\u{FFFD}\n")
+        );
+        assert_eq!(output_to_string_lossy(&value), "caf\u{FFFD}\n");
+        assert_eq!(output_to_string_lossy(&json!("plain")), "plain");
+        // Not a byte array
+        assert_eq!(bytes_to_string_lossy(&json!([1, 300])), None);
+        assert_eq!(bytes_to_string_lossy(&json!("abc")), None);
+    }
+
+    #[test]
+    fn test_truncation_keeps_char_boundaries_and_marks_the_cut() {
+        assert_eq!(truncate_to("short", 16), None);
+        assert_eq!(truncate_to(&"x".repeat(64), 0), None);
+
+        // "é" is two bytes, so a 3 byte limit cuts before the second one
+        let truncated = truncate_to("éé tail", 3).unwrap();
+        assert_eq!(truncated, "é\n… output truncated, showing 2 of 9 bytes");
+    }
+
+    #[test]
+    fn test_truncated_output_is_retrievable_in_full() {
+        let store = MsgStore::new();
+        let call_id = "call_output_test";
+        let output = "line\n".repeat(DEFAULT_OUTPUT_LIMIT_BYTES);
+
+        let truncated = truncate_output(&store, call_id, &output).unwrap();
+        assert!(truncated.starts_with(&output[..DEFAULT_OUTPUT_LIMIT_BYTES]));
+        assert!(truncated.ends_with(&format!(
+            "output truncated, showing {} of {} bytes",
+            DEFAULT_OUTPUT_LIMIT_BYTES,
+            output.len()
+        )));
+        let full_outputs = store.full_outputs();
+        assert_eq!(full_outputs.get(call_id).as_deref(), Some(output.as_str()));
+
+        assert_eq!(truncate_output(&store, "call_short_output", "ok"), None);
+        assert_eq!(full_outputs.get("call_short_output"), None);
+        // Another execution's call with the same id has its own output
+        assert_eq!(MsgStore::new().full_outputs().get(call_id), None);
+    }
+}
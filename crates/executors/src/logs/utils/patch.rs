@@ -4,7 +4,7 @@ use serde_json::{from_value, json};
 use ts_rs::TS;
 use utils::diff::Diff;
 
-use crate::logs::NormalizedEntry;
+use crate::logs::{NormalizedEntry, TokenUsage};
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, TS)]
 #[serde(rename_all = "lowercase")]
@@ -113,4 +113,14 @@ impl ConversationPatch {
 
         from_value(json!([patch_entry])).unwrap()
     }
+
+    /// Create a patch setting the conversation's token usage
+    pub fn token_usage(usage: TokenUsage) -> Patch {
+        from_value(json!([{
+            "op": PatchOperation::Add,
+            "path": "/token_usage",
+            "value": usage,
+        }]))
+        .unwrap()
+    }
 }
@@ -0,0 +1,118 @@
+//! Re-running a normalizer over persisted raw logs
+
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use serde_json::json;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+use crate::logs::NormalizedEntry;
+
+/// A finished store holding only the stdout/stderr of `raw_logs`, ready for an
+/// executor's `normalize_logs`
+pub fn raw_log_store(raw_logs: Vec<LogMsg>) -> Arc<MsgStore> {
+    let store = Arc::new(MsgStore::new());
+    for msg in raw_logs {
+        if matches!(msg, LogMsg::Stdout(_) | LogMsg::Stderr(_)) {
+            store.push(msg);
+        }
+    }
+    store.push_finished();
+    store
+}
+
+/// Wait for the normalizer running on `store` to finish, then return the
+/// conversation it produced
+pub async fn collect_entries(store: Arc<MsgStore>) -> Vec<NormalizedEntry> {
+    let stream = store.history_plus_stream();
+    // The stream ends once the normalizer drops its handles on the store
+    drop(store);
+    match stream.try_collect::<Vec<_>>().await {
+        Ok(messages) => conversation_entries(&messages),
+        Err(e) => {
+            tracing::warn!("Failed to read normalized logs: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Conversation entries reconstructed by applying the normalized JSON patches
+/// in `messages`, in order
+pub fn conversation_entries(messages: &[LogMsg]) -> Vec<NormalizedEntry> {
+    let mut doc = json!({ "entries": [] });
+    for msg in messages {
+        if let LogMsg::JsonPatch(patch) = msg
+            && let Err(e) = json_patch::patch(&mut doc, patch)
+        {
+            tracing::warn!("Failed to apply log patch: {}", e);
+        }
+    }
+
+    let entries = match doc["entries"].take() {
+        serde_json::Value::Array(entries) => entries,
+        _ => Vec::new(),
+    };
+    entries
+        .into_iter()
+        .filter(|entry| entry["type"] == "NORMALIZED_ENTRY")
+        .filter_map(|mut entry| serde_json::from_value(entry["content"].take()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        command::CmdOverrides,
+        executors::{
+            AppendPrompt, AppendPromptFile, StandardCodingAgentExecutor, claude::ClaudeCode,
+        },
+        logs::NormalizedEntryType,
+    };
+
+    #[tokio::test]
+    async fn renormalizes_persisted_claude_logs() {
+        let executor = ClaudeCode {
+            claude_code_router: Some(false),
+            plan: None,
+            append_prompt: AppendPrompt::default(),
+            append_prompt_file: AppendPromptFile::default(),
+            dangerously_skip_permissions: None,
+            working_dir_env: None,
+            cmd: CmdOverrides {
+                base_command_override: None,
+                additional_params: None,
+                raw_additional_params: None,
+                detached: None,
+                mcp_config_path_override: None,
+            },
+        };
+        // As persisted: raw output plus the stale patches of an older parser
+        let raw_logs = vec![
+            LogMsg::Stdout(
+                r#"{"type":"system","subtype":"init","session_id":"abc123"}"#.to_string() + "\n",
+            ),
+            LogMsg::JsonPatch(json_patch::Patch(Vec::new())),
+            LogMsg::Stdout(
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done."}]}}"#
+                    .to_string()
+                    + "\n",
+            ),
+            LogMsg::Finished,
+        ];
+
+        let store = raw_log_store(raw_logs);
+        executor.normalize_logs(store.clone(), Path::new("/tmp/test-worktree"));
+        let entries = collect_entries(store).await;
+
+        assert!(!entries.is_empty());
+        let last = entries.last().unwrap();
+        assert!(matches!(
+            last.entry_type,
+            NormalizedEntryType::AssistantMessage
+        ));
+        assert_eq!(last.content, "Done.");
+    }
+}
@@ -2,15 +2,57 @@
 //!
 //! These helpers abstract over JSON vs TOML formats used by different agents.
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+    sync::LazyLock,
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::fs;
+use tokio::{fs, sync::Mutex};
 use ts_rs::TS;
 
 use crate::executors::ExecutorError;
 
+/// Name of the vibe-kanban entry in agent MCP configs, reserved for it
+pub const VIBE_KANBAN_MCP_SERVER: &str = "vibe_kanban";
+
+/// An additional MCP server a project registers with its coding agents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct McpServerDefinition {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Check that project MCP servers have a command and distinct names that do
+/// not collide with the vibe-kanban entry
+pub fn validate_mcp_servers(servers: &[McpServerDefinition]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for server in servers {
+        let name = server.name.trim();
+        if name.is_empty() {
+            return Err("MCP server name must not be empty".to_string());
+        }
+        if name == VIBE_KANBAN_MCP_SERVER {
+            return Err(format!(
+                "MCP server name '{VIBE_KANBAN_MCP_SERVER}' is reserved for vibe-kanban"
+            ));
+        }
+        if server.command.trim().is_empty() {
+            return Err(format!("MCP server '{name}' needs a command"));
+        }
+        if !names.insert(name) {
+            return Err(format!("MCP server name '{name}' is used more than once"));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct McpConfig {
     servers: HashMap<String, serde_json::Value>,
@@ -38,6 +80,143 @@ impl McpConfig {
     pub fn set_servers(&mut self, servers: HashMap<String, serde_json::Value>) {
         self.servers = servers;
     }
+
+    /// Insert `entries` into the servers object at `servers_path` in `config`,
+    /// creating it if missing and keeping the servers already configured there
+    pub fn merge_servers(
+        &self,
+        config: &mut Value,
+        entries: impl IntoIterator<Item = (String, Value)>,
+    ) {
+        let mut current = config;
+        for part in &self.servers_path {
+            if !current.is_object() {
+                *current = serde_json::json!({});
+            }
+            current = current
+                .as_object_mut()
+                .expect("just ensured an object")
+                .entry(part.clone())
+                .or_insert_with(|| serde_json::json!({}));
+        }
+        if !current.is_object() {
+            *current = serde_json::json!({});
+        }
+        let servers = current.as_object_mut().expect("just ensured an object");
+        servers.extend(entries);
+    }
+
+    /// The servers object at `servers_path` in `config`, if it has one
+    fn servers_mut<'a>(
+        &self,
+        config: &'a mut Value,
+    ) -> Option<&'a mut serde_json::Map<String, Value>> {
+        self.servers_path
+            .iter()
+            .try_fold(config, |current, part| current.get_mut(part))?
+            .as_object_mut()
+    }
+}
+
+/// A project server some running execution registered in an agent config file
+struct RegisteredServer {
+    /// Executions still using the entry
+    count: usize,
+    /// The user's own entry of the same name, restored once no execution uses it
+    previous: Option<Value>,
+}
+
+/// Project servers registered by running executions, per agent config file.
+/// Held across each read-modify-write so concurrent runs keep each other's entries.
+static REGISTERED_SERVERS: LazyLock<Mutex<HashMap<PathBuf, HashMap<String, RegisteredServer>>>> =
+    LazyLock::new(Default::default);
+
+/// Project MCP servers one execution registered in an agent config file.
+/// [`McpRegistration::release`] takes them out again when the execution ends.
+#[derive(Debug, Clone)]
+pub struct McpRegistration {
+    config_path: PathBuf,
+    mcp_config: McpConfig,
+    names: Vec<String>,
+}
+
+impl McpRegistration {
+    /// The config file the servers were written to
+    pub fn config_path(&self) -> &std::path::Path {
+        &self.config_path
+    }
+
+    /// Apply `merge` to the config file and count the execution against `names`
+    pub(crate) async fn register(
+        config_path: PathBuf,
+        mcp_config: McpConfig,
+        names: Vec<String>,
+        merge: impl FnOnce(&mut Value),
+    ) -> Result<Self, ExecutorError> {
+        let mut registered = REGISTERED_SERVERS.lock().await;
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let mut config = read_agent_config(&config_path, &mcp_config).await?;
+        let existing = mcp_config
+            .servers_mut(&mut config)
+            .map(|servers| servers.clone());
+        merge(&mut config);
+        write_agent_config(&config_path, &mcp_config, &config).await?;
+
+        let servers = registered.entry(config_path.clone()).or_default();
+        for name in &names {
+            servers
+                .entry(name.clone())
+                .or_insert_with(|| RegisteredServer {
+                    count: 0,
+                    previous: existing.as_ref().and_then(|e| e.get(name).cloned()),
+                })
+                .count += 1;
+        }
+        Ok(Self {
+            config_path,
+            mcp_config,
+            names,
+        })
+    }
+
+    /// Remove the servers no other running execution registered, putting back
+    /// any entry of the user's they replaced
+    pub async fn release(self) -> Result<(), ExecutorError> {
+        let mut registered = REGISTERED_SERVERS.lock().await;
+        let Some(servers) = registered.get_mut(&self.config_path) else {
+            return Ok(());
+        };
+        let mut unused = Vec::new();
+        for name in self.names {
+            let Some(server) = servers.get_mut(&name) else {
+                continue;
+            };
+            server.count -= 1;
+            if server.count == 0 {
+                let previous = servers.remove(&name).and_then(|server| server.previous);
+                unused.push((name, previous));
+            }
+        }
+        if servers.is_empty() {
+            registered.remove(&self.config_path);
+        }
+        if unused.is_empty() {
+            return Ok(());
+        }
+
+        let mut config = read_agent_config(&self.config_path, &self.mcp_config).await?;
+        if let Some(servers) = self.mcp_config.servers_mut(&mut config) {
+            for (name, previous) in unused {
+                match previous {
+                    Some(entry) => servers.insert(name, entry),
+                    None => servers.remove(&name),
+                };
+            }
+        }
+        write_agent_config(&self.config_path, &self.mcp_config, &config).await
+    }
 }
 
 /// Read an agent's external config file (JSON or TOML) and normalize it to serde_json::Value.
@@ -79,3 +258,194 @@ pub async fn write_agent_config(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::executors::{CodingAgent, StandardCodingAgentExecutor};
+
+    fn agent(name: &str) -> CodingAgent {
+        serde_json::from_value(json!({ name: {} })).unwrap()
+    }
+
+    fn docs_server() -> McpServerDefinition {
+        McpServerDefinition {
+            name: "docs".to_string(),
+            command: "docs-mcp".to_string(),
+            args: vec!["--stdio".to_string()],
+            env: BTreeMap::from([("DOCS_TOKEN".to_string(), "secret".to_string())]),
+        }
+    }
+
+    #[test]
+    fn merges_into_codex_mcp_servers() {
+        let codex = agent("CODEX");
+        let mut config = json!({
+            "model": "o3",
+            "mcp_servers": { "github": { "command": "gh-mcp" } }
+        });
+
+        codex.merge_mcp_servers(&mut config, &[docs_server()]);
+
+        let servers = &config["mcp_servers"];
+        assert_eq!(servers["github"]["command"], "gh-mcp");
+        assert_eq!(servers[VIBE_KANBAN_MCP_SERVER]["command"], "npx");
+        assert_eq!(servers["docs"]["command"], "docs-mcp");
+        assert_eq!(servers["docs"]["args"], json!(["--stdio"]));
+        assert_eq!(servers["docs"]["env"]["DOCS_TOKEN"], "secret");
+        assert_eq!(config["model"], "o3");
+        // The merged config must still be representable as Codex's TOML
+        toml::to_string(&serde_json::from_value::<toml::Value>(config).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn merges_into_amp_dotted_key() {
+        let amp = agent("AMP");
+        let mut config = json!({});
+
+        amp.merge_mcp_servers(&mut config, &[docs_server()]);
+
+        let servers = &config["amp.mcpServers"];
+        assert!(servers[VIBE_KANBAN_MCP_SERVER].is_object());
+        assert_eq!(servers["docs"]["command"], "docs-mcp");
+        assert_eq!(servers["docs"]["env"]["DOCS_TOKEN"], "secret");
+        assert!(config.get("amp").is_none());
+    }
+
+    #[test]
+    fn merges_into_opencode_mcp_with_command_array() {
+        let opencode = agent("OPENCODE");
+        let mut config = json!({
+            "$schema": "https://opencode.ai/config.json",
+            "mcp": { "local-db": { "type": "local", "command": ["db-mcp"] } }
+        });
+
+        opencode.merge_mcp_servers(&mut config, &[docs_server()]);
+
+        let servers = &config["mcp"];
+        assert_eq!(servers["local-db"]["command"], json!(["db-mcp"]));
+        assert_eq!(servers[VIBE_KANBAN_MCP_SERVER]["type"], "local");
+        assert_eq!(servers["docs"]["type"], "local");
+        assert_eq!(servers["docs"]["command"], json!(["docs-mcp", "--stdio"]));
+        assert_eq!(servers["docs"]["environment"]["DOCS_TOKEN"], "secret");
+        assert_eq!(servers["docs"]["enabled"], true);
+        assert_eq!(config["$schema"], "https://opencode.ai/config.json");
+
+        // A config file without the schema tag gets one
+        let mut untagged = json!({ "model": "anthropic/claude-sonnet-4" });
+        opencode.merge_mcp_servers(&mut untagged, &[]);
+        assert_eq!(untagged["$schema"], "https://opencode.ai/config.json");
+        assert!(untagged["mcp"][VIBE_KANBAN_MCP_SERVER].is_object());
+    }
+
+    #[tokio::test]
+    async fn writes_to_project_local_override_path() {
+        let worktree = std::env::temp_dir().join(format!("vk-mcp-{}", uuid::Uuid::new_v4()));
+        let opencode: CodingAgent = serde_json::from_value(json!({
+            "OPENCODE": { "mcp_config_path_override": ".opencode/config.json" }
+        }))
+        .unwrap();
+        let local_path = worktree.join(".opencode/config.json");
+        assert_eq!(
+            opencode.mcp_config_path(&worktree),
+            Some(local_path.clone())
+        );
+
+        opencode
+            .register_mcp_servers(&worktree, &[docs_server()])
+            .await
+            .unwrap();
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&local_path).unwrap()).unwrap();
+        assert_eq!(
+            written["mcp"]["docs"]["command"],
+            json!(["docs-mcp", "--stdio"])
+        );
+        assert!(written["mcp"][VIBE_KANBAN_MCP_SERVER].is_object());
+
+        // Absolute overrides are used as is; no override falls back to the global file
+        let elsewhere = worktree.join("elsewhere.json");
+        let absolute: CodingAgent = serde_json::from_value(json!({
+            "OPENCODE": { "mcp_config_path_override": elsewhere }
+        }))
+        .unwrap();
+        assert_eq!(absolute.mcp_config_path(&worktree), Some(elsewhere));
+        let global = agent("OPENCODE");
+        assert_eq!(
+            global.mcp_config_path(&worktree),
+            global.default_mcp_config_path()
+        );
+
+        std::fs::remove_dir_all(&worktree).unwrap();
+    }
+
+    #[tokio::test]
+    async fn released_servers_leave_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("vk-mcp-{}", uuid::Uuid::new_v4()));
+        let config_path = dir.join("config.toml");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &config_path,
+            "[mcp_servers.docs]\ncommand = \"my-docs\"\n\n[mcp_servers.github]\ncommand = \"gh-mcp\"\n",
+        )
+        .unwrap();
+        let codex: CodingAgent = serde_json::from_value(json!({
+            "CODEX": { "mcp_config_path_override": config_path }
+        }))
+        .unwrap();
+        let search = McpServerDefinition {
+            name: "search".to_string(),
+            ..docs_server()
+        };
+        let read = || -> Value {
+            toml::from_str::<toml::Value>(&std::fs::read_to_string(&config_path).unwrap())
+                .map(|config| serde_json::to_value(config).unwrap())
+                .unwrap()
+        };
+
+        let first = codex
+            .register_mcp_servers(&dir, &[docs_server(), search.clone()])
+            .await
+            .unwrap()
+            .unwrap();
+        let second = codex
+            .register_mcp_servers(&dir, &[search])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read()["mcp_servers"]["docs"]["command"], "docs-mcp");
+
+        // The user's own entry comes back; a server another run uses stays
+        first.release().await.unwrap();
+        let servers = &read()["mcp_servers"];
+        assert_eq!(servers["docs"]["command"], "my-docs");
+        assert_eq!(servers["search"]["command"], "docs-mcp");
+        assert_eq!(servers["github"]["command"], "gh-mcp");
+
+        second.release().await.unwrap();
+        assert!(read()["mcp_servers"].get("search").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_reserved_and_duplicate_names() {
+        assert!(validate_mcp_servers(&[docs_server()]).is_ok());
+
+        let reserved = McpServerDefinition {
+            name: VIBE_KANBAN_MCP_SERVER.to_string(),
+            ..docs_server()
+        };
+        assert!(validate_mcp_servers(&[reserved]).is_err());
+        assert!(validate_mcp_servers(&[docs_server(), docs_server()]).is_err());
+
+        let no_command = McpServerDefinition {
+            command: " ".to_string(),
+            ..docs_server()
+        };
+        assert!(validate_mcp_servers(&[no_command]).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use std::{collections::HashMap, fs, str::FromStr, sync::RwLock};
 
 use convert_case::{Case, Casing};
 use lazy_static::lazy_static;
+use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize, de::Error as DeError};
 use thiserror::Error;
 use ts_rs::TS;
@@ -54,7 +55,7 @@ lazy_static! {
 const DEFAULT_PROFILES_JSON: &str = include_str!("../default_profiles.json");
 
 // Executor-centric profile identifier
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, Hash, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Hash, Eq)]
 pub struct ExecutorProfileId {
     /// The executor type (e.g., "CLAUDE_CODE", "AMP")
     #[serde(alias = "profile", deserialize_with = "de_base_coding_agent_kebab")]
@@ -224,6 +225,7 @@ impl ExecutorConfigs {
             Ok(mut user_overrides) => {
                 tracing::info!("Loaded user profile overrides from profiles.json");
                 user_overrides.canonicalise();
+                user_overrides.warn_about_overrides();
                 Self::merge_with_defaults(defaults, user_overrides)
             }
             Err(e) => {
@@ -236,6 +238,27 @@ impl ExecutorConfigs {
         }
     }
 
+    /// Warn about saved command overrides that no longer work as they did:
+    /// unsafe parameters now fail at spawn time, and multi-word parameters
+    /// are passed as a single argument
+    fn warn_about_overrides(&self) {
+        for (executor_key, profile) in &self.executors {
+            for (config_name, config) in &profile.configurations {
+                let cmd = config.cmd_overrides();
+                if let Err(e) = cmd.validate() {
+                    tracing::warn!("Profile {executor_key}:{config_name} will fail to start: {e}");
+                }
+                for param in cmd.multi_word_params() {
+                    tracing::warn!(
+                        "Profile {executor_key}:{config_name}: additional parameter {param:?} is \
+                         passed as one argument; split it into separate entries if it holds \
+                         several"
+                    );
+                }
+            }
+        }
+    }
+
     /// Save user profile overrides to file (only saves what differs from defaults)
     pub fn save_overrides(&self) -> Result<(), ProfileError> {
         let profiles_path = utils::assets::profiles_path();
@@ -364,12 +387,15 @@ impl ExecutorConfigs {
             }
 
             // Ensure configuration names don't conflict with reserved words
-            for config_name in profile.configurations.keys() {
+            for (config_name, config) in &profile.configurations {
                 if config_name.starts_with("__") {
                     return Err(ProfileError::Validation(format!(
                         "Configuration name '{config_name}' is reserved (starts with '__')"
                     )));
                 }
+                config.cmd_overrides().validate().map_err(|e| {
+                    ProfileError::Validation(format!("{executor_key}:{config_name}: {e}"))
+                })?;
             }
         }
         Ok(())
@@ -425,3 +451,25 @@ impl ExecutorConfigs {
         Err(ProfileError::NoAvailableExecutorProfile)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsafe_additional_params_fail_validation() {
+        let profiles: ExecutorConfigs = serde_json::from_value(serde_json::json!({
+            "executors": {
+                "CLAUDE_CODE": {
+                    "DEFAULT": { "CLAUDE_CODE": { "additional_params": ["--model", "$(id)"] } }
+                }
+            }
+        }))
+        .unwrap();
+        let err = ExecutorConfigs::validate_merged(&profiles).unwrap_err();
+        assert!(
+            matches!(err, ProfileError::Validation(ref message) if message.contains("$(id)")),
+            "{err}"
+        );
+    }
+}
@@ -41,3 +41,46 @@ pub async fn kill_process_group(child: &mut AsyncGroupChild) -> Result<(), Conta
     let _ = child.wait().await;
     Ok(())
 }
+
+/// Kill the process group led by `pid` when we have no child handle for it,
+/// e.g. a detached agent we reattached to after a restart.
+pub async fn kill_process_group_by_pid(pid: u32) -> Result<(), ContainerError> {
+    #[cfg(unix)]
+    {
+        let pgid = getpgid(Some(Pid::from_raw(pid as i32)))
+            .map_err(|e| ContainerError::KillFailed(std::io::Error::other(e)))?;
+
+        for sig in [Signal::SIGINT, Signal::SIGTERM, Signal::SIGKILL] {
+            if let Err(e) = killpg(pgid, sig) {
+                tracing::warn!(
+                    "Failed to send signal {:?} to process group {}: {}",
+                    sig,
+                    pgid,
+                    e
+                );
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            if !is_process_alive(pid) {
+                break;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+    }
+    Ok(())
+}
+
+/// Check whether a process with this PID still exists
+pub fn is_process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        nix::sys::signal::kill(Pid::from_raw(pid as i32), None).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
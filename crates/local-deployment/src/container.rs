@@ -10,6 +10,7 @@ use anyhow::anyhow;
 use async_stream::try_stream;
 use async_trait::async_trait;
 use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
 use command_group::AsyncGroupChild;
 use db::{
     DBService,
@@ -17,6 +18,7 @@ use db::{
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
         },
+        execution_process_logs::ExecutionProcessLogs,
         executor_session::ExecutorSession,
         merge::Merge,
         project::Project,
@@ -26,66 +28,116 @@ use db::{
 };
 use deployment::DeploymentError;
 use executors::{
-    actions::{Executable, ExecutorAction},
+    actions::{Executable, ExecutorAction, ExecutorActionType},
+    approvals::{ApprovalDecision, PendingApprovals},
+    command::{describe_missing_command, push_error_entry},
+    detached::{self, DetachedOutput},
+    executors::{
+        CodingAgent, StandardCodingAgentExecutor,
+        codex::{Codex, ProtoEvent},
+    },
     logs::{
         NormalizedEntry, NormalizedEntryType,
         utils::{ConversationPatch, patch::escape_json_pointer_segment},
     },
+    mcp_config::McpRegistration,
+    profile::ExecutorConfigs,
 };
 use futures::{StreamExt, TryStreamExt, stream::select};
-use notify_debouncer_full::DebouncedEvent;
 use serde_json::json;
 use services::services::{
-    analytics::AnalyticsContext,
+    analytics::{AnalyticsContext, analytics_allowed},
     config::Config,
     container::{ContainerError, ContainerRef, ContainerService},
-    filesystem_watcher,
-    git::{DiffTarget, GitService},
+    events::EventService,
+    execution_slots::ExecutionSlots,
+    filesystem_watcher::{self, WorktreeWatchers},
+    git::{DiffOptionsExt, DiffTarget, GitService, GitServiceError},
     image::ImageService,
+    metrics::metrics,
     notification::NotificationService,
     worktree_manager::WorktreeManager,
 };
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::RwLock,
+    task::JoinHandle,
+};
 use tokio_util::io::ReaderStream;
 use utils::{
-    log_msg::LogMsg,
+    diff::{Diff, DiffSummary},
+    log_msg::{LogMsg, LogStream},
     msg_store::MsgStore,
+    test_summary::parse_test_summary,
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 
-use crate::command;
+use crate::{command, watchdog};
+
+/// A detached agent whose output is read from log files rather than pipes
+struct DetachedProcess {
+    pid: u32,
+    log_forwarder: JoinHandle<()>,
+}
+
+/// Where an attempt's diff is computed from.
+enum DiffSource {
+    Merged {
+        repo_path: PathBuf,
+        merge_commit: String,
+    },
+    Worktree {
+        worktree_path: PathBuf,
+        task_branch: String,
+    },
+}
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
+    detached_store: Arc<RwLock<HashMap<Uuid, DetachedProcess>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
     config: Arc<RwLock<Config>>,
     git: GitService,
     image_service: ImageService,
     analytics: Option<AnalyticsContext>,
+    execution_slots: ExecutionSlots,
+    events: EventService,
+    worktree_watchers: WorktreeWatchers,
+    pending_approvals: PendingApprovals,
+    mcp_registrations: Arc<RwLock<HashMap<Uuid, McpRegistration>>>,
 }
 
 impl LocalContainerService {
-    pub fn new(
+    pub async fn new(
         db: DBService,
         msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
         config: Arc<RwLock<Config>>,
         git: GitService,
         image_service: ImageService,
         analytics: Option<AnalyticsContext>,
+        events: EventService,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
+        let detached_store = Arc::new(RwLock::new(HashMap::new()));
+        let execution_slots = ExecutionSlots::new(config.read().await.max_concurrent_executions);
 
         LocalContainerService {
             db,
             child_store,
+            detached_store,
             msg_stores,
             config,
             git,
             image_service,
             analytics,
+            execution_slots,
+            events,
+            worktree_watchers: WorktreeWatchers::default(),
+            pending_approvals: PendingApprovals::default(),
+            mcp_registrations: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -104,6 +156,67 @@ impl LocalContainerService {
         map.remove(id);
     }
 
+    async fn write_to_stdin(&self, id: &Uuid, input: &[u8]) -> Result<(), ContainerError> {
+        let child = self.get_child_from_store(id).await.ok_or_else(|| {
+            ContainerError::Other(anyhow!("Child process not found for execution"))
+        })?;
+        let mut child_guard = child.write().await;
+        let stdin = child_guard.inner().stdin.as_mut().ok_or_else(|| {
+            ContainerError::Other(anyhow!("Execution does not accept input on stdin"))
+        })?;
+        stdin.write_all(input).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Take an ended execution's project MCP servers out of its agent's config
+    async fn release_mcp_servers(&self, exec_id: &Uuid) {
+        let registration = self.mcp_registrations.write().await.remove(exec_id);
+        if let Some(registration) = registration
+            && let Err(e) = registration.release().await
+        {
+            tracing::warn!(
+                "Failed to remove project MCP servers of execution {}: {}",
+                exec_id,
+                e
+            );
+        }
+    }
+
+    /// Follow a live `codex proto` run: track the approvals it blocks on and
+    /// shut it down once its turn completes, since proto never exits by itself.
+    /// Replayed logs never reach this, so finished runs register nothing.
+    async fn spawn_codex_proto_driver(&self, exec_id: Uuid) {
+        let Some(msg_store) = self.get_msg_store_by_id(&exec_id).await else {
+            return;
+        };
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut lines = msg_store.stdout_lines_stream();
+            while let Some(Ok(line)) = lines.next().await {
+                match Codex::proto_event(&line) {
+                    Some(ProtoEvent::ApprovalRequested(call_id)) => {
+                        container.pending_approvals.register(exec_id, &call_id);
+                    }
+                    Some(ProtoEvent::TaskComplete) => {
+                        let shutdown = Codex::shutdown_submission();
+                        if let Err(e) = container
+                            .write_to_stdin(&exec_id, shutdown.as_bytes())
+                            .await
+                        {
+                            tracing::warn!("Failed to shut down codex run {}: {}", exec_id, e);
+                        }
+                        if let Some(child) = container.get_child_from_store(&exec_id).await {
+                            drop(child.write().await.inner().stdin.take());
+                        }
+                        break;
+                    }
+                    None => {}
+                }
+            }
+        });
+    }
+
     /// A context is finalized when
     /// - The next action is None (no follow-up actions)
     /// - The run reason is not DevServer
@@ -259,8 +372,107 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Prune worktrees of finished attempts beyond the configured count and age limits,
+    /// least recently active first
+    pub async fn prune_worktrees(
+        db: &DBService,
+        config: &Arc<RwLock<Config>>,
+        analytics: &Option<AnalyticsContext>,
+    ) -> Result<(), DeploymentError> {
+        let (max_count, max_age_days, analytics_enabled) = {
+            let config = config.read().await;
+            (
+                config.max_worktree_count,
+                config.max_worktree_age_days,
+                analytics_allowed(config.analytics_enabled),
+            )
+        };
+        if max_count.is_none() && max_age_days.is_none() {
+            return Ok(());
+        }
+
+        let candidates = TaskAttempt::find_prunable_worktrees(&db.pool).await?;
+        let live_count = TaskAttempt::count_live_worktrees(&db.pool).await? as usize;
+        let activity: Vec<(usize, DateTime<Utc>)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, _, last_activity))| (i, *last_activity))
+            .collect();
+        let to_prune = WorktreeManager::select_worktrees_to_prune(
+            &activity,
+            live_count,
+            max_count,
+            max_age_days,
+            Utc::now(),
+        );
+
+        for i in to_prune {
+            let (attempt_id, worktree_path, git_repo_path, last_activity) = &candidates[i];
+            match Self::prune_worktree(
+                db,
+                *attempt_id,
+                PathBuf::from(worktree_path),
+                PathBuf::from(git_repo_path),
+            )
+            .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::info!(
+                        "Kept worktree of attempt {attempt_id}: a process started in it"
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to prune worktree for attempt {attempt_id}: {e}");
+                    continue;
+                }
+            }
+            tracing::info!(
+                "Pruned worktree {} for attempt {} (last active {})",
+                worktree_path,
+                attempt_id,
+                last_activity
+            );
+            if analytics_enabled && let Some(analytics) = analytics {
+                analytics.analytics_service.track_event(
+                    &analytics.user_id,
+                    "worktree_pruned",
+                    Some(json!({
+                        "attempt_id": attempt_id.to_string(),
+                        "idle_days": (Utc::now() - *last_activity).num_days(),
+                    })),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the worktree of `attempt_id` picked for pruning, unless a process
+    /// was started in it since. Returns whether the worktree was removed.
+    async fn prune_worktree(
+        db: &DBService,
+        attempt_id: Uuid,
+        worktree_path: PathBuf,
+        git_repo_path: PathBuf,
+    ) -> Result<bool, DeploymentError> {
+        let processes = ExecutionProcess::find_by_task_attempt_id(&db.pool, attempt_id).await?;
+        if processes.iter().any(|process| {
+            matches!(
+                process.status,
+                ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+            )
+        }) {
+            return Ok(false);
+        }
+        Self::cleanup_expired_attempt(db, attempt_id, worktree_path, git_repo_path).await?;
+        Ok(true)
+    }
+
     pub async fn spawn_worktree_cleanup(&self) {
         let db = self.db.clone();
+        let config = self.config.clone();
+        let analytics = self.analytics.clone();
         let mut cleanup_interval = tokio::time::interval(tokio::time::Duration::from_secs(1800)); // 30 minutes
         self.cleanup_orphaned_worktrees().await;
         tokio::spawn(async move {
@@ -277,54 +489,218 @@ impl LocalContainerService {
                     .unwrap_or_else(|e| {
                         tracing::error!("Failed to clean up expired worktree attempts: {}", e)
                     });
+                Self::prune_worktrees(&db, &config, &analytics)
+                    .await
+                    .unwrap_or_else(|e| tracing::error!("Failed to prune worktrees: {}", e));
+            }
+        });
+    }
+
+    /// Periodically check that a running coding agent has not moved its worktree
+    /// off the task branch. Divergence is reported in the process logs and, unless
+    /// the config allows agents to switch branches, the task branch is checked out again.
+    fn spawn_branch_guard(&self, exec_id: Uuid, worktree_path: PathBuf, branch: String) {
+        let git = self.git().clone();
+        let config = self.config.clone();
+        let msg_stores = self.msg_stores.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(10));
+            let mut reported: Option<String> = None;
+            loop {
+                interval.tick().await;
+                let Some(msg_store) = msg_stores.read().await.get(&exec_id).cloned() else {
+                    break;
+                };
+
+                let reattach = !config.read().await.allow_agent_branch_switch;
+                match git.ensure_on_branch(&worktree_path, &branch, reattach) {
+                    Ok(None) => reported = None,
+                    Ok(Some(current)) => {
+                        let message = if reattach {
+                            format!(
+                                "Worktree was moved from task branch '{branch}' to '{current}'; switched back to '{branch}'"
+                            )
+                        } else {
+                            format!(
+                                "Worktree was moved from task branch '{branch}' to '{current}'; changes on '{current}' are not tracked by this attempt"
+                            )
+                        };
+                        if reattach || reported.as_deref() != Some(current.as_str()) {
+                            tracing::warn!("{} (execution {})", message, exec_id);
+                            msg_store.push_stderr(format!("{message}\n"));
+                        }
+                        reported = (!reattach).then_some(current);
+                    }
+                    Err(GitServiceError::WorktreeDirty(current, _)) => {
+                        if reported.as_deref() != Some(current.as_str()) {
+                            let message = format!(
+                                "Worktree was moved from task branch '{branch}' to '{current}' and has uncommitted changes; not switching back"
+                            );
+                            tracing::warn!("{} (execution {})", message, exec_id);
+                            msg_store.push_stderr(format!("{message}\n"));
+                            reported = Some(current);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::debug!("Branch check failed for execution {}: {}", exec_id, e)
+                    }
+                }
+            }
+        });
+    }
+
+    /// Kill an execution that goes `timeout` without output and mark it failed,
+    /// recording `reason` as its failure reason
+    fn spawn_inactivity_watchdog(&self, exec_id: Uuid, timeout: Duration, reason: String) {
+        let container = self.clone();
+
+        tokio::spawn(async move {
+            let Some(msg_store) = container.msg_stores.read().await.get(&exec_id).cloned() else {
+                return;
+            };
+            if !watchdog::wait_for_inactivity(&msg_store, timeout).await {
+                return;
+            }
+            match ExecutionProcess::find_by_id(&container.db.pool, exec_id).await {
+                Ok(Some(process)) if process.status == ExecutionProcessStatus::Running => {}
+                _ => return,
+            }
+
+            tracing::warn!("{} (execution {})", reason, exec_id);
+            msg_store.push_stderr(format!("{reason}\n"));
+            if let Err(e) =
+                ExecutionProcess::mark_failed(&container.db.pool, exec_id, &reason).await
+            {
+                tracing::error!("Failed to mark execution {} as timed out: {}", exec_id, e);
+            }
+
+            let kill_result = if let Some(child) = container.get_child_from_store(&exec_id).await {
+                let mut child_guard = child.write().await;
+                command::kill_process_group(&mut child_guard).await
+            } else if let Some(pid) = container
+                .detached_store
+                .read()
+                .await
+                .get(&exec_id)
+                .map(|detached| detached.pid)
+            {
+                command::kill_process_group_by_pid(pid).await
+            } else {
+                Ok(())
+            };
+            if let Err(e) = kill_result {
+                tracing::error!("Failed to kill timed out execution {}: {}", exec_id, e);
             }
         });
     }
 
+    /// Announce changes in the attempt's worktree on the event stream while it runs
+    fn watch_worktree(&self, attempt_id: Uuid, worktree_path: PathBuf) {
+        let events = self.events.clone();
+        if let Err(e) = self
+            .worktree_watchers
+            .subscribe(attempt_id, worktree_path, move |paths| {
+                let events = events.clone();
+                async move { events.push_diff_changed(attempt_id, paths).await }
+            })
+        {
+            tracing::warn!("Failed to watch worktree of attempt {}: {}", attempt_id, e);
+        }
+    }
+
+    /// Stop watching the attempt's worktree once none of its processes, other
+    /// than dev servers, are queued or running
+    async fn unwatch_worktree_if_idle(&self, attempt_id: Uuid) {
+        let Ok(processes) =
+            ExecutionProcess::find_by_task_attempt_id(&self.db.pool, attempt_id).await
+        else {
+            return;
+        };
+        let active = processes.iter().any(|process| {
+            matches!(
+                process.status,
+                ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+            ) && !matches!(process.run_reason, ExecutionProcessRunReason::DevServer)
+        });
+        if !active {
+            self.worktree_watchers.unsubscribe(&attempt_id);
+        }
+    }
+
     /// Spawn a background task that polls the child process for completion and
     /// cleans up the execution entry when it exits.
     pub fn spawn_exit_monitor(&self, exec_id: &Uuid) -> JoinHandle<()> {
         let exec_id = *exec_id;
         let child_store = self.child_store.clone();
+        let detached_store = self.detached_store.clone();
         let msg_stores = self.msg_stores.clone();
         let db = self.db.clone();
         let config = self.config.clone();
         let container = self.clone();
         let analytics = self.analytics.clone();
 
+        metrics().execution_started();
+
         tokio::spawn(async move {
             loop {
-                let status_opt = {
-                    let child_lock = {
-                        let map = child_store.read().await;
-                        map.get(&exec_id)
-                            .cloned()
-                            .unwrap_or_else(|| panic!("Child handle missing for {exec_id}"))
-                    };
-
+                let child_lock = {
+                    let map = child_store.read().await;
+                    map.get(&exec_id).cloned()
+                };
+                let status_opt = if let Some(child_lock) = child_lock {
                     let mut child_handler = child_lock.write().await;
                     match child_handler.try_wait() {
-                        Ok(Some(status)) => Some(Ok(status)),
-                        Ok(None) => None,
-                        Err(e) => Some(Err(e)),
-                    }
-                };
-
-                // Update execution process and cleanup if exit
-                if let Some(status_result) = status_opt {
-                    // Update execution process record with completion info
-                    let (exit_code, status) = match status_result {
-                        Ok(exit_status) => {
+                        Ok(Some(exit_status)) => {
                             let code = exit_status.code().unwrap_or(-1) as i64;
                             let status = if exit_status.success() {
                                 ExecutionProcessStatus::Completed
                             } else {
                                 ExecutionProcessStatus::Failed
                             };
-                            (Some(code), status)
+                            Some((Some(code), status))
                         }
-                        Err(_) => (None, ExecutionProcessStatus::Failed),
+                        Ok(None) => None,
+                        Err(_) => Some((None, ExecutionProcessStatus::Failed)),
+                    }
+                } else {
+                    // Reattached detached agents are not our children, so poll
+                    // the PID and read the exit code recorded by the wrapper
+                    let Some(pid) = detached_store
+                        .read()
+                        .await
+                        .get(&exec_id)
+                        .map(|detached| detached.pid)
+                    else {
+                        // Removed by `stop_execution`, which already finalized the process
+                        tracing::debug!("Execution {} was stopped, ending exit monitor", exec_id);
+                        break;
                     };
+                    if command::is_process_alive(pid) {
+                        None
+                    } else {
+                        let exit_code = DetachedOutput::for_pid(pid).read_exit_code();
+                        let status = if exit_code == Some(0) {
+                            ExecutionProcessStatus::Completed
+                        } else {
+                            ExecutionProcessStatus::Failed
+                        };
+                        Some((exit_code, status))
+                    }
+                };
+
+                // Update execution process and cleanup if exit
+                if let Some((exit_code, status)) = status_opt {
+                    // Let detached log tailers drain what is left in the log files
+                    let detached = detached_store.write().await.remove(&exec_id);
+                    let was_detached = detached.is_some();
+                    if let Some(mut detached) = detached
+                        && tokio::time::timeout(Duration::from_secs(5), &mut detached.log_forwarder)
+                            .await
+                            .is_err()
+                    {
+                        tracing::warn!("Timed out draining detached logs for {}", exec_id);
+                    }
 
                     if !ExecutionProcess::was_killed(&db.pool, exec_id).await
                         && let Err(e) = ExecutionProcess::update_completion(
@@ -337,6 +713,18 @@ impl LocalContainerService {
                     {
                         tracing::error!("Failed to update execution process completion: {}", e);
                     }
+                    // The shell started fine but could not find the command it was given
+                    if status == ExecutionProcessStatus::Failed
+                        && let Some(store) = msg_stores.read().await.get(&exec_id).cloned()
+                        && let Some(message) = describe_missing_command(
+                            exit_code,
+                            &store.raw_output(LogStream::Stderr),
+                        )
+                    {
+                        tracing::error!("Execution process {} failed: {}", exec_id, message);
+                        push_error_entry(&store, &message);
+                    }
+                    container.execution_slots.release(&exec_id);
 
                     if let Ok(ctx) = ExecutionProcess::load_context(&db.pool, exec_id).await {
                         // Update executor session summary if available
@@ -344,6 +732,15 @@ impl LocalContainerService {
                             tracing::warn!("Failed to update executor session summary: {}", e);
                         }
 
+                        if matches!(
+                            ctx.execution_process.run_reason,
+                            ExecutionProcessRunReason::CodingAgent
+                        ) && config.read().await.auto_summarize_attempts
+                            && let Err(e) = container.update_task_attempt_summary(&ctx).await
+                        {
+                            tracing::warn!("Failed to update task attempt summary: {}", e);
+                        }
+
                         // (moved) capture after-head commit occurs later, after commit/next-action handling
 
                         if matches!(
@@ -395,12 +792,40 @@ impl LocalContainerService {
                             }
                         }
 
-                        if Self::should_finalize(&ctx) {
+                        // A failed verify command either holds the task for a fix
+                        // or lets the rest of the chain run
+                        let mut hold_for_fix = false;
+                        if matches!(
+                            ctx.execution_process.run_reason,
+                            ExecutionProcessRunReason::VerifyScript
+                        ) && !matches!(
+                            ctx.execution_process.status,
+                            ExecutionProcessStatus::Killed
+                        ) && !container.record_verification(&ctx).await
+                        {
+                            hold_for_fix = ctx
+                                .task
+                                .parent_project(&db.pool)
+                                .await
+                                .ok()
+                                .flatten()
+                                .is_some_and(|project| project.keep_in_progress_on_verify_failure);
+                            if !hold_for_fix
+                                && let Err(e) = container.try_start_next_action(&ctx).await
+                            {
+                                tracing::error!(
+                                    "Failed to start next action after failed verification: {}",
+                                    e
+                                );
+                            }
+                        }
+
+                        if !hold_for_fix && Self::should_finalize(&ctx) {
                             Self::finalize_task(&db, &config, &ctx).await;
                         }
 
                         // Fire event when CodingAgent execution has finished
-                        if config.read().await.analytics_enabled == Some(true)
+                        if analytics_allowed(config.read().await.analytics_enabled)
                             && matches!(
                                 &ctx.execution_process.run_reason,
                                 ExecutionProcessRunReason::CodingAgent
@@ -433,6 +858,9 @@ impl LocalContainerService {
                                 e
                             );
                         }
+                        container
+                            .unwatch_worktree_if_idle(ctx.task_attempt.id)
+                            .await;
                     }
 
                     // Cleanup msg store
@@ -451,12 +879,24 @@ impl LocalContainerService {
 
                     // Cleanup child handle
                     child_store.write().await.remove(&exec_id);
+                    container.pending_approvals.clear(&exec_id);
+                    container.release_mcp_servers(&exec_id).await;
+                    if was_detached {
+                        detached::cleanup(&exec_id);
+                    }
+
+                    if let Err(e) = container.start_queued_executions().await {
+                        tracing::error!("Failed to start queued execution processes: {}", e);
+                    }
                     break;
                 }
 
                 // still running, sleep and try again
                 tokio::time::sleep(Duration::from_millis(250)).await;
             }
+            // Counted here rather than where the process is finalized, since
+            // that is either this monitor or `stop_execution`
+            metrics().execution_finished();
         })
     }
 
@@ -470,6 +910,66 @@ impl LocalContainerService {
         format!("vk/{}-{}", short_uuid(attempt_id), task_title_id)
     }
 
+    /// Set up the worktree for an attempt on `git_branch_name`, creating the
+    /// branch from the attempt's base branch when `create_branch` is set
+    async fn create_worktree_for_branch(
+        &self,
+        task_attempt: &TaskAttempt,
+        task: &Task,
+        git_branch_name: &str,
+        create_branch: bool,
+    ) -> Result<ContainerRef, ContainerError> {
+        let worktree_dir_name =
+            LocalContainerService::dir_name_from_task_attempt(&task_attempt.id, &task.title);
+        let worktree_path = WorktreeManager::get_worktree_base_dir().join(&worktree_dir_name);
+
+        let project = task
+            .parent_project(&self.db.pool)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        WorktreeManager::create_worktree(
+            &project.git_repo_path,
+            git_branch_name,
+            &worktree_path,
+            &task_attempt.base_branch,
+            create_branch,
+        )
+        .await?;
+
+        // Copy files specified in the project's copy_files field
+        if let Some(copy_files) = &project.copy_files
+            && !copy_files.trim().is_empty()
+        {
+            self.copy_project_files(&project.git_repo_path, &worktree_path, copy_files)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to copy project files: {}", e);
+                });
+        }
+
+        // Copy task images from cache to worktree
+        if let Err(e) = self
+            .image_service
+            .copy_images_by_task_to_worktree(&worktree_path, task.id)
+            .await
+        {
+            tracing::warn!("Failed to copy task images to worktree: {}", e);
+        }
+
+        // Update both container_ref and branch in the database
+        TaskAttempt::update_container_ref(
+            &self.db.pool,
+            task_attempt.id,
+            &worktree_path.to_string_lossy(),
+        )
+        .await?;
+
+        TaskAttempt::update_branch(&self.db.pool, task_attempt.id, git_branch_name).await?;
+
+        Ok(worktree_path.to_string_lossy().to_string())
+    }
+
     async fn track_child_msgs_in_store(&self, id: Uuid, child: &mut AsyncGroupChild) {
         let store = Arc::new(MsgStore::new());
 
@@ -494,6 +994,28 @@ impl LocalContainerService {
         map.insert(id, store);
     }
 
+    /// Like `track_child_msgs_in_store`, but for detached agents: follow the
+    /// log files written by the agent's wrapper shell until it exits.
+    async fn track_detached_msgs_in_store(&self, id: Uuid, pid: u32) {
+        let store = Arc::new(MsgStore::new());
+        let output = DetachedOutput::for_pid(pid);
+
+        let out = tail_detached_log(output.stdout.clone(), output.exit_code.clone(), pid)
+            .map_ok(LogMsg::Stdout);
+        let err = tail_detached_log(output.stderr.clone(), output.exit_code.clone(), pid)
+            .map_ok(LogMsg::Stderr);
+
+        let merged = select(out, err);
+        let log_forwarder = store.clone().spawn_forwarder(merged);
+
+        self.detached_store
+            .write()
+            .await
+            .insert(id, DetachedProcess { pid, log_forwarder });
+        let mut map = self.msg_stores().write().await;
+        map.insert(id, store);
+    }
+
     /// Get the worktree path for a task attempt
     #[allow(dead_code)]
     async fn get_worktree_path(
@@ -529,28 +1051,114 @@ impl LocalContainerService {
         Ok(project_repo_path)
     }
 
-    /// Create a diff stream for merged attempts (never changes)
-    fn create_merged_diff_stream(
+    /// Snapshot of the attempt's diffs, optionally limited to some paths
+    async fn attempt_diffs(
         &self,
-        project_repo_path: &Path,
-        merge_commit_id: &str,
-    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
-    {
-        let diffs = self.git().get_diffs(
-            DiffTarget::Commit {
-                repo_path: project_repo_path,
-                commit_sha: merge_commit_id,
-            },
-            None,
-        )?;
+        task_attempt: &TaskAttempt,
+        path_filter: Option<&[&str]>,
+    ) -> Result<Vec<Diff>, ContainerError> {
+        let diff_opts = DiffOptionsExt {
+            ignore_whitespace: self.config.read().await.diff_ignore_whitespace,
+        };
 
-        let stream = futures::stream::iter(diffs.into_iter().map(|diff| {
-            let entry_index = GitService::diff_path(&diff);
-            let patch =
-                ConversationPatch::add_diff(escape_json_pointer_segment(&entry_index), diff);
-            let event = LogMsg::JsonPatch(patch).to_sse_event();
-            Ok::<_, std::io::Error>(event)
-        }))
+        Ok(match self.resolve_diff_source(task_attempt).await? {
+            DiffSource::Merged {
+                repo_path,
+                merge_commit,
+            } => self.git().get_diffs_with_options(
+                DiffTarget::Commit {
+                    repo_path: &repo_path,
+                    commit_sha: &merge_commit,
+                },
+                path_filter,
+                diff_opts,
+            )?,
+            DiffSource::Worktree {
+                worktree_path,
+                task_branch,
+            } => self.git().get_diffs_with_options(
+                DiffTarget::Worktree {
+                    worktree_path: &worktree_path,
+                    branch_name: &task_branch,
+                    base_branch: &task_attempt.base_branch,
+                },
+                path_filter,
+                diff_opts,
+            )?,
+        })
+    }
+
+    /// Decide whether an attempt's diff comes from its merge commit or its worktree.
+    /// The merged diff is shown once the work is merged and nothing new is on the
+    /// branch or in the container.
+    async fn resolve_diff_source(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<DiffSource, ContainerError> {
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        let latest_merge =
+            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
+        let task_branch = task_attempt
+            .branch
+            .clone()
+            .ok_or(ContainerError::Other(anyhow!(
+                "Task attempt {} does not have a branch",
+                task_attempt.id
+            )))?;
+
+        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
+            &project_repo_path,
+            &task_branch,
+            &task_attempt.base_branch,
+        ) {
+            ahead > 0
+        } else {
+            false
+        };
+
+        if let Some(merge) = &latest_merge
+            && let Some(commit) = merge.merge_commit()
+            && self.is_container_clean(task_attempt).await?
+            && !is_ahead
+        {
+            return Ok(DiffSource::Merged {
+                repo_path: project_repo_path,
+                merge_commit: commit,
+            });
+        }
+
+        // worktree is needed for non-merged diffs
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
+        Ok(DiffSource::Worktree {
+            worktree_path: PathBuf::from(container_ref),
+            task_branch,
+        })
+    }
+
+    /// Create a diff stream for merged attempts (never changes)
+    fn create_merged_diff_stream(
+        &self,
+        project_repo_path: &Path,
+        merge_commit_id: &str,
+        diff_opts: DiffOptionsExt,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
+    {
+        let diffs = self.git().get_diffs_with_options(
+            DiffTarget::Commit {
+                repo_path: project_repo_path,
+                commit_sha: merge_commit_id,
+            },
+            None,
+            diff_opts,
+        )?;
+
+        let stream = futures::stream::iter(diffs.into_iter().map(|diff| {
+            let entry_index = GitService::diff_path(&diff);
+            let patch =
+                ConversationPatch::add_diff(escape_json_pointer_segment(&entry_index), diff);
+            let event = LogMsg::JsonPatch(patch).to_sse_event();
+            Ok::<_, std::io::Error>(event)
+        }))
         .chain(futures::stream::once(async {
             Ok::<_, std::io::Error>(LogMsg::Finished.to_sse_event())
         }))
@@ -565,17 +1173,19 @@ impl LocalContainerService {
         worktree_path: &Path,
         task_branch: &str,
         base_branch: &str,
+        diff_opts: DiffOptionsExt,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
     {
         // Get initial snapshot
         let git_service = self.git().clone();
-        let initial_diffs = git_service.get_diffs(
+        let initial_diffs = git_service.get_diffs_with_options(
             DiffTarget::Worktree {
                 worktree_path,
                 branch_name: task_branch,
                 base_branch,
             },
             None,
+            diff_opts,
         )?;
 
         let initial_stream = futures::stream::iter(initial_diffs.into_iter().map(|diff| {
@@ -602,7 +1212,7 @@ impl LocalContainerService {
                 while let Some(result) = rx.next().await {
                     match result {
                         Ok(events) => {
-                            let changed_paths = Self::extract_changed_paths(&events, &canonical_worktree_path, &worktree_path);
+                            let changed_paths = filesystem_watcher::changed_paths(&events, &canonical_worktree_path, &worktree_path);
 
                             if !changed_paths.is_empty() {
                                 for event in Self::process_file_changes(
@@ -611,6 +1221,7 @@ impl LocalContainerService {
                                     &task_branch,
                                     &base_branch,
                                     &changed_paths,
+                                    diff_opts,
                                 ).map_err(|e| {
                                     tracing::error!("Error processing file changes: {}", e);
                                     io::Error::other(e.to_string())
@@ -636,25 +1247,6 @@ impl LocalContainerService {
         Ok(combined_stream.boxed())
     }
 
-    /// Extract changed file paths from filesystem events
-    fn extract_changed_paths(
-        events: &[DebouncedEvent],
-        canonical_worktree_path: &Path,
-        worktree_path: &Path,
-    ) -> Vec<String> {
-        events
-            .iter()
-            .flat_map(|event| &event.paths)
-            .filter_map(|path| {
-                path.strip_prefix(canonical_worktree_path)
-                    .or_else(|_| path.strip_prefix(worktree_path))
-                    .ok()
-                    .map(|p| p.to_string_lossy().replace('\\', "/"))
-            })
-            .filter(|s| !s.is_empty())
-            .collect()
-    }
-
     /// Process file changes and generate diff events
     fn process_file_changes(
         git_service: &GitService,
@@ -662,16 +1254,18 @@ impl LocalContainerService {
         task_branch: &str,
         base_branch: &str,
         changed_paths: &[String],
+        diff_opts: DiffOptionsExt,
     ) -> Result<Vec<Event>, ContainerError> {
         let path_filter: Vec<&str> = changed_paths.iter().map(|s| s.as_str()).collect();
 
-        let current_diffs = git_service.get_diffs(
+        let current_diffs = git_service.get_diffs_with_options(
             DiffTarget::Worktree {
                 worktree_path,
                 branch_name: task_branch,
                 base_branch,
             },
             Some(&path_filter),
+            diff_opts,
         )?;
 
         let mut events = Vec::new();
@@ -715,6 +1309,10 @@ impl ContainerService for LocalContainerService {
         &self.git
     }
 
+    fn execution_slots(&self) -> &ExecutionSlots {
+        &self.execution_slots
+    }
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf {
         PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default())
     }
@@ -725,58 +1323,25 @@ impl ContainerService for LocalContainerService {
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
-        let worktree_dir_name =
-            LocalContainerService::dir_name_from_task_attempt(&task_attempt.id, &task.title);
-        let worktree_path = WorktreeManager::get_worktree_base_dir().join(&worktree_dir_name);
-
         let git_branch_name =
             LocalContainerService::git_branch_from_task_attempt(&task_attempt.id, &task.title);
 
-        let project = task
-            .parent_project(&self.db.pool)
+        self.create_worktree_for_branch(task_attempt, &task, &git_branch_name, true)
+            .await
+    }
+
+    async fn adopt_branch(
+        &self,
+        task_attempt: &TaskAttempt,
+        branch_name: &str,
+    ) -> Result<ContainerRef, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db.pool)
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
 
-        WorktreeManager::create_worktree(
-            &project.git_repo_path,
-            &git_branch_name,
-            &worktree_path,
-            &task_attempt.base_branch,
-            true, // create new branch
-        )
-        .await?;
-
-        // Copy files specified in the project's copy_files field
-        if let Some(copy_files) = &project.copy_files
-            && !copy_files.trim().is_empty()
-        {
-            self.copy_project_files(&project.git_repo_path, &worktree_path, copy_files)
-                .await
-                .unwrap_or_else(|e| {
-                    tracing::warn!("Failed to copy project files: {}", e);
-                });
-        }
-
-        // Copy task images from cache to worktree
-        if let Err(e) = self
-            .image_service
-            .copy_images_by_task_to_worktree(&worktree_path, task.id)
+        self.create_worktree_for_branch(task_attempt, &task, branch_name, false)
             .await
-        {
-            tracing::warn!("Failed to copy task images to worktree: {}", e);
-        }
-
-        // Update both container_ref and branch in the database
-        TaskAttempt::update_container_ref(
-            &self.db.pool,
-            task_attempt.id,
-            &worktree_path.to_string_lossy(),
-        )
-        .await?;
-
-        TaskAttempt::update_branch(&self.db.pool, task_attempt.id, &git_branch_name).await?;
-
-        Ok(worktree_path.to_string_lossy().to_string())
     }
 
     async fn delete_inner(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
@@ -872,30 +1437,161 @@ impl ContainerService for LocalContainerService {
             )))?;
         let current_dir = PathBuf::from(container_ref);
 
+        // Register the project's extra MCP servers with the agent before it starts
+        if let Some(agent) = executor_action.coding_agent()
+            && let Some(task) = task_attempt.parent_task(&self.db.pool).await?
+            && let Some(project) = task.parent_project(&self.db.pool).await?
+            && let Some(servers) = project.mcp_servers
+        {
+            match agent.register_mcp_servers(&current_dir, &servers).await {
+                Ok(Some(registration)) => {
+                    // A project-local config file must not end up in the agent's commits
+                    if let Ok(relative) = registration.config_path().strip_prefix(&current_dir)
+                        && let Err(e) = self.git.exclude_path(&current_dir, relative)
+                    {
+                        tracing::warn!(
+                            "Failed to exclude {} from commits: {}",
+                            relative.display(),
+                            e
+                        );
+                    }
+                    self.mcp_registrations
+                        .write()
+                        .await
+                        .insert(execution_process.id, registration);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    "Failed to write project MCP servers for execution {}: {}",
+                    execution_process.id,
+                    e
+                ),
+            }
+        }
+
         // Create the child and stream, add to execution tracker
-        let mut child = executor_action.spawn(&current_dir).await?;
+        let env = task_attempt.execution_env();
+        if !env.is_empty() {
+            tracing::debug!(
+                "Spawning execution process {} with env overrides {:?}",
+                execution_process.id,
+                env
+            );
+        }
+        let mut child = match executor_action.spawn(&current_dir, &env).await {
+            Ok(child) => child,
+            Err(e) => {
+                self.release_mcp_servers(&execution_process.id).await;
+                return Err(e.into());
+            }
+        };
 
-        self.track_child_msgs_in_store(execution_process.id, &mut child)
-            .await;
+        if executor_action.is_detached() {
+            let pid = child.inner().id().ok_or(ContainerError::Other(anyhow!(
+                "Detached process exited before its PID could be recorded"
+            )))?;
+            detached::record_pid(&execution_process.id, pid)?;
+            self.track_detached_msgs_in_store(execution_process.id, pid)
+                .await;
+        } else {
+            self.track_child_msgs_in_store(execution_process.id, &mut child)
+                .await;
+        }
 
         self.add_child_to_store(execution_process.id, child).await;
 
+        if let Some(CodingAgent::Codex(codex)) = executor_action.coding_agent()
+            && codex.uses_proto()
+            && !executor_action.is_detached()
+        {
+            self.spawn_codex_proto_driver(execution_process.id).await;
+        }
+
+        if !matches!(
+            execution_process.run_reason,
+            ExecutionProcessRunReason::DevServer
+        ) {
+            self.watch_worktree(task_attempt.id, current_dir.clone());
+        }
+
         // Spawn exit monitor
         let _hn = self.spawn_exit_monitor(&execution_process.id);
 
+        // Dev servers can legitimately stay quiet for a long time
+        if let Some(minutes) = self.config.read().await.execution_timeout_minutes
+            && minutes > 0
+            && !matches!(
+                execution_process.run_reason,
+                ExecutionProcessRunReason::DevServer
+            )
+        {
+            self.spawn_inactivity_watchdog(
+                execution_process.id,
+                Duration::from_secs(u64::from(minutes) * 60),
+                format!("Killed after {minutes} minute(s) without output"),
+            );
+        }
+
+        if matches!(
+            execution_process.run_reason,
+            ExecutionProcessRunReason::CodingAgent
+        ) && let Some(branch) = &task_attempt.branch
+        {
+            self.spawn_branch_guard(execution_process.id, current_dir, branch.clone());
+        }
+
         Ok(())
     }
 
+    async fn answer_approval(
+        &self,
+        execution_process: &ExecutionProcess,
+        call_id: &str,
+        decision: ApprovalDecision,
+    ) -> Result<bool, ContainerError> {
+        if !self.pending_approvals.take(&execution_process.id, call_id) {
+            return Ok(false);
+        }
+        let response = Codex::approval_response(call_id, decision);
+        if let Err(e) = self
+            .write_to_stdin(&execution_process.id, response.as_bytes())
+            .await
+        {
+            self.pending_approvals
+                .register(execution_process.id, call_id);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
     async fn stop_execution(
         &self,
         execution_process: &ExecutionProcess,
     ) -> Result<(), ContainerError> {
-        let child = self
-            .get_child_from_store(&execution_process.id)
+        // Nothing was spawned yet, so dropping it from the queue is enough
+        if execution_process.status == ExecutionProcessStatus::Queued {
+            ExecutionProcess::update_completion(
+                &self.db.pool,
+                execution_process.id,
+                ExecutionProcessStatus::Killed,
+                None,
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let child = self.get_child_from_store(&execution_process.id).await;
+        let detached_pid = self
+            .detached_store
+            .read()
             .await
-            .ok_or_else(|| {
-                ContainerError::Other(anyhow!("Child process not found for execution"))
-            })?;
+            .get(&execution_process.id)
+            .map(|detached| detached.pid);
+        if child.is_none() && detached_pid.is_none() {
+            return Err(ContainerError::Other(anyhow!(
+                "Child process not found for execution"
+            )));
+        }
         ExecutionProcess::update_completion(
             &self.db.pool,
             execution_process.id,
@@ -905,23 +1601,46 @@ impl ContainerService for LocalContainerService {
         .await?;
 
         // Kill the child process and remove from the store
-        {
+        let kill_result = if let Some(child) = child {
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
-                tracing::error!(
-                    "Failed to stop execution process {}: {}",
-                    execution_process.id,
-                    e
-                );
-                return Err(e);
-            }
+            command::kill_process_group(&mut child_guard).await
+        } else if let Some(pid) = detached_pid {
+            // Reattached after a restart, so there is no child handle
+            command::kill_process_group_by_pid(pid).await
+        } else {
+            Ok(())
+        };
+        if let Err(e) = kill_result {
+            tracing::error!(
+                "Failed to stop execution process {}: {}",
+                execution_process.id,
+                e
+            );
+            return Err(e);
         }
         self.remove_child_from_store(&execution_process.id).await;
+        self.pending_approvals.clear(&execution_process.id);
+        self.release_mcp_servers(&execution_process.id).await;
+        self.execution_slots.release(&execution_process.id);
+        if let Err(e) = self.start_queued_executions().await {
+            tracing::error!("Failed to start queued execution processes: {}", e);
+        }
+        if self
+            .detached_store
+            .write()
+            .await
+            .remove(&execution_process.id)
+            .is_some()
+        {
+            detached::cleanup(&execution_process.id);
+        }
 
         // Mark the process finished in the MsgStore
         if let Some(msg) = self.msg_stores.write().await.remove(&execution_process.id) {
             msg.push_finished();
         }
+        self.unwatch_worktree_if_idle(execution_process.task_attempt_id)
+            .await;
 
         // Update task status to InReview when execution is stopped
         if let Ok(ctx) = ExecutionProcess::load_context(&self.db.pool, execution_process.id).await
@@ -956,56 +1675,184 @@ impl ContainerService for LocalContainerService {
         Ok(())
     }
 
+    async fn try_reattach(
+        &self,
+        execution_process: &ExecutionProcess,
+    ) -> Result<bool, ContainerError> {
+        let Some(pid) = detached::read_pid(&execution_process.id) else {
+            return Ok(false);
+        };
+        let output = DetachedOutput::for_pid(pid);
+        if !command::is_process_alive(pid) && !output.exit_code.exists() {
+            // Killed without recording an exit code; nothing left to follow
+            detached::cleanup(&execution_process.id);
+            return Ok(false);
+        }
+
+        // The log files hold the complete output, so they replace whatever was
+        // persisted before the restart
+        ExecutionProcessLogs::delete_by_execution_id(&self.db.pool, execution_process.id).await?;
+        self.track_detached_msgs_in_store(execution_process.id, pid)
+            .await;
+        // Reattached processes are already running, so they take a slot if
+        // there is one but never wait for it
+        self.execution_slots.try_acquire(execution_process.id);
+
+        let (executor_profile_id, follow_up) = match execution_process.executor_action()?.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                (Some(&request.executor_profile_id), false)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                (Some(&request.executor_profile_id), true)
+            }
+            ExecutorActionType::ScriptRequest(_) => (None, false),
+        };
+        if let Some(executor_profile_id) = executor_profile_id
+            && let Some(executor) =
+                ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+            && let Some(task_attempt) = execution_process.parent_task_attempt(&self.db.pool).await?
+        {
+            let current_dir = self.task_attempt_to_current_dir(&task_attempt);
+            if follow_up {
+                executor.normalize_follow_up_logs(msg_store, &current_dir);
+            } else {
+                executor.normalize_logs(msg_store, &current_dir);
+            }
+        }
+
+        self.spawn_stream_raw_logs_to_db(&execution_process.id);
+        let _hn = self.spawn_exit_monitor(&execution_process.id);
+
+        tracing::info!(
+            "Reattached to detached execution process {} (pid {})",
+            execution_process.id,
+            pid
+        );
+        Ok(true)
+    }
+
     async fn get_diff(
         &self,
         task_attempt: &TaskAttempt,
+        ignore_whitespace: Option<bool>,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
     {
-        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
-        let latest_merge =
-            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
-        let task_branch = task_attempt
-            .branch
-            .clone()
-            .ok_or(ContainerError::Other(anyhow!(
-                "Task attempt {} does not have a branch",
-                task_attempt.id
-            )))?;
+        let ignore_whitespace = match ignore_whitespace {
+            Some(ignore) => ignore,
+            None => self.config.read().await.diff_ignore_whitespace,
+        };
+        let diff_opts = DiffOptionsExt { ignore_whitespace };
+
+        match self.resolve_diff_source(task_attempt).await? {
+            DiffSource::Merged {
+                repo_path,
+                merge_commit,
+            } => self.create_merged_diff_stream(&repo_path, &merge_commit, diff_opts),
+            DiffSource::Worktree {
+                worktree_path,
+                task_branch,
+            } => {
+                // Handle ongoing attempts (live streaming diff)
+                self.create_live_diff_stream(
+                    &worktree_path,
+                    &task_branch,
+                    &task_attempt.base_branch,
+                    diff_opts,
+                )
+                .await
+            }
+        }
+    }
 
-        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
-            &project_repo_path,
-            &task_branch,
-            &task_attempt.base_branch,
-        ) {
-            ahead > 0
-        } else {
-            false
+    async fn stream_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        ignore_whitespace: Option<bool>,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>
+    {
+        let ignore_whitespace = match ignore_whitespace {
+            Some(ignore) => ignore,
+            None => self.config.read().await.diff_ignore_whitespace,
         };
+        let diff_opts = DiffOptionsExt { ignore_whitespace };
+        let source = self.resolve_diff_source(task_attempt).await?;
+        let base_branch = task_attempt.base_branch.clone();
+        let git = self.git().clone();
+
+        // A small buffer keeps the walk from racing far ahead of a slow client
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<Event, io::Error>>(16);
+        tokio::task::spawn_blocking(move || {
+            let target = match &source {
+                DiffSource::Merged {
+                    repo_path,
+                    merge_commit,
+                } => DiffTarget::Commit {
+                    repo_path,
+                    commit_sha: merge_commit,
+                },
+                DiffSource::Worktree {
+                    worktree_path,
+                    task_branch,
+                } => DiffTarget::Worktree {
+                    worktree_path,
+                    branch_name: task_branch,
+                    base_branch: &base_branch,
+                },
+            };
+            let result = git.stream_diffs(target, None, diff_opts, &mut |diff| {
+                let entry_index = GitService::diff_path(&diff);
+                let patch =
+                    ConversationPatch::add_diff(escape_json_pointer_segment(&entry_index), diff);
+                // Stop walking once the client has gone away
+                tx.blocking_send(Ok(LogMsg::JsonPatch(patch).to_sse_event()))
+                    .is_ok()
+            });
+            let last = match result {
+                Ok(()) => Ok(LogMsg::Finished.to_sse_event()),
+                Err(e) => Err(io::Error::other(e.to_string())),
+            };
+            let _ = tx.blocking_send(last);
+        });
 
-        // Show merged diff when no new work is on the branch or container
-        if let Some(merge) = &latest_merge
-            && let Some(commit) = merge.merge_commit()
-            && self.is_container_clean(task_attempt).await?
-            && !is_ahead
-        {
-            return self.create_merged_diff_stream(&project_repo_path, &commit);
-        }
+        Ok(tokio_stream::wrappers::ReceiverStream::new(rx).boxed())
+    }
 
-        // worktree is needed for non-merged diffs
-        let container_ref = self.ensure_container_exists(task_attempt).await?;
-        let worktree_path = PathBuf::from(container_ref);
+    async fn get_file_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        path: &str,
+    ) -> Result<Option<Diff>, ContainerError> {
+        let diffs = self.attempt_diffs(task_attempt, Some(&[path])).await?;
+        Ok(diffs.into_iter().find(|diff| {
+            diff.new_path.as_deref() == Some(path) || diff.old_path.as_deref() == Some(path)
+        }))
+    }
 
-        // Handle ongoing attempts (live streaming diff)
-        self.create_live_diff_stream(&worktree_path, &task_branch, &task_attempt.base_branch)
-            .await
+    async fn get_diff_summary(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<DiffSummary, ContainerError> {
+        let diffs = self.attempt_diffs(task_attempt, None).await?;
+        Ok(DiffSummary::from_diffs(&diffs))
     }
 
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
-        if !matches!(
-            ctx.execution_process.run_reason,
-            ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript,
-        ) {
-            return Ok(false);
+        match ctx.execution_process.run_reason {
+            ExecutionProcessRunReason::CodingAgent | ExecutionProcessRunReason::CleanupScript => {}
+            ExecutionProcessRunReason::SetupScript => {
+                // Setup changes are only committed when the project opts in, so that
+                // they stay out of the coding agent's diff
+                let commit_setup_changes = ctx
+                    .task
+                    .parent_project(&self.db().pool)
+                    .await?
+                    .is_some_and(|project| project.commit_setup_changes);
+                if !commit_setup_changes {
+                    return Ok(false);
+                }
+            }
+            _ => return Ok(false),
         }
 
         let message = match ctx.execution_process.run_reason {
@@ -1048,6 +1895,12 @@ impl ContainerService for LocalContainerService {
                     ctx.task_attempt.id
                 )
             }
+            ExecutionProcessRunReason::SetupScript => {
+                format!(
+                    "Setup script changes for task attempt {}",
+                    ctx.task_attempt.id
+                )
+            }
             _ => Err(ContainerError::Other(anyhow::anyhow!(
                 "Invalid run reason for commit"
             )))?,
@@ -1193,4 +2046,475 @@ impl LocalContainerService {
 
         Ok(())
     }
+
+    /// Store the agent's final message as the attempt summary, so the latest
+    /// coding agent run (initial or follow-up) describes the attempt
+    /// Record the verify command's result on the attempt, returning whether it passed
+    async fn record_verification(&self, ctx: &ExecutionContext) -> bool {
+        let exit_code = ctx.execution_process.exit_code;
+        let passed = exit_code == Some(0);
+        let output = self
+            .msg_stores
+            .read()
+            .await
+            .get(&ctx.execution_process.id)
+            .map(|store| {
+                store
+                    .get_history()
+                    .into_iter()
+                    .filter_map(|msg| match msg {
+                        LogMsg::Stdout(s) | LogMsg::Stderr(s) => Some(s),
+                        _ => None,
+                    })
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+        let summary = match (parse_test_summary(&output), exit_code) {
+            (Some(tests), _) => tests.to_string(),
+            (None, Some(code)) => format!("exited with code {code}"),
+            (None, None) => "exited without a status".to_string(),
+        };
+
+        if let Err(e) =
+            TaskAttempt::update_verification(&self.db.pool, ctx.task_attempt.id, passed, &summary)
+                .await
+        {
+            tracing::warn!("Failed to record verification result: {}", e);
+        }
+        passed
+    }
+
+    async fn update_task_attempt_summary(
+        &self,
+        ctx: &ExecutionContext,
+    ) -> Result<(), anyhow::Error> {
+        match self.extract_last_assistant_message(&ctx.execution_process.id) {
+            Some(summary) => {
+                TaskAttempt::update_summary(&self.db.pool, ctx.task_attempt.id, &summary).await?
+            }
+            None => tracing::debug!(
+                "No assistant message to summarize attempt {}",
+                ctx.task_attempt.id
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// Stream the contents of a detached agent's log file, following appends until
+/// the agent has exited and everything it wrote has been read.
+fn tail_detached_log(
+    path: PathBuf,
+    exit_code_path: PathBuf,
+    pid: u32,
+) -> impl futures::Stream<Item = io::Result<String>> {
+    try_stream! {
+        let mut offset = 0u64;
+        loop {
+            // Check before reading so output written just before exit is not lost
+            let finished = exit_code_path.exists() || !command::is_process_alive(pid);
+            let chunk = read_log_chunk(&path, offset).await?;
+            if !chunk.is_empty() {
+                offset += chunk.len() as u64;
+                yield String::from_utf8_lossy(&chunk).into_owned();
+                continue;
+            }
+            if finished {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+}
+
+/// Read up to 64KiB from `path` starting at `offset`; a missing file reads as empty
+async fn read_log_chunk(path: &Path, offset: u64) -> io::Result<Vec<u8>> {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    file.seek(io::SeekFrom::Start(offset)).await?;
+    let mut chunk = Vec::new();
+    file.take(64 * 1024).read_to_end(&mut chunk).await?;
+    Ok(chunk)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, time::Instant};
+
+    use executors::{
+        actions::{
+            coding_agent_follow_up::CodingAgentFollowUpRequest,
+            script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        },
+        executors::BaseCodingAgent,
+        profile::ExecutorProfileId,
+    };
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_support::{
+        cleanup_script_action, container, create_attempt, quiet_config, setup_pool, wait_for_exit,
+    };
+
+    #[tokio::test]
+    async fn queued_process_starts_only_after_the_running_one_finishes() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(
+            &pool,
+            Config {
+                max_concurrent_executions: Some(1),
+                ..quiet_config()
+            },
+        )
+        .await;
+
+        let first = container
+            .start_execution(
+                &attempt,
+                &cleanup_script_action("sleep 0.5"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+        let second = container
+            .start_execution(
+                &attempt,
+                &cleanup_script_action("true"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.status, ExecutionProcessStatus::Running);
+        assert_eq!(second.status, ExecutionProcessStatus::Queued);
+
+        let first = wait_for_exit(&pool, first.id).await;
+        let second = wait_for_exit(&pool, second.id).await;
+        assert_eq!(first.status, ExecutionProcessStatus::Completed);
+        assert_eq!(second.status, ExecutionProcessStatus::Completed);
+        assert!(second.started_at >= first.completed_at.unwrap());
+    }
+
+    #[tokio::test]
+    async fn raised_limit_starts_queued_processes_without_a_restart() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(
+            &pool,
+            Config {
+                max_concurrent_executions: Some(1),
+                ..quiet_config()
+            },
+        )
+        .await;
+
+        let mut processes = Vec::new();
+        for _ in 0..2 {
+            let process = container
+                .start_execution(
+                    &attempt,
+                    &cleanup_script_action("sleep 30"),
+                    &ExecutionProcessRunReason::CleanupScript,
+                )
+                .await
+                .unwrap();
+            processes.push(process);
+        }
+        assert_eq!(processes[1].status, ExecutionProcessStatus::Queued);
+
+        container.execution_slots().set_limit(Some(2));
+        container.start_queued_executions().await.unwrap();
+        let second = ExecutionProcess::find_by_id(&pool, processes[1].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.status, ExecutionProcessStatus::Running);
+
+        for process in [&processes[0], &second] {
+            container.stop_execution(process).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn pruning_keeps_worktrees_with_running_processes() {
+        let pool = setup_pool().await;
+        let idle_worktree = TempDir::new().unwrap();
+        let busy_worktree = TempDir::new().unwrap();
+        let idle = create_attempt(&pool, idle_worktree.path()).await;
+        let busy = create_attempt(&pool, busy_worktree.path()).await;
+        let container = container(
+            &pool,
+            Config {
+                max_worktree_count: Some(0),
+                ..quiet_config()
+            },
+        )
+        .await;
+        let running = container
+            .start_execution(
+                &busy,
+                &cleanup_script_action("sleep 30"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+
+        LocalContainerService::prune_worktrees(&container.db, &container.config, &None)
+            .await
+            .unwrap();
+
+        let worktree_deleted = |id| {
+            let pool = pool.clone();
+            async move {
+                TaskAttempt::find_by_id(&pool, id)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .worktree_deleted
+            }
+        };
+        assert!(!idle_worktree.path().exists());
+        assert!(worktree_deleted(idle.id).await);
+        assert!(busy_worktree.path().exists());
+        assert!(!worktree_deleted(busy.id).await);
+
+        container.stop_execution(&running).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn worktree_is_kept_when_a_process_starts_after_it_was_picked() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(&pool, quiet_config()).await;
+        let repo = worktree.path().join("no-such-repo");
+
+        // Picked while idle; the process starts before the worktree is removed
+        let candidates = TaskAttempt::find_prunable_worktrees(&pool).await.unwrap();
+        assert_eq!(candidates[0].0, attempt.id);
+        let running = container
+            .start_execution(
+                &attempt,
+                &cleanup_script_action("sleep 30"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+        let pruned = LocalContainerService::prune_worktree(
+            &container.db,
+            attempt.id,
+            worktree.path().to_path_buf(),
+            repo.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(!pruned);
+        assert!(worktree.path().exists());
+
+        container.stop_execution(&running).await.unwrap();
+        wait_for_exit(&pool, running.id).await;
+        let pruned = LocalContainerService::prune_worktree(
+            &container.db,
+            attempt.id,
+            worktree.path().to_path_buf(),
+            repo,
+        )
+        .await
+        .unwrap();
+        assert!(pruned);
+        assert!(!worktree.path().exists());
+    }
+
+    #[tokio::test]
+    async fn retry_reruns_the_failed_action_against_the_attempt() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(&pool, quiet_config()).await;
+
+        let failed = container
+            .start_execution(
+                &attempt,
+                &cleanup_script_action("exit 1"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+        let failed = wait_for_exit(&pool, failed.id).await;
+        assert_eq!(failed.status, ExecutionProcessStatus::Failed);
+
+        let retryable = ExecutionProcess::find_retryable_by_task_attempt_id(&pool, attempt.id)
+            .await
+            .unwrap()
+            .expect("the failed process is retryable");
+        assert_eq!(retryable.id, failed.id);
+        let retry = container
+            .retry_execution(&attempt, &retryable)
+            .await
+            .unwrap();
+        assert_eq!(retry.status, ExecutionProcessStatus::Running);
+        assert_eq!(retry.run_reason, ExecutionProcessRunReason::CleanupScript);
+        assert_eq!(retry.retry_of, Some(failed.id));
+
+        // The retry failed too, so it is now the one to retry
+        wait_for_exit(&pool, retry.id).await;
+        let retryable = ExecutionProcess::find_retryable_by_task_attempt_id(&pool, attempt.id)
+            .await
+            .unwrap();
+        assert_eq!(retryable.map(|process| process.id), Some(retry.id));
+    }
+
+    #[tokio::test]
+    async fn silent_execution_is_killed_and_marked_failed() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(&pool, quiet_config()).await;
+
+        let process = container
+            .start_execution(
+                &attempt,
+                &cleanup_script_action("sleep 30"),
+                &ExecutionProcessRunReason::CleanupScript,
+            )
+            .await
+            .unwrap();
+        let started = Instant::now();
+        let reason = "Killed after 300ms without output".to_string();
+        container.spawn_inactivity_watchdog(process.id, Duration::from_millis(300), reason.clone());
+
+        let mut process = wait_for_exit(&pool, process.id).await;
+        assert_eq!(process.status, ExecutionProcessStatus::Failed);
+        assert_eq!(process.failure_reason, Some(reason.clone()));
+
+        // The exit monitor records the exit of the killed process group
+        // without clearing the reason
+        while process.exit_code.is_none() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "process was not killed"
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            process = ExecutionProcess::find_by_id(&pool, process.id)
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        assert_eq!(process.status, ExecutionProcessStatus::Failed);
+        assert_eq!(process.failure_reason, Some(reason));
+    }
+
+    #[tokio::test]
+    async fn reopened_task_runs_its_follow_up_and_is_back_in_progress() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let mut attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(&pool, quiet_config()).await;
+        Task::update_status(&pool, attempt.task_id, TaskStatus::InReview)
+            .await
+            .unwrap();
+
+        // Stand in for the agent CLI so the follow-up runs without the network
+        let bin = TempDir::new().unwrap();
+        let npx = bin.path().join("npx");
+        std::fs::write(&npx, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&npx, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = format!(
+            "{}:{}",
+            bin.path().display(),
+            std::env::var("PATH").unwrap_or_default()
+        );
+        attempt.env_overrides = Some(sqlx::types::Json(HashMap::from([(
+            "PATH".to_string(),
+            path,
+        )])));
+
+        let follow_up = ExecutorAction::new(
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt: "The redirect still loops".to_string(),
+                session_id: "session-1".to_string(),
+                executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            }),
+            None,
+        );
+        let process = container
+            .start_execution(
+                &attempt,
+                &follow_up,
+                &ExecutionProcessRunReason::CodingAgent,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(process.status, ExecutionProcessStatus::Running);
+        assert!(matches!(
+            &process.executor_action().unwrap().typ,
+            ExecutorActionType::CodingAgentFollowUpRequest(request)
+                if request.session_id == "session-1"
+        ));
+        assert!(container.get_child_from_store(&process.id).await.is_some());
+        let task = Task::find_by_id(&pool, attempt.task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+
+        container.stop_execution(&process).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn dry_run_script_is_logged_without_spawning() {
+        let pool = setup_pool().await;
+        let worktree = TempDir::new().unwrap();
+        let attempt = create_attempt(&pool, worktree.path()).await;
+        let container = container(&pool, quiet_config()).await;
+        Task::update_status(&pool, attempt.task_id, TaskStatus::InReview)
+            .await
+            .unwrap();
+        let marker = worktree.path().join("ran");
+        let script = format!("touch {}", marker.display());
+        let dry_run = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: script.clone(),
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+                dry_run: true,
+            }),
+            None,
+        );
+
+        let process = container
+            .start_execution(&attempt, &dry_run, &ExecutionProcessRunReason::SetupScript)
+            .await
+            .unwrap();
+
+        assert!(container.get_child_from_store(&process.id).await.is_none());
+        let process = wait_for_exit(&pool, process.id).await;
+        assert_eq!(process.status, ExecutionProcessStatus::Completed);
+        assert!(!marker.exists());
+        let task = Task::find_by_id(&pool, attempt.task_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::InReview);
+
+        let msg_store = container.get_msg_store_by_id(&process.id).await.unwrap();
+        let mut conversation = json!({ "entries": [] });
+        for msg in msg_store.get_history() {
+            if let LogMsg::JsonPatch(patch) = msg {
+                json_patch::patch(&mut conversation, &patch).unwrap();
+            }
+        }
+        let entries = conversation["entries"].as_array().unwrap();
+        assert!(entries.iter().any(|entry| {
+            entry["content"]["content"]
+                .as_str()
+                .is_some_and(|content| content.contains(&script))
+        }));
+    }
 }
@@ -1,13 +1,20 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use db::DBService;
+use chrono::Utc;
+use db::{
+    DBService,
+    models::{
+        execution_process::ExecutionProcess, execution_process_logs::ExecutionProcessLogs,
+        task::Task,
+    },
+};
 use deployment::{Deployment, DeploymentError};
 use executors::profile::ExecutorConfigs;
 use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     auth::AuthService,
-    config::{Config, load_config_from_file, save_config_to_file},
+    config::{Config, LogRetentionConfig, load_config_from_file, save_config_to_file},
     container::ContainerService,
     events::EventService,
     file_search_cache::FileSearchCache,
@@ -24,6 +31,67 @@ use crate::container::LocalContainerService;
 
 mod command;
 pub mod container;
+#[cfg(test)]
+mod test_support;
+mod watchdog;
+
+/// How long soft-deleted tasks stay in the trash before they are purged.
+const TASK_TRASH_RETENTION_DAYS: i64 = 30;
+
+/// How long the in-memory logs of a finished process outlive it.
+const FINISHED_MSG_STORE_GRACE_MINUTES: i64 = 10;
+
+/// Delete persisted execution logs that fall outside the configured retention.
+async fn sweep_persisted_logs(db: &DBService, retention: &LogRetentionConfig) {
+    if let Some(days) = retention.max_age_days {
+        let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+        match ExecutionProcessLogs::delete_for_attempts_completed_before(&db.pool, cutoff).await {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!(
+                "Deleted persisted logs of {} execution processes from attempts finished over {} days ago",
+                deleted,
+                days
+            ),
+            Err(e) => tracing::error!("Failed to delete expired execution logs: {}", e),
+        }
+    }
+
+    if let Some(max_mb) = retention.max_total_size_mb {
+        let max_bytes = max_mb as i64 * 1024 * 1024;
+        match ExecutionProcessLogs::prune_to_size(&db.pool, max_bytes).await {
+            Ok(0) => {}
+            Ok(freed) => tracing::info!(
+                "Deleted {} bytes of persisted execution logs to stay under {} MB",
+                freed,
+                max_mb
+            ),
+            Err(e) => tracing::error!("Failed to prune execution logs by size: {}", e),
+        }
+    }
+}
+
+/// Drop in-memory log stores of processes that finished a while ago. The exit
+/// monitor removes most of them, but dry runs and failed spawns have none.
+async fn prune_finished_msg_stores(
+    db: &DBService,
+    msg_stores: &RwLock<HashMap<Uuid, Arc<MsgStore>>>,
+) {
+    let cutoff = Utc::now() - chrono::Duration::minutes(FINISHED_MSG_STORE_GRACE_MINUTES);
+    let ids: Vec<Uuid> = msg_stores.read().await.keys().copied().collect();
+    for id in ids {
+        let finished = match ExecutionProcess::find_by_id(&db.pool, id).await {
+            Ok(Some(process)) => process.completed_at.is_some_and(|at| at < cutoff),
+            Ok(None) => true,
+            Err(e) => {
+                tracing::error!("Failed to look up execution process {}: {}", id, e);
+                return;
+            }
+        };
+        if finished && let Some(store) = msg_stores.write().await.remove(&id) {
+            store.push_finished();
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct LocalDeployment {
@@ -68,6 +136,14 @@ impl Deployment for LocalDeployment {
 
         // Always save config (may have been migrated or version updated)
         save_config_to_file(&raw_config, &config_path()).await?;
+        utils::path::set_forward_slash_paths(raw_config.forward_slash_paths);
+        executors::logs::utils::output::set_output_limit_bytes(
+            raw_config.command_output_limit_bytes as usize,
+        );
+        utils::shell::set_shell_override(
+            raw_config.shell_override.as_deref(),
+            &raw_config.shell_override_args,
+        );
 
         let config = Arc::new(RwLock::new(raw_config));
         let sentry = SentryService::new();
@@ -103,12 +179,44 @@ impl Deployment for LocalDeployment {
             });
         }
 
+        {
+            let db = db.clone();
+            tokio::spawn(async move {
+                let cutoff = Utc::now() - chrono::Duration::days(TASK_TRASH_RETENTION_DAYS);
+                match Task::purge_deleted_before(&db.pool, cutoff).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!(
+                        "Purged {} tasks that were in the trash for more than {} days",
+                        purged,
+                        TASK_TRASH_RETENTION_DAYS
+                    ),
+                    Err(e) => tracing::error!("Failed to purge deleted tasks: {}", e),
+                }
+            });
+        }
+
+        {
+            let db = db.clone();
+            let config = config.clone();
+            let msg_stores = msg_stores.clone();
+            tokio::spawn(async move {
+                let mut sweep_interval = tokio::time::interval(Duration::from_secs(3600));
+                loop {
+                    sweep_interval.tick().await;
+                    let retention = config.read().await.log_retention.clone();
+                    sweep_persisted_logs(&db, &retention).await;
+                    prune_finished_msg_stores(&db, &msg_stores).await;
+                }
+            });
+        }
+
         // We need to make analytics accessible to the ContainerService
         // TODO: Handle this more gracefully
         let analytics_ctx = analytics.as_ref().map(|s| AnalyticsContext {
             user_id: user_id.clone(),
             analytics_service: s.clone(),
         });
+        let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
         let container = LocalContainerService::new(
             db.clone(),
             msg_stores.clone(),
@@ -116,10 +224,11 @@ impl Deployment for LocalDeployment {
             git.clone(),
             image.clone(),
             analytics_ctx,
-        );
+            events.clone(),
+        )
+        .await;
         container.spawn_worktree_cleanup().await;
 
-        let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
         let file_search_cache = Arc::new(FileSearchCache::new());
 
         Ok(Self {
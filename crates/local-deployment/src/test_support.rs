@@ -0,0 +1,154 @@
+//! Fixtures shared by the in-crate tests
+
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessStatus},
+        task::{CreateTask, Task},
+        task_attempt::{CreateTaskAttempt, TaskAttempt},
+    },
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    executors::BaseCodingAgent,
+};
+use services::services::{
+    config::{Config, NotificationConfig},
+    events::EventService,
+    git::GitService,
+    image::ImageService,
+};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use tokio::sync::RwLock;
+use utils::msg_store::MsgStore;
+use uuid::Uuid;
+
+use crate::container::LocalContainerService;
+
+/// Open a migrated in-memory database
+pub async fn setup_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    sqlx::migrate!("../db/migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Default config, without sounds or push notifications when a task finishes
+pub fn quiet_config() -> Config {
+    let defaults = Config::default();
+    Config {
+        notifications: NotificationConfig {
+            sound_enabled: false,
+            push_enabled: false,
+            ..defaults.notifications
+        },
+        ..defaults
+    }
+}
+
+/// A container service over `pool`, limited the way `config` asks
+pub async fn container(pool: &SqlitePool, config: Config) -> LocalContainerService {
+    let db = DBService { pool: pool.clone() };
+    let events = EventService::new(
+        db.clone(),
+        Arc::new(MsgStore::new()),
+        Arc::new(RwLock::new(0)),
+    );
+    LocalContainerService::new(
+        db,
+        Arc::new(RwLock::new(HashMap::new())),
+        Arc::new(RwLock::new(config)),
+        GitService::new(),
+        ImageService::new(pool.clone()).expect("create image service"),
+        None,
+        events,
+    )
+    .await
+}
+
+/// Create a project with one task and return an attempt whose worktree is
+/// `worktree`
+pub async fn create_attempt(pool: &SqlitePool, worktree: &Path) -> TaskAttempt {
+    let project_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, $2, $3)")
+        .bind(project_id)
+        .bind("test project")
+        .bind(format!("/tmp/test-project-{project_id}"))
+        .execute(pool)
+        .await
+        .expect("insert project");
+    let task = Task::create(
+        pool,
+        &CreateTask {
+            project_id,
+            title: "task".to_string(),
+            description: None,
+            parent_task_attempt: None,
+            image_ids: None,
+            assignee: None,
+        },
+        Uuid::new_v4(),
+    )
+    .await
+    .expect("create task");
+    let attempt = TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            env_overrides: None,
+        },
+        task.id,
+    )
+    .await
+    .expect("create attempt");
+    let worktree = worktree.to_string_lossy();
+    TaskAttempt::update_container_ref(pool, attempt.id, &worktree)
+        .await
+        .expect("set worktree");
+    TaskAttempt::find_by_id(pool, attempt.id)
+        .await
+        .expect("load attempt")
+        .expect("attempt exists")
+}
+
+pub fn cleanup_script_action(script: &str) -> ExecutorAction {
+    ExecutorAction::new(
+        ExecutorActionType::ScriptRequest(ScriptRequest {
+            script: script.to_string(),
+            language: ScriptRequestLanguage::Bash,
+            context: ScriptContext::CleanupScript,
+            dry_run: false,
+        }),
+        None,
+    )
+}
+
+/// Wait until process `id` is no longer queued or running, and return it
+pub async fn wait_for_exit(pool: &SqlitePool, id: Uuid) -> ExecutionProcess {
+    for _ in 0..200 {
+        let process = ExecutionProcess::find_by_id(pool, id)
+            .await
+            .expect("load process")
+            .expect("process exists");
+        if !matches!(
+            process.status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        ) {
+            return process;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("execution process {id} did not exit");
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+
+/// Wait until `msg_store` goes `timeout` without a new message, or finishes.
+/// Returns `true` when the timeout elapsed first; any message resets it.
+pub async fn wait_for_inactivity(msg_store: &MsgStore, timeout: Duration) -> bool {
+    let mut rx = msg_store.get_receiver();
+    loop {
+        match tokio::time::timeout(timeout, rx.recv()).await {
+            Err(_) => return true,
+            Ok(Ok(LogMsg::Finished)) | Ok(Err(RecvError::Closed)) => return false,
+            Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => {}
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Stdio, sync::Arc, time::Instant};
+
+    use command_group::{AsyncCommandGroup, AsyncGroupChild};
+    use futures::TryStreamExt;
+    use tokio::process::Command;
+    use tokio_util::io::ReaderStream;
+
+    use super::*;
+    use crate::command::kill_process_group;
+
+    fn spawn_tracked(script: &str) -> (AsyncGroupChild, Arc<MsgStore>) {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .group_spawn()
+            .unwrap();
+        let store = Arc::new(MsgStore::new());
+        let out = ReaderStream::new(child.inner().stdout.take().unwrap())
+            .map_ok(|chunk| LogMsg::Stdout(String::from_utf8_lossy(&chunk).into_owned()));
+        store.clone().spawn_forwarder(out);
+        (child, store)
+    }
+
+    #[tokio::test]
+    async fn silent_process_is_killed_after_timeout() {
+        let (mut child, store) = spawn_tracked("sleep 30");
+        let started = Instant::now();
+
+        assert!(wait_for_inactivity(&store, Duration::from_millis(300)).await);
+        kill_process_group(&mut child).await.unwrap();
+
+        let status = child.inner().try_wait().unwrap();
+        assert!(status.is_some_and(|status| !status.success()));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn output_keeps_the_process_alive() {
+        let (mut child, store) = spawn_tracked("for i in 1 2 3 4 5; do echo $i; sleep 0.2; done");
+        let finisher = {
+            let store = store.clone();
+            tokio::spawn(async move {
+                child.wait().await.unwrap();
+                store.push_finished();
+            })
+        };
+
+        // The run takes about a second, longer than the timeout, but never goes quiet
+        assert!(!wait_for_inactivity(&store, Duration::from_millis(600)).await);
+        finisher.await.unwrap();
+    }
+}
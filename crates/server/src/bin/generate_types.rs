@@ -12,6 +12,7 @@ fn generate_types_content() -> String {
     let decls: Vec<String> = vec![
         services::services::filesystem::DirectoryEntry::decl(),
         services::services::filesystem::DirectoryListResponse::decl(),
+        services::services::filesystem::FuzzyFileMatch::decl(),
         db::models::project::Project::decl(),
         db::models::project::ProjectWithBranch::decl(),
         db::models::project::CreateProject::decl(),
@@ -21,6 +22,7 @@ fn generate_types_content() -> String {
         services::services::file_search_cache::SearchMode::decl(),
         executors::actions::ExecutorAction::decl(),
         executors::mcp_config::McpConfig::decl(),
+        executors::mcp_config::McpServerDefinition::decl(),
         executors::actions::ExecutorActionType::decl(),
         executors::actions::script::ScriptContext::decl(),
         executors::actions::script::ScriptRequest::decl(),
@@ -43,23 +45,41 @@ fn generate_types_content() -> String {
         server::routes::config::McpServerQuery::decl(),
         server::routes::config::UpdateMcpServersBody::decl(),
         server::routes::config::GetMcpServerResponse::decl(),
+        server::routes::config::AgentAvailability::decl(),
+        server::routes::health::ExecutorHealth::decl(),
+        executors::agent_version::AgentVersionInfo::decl(),
         server::routes::task_attempts::CreateFollowUpAttempt::decl(),
         server::routes::task_attempts::CreateGitHubPrRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         services::services::github_service::GitHubServiceError::decl(),
         services::services::config::Config::decl(),
         services::services::config::NotificationConfig::decl(),
+        services::services::config::NotificationEvents::decl(),
         services::services::config::ThemeMode::decl(),
         services::services::config::EditorConfig::decl(),
         services::services::config::EditorType::decl(),
         services::services::config::GitHubConfig::decl(),
         services::services::config::SoundFile::decl(),
+        services::services::config::LogRetentionConfig::decl(),
+        services::services::config::RateLimitConfig::decl(),
+        services::services::config::WebhookEvent::decl(),
+        services::services::config::WebhookConfig::decl(),
         services::services::auth::DeviceFlowStartResponse::decl(),
         server::routes::auth::DevicePollStatus::decl(),
         server::routes::auth::CheckTokenResponse::decl(),
         services::services::git::GitBranch::decl(),
+        services::services::git::ConflictedFile::decl(),
+        services::services::git::KeptStash::decl(),
+        services::services::git::MergeStrategy::decl(),
+        services::services::git::MergeOutcome::decl(),
+        services::services::git::MergePreview::decl(),
+        services::services::git::WorktreeFileStatus::decl(),
+        services::services::git::RepoHealth::decl(),
+        services::services::git::GitOperation::decl(),
+        services::services::git::CommitInfo::decl(),
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
+        utils::diff::DiffSummary::decl(),
         utils::diff::FileDiffDetails::decl(),
         services::services::github_service::RepositoryInfo::decl(),
         executors::command::CommandBuilder::decl(),
@@ -73,24 +93,39 @@ fn generate_types_content() -> String {
         executors::executors::codex::Codex::decl(),
         executors::executors::codex::SandboxMode::decl(),
         executors::executors::codex::ApprovalPolicy::decl(),
+        executors::executors::codex::ReasoningEffort::decl(),
+        executors::approvals::ApprovalDecision::decl(),
         executors::executors::cursor::Cursor::decl(),
         executors::executors::opencode::Opencode::decl(),
         executors::executors::qwen::QwenCode::decl(),
         executors::executors::warp_cli::WarpCli::decl(),
+        executors::executors::aider::Aider::decl(),
         executors::executors::AppendPrompt::decl(),
+        executors::executors::AppendPromptFile::decl(),
         executors::actions::coding_agent_initial::CodingAgentInitialRequest::decl(),
         executors::actions::coding_agent_follow_up::CodingAgentFollowUpRequest::decl(),
         server::routes::task_attempts::CreateTaskAttemptBody::decl(),
+        server::routes::tasks::AdoptBranchRequest::decl(),
+        server::routes::tasks::TaskAttemptComparison::decl(),
+        server::routes::tasks::AddTaskDependency::decl(),
+        server::routes::tasks::ReopenTask::decl(),
         server::routes::task_attempts::RebaseTaskAttemptRequest::decl(),
+        server::routes::task_attempts::RenameBranchRequest::decl(),
         server::routes::task_attempts::RestoreAttemptRequest::decl(),
         server::routes::task_attempts::RestoreAttemptResult::decl(),
-        server::routes::task_attempts::CommitInfo::decl(),
         server::routes::task_attempts::CommitCompareResult::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
+        server::routes::task_attempts::BaseBranchStatus::decl(),
+        server::routes::task_attempts::BranchGraph::decl(),
+        server::routes::export::ProjectExport::decl(),
+        server::routes::export::ExportedTask::decl(),
+        server::routes::export::ExportedTaskAttempt::decl(),
+        server::routes::export::ProjectImportSummary::decl(),
         db::models::task_attempt::TaskAttempt::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        server::routes::execution_processes::ApproveExecutionRequest::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
@@ -102,6 +137,7 @@ fn generate_types_content() -> String {
         executors::logs::CommandExitStatus::decl(),
         executors::logs::CommandRunResult::decl(),
         executors::logs::NormalizedConversation::decl(),
+        executors::logs::TokenUsage::decl(),
         executors::logs::NormalizedEntry::decl(),
         executors::logs::NormalizedEntryType::decl(),
         executors::logs::FileChange::decl(),
@@ -169,6 +205,7 @@ fn generate_schemas() -> Result<(), Box<dyn std::error::Error>> {
     write_schema::<executors::executors::opencode::Opencode>("opencode", schemas_dir)?;
     write_schema::<executors::executors::qwen::QwenCode>("qwen_code", schemas_dir)?;
     write_schema::<executors::executors::warp_cli::WarpCli>("warp_cli", schemas_dir)?;
+    write_schema::<executors::executors::aider::Aider>("aider", schemas_dir)?;
 
     Ok(())
 }
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use rmcp::{ServiceExt, transport::stdio};
-use server::mcp::task_server::TaskServer;
+use server::mcp::{batch::with_batch_support, task_server::TaskServer};
 use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
 use tracing_subscriber::{EnvFilter, prelude::*};
 use utils::{assets::asset_dir, sentry::sentry_layer};
@@ -49,8 +49,9 @@ fn main() -> anyhow::Result<()> {
             let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(false);
             let pool = SqlitePool::connect_with(options).await?;
 
+            let (stdin, stdout) = stdio();
             let service = TaskServer::new(pool)
-                .serve(stdio())
+                .serve(with_batch_support(stdin, stdout))
                 .await
                 .inspect_err(|e| {
                     tracing::error!("serving error: {:?}", e);
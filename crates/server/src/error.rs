@@ -4,13 +4,18 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use db::models::{project::ProjectError, task_attempt::TaskAttemptError};
+use db::models::{project::ProjectError, task::TaskError, task_attempt::TaskAttemptError};
 use deployment::DeploymentError;
 use executors::executors::ExecutorError;
 use git2::Error as Git2Error;
 use services::services::{
-    auth::AuthError, config::ConfigError, container::ContainerError, git::GitServiceError,
-    github_service::GitHubServiceError, image::ImageError, worktree_manager::WorktreeError,
+    auth::AuthError,
+    config::ConfigError,
+    container::ContainerError,
+    git::{ConflictedFile, GitServiceError, KeptStash},
+    github_service::GitHubServiceError,
+    image::ImageError,
+    worktree_manager::WorktreeError,
 };
 use thiserror::Error;
 use utils::response::ApiResponse;
@@ -21,6 +26,8 @@ pub enum ApiError {
     #[error(transparent)]
     Project(#[from] ProjectError),
     #[error(transparent)]
+    Task(#[from] TaskError),
+    #[error(transparent)]
     TaskAttempt(#[from] TaskAttemptError),
     #[error(transparent)]
     GitService(#[from] GitServiceError),
@@ -48,6 +55,18 @@ pub enum ApiError {
     Io(#[from] std::io::Error),
     #[error("Conflict: {0}")]
     Conflict(String),
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+}
+
+impl ApiError {
+    /// A branch named in a request that does not resolve is the client's mistake
+    pub fn from_branch_lookup(err: GitServiceError) -> Self {
+        match err {
+            GitServiceError::BranchNotFound(_) => ApiError::BadRequest(err.to_string()),
+            err => ApiError::GitService(err),
+        }
+    }
 }
 
 impl From<Git2Error> for ApiError {
@@ -59,8 +78,39 @@ impl From<Git2Error> for ApiError {
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status_code, error_type) = match &self {
+            ApiError::GitService(GitServiceError::InsufficientDiskSpace(_))
+            | ApiError::Worktree(WorktreeError::InsufficientDiskSpace(_)) => {
+                (StatusCode::INSUFFICIENT_STORAGE, "InsufficientDiskSpace")
+            }
             ApiError::Project(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectError"),
+            ApiError::Task(TaskError::TaskNotFound) => (StatusCode::NOT_FOUND, "TaskNotFound"),
+            ApiError::Task(TaskError::CrossProjectDependency) => {
+                (StatusCode::BAD_REQUEST, "CrossProjectDependency")
+            }
+            ApiError::Task(TaskError::DependencyCycle) => (StatusCode::CONFLICT, "DependencyCycle"),
+            ApiError::Task(_) => (StatusCode::INTERNAL_SERVER_ERROR, "TaskError"),
             ApiError::TaskAttempt(_) => (StatusCode::INTERNAL_SERVER_ERROR, "TaskAttemptError"),
+            ApiError::GitService(GitServiceError::MergeConflicts(..)) => {
+                (StatusCode::CONFLICT, "MergeConflicts")
+            }
+            ApiError::GitService(GitServiceError::WorktreeDirty(..)) => {
+                (StatusCode::CONFLICT, "WorktreeDirty")
+            }
+            ApiError::GitService(GitServiceError::StashPopFailed(..)) => {
+                (StatusCode::CONFLICT, "StashPopFailed")
+            }
+            ApiError::GitService(GitServiceError::TagExists(_)) => {
+                (StatusCode::CONFLICT, "TagExists")
+            }
+            ApiError::GitService(GitServiceError::BranchExists(_)) => {
+                (StatusCode::CONFLICT, "BranchExists")
+            }
+            ApiError::GitService(GitServiceError::InvalidBranchName(_)) => {
+                (StatusCode::BAD_REQUEST, "InvalidBranchName")
+            }
+            ApiError::GitService(GitServiceError::RepoMissing(_)) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "RepoMissing")
+            }
             ApiError::GitService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
             ApiError::Auth(_) => (StatusCode::INTERNAL_SERVER_ERROR, "AuthError"),
@@ -79,6 +129,7 @@ impl IntoResponse for ApiError {
             ApiError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IoError"),
             ApiError::Multipart(_) => (StatusCode::BAD_REQUEST, "MultipartError"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
+            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
         };
 
         let error_message = match &self {
@@ -95,10 +146,98 @@ impl IntoResponse for ApiError {
                 }
             },
             ApiError::Multipart(_) => "Failed to upload file. Please ensure the file is valid and try again.".to_string(),
-            ApiError::Conflict(msg) => msg.clone(),
+            ApiError::Conflict(msg) | ApiError::BadRequest(msg) => msg.clone(),
+            ApiError::GitService(GitServiceError::RepoMissing(path)) => format!(
+                "No repository found at {path}; it may have been deleted. Recreate the attempt's worktree to continue."
+            ),
             _ => format!("{}: {}", error_type, self),
         };
+        // Conflicts carry the conflicted files so the client can show them
+        if let ApiError::GitService(GitServiceError::MergeConflicts(_, files)) = self {
+            let response = ApiResponse::<(), Vec<ConflictedFile>>::error_with_message_and_data(
+                &error_message,
+                files,
+            );
+            return (status_code, Json(response)).into_response();
+        }
+        // Tell the client where the rebase left HEAD and the changes it could not restore
+        if let ApiError::GitService(GitServiceError::StashPopFailed(kept, _)) = self {
+            let response =
+                ApiResponse::<(), KeptStash>::error_with_message_and_data(&error_message, kept);
+            return (status_code, Json(response)).into_response();
+        }
         let response = ApiResponse::<()>::error(&error_message);
         (status_code, Json(response)).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn merge_conflicts_list_the_conflicted_files() {
+        let error = ApiError::GitService(GitServiceError::MergeConflicts(
+            "Rebase failed".to_string(),
+            vec![ConflictedFile {
+                path: "src/lib.rs".to_string(),
+                base: Some("base\n".to_string()),
+                ours: Some("ours\n".to_string()),
+                theirs: None,
+            }],
+        ));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert!(body["message"].as_str().unwrap().contains("Rebase failed"));
+        assert_eq!(body["error_data"][0]["path"], "src/lib.rs");
+        assert_eq!(body["error_data"][0]["theirs"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn unrestored_stash_reports_head_and_stash() {
+        let error = ApiError::GitService(GitServiceError::StashPopFailed(
+            KeptStash {
+                head: "abc123".to_string(),
+                stash: "def456".to_string(),
+            },
+            "conflict in base.txt".to_string(),
+        ));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .contains("kept in stash def456")
+        );
+        assert_eq!(body["error_data"]["head"], "abc123");
+        assert_eq!(body["error_data"]["stash"], "def456");
+    }
+
+    #[test]
+    fn unknown_requested_branches_are_bad_requests() {
+        let error = ApiError::from_branch_lookup(GitServiceError::BranchNotFound(
+            "feat (did you mean: feature?)".to_string(),
+        ));
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let error = ApiError::from_branch_lookup(GitServiceError::InvalidRepository(
+            "not a repo".to_string(),
+        ));
+        assert_eq!(
+            error.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -2,6 +2,8 @@ pub mod error;
 pub mod mcp;
 pub mod middleware;
 pub mod routes;
+#[cfg(test)]
+mod test_support;
 
 // #[cfg(feature = "cloud")]
 // type DeploymentImpl = vibe_kanban_cloud::deployment::CloudDeployment;
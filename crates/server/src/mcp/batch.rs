@@ -0,0 +1,508 @@
+//! JSON-RPC 2.0 batch support for the line-delimited MCP transport.
+//!
+//! rmcp handles one message per line. This adapter sits between the client and
+//! the server: a batch array from the client is unpacked into single messages,
+//! and the server's responses to them are held back until the whole batch has
+//! been answered, then written as one array in request order.
+//!
+//! Requests are forwarded under ids unique to their batch, so responses cannot
+//! be mixed up with another batch, or a single request, reusing a client id.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::{Value, json};
+use tokio::io::{
+    AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, duplex,
+};
+
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// Requests of a batch still unanswered after this long get an error response,
+/// so one lost request does not hold back the rest of the batch
+const BATCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Wrap the client's `reader`/`writer` so batches are unpacked before they
+/// reach the server. Serve the returned pair instead of the original one.
+pub fn with_batch_support<R, W>(reader: R, writer: W) -> (DuplexStream, DuplexStream)
+where
+    R: AsyncRead + Send + Unpin + 'static,
+    W: AsyncWrite + Send + Unpin + 'static,
+{
+    let (server_reader, mut to_server) = duplex(PIPE_CAPACITY);
+    let (server_writer, from_server) = duplex(PIPE_CAPACITY);
+    let batches = Arc::new(Mutex::new(Batches::default()));
+    let writer = Arc::new(tokio::sync::Mutex::new(writer));
+
+    {
+        let batches = batches.clone();
+        let writer = writer.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let inbound = batches.lock().unwrap().unpack(&line);
+                if let Some(batch_id) = inbound.pending_batch {
+                    let batches = batches.clone();
+                    let writer = writer.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(BATCH_TIMEOUT).await;
+                        let expired = batches.lock().unwrap().expire(batch_id);
+                        if let Some(reply) = expired {
+                            let _ = write_line(&writer, &reply).await;
+                        }
+                    });
+                }
+                for message in inbound.forward {
+                    if to_server
+                        .write_all(format!("{message}\n").as_bytes())
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                if let Some(reply) = inbound.reply
+                    && write_line(&writer, &reply).await.is_err()
+                {
+                    return;
+                }
+            }
+            // Dropping the pipe tells the server the client went away
+        });
+    }
+
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(from_server).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let outbound = batches.lock().unwrap().collect(&line);
+            if let Some(line) = outbound
+                && write_line(&writer, &line).await.is_err()
+            {
+                return;
+            }
+        }
+    });
+
+    (server_reader, server_writer)
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(
+    writer: &tokio::sync::Mutex<W>,
+    line: &str,
+) -> std::io::Result<()> {
+    let mut writer = writer.lock().await;
+    writer.write_all(format!("{line}\n").as_bytes()).await?;
+    writer.flush().await
+}
+
+fn invalid_request(id: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": -32600, "message": "Invalid Request" },
+    })
+}
+
+fn timed_out(id: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": -32603, "message": "No response from the server in time" },
+    })
+}
+
+/// Whether a batch element is a request or notification the server can take
+fn is_valid_message(item: &Value) -> bool {
+    item.get("jsonrpc").and_then(Value::as_str) == Some("2.0")
+        && item.get("method").is_some_and(Value::is_string)
+        && item
+            .get("id")
+            .is_none_or(|id| id.is_string() || id.is_number())
+}
+
+/// What to do with a line read from the client
+#[derive(Debug, Default)]
+struct Inbound {
+    /// Messages to pass to the server, one per line
+    forward: Vec<String>,
+    /// Written straight back to the client, for batches the server never sees
+    reply: Option<String>,
+    /// Batch now waiting on the server's responses
+    pending_batch: Option<u64>,
+}
+
+#[derive(Debug)]
+enum Slot {
+    /// Waiting for the response to the request forwarded as `forwarded_id`;
+    /// `id` is the one the client sent
+    Pending {
+        forwarded_id: String,
+        id: Value,
+    },
+    Ready(Value),
+}
+
+#[derive(Debug)]
+struct Batch {
+    id: u64,
+    slots: Vec<Slot>,
+}
+
+/// Batches whose responses are still being collected
+#[derive(Debug, Default)]
+struct Batches {
+    next_id: u64,
+    pending: Vec<Batch>,
+}
+
+impl Batches {
+    fn unpack(&mut self, line: &str) -> Inbound {
+        let Ok(Value::Array(items)) = serde_json::from_str::<Value>(line) else {
+            return Inbound {
+                forward: vec![line.to_string()],
+                ..Default::default()
+            };
+        };
+        if items.is_empty() {
+            return Inbound {
+                reply: Some(invalid_request(Value::Null).to_string()),
+                ..Default::default()
+            };
+        }
+
+        let batch_id = self.next_id;
+        self.next_id += 1;
+        let mut forward = Vec::new();
+        let mut slots = Vec::new();
+        for (index, mut item) in items.into_iter().enumerate() {
+            if !is_valid_message(&item) {
+                slots.push(Slot::Ready(invalid_request(
+                    item.get("id").cloned().unwrap_or(Value::Null),
+                )));
+                continue;
+            }
+            // Notifications get no response
+            if let Some(id) = item.get("id").cloned() {
+                let forwarded_id = format!("batch-{batch_id}-{index}");
+                item["id"] = Value::String(forwarded_id.clone());
+                slots.push(Slot::Pending { forwarded_id, id });
+            }
+            forward.push(item.to_string());
+        }
+
+        let mut inbound = Inbound {
+            forward,
+            ..Default::default()
+        };
+        if slots.iter().all(|slot| matches!(slot, Slot::Ready(_))) {
+            // Nothing for the server to answer; reply now, or not at all
+            // for a batch of notifications
+            if !slots.is_empty() {
+                inbound.reply = Some(Self::responses(slots).to_string());
+            }
+        } else {
+            self.pending.push(Batch {
+                id: batch_id,
+                slots,
+            });
+            inbound.pending_batch = Some(batch_id);
+        }
+        inbound
+    }
+
+    /// Hold back responses that belong to a batch. Returns the line to write
+    /// to the client, if any.
+    fn collect(&mut self, line: &str) -> Option<String> {
+        let Ok(mut message) = serde_json::from_str::<Value>(line) else {
+            return Some(line.to_string());
+        };
+        let is_response = message.get("result").is_some() || message.get("error").is_some();
+        let Some(response_id) = message
+            .get("id")
+            .filter(|_| is_response)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            return Some(line.to_string());
+        };
+
+        let Some((batch_index, slot_index)) =
+            self.pending
+                .iter()
+                .enumerate()
+                .find_map(|(batch_index, batch)| {
+                    batch
+                        .slots
+                        .iter()
+                        .position(|slot| {
+                            matches!(
+                                slot,
+                                Slot::Pending { forwarded_id, .. } if *forwarded_id == response_id
+                            )
+                        })
+                        .map(|slot_index| (batch_index, slot_index))
+                })
+        else {
+            return Some(line.to_string());
+        };
+        let slots = &mut self.pending[batch_index].slots;
+        if let Slot::Pending { id, .. } = &slots[slot_index] {
+            message["id"] = id.clone();
+        }
+        slots[slot_index] = Slot::Ready(message);
+        if slots
+            .iter()
+            .any(|slot| matches!(slot, Slot::Pending { .. }))
+        {
+            return None;
+        }
+        let batch = self.pending.remove(batch_index);
+        Some(Self::responses(batch.slots).to_string())
+    }
+
+    /// Answer what is still pending in batch `batch_id` with errors. Returns
+    /// the batch's responses, unless it was already complete.
+    fn expire(&mut self, batch_id: u64) -> Option<String> {
+        let batch_index = self.pending.iter().position(|batch| batch.id == batch_id)?;
+        let batch = self.pending.remove(batch_index);
+        let slots = batch
+            .slots
+            .into_iter()
+            .map(|slot| match slot {
+                Slot::Pending { id, .. } => Slot::Ready(timed_out(id)),
+                ready => ready,
+            })
+            .collect();
+        Some(Self::responses(slots).to_string())
+    }
+
+    fn responses(slots: Vec<Slot>) -> Value {
+        Value::Array(
+            slots
+                .into_iter()
+                .filter_map(|slot| match slot {
+                    Slot::Ready(response) => Some(response),
+                    Slot::Pending { .. } => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use rmcp::ServiceExt;
+    use tokio::io::WriteHalf;
+
+    use super::*;
+    use crate::{
+        mcp::task_server::TaskServer,
+        test_support::{create_project, setup_pool},
+    };
+
+    fn call(id: u64, tool: &str, arguments: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": arguments },
+        })
+    }
+
+    async fn send(writer: &mut WriteHalf<DuplexStream>, message: Value) {
+        writer
+            .write_all(format!("{message}\n").as_bytes())
+            .await
+            .unwrap();
+    }
+
+    fn tool_output(response: &Value) -> Value {
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn batch_responses_come_back_in_request_order() {
+        let pool = setup_pool().await;
+        let project_id = create_project(&pool, Path::new("/tmp/batch-test")).await.id;
+
+        let (client, server) = duplex(PIPE_CAPACITY);
+        let (server_read, server_write) = tokio::io::split(server);
+        let transport = with_batch_support(server_read, server_write);
+        tokio::spawn(async move {
+            let service = TaskServer::new(pool).serve(transport).await.unwrap();
+            let _ = service.waiting().await;
+        });
+
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut responses = BufReader::new(client_read).lines();
+
+        send(
+            &mut client_write,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-03-26",
+                    "capabilities": {},
+                    "clientInfo": { "name": "batch-test", "version": "0.0.0" },
+                },
+            }),
+        )
+        .await;
+        let initialized: Value =
+            serde_json::from_str(&responses.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(initialized["id"], 1);
+        send(
+            &mut client_write,
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        )
+        .await;
+
+        send(
+            &mut client_write,
+            json!([
+                call(2, "list_projects", json!({})),
+                call(
+                    3,
+                    "create_task",
+                    json!({ "project_id": project_id.to_string(), "title": "Batched task" }),
+                ),
+                call(4, "no_such_tool", json!({})),
+                json!({ "jsonrpc": "2.0", "id": 5 }),
+            ]),
+        )
+        .await;
+        let batch: Value =
+            serde_json::from_str(&responses.next_line().await.unwrap().unwrap()).unwrap();
+        let batch = batch.as_array().expect("batch responses are an array");
+
+        let ids: Vec<&Value> = batch.iter().map(|response| &response["id"]).collect();
+        assert_eq!(ids, [&json!(2), &json!(3), &json!(4), &json!(5)]);
+
+        let projects = tool_output(&batch[0]);
+        assert_eq!(projects["count"], 1);
+        assert_eq!(projects["projects"][0]["name"], "test project");
+        let created = tool_output(&batch[1]);
+        assert_eq!(created["success"], true);
+
+        // Failures stay confined to their own entries
+        assert!(batch[2].get("error").is_some());
+        assert_eq!(batch[3]["error"]["code"], -32600);
+    }
+
+    #[test]
+    fn empty_and_notification_only_batches() {
+        let mut batches = Batches::default();
+
+        let empty = batches.unpack("[]");
+        assert!(empty.forward.is_empty());
+        let reply: Value = serde_json::from_str(&empty.reply.unwrap()).unwrap();
+        assert_eq!(reply["error"]["code"], -32600);
+
+        let notifications = batches.unpack(
+            r#"[{"jsonrpc":"2.0","method":"notifications/initialized"},
+                {"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1}}]"#,
+        );
+        assert_eq!(notifications.forward.len(), 2);
+        assert!(notifications.reply.is_none());
+        assert!(batches.pending.is_empty());
+    }
+
+    #[test]
+    fn single_messages_pass_through() {
+        let mut batches = Batches::default();
+        let request = r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#;
+        assert_eq!(batches.unpack(request).forward, [request]);
+
+        let response = r#"{"jsonrpc":"2.0","id":7,"result":{"tools":[]}}"#;
+        assert_eq!(batches.collect(response).as_deref(), Some(response));
+    }
+
+    #[test]
+    fn reused_ids_are_matched_within_their_own_batch() {
+        let mut batches = Batches::default();
+        let request = |id: u64| json!({ "jsonrpc": "2.0", "id": id, "method": "tools/list" });
+        let first = batches.unpack(&json!([request(1), request(1)]).to_string());
+        let second = batches.unpack(&json!([request(1)]).to_string());
+        assert_ne!(first.pending_batch, second.pending_batch);
+
+        let forwarded_id = |message: &str| {
+            serde_json::from_str::<Value>(message).unwrap()["id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let respond = |id: String, n: u64| {
+            json!({ "jsonrpc": "2.0", "id": id, "result": { "n": n } }).to_string()
+        };
+        let ids: Vec<String> = first
+            .forward
+            .iter()
+            .chain(&second.forward)
+            .map(|message| forwarded_id(message))
+            .collect();
+
+        // The second batch completes on its own, under the client's id
+        let reply = batches.collect(&respond(ids[2].clone(), 3)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&reply).unwrap(),
+            json!([{ "jsonrpc": "2.0", "id": 1, "result": { "n": 3 } }])
+        );
+        // A single request with the same id is not taken by the first batch
+        let single = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        assert_eq!(batches.collect(single).as_deref(), Some(single));
+
+        assert!(batches.collect(&respond(ids[1].clone(), 2)).is_none());
+        let reply = batches.collect(&respond(ids[0].clone(), 1)).unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply[0]["result"]["n"], 1);
+        assert_eq!(reply[1]["result"]["n"], 2);
+        assert!(batches.pending.is_empty());
+    }
+
+    #[test]
+    fn unanswered_batch_expires_with_errors() {
+        let mut batches = Batches::default();
+        let inbound = batches.unpack(
+            r#"[{"jsonrpc":"2.0","id":"a","method":"tools/list"},
+                {"jsonrpc":"2.0","id":"b","method":"tools/list"}]"#,
+        );
+        let batch_id = inbound.pending_batch.unwrap();
+        let answered: Value = serde_json::from_str(&inbound.forward[0]).unwrap();
+        let response = json!({ "jsonrpc": "2.0", "id": answered["id"], "result": {} });
+        assert!(batches.collect(&response.to_string()).is_none());
+
+        let reply: Value = serde_json::from_str(&batches.expire(batch_id).unwrap()).unwrap();
+        assert_eq!(reply[0]["id"], "a");
+        assert!(reply[0].get("result").is_some());
+        assert_eq!(reply[1]["id"], "b");
+        assert_eq!(reply[1]["error"]["code"], -32603);
+        assert!(batches.expire(batch_id).is_none());
+    }
+
+    #[test]
+    fn malformed_elements_are_not_forwarded() {
+        let mut batches = Batches::default();
+        let inbound = batches.unpack(
+            r#"[{"id":1,"method":"tools/list"},
+                {"jsonrpc":"2.0","id":{"nested":true},"method":"tools/list"},
+                42]"#,
+        );
+        assert!(inbound.forward.is_empty());
+        assert!(inbound.pending_batch.is_none());
+        let reply: Value = serde_json::from_str(&inbound.reply.unwrap()).unwrap();
+        let codes: Vec<&Value> = reply
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|response| &response["error"]["code"])
+            .collect();
+        assert_eq!(codes, [&json!(-32600), &json!(-32600), &json!(-32600)]);
+    }
+}
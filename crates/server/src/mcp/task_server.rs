@@ -1,4 +1,4 @@
-use std::{future::Future, path::PathBuf};
+use std::{future::Future, path::PathBuf, time::Duration};
 
 use db::models::{
     project::Project,
@@ -25,8 +25,15 @@ pub struct CreateTaskRequest {
     pub title: String,
     #[schemars(description = "Optional description of the task")]
     pub description: Option<String>,
+    #[schemars(
+        description = "If true, return a task with the same title (ignoring case and surrounding whitespace) created in the project in the last few minutes instead of creating a duplicate"
+    )]
+    pub dedupe: Option<bool>,
 }
 
+/// How far back `create_task` with `dedupe` looks for a task with the same title
+const DEDUPE_WINDOW: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug, Serialize, schemars::JsonSchema)]
 pub struct CreateTaskResponse {
     pub success: bool,
@@ -218,6 +225,7 @@ impl TaskServer {
             project_id,
             title,
             description,
+            dedupe,
         }): Parameters<CreateTaskRequest>,
     ) -> Result<CallToolResult, ErrorData> {
         // Parse project_id from string to UUID
@@ -264,6 +272,37 @@ impl TaskServer {
             Ok(true) => {}
         }
 
+        if dedupe.unwrap_or(false) {
+            match Task::find_recent_by_title(&self.pool, project_uuid, &title, DEDUPE_WINDOW).await
+            {
+                Ok(Some(existing)) => {
+                    let response = CreateTaskResponse {
+                        success: true,
+                        task_id: existing.id.to_string(),
+                        message: "A task with this title already exists".to_string(),
+                    };
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string_pretty(&response)
+                            .unwrap_or_else(|_| "Task already exists".to_string()),
+                    )]));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let error_response = serde_json::json!({
+                        "success": false,
+                        "error": "Failed to check for duplicate tasks",
+                        "details": e.to_string(),
+                        "project_id": project_id,
+                        "title": title
+                    });
+                    return Ok(CallToolResult::error(vec![Content::text(
+                        serde_json::to_string_pretty(&error_response)
+                            .unwrap_or_else(|_| "Database error".to_string()),
+                    )]));
+                }
+            }
+        }
+
         let task_id = Uuid::new_v4();
         let create_task_data = CreateTask {
             project_id: project_uuid,
@@ -271,6 +310,7 @@ impl TaskServer {
             description: description.clone(),
             parent_task_attempt: None,
             image_ids: None,
+            assignee: None,
         };
 
         match Task::create(&self.pool, &create_task_data, task_id).await {
@@ -582,6 +622,8 @@ impl TaskServer {
             new_description,
             new_status,
             new_parent_task_attempt,
+            current_task.estimate_minutes,
+            current_task.assignee,
         )
         .await
         {
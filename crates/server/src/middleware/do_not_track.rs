@@ -0,0 +1,12 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use services::services::analytics::with_do_not_track_header;
+
+/// Turn analytics off while handling a request that sends `DNT: 1`
+pub async fn do_not_track_middleware(request: Request, next: Next) -> Response {
+    let dnt = request
+        .headers()
+        .get("dnt")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    with_do_not_track_header(dnt.as_deref(), next.run(request)).await
+}
@@ -1,3 +1,7 @@
+pub mod do_not_track;
 pub mod model_loaders;
+pub mod rate_limit;
 
+pub use do_not_track::*;
 pub use model_loaders::*;
+pub use rate_limit::*;
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use services::services::config::{Config, RateLimitConfig};
+use tokio::sync::RwLock;
+use utils::response::ApiResponse;
+
+/// Past this many tracked clients, buckets that have refilled are dropped
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// API routes that hold a connection open, or must stay reachable for monitoring
+const EXEMPT_SUFFIXES: [&str; 3] = ["/stream", "/raw-logs", "/normalized-logs"];
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token buckets keyed by client IP, sized from the live config
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RwLock<Config>>,
+    buckets: Arc<Mutex<HashMap<IpAddr, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            config,
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Take a token for `ip`, or return how long until one is available
+    fn acquire(&self, ip: IpAddr, limits: &RateLimitConfig) -> Result<(), Duration> {
+        let per_second = f64::from(limits.requests_per_minute) / 60.0;
+        let burst = f64::from(limits.burst.max(1));
+        let now = Instant::now();
+        let refill = |bucket: &Bucket| {
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            (bucket.tokens + elapsed * per_second).min(burst)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| refill(bucket) < burst);
+        }
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+        bucket.tokens = refill(bucket);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else if per_second > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        } else {
+            Err(Duration::from_secs(60))
+        }
+    }
+}
+
+fn is_exempt(path: &str) -> bool {
+    let Some(route) = path.strip_prefix("/api") else {
+        // Frontend assets and metrics are not rate limited
+        return true;
+    };
+    let route = route.trim_end_matches('/');
+    route == "/health"
+        || route.starts_with("/health/")
+        || route == "/events"
+        || EXEMPT_SUFFIXES.iter().any(|suffix| route.ends_with(suffix))
+}
+
+/// Reject API requests from clients that exceed the configured rate with 429
+pub async fn rate_limit_middleware(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limits = limiter.config.read().await.rate_limit.clone();
+    if !limits.enabled || is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match limiter.acquire(ip, &limits) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            tracing::debug!("Rate limited {} on {}", ip, request.uri().path());
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                ResponseJson(ApiResponse::<()>::error(
+                    "Too many requests; try again later",
+                )),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, middleware::from_fn_with_state, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn limited_router(rate_limit: RateLimitConfig) -> Router {
+        let config = Config {
+            rate_limit,
+            ..Config::default()
+        };
+        let limiter = RateLimiter::new(Arc::new(RwLock::new(config)));
+        Router::new()
+            .route("/api/tasks", get(|| async { "ok" }))
+            .route("/api/events", get(|| async { "ok" }))
+            .route("/api/task-attempts/{id}/diff", get(|| async { "ok" }))
+            .route(
+                "/api/task-attempts/{id}/diff/stream",
+                get(|| async { "ok" }),
+            )
+            .route("/api/health", get(|| async { "ok" }))
+            .layer(from_fn_with_state(limiter, rate_limit_middleware))
+    }
+
+    async fn get_from(app: &Router, path: &str, ip: [u8; 4]) -> Response {
+        let mut request = axum::http::Request::builder()
+            .uri(path)
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+        app.clone().oneshot(request).await.unwrap()
+    }
+
+    fn limits(requests_per_minute: u32, burst: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            requests_per_minute,
+            burst,
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_past_burst_then_recovers() {
+        // 10 requests per second: a token every 100ms
+        let app = limited_router(limits(600, 2));
+        let client = [10, 0, 0, 1];
+
+        for _ in 0..2 {
+            let response = get_from(&app, "/api/tasks", client).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let limited = get_from(&app, "/api/tasks", client).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "1");
+
+        // Other clients have their own bucket
+        let other = get_from(&app, "/api/tasks", [10, 0, 0, 2]).await;
+        assert_eq!(other.status(), StatusCode::OK);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let recovered = get_from(&app, "/api/tasks", client).await;
+        assert_eq!(recovered.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn exempt_routes_and_disabled_limiter_pass_through() {
+        let app = limited_router(limits(0, 1));
+        let client = [10, 0, 0, 1];
+
+        assert_eq!(
+            get_from(&app, "/api/tasks", client).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            get_from(&app, "/api/tasks", client).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        for path in [
+            "/api/events",
+            "/api/health",
+            "/api/task-attempts/1/diff/stream",
+        ] {
+            assert_eq!(get_from(&app, path, client).await.status(), StatusCode::OK);
+        }
+        // Only the diff stream holds a connection open
+        assert_eq!(
+            get_from(&app, "/api/task-attempts/1/diff", client)
+                .await
+                .status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        let app = limited_router(RateLimitConfig {
+            enabled: false,
+            ..limits(0, 1)
+        });
+        for _ in 0..3 {
+            assert_eq!(
+                get_from(&app, "/api/tasks", client).await.status(),
+                StatusCode::OK
+            );
+        }
+    }
+}
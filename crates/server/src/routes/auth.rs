@@ -105,7 +105,7 @@ async fn github_check_token(
             CheckTokenResponse::Invalid,
         )));
     };
-    let gh = GitHubService::new(&token)?;
+    let gh = GitHubService::new(&token, &gh_config.extra_headers)?;
     match gh.check_token().await {
         Ok(()) => Ok(ResponseJson(ApiResponse::success(
             CheckTokenResponse::Valid,
@@ -10,13 +10,19 @@ use axum::{
 };
 use deployment::{Deployment, DeploymentError};
 use executors::{
+    agent_version::AgentVersionInfo,
     executors::{BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor},
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
+use futures_util::future::join_all;
+use schemars::{SchemaGenerator, generate::SchemaSettings};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use services::services::config::{Config, ConfigError, SoundFile, save_config_to_file};
+use services::services::{
+    config::{Config, ConfigError, SoundFile, save_config_to_file},
+    container::ContainerService,
+};
 use tokio::fs;
 use ts_rs::TS;
 use utils::{assets::config_path, response::ApiResponse};
@@ -27,9 +33,11 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/info", get(get_user_system_info))
         .route("/config", put(update_config))
+        .route("/config/schema", get(get_config_schema))
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route("/agents/availability", get(get_agent_availability))
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -111,6 +119,22 @@ async fn update_config(
             let mut config = deployment.config().write().await;
             *config = new_config.clone();
             drop(config);
+            utils::path::set_forward_slash_paths(new_config.forward_slash_paths);
+            executors::logs::utils::output::set_output_limit_bytes(
+                new_config.command_output_limit_bytes as usize,
+            );
+            utils::shell::set_shell_override(
+                new_config.shell_override.as_deref(),
+                &new_config.shell_override_args,
+            );
+            let container = deployment.container();
+            container
+                .execution_slots()
+                .set_limit(new_config.max_concurrent_executions);
+            // A raised limit frees slots for processes that are already queued
+            if let Err(e) = container.start_queued_executions().await {
+                tracing::error!("Failed to start queued execution processes: {}", e);
+            }
 
             // If analytics was just enabled (changed from None/false to true), track session_start
             if new_config.analytics_enabled == Some(true) && old_analytics_enabled != Some(true) {
@@ -125,6 +149,25 @@ async fn update_config(
     }
 }
 
+/// Draft-07 JSON schema of the current config version with subschemas inlined,
+/// so settings forms can be rendered from it
+pub fn config_schema() -> Value {
+    let mut settings = SchemaSettings::draft07();
+    settings.inline_subschemas = true;
+    let generator: SchemaGenerator = settings.into_generator();
+    let mut schema =
+        serde_json::to_value(generator.into_root_schema_for::<Config>()).unwrap_or_default();
+    // A root title makes RJSF wrap the whole form in an extra field container
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("title");
+    }
+    schema
+}
+
+async fn get_config_schema() -> ResponseJson<ApiResponse<Value>> {
+    ResponseJson(ApiResponse::success(config_schema()))
+}
+
 async fn get_sound(Path(sound): Path<SoundFile>) -> Result<Response, ApiError> {
     let sound = sound.serve().await.map_err(DeploymentError::Other)?;
     let response = Response::builder()
@@ -319,6 +362,35 @@ fn set_mcp_servers_in_config_path(
     Ok(())
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct AgentAvailability {
+    pub available: bool,
+    /// Installed vs recommended CLI version, when the executor supports version checks
+    pub version: Option<AgentVersionInfo>,
+}
+
+/// Check every configured executor's CLI, keyed by executor name
+pub async fn probe_agent_availability() -> HashMap<String, AgentAvailability> {
+    let profiles = ExecutorConfigs::get_cached();
+    // Version probes may spawn `npx`, so run them concurrently
+    let checks = profiles.executors.keys().filter_map(|key| {
+        let agent = profiles.get_coding_agent(&ExecutorProfileId::new(*key))?;
+        Some(async move {
+            let (available, version) =
+                tokio::join!(agent.check_availability(), agent.check_version());
+            (key.to_string(), AgentAvailability { available, version })
+        })
+    });
+    join_all(checks).await.into_iter().collect()
+}
+
+/// Availability and version status per executor (e.g., { "CODEX": { available: true, ... } })
+async fn get_agent_availability(
+    State(_deployment): State<DeploymentImpl>,
+) -> ResponseJson<ApiResponse<HashMap<String, AgentAvailability>>> {
+    ResponseJson(ApiResponse::success(probe_agent_availability().await))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProfilesContent {
     pub content: String,
@@ -377,3 +449,45 @@ async fn update_profiles(
         ))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_schema_describes_settings_and_enum_options() {
+        let schema = config_schema();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in [
+            "config_version",
+            "theme",
+            "executor_profile",
+            "notifications",
+            "editor",
+            "github",
+            "analytics_enabled",
+            "rate_limit",
+            "shell_override",
+        ] {
+            assert!(properties.contains_key(key), "missing {key}");
+        }
+
+        let options = |value: &Value| -> Vec<String> {
+            value["enum"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|option| option.as_str().unwrap().to_string())
+                .collect()
+        };
+        assert!(options(&properties["theme"]).contains(&"DARK".to_string()));
+        assert!(
+            options(&properties["notifications"]["properties"]["sound_file"])
+                .contains(&"COW_MOOING".to_string())
+        );
+        assert!(
+            options(&properties["editor"]["properties"]["editor_type"])
+                .contains(&"VS_CODE".to_string())
+        );
+    }
+}
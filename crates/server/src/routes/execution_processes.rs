@@ -1,19 +1,23 @@
 use axum::{
-    BoxError, Extension, Router,
+    BoxError, Extension, Json, Router,
     extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     middleware::from_fn_with_state,
     response::{
-        Json as ResponseJson, Sse,
+        IntoResponse, Json as ResponseJson, Response, Sse,
         sse::{Event, KeepAlive},
     },
     routing::{get, post},
 };
-use db::models::execution_process::ExecutionProcess;
+use db::models::execution_process::{ExecutionProcess, ExecutionProcessStatus};
 use deployment::Deployment;
+use executors::{approvals::ApprovalDecision, logs::NormalizedEntry};
 use futures_util::TryStreamExt;
 use serde::Deserialize;
 use services::services::container::ContainerService;
-use utils::response::ApiResponse;
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::{log_msg::LogStream, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_execution_process_middleware};
@@ -56,6 +60,54 @@ pub async fn stream_raw_logs(
     Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RawLogsQuery {
+    #[serde(default)]
+    pub stream: LogStream,
+}
+
+/// Raw logs as a live event stream for `EventSource` clients, otherwise the
+/// output recorded so far as plain text, e.g. for `curl` while debugging
+pub async fn get_raw_logs(
+    State(deployment): State<DeploymentImpl>,
+    Path(exec_id): Path<Uuid>,
+    Query(query): Query<RawLogsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let wants_event_stream = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if wants_event_stream {
+        return stream_raw_logs(State(deployment), Path(exec_id))
+            .await
+            .map(IntoResponse::into_response);
+    }
+
+    let text = deployment
+        .container()
+        .raw_log_text(&exec_id, query.stream)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response())
+}
+
+/// Complete output of a command whose output was truncated in the conversation
+pub async fn get_tool_output(
+    State(deployment): State<DeploymentImpl>,
+    Path((exec_id, call_id)): Path<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
+    ExecutionProcess::find_by_id(&deployment.db().pool, exec_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let output = deployment
+        .container()
+        .full_tool_output(&exec_id, &call_id)
+        .await
+        .ok_or(SqlxError::RowNotFound)?;
+    Ok(ResponseJson(ApiResponse::success(output)))
+}
+
 pub async fn stream_normalized_logs(
     State(deployment): State<DeploymentImpl>,
     Path(exec_id): Path<Uuid>,
@@ -75,6 +127,16 @@ pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !matches!(
+        execution_process.status,
+        ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+    ) {
+        return Err(ApiError::Conflict(format!(
+            "Execution process {} is not running",
+            execution_process.id
+        )));
+    }
+
     deployment
         .container()
         .stop_execution(&execution_process)
@@ -83,12 +145,73 @@ pub async fn stop_execution_process(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Re-parse a finished process's persisted raw logs with the current normalizer,
+/// so parser fixes reach old attempts without re-running the agent
+pub async fn renormalize_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<NormalizedEntry>>>, ApiError> {
+    if matches!(execution_process.status, ExecutionProcessStatus::Running) {
+        return Err(ApiError::Conflict(format!(
+            "Execution process {} is still running",
+            execution_process.id
+        )));
+    }
+
+    let entries = deployment
+        .container()
+        .renormalize_logs(&execution_process.id)
+        .await
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Execution process {} has no persisted agent logs to renormalize",
+                execution_process.id
+            ))
+        })?;
+
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ApproveExecutionRequest {
+    pub call_id: String,
+    pub decision: ApprovalDecision,
+}
+
+/// Answer an agent's pending approval request, unblocking the run
+pub async fn approve_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ApproveExecutionRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !matches!(execution_process.status, ExecutionProcessStatus::Running) {
+        return Err(ApiError::Conflict(format!(
+            "Execution process {} is not running",
+            execution_process.id
+        )));
+    }
+    let answered = deployment
+        .container()
+        .answer_approval(&execution_process, &payload.call_id, payload.decision)
+        .await?;
+    if !answered {
+        return Err(ApiError::BadRequest(format!(
+            "No pending approval for call {}",
+            payload.call_id
+        )));
+    }
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
-        .route("/raw-logs", get(stream_raw_logs))
+        .route("/approve", post(approve_execution_process))
+        .route("/raw-logs", get(get_raw_logs))
         .route("/normalized-logs", get(stream_normalized_logs))
+        .route("/renormalize", post(renormalize_execution_process))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_execution_process_middleware,
@@ -96,6 +219,8 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_execution_processes))
+        // Outside the process loader, which expects `{id}` to be the only path parameter
+        .route("/{id}/tool-output/{call_id}", get(get_tool_output))
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/execution-processes", task_attempts_router)
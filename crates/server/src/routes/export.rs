@@ -0,0 +1,303 @@
+use std::collections::HashSet;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use chrono::{DateTime, Utc};
+use db::models::{
+    project::Project,
+    task::Task,
+    task_attempt::TaskAttempt,
+    task_template::{CreateTaskTemplate, TaskTemplate},
+};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Bumped whenever the export document changes incompatibly
+pub const PROJECT_EXPORT_VERSION: u32 = 1;
+
+/// A project's board as exported by `GET /projects/{id}/export`
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct ProjectExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub project: Project,
+    /// Oldest first, so re-importing keeps the board order
+    pub tasks: Vec<ExportedTask>,
+    pub templates: Vec<TaskTemplate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct ExportedTask {
+    pub task: Task,
+    pub attempts: Vec<ExportedTaskAttempt>,
+}
+
+/// Attempt metadata without worktree details; attempts are not re-created on import
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct ExportedTaskAttempt {
+    pub branch: Option<String>,
+    pub base_branch: String,
+    pub executor: String,
+    pub summary: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TaskAttempt> for ExportedTaskAttempt {
+    fn from(attempt: TaskAttempt) -> Self {
+        Self {
+            branch: attempt.branch,
+            base_branch: attempt.base_branch,
+            executor: attempt.executor,
+            summary: attempt.summary,
+            created_at: attempt.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct ProjectImportSummary {
+    pub tasks_imported: usize,
+    pub templates_imported: usize,
+    /// Templates whose name the project already uses
+    pub templates_skipped: usize,
+}
+
+pub async fn build_project_export(
+    pool: &SqlitePool,
+    project: Project,
+) -> Result<ProjectExport, ApiError> {
+    let mut tasks = Vec::new();
+    for task in Task::find_by_project_id(pool, project.id).await? {
+        let attempts = TaskAttempt::fetch_all(pool, Some(task.id))
+            .await?
+            .into_iter()
+            .map(ExportedTaskAttempt::from)
+            .collect();
+        tasks.push(ExportedTask { task, attempts });
+    }
+    let templates = TaskTemplate::find_by_project_id(pool, Some(project.id)).await?;
+
+    Ok(ProjectExport {
+        version: PROJECT_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        project,
+        tasks,
+        templates,
+    })
+}
+
+/// Re-create the tasks and templates of `export` in `project_id` under fresh ids.
+/// Execution history is not part of an export and attempts are not re-created.
+/// Runs in a single transaction, so a failed import leaves the project untouched.
+pub async fn import_project_export(
+    pool: &SqlitePool,
+    project_id: Uuid,
+    export: &ProjectExport,
+) -> Result<ProjectImportSummary, ApiError> {
+    if export.version != PROJECT_EXPORT_VERSION {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported export version {} (expected {})",
+            export.version, PROJECT_EXPORT_VERSION
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let mut tasks: Vec<&Task> = export.tasks.iter().map(|exported| &exported.task).collect();
+    tasks.sort_by_key(|task| task.created_at);
+    for task in &tasks {
+        Task::import(&mut *tx, task, project_id, Uuid::new_v4()).await?;
+    }
+
+    let mut existing_names: HashSet<String> =
+        TaskTemplate::find_by_project_id(&mut *tx, Some(project_id))
+            .await?
+            .into_iter()
+            .map(|template| template.template_name)
+            .collect();
+    let mut templates_imported = 0;
+    for template in &export.templates {
+        if !existing_names.insert(template.template_name.clone()) {
+            continue;
+        }
+        TaskTemplate::create(
+            &mut *tx,
+            &CreateTaskTemplate {
+                project_id: Some(project_id),
+                title: template.title.clone(),
+                description: template.description.clone(),
+                template_name: template.template_name.clone(),
+            },
+        )
+        .await?;
+        templates_imported += 1;
+    }
+    tx.commit().await?;
+
+    Ok(ProjectImportSummary {
+        tasks_imported: tasks.len(),
+        templates_imported,
+        templates_skipped: export.templates.len() - templates_imported,
+    })
+}
+
+pub async fn export_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ProjectExport>>, ApiError> {
+    let export = build_project_export(&deployment.db().pool, project).await?;
+    Ok(ResponseJson(ApiResponse::success(export)))
+}
+
+pub async fn import_project(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(export): Json<ProjectExport>,
+) -> Result<ResponseJson<ApiResponse<ProjectImportSummary>>, ApiError> {
+    let summary = import_project_export(&deployment.db().pool, project.id, &export).await?;
+    deployment
+        .track_if_analytics_allowed(
+            "project_imported",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "tasks_imported": summary.tasks_imported,
+            }),
+        )
+        .await;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use db::models::task::{CreateTask, TaskStatus};
+
+    use super::*;
+    use crate::test_support::{create_project, create_task, setup_pool};
+
+    #[tokio::test]
+    async fn export_round_trips_into_a_new_project() {
+        let pool = setup_pool().await;
+        let source = create_project(&pool, Path::new("/tmp/export-source")).await;
+
+        let seeded = [
+            ("write docs", TaskStatus::Todo),
+            ("fix login", TaskStatus::InReview),
+            ("ship release", TaskStatus::Done),
+        ];
+        for (title, status) in &seeded {
+            let task = Task::create(
+                &pool,
+                &CreateTask {
+                    project_id: source.id,
+                    title: title.to_string(),
+                    description: Some(format!("{title} details")),
+                    parent_task_attempt: None,
+                    image_ids: None,
+                    assignee: None,
+                },
+                Uuid::new_v4(),
+            )
+            .await
+            .unwrap();
+            Task::update_status(&pool, task.id, status.clone())
+                .await
+                .unwrap();
+        }
+        TaskTemplate::create(
+            &pool,
+            &CreateTaskTemplate {
+                project_id: Some(source.id),
+                title: "Bug".to_string(),
+                description: None,
+                template_name: "bug".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let export = build_project_export(&pool, source.clone()).await.unwrap();
+        // The document survives a trip through JSON
+        let export: ProjectExport =
+            serde_json::from_str(&serde_json::to_string(&export).unwrap()).unwrap();
+        assert_eq!(export.tasks.len(), 3);
+
+        let target = create_project(&pool, Path::new("/tmp/export-target")).await;
+        let summary = import_project_export(&pool, target.id, &export)
+            .await
+            .unwrap();
+        assert_eq!(summary.tasks_imported, 3);
+        assert_eq!(summary.templates_imported, 1);
+
+        let source_tasks = Task::find_by_project_id(&pool, source.id).await.unwrap();
+        let imported = Task::find_by_project_id(&pool, target.id).await.unwrap();
+        let titles_and_statuses = |tasks: &[Task]| {
+            tasks
+                .iter()
+                .map(|task| (task.title.clone(), task.status.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            titles_and_statuses(&imported),
+            titles_and_statuses(&source_tasks)
+        );
+        assert!(
+            imported
+                .iter()
+                .all(|task| source_tasks.iter().all(|source| source.id != task.id))
+        );
+
+        // Importing again keeps the existing template instead of failing
+        let summary = import_project_export(&pool, target.id, &export)
+            .await
+            .unwrap();
+        assert_eq!(summary.templates_skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn failed_import_leaves_the_project_untouched() {
+        let pool = setup_pool().await;
+        let source = create_project(&pool, Path::new("/tmp/export-source")).await;
+        create_task(&pool, source.id, "write docs").await;
+        TaskTemplate::create(
+            &pool,
+            &CreateTaskTemplate {
+                project_id: Some(source.id),
+                title: "Bug".to_string(),
+                description: None,
+                template_name: "bug".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let export = build_project_export(&pool, source).await.unwrap();
+
+        // Make the template insert fail after the tasks were written
+        sqlx::query(
+            "CREATE TRIGGER reject_templates BEFORE INSERT ON task_templates
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let target = create_project(&pool, Path::new("/tmp/export-target")).await;
+        assert!(
+            import_project_export(&pool, target.id, &export)
+                .await
+                .is_err()
+        );
+        assert!(
+            Task::find_by_project_id(&pool, target.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
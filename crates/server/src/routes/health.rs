@@ -1,6 +1,119 @@
+use std::{
+    collections::HashMap,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
 use axum::response::Json;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 
+use crate::routes::config::{AgentAvailability, probe_agent_availability};
+
+/// Executor checks spawn processes, so their results are reused for this long
+const EXECUTOR_HEALTH_TTL: Duration = Duration::from_secs(60);
+
+static EXECUTOR_HEALTH_CACHE: LazyLock<ExecutorHealthCache> =
+    LazyLock::new(ExecutorHealthCache::default);
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorHealth {
+    /// Availability and detected version per executor
+    pub executors: HashMap<String, AgentAvailability>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct ExecutorHealthCache {
+    entry: Mutex<Option<(Instant, ExecutorHealth)>>,
+}
+
+impl ExecutorHealthCache {
+    /// Cached report if younger than `ttl`, otherwise a fresh one from `probe`.
+    /// The lock is held while probing so concurrent polls share one check.
+    async fn get_or_probe<F, Fut>(&self, ttl: Duration, probe: F) -> ExecutorHealth
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = HashMap<String, AgentAvailability>>,
+    {
+        let mut entry = self.entry.lock().await;
+        if let Some((checked, health)) = entry.as_ref()
+            && checked.elapsed() < ttl
+        {
+            return health.clone();
+        }
+        let health = ExecutorHealth {
+            executors: probe().await,
+            checked_at: Utc::now(),
+        };
+        *entry = Some((Instant::now(), health.clone()));
+        health
+    }
+}
+
 pub async fn health_check() -> Json<ApiResponse<String>> {
     Json(ApiResponse::success("OK".to_string()))
 }
+
+/// Whether each executor's CLI can be found, to diagnose missing installs
+/// before a task runs
+pub async fn executor_health() -> Json<ApiResponse<ExecutorHealth>> {
+    let health = EXECUTOR_HEALTH_CACHE
+        .get_or_probe(EXECUTOR_HEALTH_TTL, probe_agent_availability)
+        .await;
+    Json(ApiResponse::success(health))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn mock_availability() -> HashMap<String, AgentAvailability> {
+        HashMap::from([
+            (
+                "CLAUDE_CODE".to_string(),
+                AgentAvailability {
+                    available: true,
+                    version: None,
+                },
+            ),
+            (
+                "GEMINI".to_string(),
+                AgentAvailability {
+                    available: false,
+                    version: None,
+                },
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn report_maps_agents_to_availability_and_is_cached() {
+        let cache = ExecutorHealthCache::default();
+        let probes = AtomicUsize::new(0);
+        let probe = || async {
+            probes.fetch_add(1, Ordering::SeqCst);
+            mock_availability()
+        };
+
+        let health = cache.get_or_probe(Duration::from_secs(60), probe).await;
+        let json = serde_json::to_value(ApiResponse::success(health)).unwrap();
+        let executors = &json["data"]["executors"];
+        assert_eq!(executors["CLAUDE_CODE"]["available"], true);
+        assert_eq!(executors["GEMINI"]["available"], false);
+        assert!(executors["GEMINI"]["version"].is_null());
+        assert!(json["data"]["checked_at"].is_string());
+
+        cache.get_or_probe(Duration::from_secs(60), probe).await;
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+
+        // An expired entry is probed again
+        cache.get_or_probe(Duration::ZERO, probe).await;
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+    }
+}
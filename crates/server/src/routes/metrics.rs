@@ -0,0 +1,39 @@
+use axum::{
+    Router,
+    extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use db::models::task::Task;
+use deployment::Deployment;
+use services::services::metrics::{MetricsSnapshot, metrics};
+
+use crate::DeploymentImpl;
+
+pub async fn get_metrics(State(deployment): State<DeploymentImpl>) -> Response {
+    let pool = &deployment.db().pool;
+    let tasks_by_status = match Task::count_by_status(pool).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::error!("Failed to count tasks for metrics: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let snapshot = MetricsSnapshot {
+        tasks_by_status,
+        db_pool_size: pool.size(),
+        db_pool_idle: pool.num_idle(),
+    };
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(&snapshot),
+    )
+        .into_response()
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/metrics", get(get_metrics))
+}
@@ -1,9 +1,18 @@
+use std::net::SocketAddr;
+
 use axum::{
     Router,
-    routing::{IntoMakeService, get},
+    extract::connect_info::IntoMakeServiceWithConnectInfo,
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
 };
+use deployment::Deployment;
+use services::services::metrics::metrics_enabled;
 
-use crate::DeploymentImpl;
+use crate::{
+    DeploymentImpl,
+    middleware::{RateLimiter, do_not_track_middleware, rate_limit_middleware},
+};
 
 pub mod auth;
 pub mod config;
@@ -12,18 +21,21 @@ pub mod filesystem;
 // pub mod github;
 pub mod events;
 pub mod execution_processes;
+pub mod export;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod metrics;
 pub mod projects;
 pub mod task_attempts;
 pub mod task_templates;
 pub mod tasks;
 
-pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
+pub fn router(deployment: DeploymentImpl) -> IntoMakeServiceWithConnectInfo<Router, SocketAddr> {
     // Create routers with different middleware layers
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
+        .route("/health/executors", get(health::executor_health))
         .merge(config::router())
         .merge(containers::router(&deployment))
         .merge(projects::router(&deployment))
@@ -35,11 +47,23 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(filesystem::router())
         .merge(events::router(&deployment))
         .nest("/images", images::routes())
-        .with_state(deployment);
+        .with_state(deployment.clone());
 
-    Router::new()
+    let mut app = Router::new()
         .route("/", get(frontend::serve_frontend_root))
         .route("/{*path}", get(frontend::serve_frontend))
         .nest("/api", base_routes)
-        .into_make_service()
+        .layer(from_fn(do_not_track_middleware))
+        .layer(from_fn_with_state(
+            RateLimiter::new(deployment.config().clone()),
+            rate_limit_middleware,
+        ));
+
+    // Only expose Prometheus metrics when explicitly enabled
+    if metrics_enabled() {
+        app = app.merge(metrics::router().with_state(deployment));
+    }
+
+    // Client addresses key the rate limiter
+    app.into_make_service_with_connect_info::<SocketAddr>()
 }
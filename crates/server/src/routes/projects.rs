@@ -8,10 +8,12 @@ use axum::{
     response::Json as ResponseJson,
     routing::{get, post},
 };
-use db::models::project::{
-    CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject,
+use db::models::{
+    project::{CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject},
+    task::Task,
 };
 use deployment::Deployment;
+use executors::{mcp_config::validate_mcp_servers, profile::ExecutorConfigs};
 use ignore::WalkBuilder;
 use services::services::{
     file_ranker::FileRanker,
@@ -21,7 +23,7 @@ use services::services::{
 use utils::{path::expand_tilde, response::ApiResponse};
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware, routes::export};
 
 pub async fn get_projects(
     State(deployment): State<DeploymentImpl>,
@@ -36,6 +38,15 @@ pub async fn get_project(
     Ok(ResponseJson(ApiResponse::success(project)))
 }
 
+/// Tasks of the project that were deleted but not purged yet
+pub async fn get_project_task_trash(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let tasks = Task::find_deleted_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(tasks)))
+}
+
 pub async fn get_project_branches(
     Extension(project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
@@ -57,6 +68,14 @@ pub async fn create_project(
         cleanup_script,
         copy_files,
         use_existing_repo,
+        require_clean_before_followup,
+        commit_setup_changes,
+        auto_assign_users,
+        verify_command,
+        keep_in_progress_on_verify_failure,
+        default_base_branch,
+        mcp_servers,
+        executor_profile,
     } = payload;
     tracing::debug!("Creating project '{}'", name);
 
@@ -134,6 +153,26 @@ pub async fn create_project(
         }
     }
 
+    let default_base_branch = default_base_branch
+        .filter(|branch| !branch.trim().is_empty())
+        .map(|branch| deployment.git().resolve_branch_name(&path, &branch))
+        .transpose()
+        .map_err(ApiError::from_branch_lookup)?;
+    if let Some(servers) = &mcp_servers
+        && let Err(e) = validate_mcp_servers(servers)
+    {
+        return Err(ApiError::BadRequest(e));
+    }
+    if let Some(profile) = &executor_profile
+        && ExecutorConfigs::get_cached()
+            .get_coding_agent(profile)
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Executor profile '{profile}' does not exist"
+        )));
+    }
+
     match Project::create(
         &deployment.db().pool,
         &CreateProject {
@@ -144,6 +183,14 @@ pub async fn create_project(
             dev_script,
             cleanup_script,
             copy_files,
+            require_clean_before_followup,
+            commit_setup_changes,
+            auto_assign_users,
+            verify_command,
+            keep_in_progress_on_verify_failure,
+            default_base_branch,
+            mcp_servers,
+            executor_profile,
         },
         id,
     )
@@ -173,7 +220,7 @@ pub async fn update_project(
     Extension(existing_project): Extension<Project>,
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<UpdateProject>,
-) -> Result<ResponseJson<ApiResponse<Project>>, StatusCode> {
+) -> Result<ResponseJson<ApiResponse<Project>>, ApiError> {
     // Destructure payload to handle field updates.
     // This allows us to treat `None` from the payload as an explicit `null` to clear a field,
     // as the frontend currently sends all fields on update.
@@ -184,6 +231,14 @@ pub async fn update_project(
         dev_script,
         cleanup_script,
         copy_files,
+        require_clean_before_followup,
+        commit_setup_changes,
+        auto_assign_users,
+        verify_command,
+        keep_in_progress_on_verify_failure,
+        default_base_branch,
+        mcp_servers,
+        executor_profile,
     } = payload;
     // If git_repo_path is being changed, check if the new path is already used by another project
     let git_repo_path = if let Some(new_git_repo_path) = git_repo_path.map(|s| expand_tilde(&s))
@@ -203,15 +258,40 @@ pub async fn update_project(
             }
             Ok(None) => new_git_repo_path,
             Err(e) => {
-                tracing::error!("Failed to check for existing git repo path: {}", e);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Err(ProjectError::GitRepoCheckFailed(e.to_string()).into());
             }
         }
     } else {
         existing_project.git_repo_path
     };
 
-    match Project::update(
+    // An empty value clears the default base branch; any other value must exist
+    let default_base_branch = match default_base_branch {
+        Some(branch) if branch.trim().is_empty() => None,
+        Some(branch) => Some(
+            deployment
+                .git()
+                .resolve_branch_name(&git_repo_path, &branch)
+                .map_err(ApiError::from_branch_lookup)?,
+        ),
+        None => existing_project.default_base_branch,
+    };
+    if let Some(servers) = &mcp_servers
+        && let Err(e) = validate_mcp_servers(servers)
+    {
+        return Err(ApiError::BadRequest(e));
+    }
+    if let Some(profile) = &executor_profile
+        && ExecutorConfigs::get_cached()
+            .get_coding_agent(profile)
+            .is_none()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Executor profile '{profile}' does not exist"
+        )));
+    }
+
+    let project = Project::update(
         &deployment.db().pool,
         existing_project.id,
         name.unwrap_or(existing_project.name),
@@ -220,15 +300,22 @@ pub async fn update_project(
         dev_script,
         cleanup_script,
         copy_files,
+        require_clean_before_followup.unwrap_or(existing_project.require_clean_before_followup),
+        commit_setup_changes.unwrap_or(existing_project.commit_setup_changes),
+        auto_assign_users.or(existing_project.auto_assign_users),
+        verify_command.or(existing_project.verify_command),
+        keep_in_progress_on_verify_failure
+            .unwrap_or(existing_project.keep_in_progress_on_verify_failure),
+        default_base_branch,
+        mcp_servers
+            .map(sqlx::types::Json)
+            .or(existing_project.mcp_servers),
+        executor_profile
+            .map(sqlx::types::Json)
+            .or(existing_project.executor_profile),
     )
-    .await
-    {
-        Ok(project) => Ok(ResponseJson(ApiResponse::success(project))),
-        Err(e) => {
-            tracing::error!("Failed to update project: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(project)))
 }
 
 pub async fn delete_project(
@@ -478,8 +565,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             get(get_project).put(update_project).delete(delete_project),
         )
         .route("/branches", get(get_project_branches))
+        .route("/tasks/trash", get(get_project_task_trash))
         .route("/search", get(search_project_files))
         .route("/open-editor", post(open_project_in_editor))
+        .route("/export", get(export::export_project))
+        .route("/import", post(export::import_project))
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
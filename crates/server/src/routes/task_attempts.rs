@@ -1,44 +1,60 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
 use axum::{
     BoxError, Extension, Json, Router,
     extract::{Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     middleware::from_fn_with_state,
     response::{
-        Json as ResponseJson, Sse,
+        IntoResponse, Json as ResponseJson, Response, Sse,
         sse::{Event, KeepAlive},
     },
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
-    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     image::TaskImage,
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
     project::{Project, ProjectError},
     task::{Task, TaskStatus},
     task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
 };
-use deployment::Deployment;
+use deployment::{Deployment, DeploymentError};
 use executors::{
     actions::{
         ExecutorAction, ExecutorActionType,
         coding_agent_follow_up::CodingAgentFollowUpRequest,
+        coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        validate_working_subdir,
     },
-    profile::ExecutorProfileId,
+    command::ExecutionEnv,
+    executors::BaseCodingAgent,
+    logs::NormalizedEntry,
+    profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures_util::TryStreamExt;
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    config::DEFAULT_MERGE_COMMIT_TEMPLATE,
     container::ContainerService,
+    filesystem::FuzzyFileMatch,
+    git::{
+        CommitInfo, GitOperation, GitService, GitServiceError, MergeOptions, MergeOutcome,
+        MergePreview, MergeStrategy, RepoHealth, WorktreeFileStatus,
+    },
     github_service::{CreatePrRequest, GitHubService, GitHubServiceError},
     image::ImageService,
 };
-use sqlx::Error as SqlxError;
+use sqlx::{Error as SqlxError, SqlitePool};
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{
+    diff::{DEFAULT_CONTEXT_LINES, Diff, DiffSummary},
+    response::ApiResponse,
+    text::render_template,
+};
 use uuid::Uuid;
 
 use crate::{DeploymentImpl, error::ApiError, middleware::load_task_attempt_middleware};
@@ -46,6 +62,9 @@ use crate::{DeploymentImpl, error::ApiError, middleware::load_task_attempt_middl
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct RebaseTaskAttemptRequest {
     pub new_base_branch: Option<String>,
+    /// Stash uncommitted changes before rebasing and restore them afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_stash: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, TS)]
@@ -66,6 +85,11 @@ pub struct RestoreAttemptResult {
     pub target_after_oid: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, TS)]
+pub struct RenameBranchRequest {
+    pub new_branch_name: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, TS)]
 pub struct CreateGitHubPrRequest {
     pub title: String,
@@ -104,16 +128,42 @@ pub async fn get_task_attempt(
 #[derive(Debug, Deserialize, ts_rs::TS)]
 pub struct CreateTaskAttemptBody {
     pub task_id: Uuid,
-    /// Executor profile specification
-    pub executor_profile_id: ExecutorProfileId,
-    pub base_branch: String,
+    /// Defaults to the project's executor profile, then the configured profile
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    /// Defaults to the project's default base branch, then the repository's default branch
+    pub base_branch: Option<String>,
+    /// Environment variables for the attempt's agent and scripts; names must match `[A-Z_][A-Z0-9_]*`
+    pub env_overrides: Option<HashMap<String, String>>,
+    /// Only log the project's setup script instead of running it and the agent
+    pub dry_run: Option<bool>,
+    /// Run the coding agent in this directory relative to the worktree, e.g. a
+    /// package of a monorepo
+    pub working_subdir: Option<PathBuf>,
 }
 
-impl CreateTaskAttemptBody {
-    /// Get the executor profile ID
-    pub fn get_executor_profile_id(&self) -> ExecutorProfileId {
-        self.executor_profile_id.clone()
+/// Load the task's project and pick the executor profile for a new attempt on
+/// it: the requested one, else the project's default, else `global_profile`.
+/// The profile must exist in `configs`.
+async fn resolve_executor_profile(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    requested: Option<ExecutorProfileId>,
+    global_profile: ExecutorProfileId,
+    configs: &ExecutorConfigs,
+) -> Result<(Project, ExecutorProfileId), ApiError> {
+    let project = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let executor_profile_id = project.executor_profile_for(requested, global_profile);
+    if configs.get_coding_agent(&executor_profile_id).is_none() {
+        return Err(ApiError::BadRequest(format!(
+            "Executor profile '{executor_profile_id}' does not exist"
+        )));
     }
+    Ok((project, executor_profile_id))
 }
 
 #[axum::debug_handler]
@@ -121,13 +171,51 @@ pub async fn create_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
-    let executor_profile_id = payload.get_executor_profile_id();
+    if let Some(name) = payload
+        .env_overrides
+        .iter()
+        .flat_map(|vars| vars.keys())
+        .find(|name| !ExecutionEnv::is_valid_name(name))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid environment variable name '{name}': expected [A-Z_][A-Z0-9_]*"
+        )));
+    }
+
+    let global_profile = deployment.config().read().await.executor_profile.clone();
+    let (project, executor_profile_id) = resolve_executor_profile(
+        &deployment.db().pool,
+        payload.task_id,
+        payload.executor_profile_id.clone(),
+        global_profile,
+        &ExecutorConfigs::get_cached(),
+    )
+    .await?;
+    if let Some(subdir) = &payload.working_subdir {
+        validate_working_subdir(subdir).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    }
+    let dry_run = payload.dry_run.unwrap_or(false);
+    if dry_run && project.setup_script.is_none() {
+        return Err(ApiError::BadRequest(
+            "Dry run needs a project setup script".to_string(),
+        ));
+    }
+    let base_branch = match project.base_branch_for(payload.base_branch.clone()) {
+        Some(branch) => deployment
+            .git()
+            .resolve_branch_name(&project.git_repo_path, &branch)
+            .map_err(ApiError::from_branch_lookup)?,
+        None => deployment
+            .git()
+            .get_default_branch_name(&project.git_repo_path)?,
+    };
 
     let task_attempt = TaskAttempt::create(
         &deployment.db().pool,
         &CreateTaskAttempt {
             executor: executor_profile_id.executor,
-            base_branch: payload.base_branch.clone(),
+            base_branch,
+            env_overrides: payload.env_overrides,
         },
         payload.task_id,
     )
@@ -135,7 +223,12 @@ pub async fn create_task_attempt(
 
     let execution_process = deployment
         .container()
-        .start_attempt(&task_attempt, executor_profile_id.clone())
+        .start_attempt(
+            &task_attempt,
+            executor_profile_id.clone(),
+            dry_run,
+            payload.working_subdir,
+        )
         .await?;
 
     deployment
@@ -168,11 +261,21 @@ pub async fn follow_up(
     Json(payload): Json<CreateFollowUpAttempt>,
 ) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
     tracing::info!("{:?}", task_attempt);
+    let execution_process = start_follow_up(&deployment, &task_attempt, payload).await?;
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
 
+/// Send `payload` to the attempt's agent, resuming its latest session, and
+/// start the resulting execution
+pub async fn start_follow_up(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+    payload: CreateFollowUpAttempt,
+) -> Result<ExecutionProcess, ApiError> {
     // Ensure worktree exists (recreate if needed for cold task support)
-    deployment
+    let container_ref = deployment
         .container()
-        .ensure_container_exists(&task_attempt)
+        .ensure_container_exists(task_attempt)
         .await?;
 
     // Get latest session id (ignoring dropped)
@@ -180,10 +283,7 @@ pub async fn follow_up(
         &deployment.db().pool,
         task_attempt.id,
     )
-    .await?
-    .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-        "Couldn't find a prior session_id, please create a new task attempt".to_string(),
-    )))?;
+    .await?;
 
     // Get ExecutionProcess for profile data
     let latest_execution_process = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
@@ -191,28 +291,34 @@ pub async fn follow_up(
         task_attempt.id,
         &ExecutionProcessRunReason::CodingAgent,
     )
-    .await?
-    .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-        "Couldn't find initial coding agent process, has it run yet?".to_string(),
-    )))?;
-    let initial_executor_profile_id = match &latest_execution_process
-        .executor_action()
-        .map_err(|e| ApiError::TaskAttempt(TaskAttemptError::ValidationError(e.to_string())))?
-        .typ
-    {
-        ExecutorActionType::CodingAgentInitialRequest(request) => {
-            Ok(request.executor_profile_id.clone())
-        }
-        ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-            Ok(request.executor_profile_id.clone())
-        }
-        _ => Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
-            "Couldn't find profile from initial request".to_string(),
-        ))),
-    }?;
+    .await?;
+    let executor = match &latest_execution_process {
+        Some(process) => match &process
+            .executor_action()
+            .map_err(|e| ApiError::TaskAttempt(TaskAttemptError::ValidationError(e.to_string())))?
+            .typ
+        {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                Ok(request.executor_profile_id.executor)
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                Ok(request.executor_profile_id.executor)
+            }
+            _ => Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                "Couldn't find profile from initial request".to_string(),
+            ))),
+        }?,
+        // Attempts adopted from an existing branch have not run an agent yet
+        None => BaseCodingAgent::from_str(&task_attempt.executor).map_err(|_| {
+            ApiError::TaskAttempt(TaskAttemptError::ValidationError(format!(
+                "Unknown executor '{}' on task attempt",
+                task_attempt.executor
+            )))
+        })?,
+    };
 
     let executor_profile_id = ExecutorProfileId {
-        executor: initial_executor_profile_id.executor,
+        executor,
         variant: payload.variant,
     };
 
@@ -228,6 +334,21 @@ pub async fn follow_up(
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
+    if project.require_clean_before_followup {
+        match deployment
+            .git()
+            .ensure_worktree_clean(std::path::Path::new(&container_ref))
+        {
+            Ok(()) => {}
+            Err(GitServiceError::WorktreeDirty(_, files)) => {
+                return Err(ApiError::Conflict(format!(
+                    "This project requires a clean worktree before follow-ups. Commit or discard the uncommitted changes first: {files}"
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
     let mut prompt = payload.prompt;
     if let Some(image_ids) = &payload.image_ids {
         TaskImage::associate_many(&deployment.db().pool, task.id, image_ids).await?;
@@ -245,37 +366,86 @@ pub async fn follow_up(
         }
     }
 
-    let cleanup_action = project.cleanup_script.map(|script| {
-        Box::new(ExecutorAction::new(
-            ExecutorActionType::ScriptRequest(ScriptRequest {
-                script,
-                language: ScriptRequestLanguage::Bash,
-                context: ScriptContext::CleanupScript,
-            }),
-            None,
-        ))
-    });
+    let cleanup_action = deployment.container().post_agent_action(&project);
 
-    let follow_up_request = CodingAgentFollowUpRequest {
-        prompt,
-        session_id,
-        executor_profile_id,
+    let action_type = match (session_id, latest_execution_process) {
+        (Some(session_id), _) => {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id,
+                executor_profile_id,
+            })
+        }
+        // No agent has run on this attempt, so start a fresh session
+        (None, None) => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id,
+        }),
+        (None, Some(_)) => {
+            return Err(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                "Couldn't find a prior session_id, please create a new task attempt".to_string(),
+            )));
+        }
     };
 
-    let follow_up_action = ExecutorAction::new(
-        ExecutorActionType::CodingAgentFollowUpRequest(follow_up_request),
-        cleanup_action,
-    );
+    // Follow-ups run where the attempt's agent has been running
+    let working_subdir = latest_execution_process
+        .as_ref()
+        .and_then(|process| process.executor_action().ok())
+        .and_then(|action| action.working_subdir.clone());
+    let follow_up_action =
+        ExecutorAction::new(action_type, cleanup_action).with_working_subdir(working_subdir);
 
     let execution_process = deployment
         .container()
         .start_execution(
-            &task_attempt,
+            task_attempt,
             &follow_up_action,
             &ExecutionProcessRunReason::CodingAgent,
         )
         .await?;
 
+    Ok(execution_process)
+}
+
+pub async fn retry_task_attempt_execution(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id).await?;
+
+    if processes.iter().any(|p| {
+        matches!(
+            p.status,
+            ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+        )
+    }) {
+        return Err(ApiError::Conflict(
+            "A process is still running for this attempt; stop it or wait for it to finish before retrying"
+                .to_string(),
+        ));
+    }
+
+    let Some(failed_process) =
+        ExecutionProcess::find_retryable_by_task_attempt_id(pool, task_attempt.id).await?
+    else {
+        return Ok(ResponseJson(ApiResponse::error(
+            "No failed process to retry for this attempt",
+        )));
+    };
+
+    // Retries run against the existing worktree, recreating it if it was cleaned up
+    deployment
+        .container()
+        .ensure_container_exists(&task_attempt)
+        .await?;
+
+    let execution_process = deployment
+        .container()
+        .retry_execution(&task_attempt, &failed_process)
+        .await?;
+
     Ok(ResponseJson(ApiResponse::success(execution_process)))
 }
 
@@ -376,20 +546,230 @@ pub async fn restore_task_attempt(
     })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Overrides the configured whitespace handling for this request
+    ignore_whitespace: Option<bool>,
+}
+
 pub async fn get_task_attempt_diff(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DiffQuery>,
     // ) -> Result<ResponseJson<ApiResponse<Diff>>, ApiError> {
 ) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
-    let stream = deployment.container().get_diff(&task_attempt).await?;
+    let stream = deployment
+        .container()
+        .get_diff(&task_attempt, query.ignore_whitespace)
+        .await?;
 
     Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
 }
 
-#[derive(Debug, Serialize, TS)]
-pub struct CommitInfo {
-    pub sha: String,
-    pub subject: String,
+/// One-shot diff that sends each file as soon as it is computed, so large
+/// worktrees start rendering before the whole diff is ready.
+pub async fn stream_task_attempt_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, BoxError>>>, ApiError> {
+    let stream = deployment
+        .container()
+        .stream_diff(&task_attempt, query.ignore_whitespace)
+        .await?;
+
+    Ok(Sse::new(stream.map_err(|e| -> BoxError { e.into() })).keep_alive(KeepAlive::default()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileDiffQuery {
+    path: String,
+}
+
+/// Diff a single file of the attempt; `null` data means the path is unchanged.
+pub async fn get_task_attempt_file_diff(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Query(query): Query<FileDiffQuery>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<Diff>>>, ApiError> {
+    if query.path.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("path is required")));
+    }
+
+    let diff = deployment
+        .container()
+        .get_file_diff(&task_attempt, &query.path)
+        .await?;
+
+    Ok(ResponseJson(ApiResponse::success(diff)))
+}
+
+/// Fuzzy file search results are capped at this many paths
+const FILE_SEARCH_LIMIT: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct FileSearchQuery {
+    q: String,
+}
+
+/// Files in the attempt's worktree fuzzy-matching `q`, for referencing them in prompts
+pub async fn search_task_attempt_files(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Query(query): Query<FileSearchQuery>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<FuzzyFileMatch>>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Ok(ResponseJson(ApiResponse::error("q is required")));
+    }
+
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&task_attempt)
+        .await?;
+    let matches = deployment
+        .filesystem()
+        .fuzzy_find_files(
+            std::path::Path::new(&container_ref),
+            &query.q,
+            FILE_SEARCH_LIMIT,
+        )
+        .await
+        .map_err(DeploymentError::from)?;
+
+    Ok(ResponseJson(ApiResponse::success(matches)))
+}
+
+pub async fn get_task_attempt_diff_summary(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<DiffSummary>>, ApiError> {
+    let summary = deployment
+        .container()
+        .get_diff_summary(&task_attempt)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorktreeStatusQuery {
+    /// Also list untracked files; only tracked changes by default
+    #[serde(default)]
+    include_untracked: bool,
+}
+
+/// Uncommitted changes in the attempt's worktree, grouped like `git status`
+pub async fn get_task_attempt_worktree_status(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<WorktreeStatusQuery>,
+) -> Result<ResponseJson<ApiResponse<WorktreeFileStatus>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&task_attempt)
+        .await?;
+    let status = deployment.git().worktree_status(
+        std::path::Path::new(&container_ref),
+        query.include_untracked,
+    )?;
+    Ok(ResponseJson(ApiResponse::success(status)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchQuery {
+    /// Unchanged lines around each change, 3 when omitted
+    context_lines: Option<usize>,
+}
+
+/// Committed changes of the attempt branch vs its base as a `text/x-patch` file
+pub async fn get_task_attempt_patch(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<PatchQuery>,
+) -> Result<Response, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
+        ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "No branch found for task attempt".to_string(),
+        ))
+    })?;
+
+    let patch = deployment.git().format_patch(
+        &project.git_repo_path,
+        branch_name,
+        &task_attempt.base_branch,
+        query.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+    )?;
+    let disposition = format!(
+        "attachment; filename=\"{}.patch\"",
+        branch_name.replace(['/', '"'], "-")
+    );
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/x-patch; charset=utf-8".to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        patch,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConversationRangeQuery {
+    from: Option<usize>,
+    /// Exclusive end index
+    to: Option<usize>,
+}
+
+/// Header carrying the total number of conversation entries of the attempt
+const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Normalized conversation entries of the attempt's coding agent runs in the
+/// `[from, to)` index range, clamped to the available entries.
+pub async fn get_task_attempt_conversation(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Query(query): Query<ConversationRangeQuery>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<
+    (
+        [(&'static str, String); 1],
+        ResponseJson<ApiResponse<Vec<NormalizedEntry>>>,
+    ),
+    ApiError,
+> {
+    let processes =
+        ExecutionProcess::find_by_task_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+
+    let mut entries = Vec::new();
+    for process in processes
+        .iter()
+        .filter(|p| !p.dropped && matches!(p.run_reason, ExecutionProcessRunReason::CodingAgent))
+    {
+        if let Some(process_entries) = deployment.container().normalized_entries(&process.id).await
+        {
+            entries.extend(process_entries);
+        }
+    }
+
+    let total = entries.len();
+    let to = query.to.unwrap_or(total).min(total);
+    let from = query.from.unwrap_or(0).min(to);
+    let window: Vec<NormalizedEntry> = entries.drain(from..to).collect();
+
+    Ok((
+        [(TOTAL_COUNT_HEADER, total.to_string())],
+        ResponseJson(ApiResponse::success(window)),
+    ))
 }
 
 pub async fn get_commit_info(
@@ -407,11 +787,8 @@ pub async fn get_commit_info(
         .ensure_container_exists(&task_attempt)
         .await?;
     let wt = std::path::Path::new(&container_ref);
-    let subject = deployment.git().get_commit_subject(wt, &sha)?;
-    Ok(ResponseJson(ApiResponse::success(CommitInfo {
-        sha,
-        subject,
-    })))
+    let info = deployment.git().get_commit_info(wt, &sha)?;
+    Ok(ResponseJson(ApiResponse::success(info)))
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -453,11 +830,82 @@ pub async fn compare_commit_to_head(
     })))
 }
 
+/// Render the configured merge commit template, falling back to the default
+/// when it renders to nothing
+fn merge_commit_message(
+    template: &str,
+    task: &Task,
+    task_attempt: &TaskAttempt,
+    branch_name: &str,
+) -> String {
+    let task_id = task.id.to_string();
+    let task_id_short = task_id.split('-').next().unwrap_or(&task_id).to_string();
+    let attempt_id = task_attempt.id.to_string();
+    let values = [
+        ("task_title", Some(task.title.as_str())),
+        (
+            "task_description",
+            task.description.as_deref().map(str::trim),
+        ),
+        ("task_id", Some(task_id.as_str())),
+        ("task_id_short", Some(task_id_short.as_str())),
+        ("attempt_id", Some(attempt_id.as_str())),
+        ("branch", Some(branch_name)),
+    ];
+
+    let message = render_template(template, &values).trim().to_string();
+    if message.is_empty() {
+        render_template(DEFAULT_MERGE_COMMIT_TEMPLATE, &values)
+            .trim()
+            .to_string()
+    } else {
+        message
+    }
+}
+
+/// Whether merging the attempt branch into its base would conflict, without
+/// merging
+pub async fn preview_task_attempt_merge(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<MergePreview>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
+        ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "No branch found for task attempt".to_string(),
+        ))
+    })?;
+
+    let preview = deployment.git().preview_merge(
+        &project.git_repo_path,
+        branch_name,
+        &task_attempt.base_branch,
+    )?;
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeTaskAttemptQuery {
+    /// Annotated tag to create on the merge commit
+    tag_name: Option<String>,
+    /// Defaults to a squash merge
+    strategy: Option<MergeStrategy>,
+}
+
 #[axum::debug_handler]
 pub async fn merge_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
+    Query(query): Query<MergeTaskAttemptQuery>,
     State(deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<MergeOutcome>>, ApiError> {
     let pool = &deployment.db().pool;
 
     let task = task_attempt
@@ -472,20 +920,6 @@ pub async fn merge_task_attempt(
         .await?;
     let worktree_path = std::path::Path::new(&container_ref);
 
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
-
-    // Create commit message with task title and description
-    let mut commit_message = format!("{} (vibe-kanban {})", ctx.task.title, first_uuid_section);
-
-    // Add description on next line if it exists
-    if let Some(description) = &ctx.task.description
-        && !description.trim().is_empty()
-    {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
-    }
-
     // Get branch name from task attempt
     let branch_name = ctx.task_attempt.branch.as_ref().ok_or_else(|| {
         ApiError::TaskAttempt(TaskAttemptError::ValidationError(
@@ -493,19 +927,37 @@ pub async fn merge_task_attempt(
         ))
     })?;
 
-    let merge_commit_id = deployment.git().merge_changes(
+    let template = deployment
+        .config()
+        .read()
+        .await
+        .merge_commit_template
+        .clone();
+    let commit_message = merge_commit_message(&template, &ctx.task, &task_attempt, branch_name);
+
+    // Attribute the squash commit to the signed-in GitHub user when known
+    let author = {
+        let github = &deployment.config().read().await.github;
+        github.username.clone().zip(github.primary_email.clone())
+    };
+    let outcome = deployment.git().merge_changes(
         &ctx.project.git_repo_path,
         worktree_path,
         branch_name,
         &ctx.task_attempt.base_branch,
         &commit_message,
+        &MergeOptions {
+            author,
+            strategy: query.strategy.unwrap_or_default(),
+            tag_name: query.tag_name.filter(|tag| !tag.trim().is_empty()),
+        },
     )?;
 
     Merge::create_direct(
         pool,
         task_attempt.id,
         &ctx.task_attempt.base_branch,
-        &merge_commit_id,
+        &outcome.sha,
     )
     .await?;
     Task::update_status(pool, ctx.task.id, TaskStatus::Done).await?;
@@ -521,7 +973,7 @@ pub async fn merge_task_attempt(
         )
         .await;
 
-    Ok(ResponseJson(ApiResponse::success(())))
+    Ok(ResponseJson(ApiResponse::success(outcome)))
 }
 
 pub async fn push_task_attempt_branch(
@@ -533,7 +985,7 @@ pub async fn push_task_attempt_branch(
         return Err(GitHubServiceError::TokenInvalid.into());
     };
 
-    let github_service = GitHubService::new(&github_token)?;
+    let github_service = GitHubService::new(&github_token, &github_config.extra_headers)?;
     github_service.check_token().await?;
 
     let branch_name = task_attempt.branch.as_ref().ok_or_else(|| {
@@ -566,7 +1018,7 @@ pub async fn create_github_pr(
         )));
     };
     // Create GitHub service instance
-    let github_service = GitHubService::new(&github_token)?;
+    let github_service = GitHubService::new(&github_token, &github_config.extra_headers)?;
     if let Err(e) = github_service.check_token().await {
         if e.is_api_data() {
             return Ok(ResponseJson(ApiResponse::error_with_data(e)));
@@ -725,6 +1177,14 @@ pub async fn open_task_attempt_in_editor(
             "No container ref found".to_string(),
         ))
     })?;
+    // Opening a deleted worktree would launch the editor on an empty directory
+    if deployment
+        .git()
+        .repo_health(std::path::Path::new(base_path))
+        == RepoHealth::Missing
+    {
+        return Err(GitServiceError::RepoMissing(base_path.clone()).into());
+    }
 
     // If a specific file path is provided, use it; otherwise use the base path
     let path = if let Some(file_path) = payload.as_ref().and_then(|req| req.file_path.as_ref()) {
@@ -761,6 +1221,21 @@ pub async fn open_task_attempt_in_editor(
     }
 }
 
+/// Rebuild the attempt's worktree on its branch after it was deleted or damaged
+pub async fn recreate_task_attempt_worktree(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<RepoHealth>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .recreate_worktree(task_attempt.id)
+        .await?;
+    let health = deployment
+        .git()
+        .repo_health(std::path::Path::new(&container_ref));
+    Ok(ResponseJson(ApiResponse::success(health)))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct BranchStatus {
     pub commits_behind: Option<usize>,
@@ -775,6 +1250,66 @@ pub struct BranchStatus {
     pub merges: Vec<Merge>,
 }
 
+#[derive(Debug, Serialize, TS)]
+pub struct BaseBranchStatus {
+    pub base_branch_name: String,
+    /// Current tip of the base branch
+    pub base_oid: String,
+    pub base_commit_date: DateTime<Utc>,
+    /// Base commit the attempt branch forked from, if the branch exists yet
+    pub fork_point_oid: Option<String>,
+    /// Commits the base branch gained since the fork point
+    pub base_commits_since_fork: Option<usize>,
+}
+
+pub async fn get_task_attempt_base_status(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<BaseBranchStatus>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    let repo_path = &ctx.project.git_repo_path;
+    let base_branch = &task_attempt.base_branch;
+
+    let base_oid = match deployment.git().get_branch_oid(repo_path, base_branch) {
+        Ok(oid) => oid,
+        Err(GitServiceError::BranchNotFound(_)) => {
+            return Ok(ResponseJson(ApiResponse::error(&format!(
+                "Base branch '{base_branch}' no longer exists in the repository"
+            ))));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let base_commit_date = deployment.git().get_commit_date(repo_path, &base_oid)?;
+
+    let (fork_point_oid, base_commits_since_fork) = match &task_attempt.branch {
+        Some(task_branch) => {
+            let fork_point = deployment
+                .git()
+                .merge_base(repo_path, task_branch, base_branch)?;
+            let (_, behind) =
+                deployment
+                    .git()
+                    .get_branch_status(repo_path, task_branch, base_branch)?;
+            (fork_point, Some(behind))
+        }
+        None => (None, None),
+    };
+
+    Ok(ResponseJson(ApiResponse::success(BaseBranchStatus {
+        base_branch_name: base_branch.clone(),
+        base_oid,
+        base_commit_date,
+        fork_point_oid,
+        base_commits_since_fork,
+    })))
+}
+
 pub async fn get_task_attempt_branch_status(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
@@ -886,14 +1421,147 @@ pub async fn get_task_attempt_branch_status(
     Ok(ResponseJson(ApiResponse::success(branch_status)))
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BranchGraph {
+    pub base_branch_name: String,
+    /// None when the attempt branch and base branch share no history
+    pub merge_base: Option<String>,
+    pub commits_ahead: usize,
+    pub commits_behind: usize,
+    /// Commits on the attempt branch that are not on the base branch, newest first
+    pub unique_commits: Vec<String>,
+}
+
+pub async fn get_task_attempt_graph(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<BranchGraph>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    let task_branch =
+        task_attempt
+            .branch
+            .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                "No branch found for task attempt".to_string(),
+            )))?;
+    let repo_path = &ctx.project.git_repo_path;
+    let base_branch = &task_attempt.base_branch;
+
+    let merge_base = deployment
+        .git()
+        .merge_base(repo_path, &task_branch, base_branch)?;
+    let (commits_ahead, commits_behind) =
+        deployment
+            .git()
+            .get_branch_status(repo_path, &task_branch, base_branch)?;
+    let unique_commits = deployment
+        .git()
+        .unique_commits(repo_path, &task_branch, base_branch)?;
+
+    Ok(ResponseJson(ApiResponse::success(BranchGraph {
+        base_branch_name: base_branch.clone(),
+        merge_base,
+        commits_ahead,
+        commits_behind,
+        unique_commits,
+    })))
+}
+
+/// Commits listed by default when no `limit` is given
+const DEFAULT_COMMITS_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct CommitsQuery {
+    limit: Option<usize>,
+}
+
+/// Commits the attempt branch has on top of its base branch, newest first
+pub async fn get_task_attempt_commits(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    Query(query): Query<CommitsQuery>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<CommitInfo>>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    let task_branch =
+        task_attempt
+            .branch
+            .ok_or(ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+                "No branch found for task attempt".to_string(),
+            )))?;
+
+    let commits = deployment.git().list_commits(
+        &ctx.project.git_repo_path,
+        &task_branch,
+        &task_attempt.base_branch,
+        query.limit.unwrap_or(DEFAULT_COMMITS_LIMIT),
+    )?;
+    Ok(ResponseJson(ApiResponse::success(commits)))
+}
+
+/// Where and how [`rebase_attempt`] rebases an attempt
+struct RebaseParams<'a> {
+    repo_path: &'a std::path::Path,
+    worktree_path: &'a std::path::Path,
+    /// The recorded base again when `None`
+    new_base_branch: Option<String>,
+    github_token: Option<String>,
+    /// Stash message for uncommitted changes; a dirty worktree is refused without it
+    auto_stash: Option<&'a str>,
+}
+
+/// Rebase the attempt's worktree from its recorded base onto the new base and
+/// record the new base on the attempt. Returns the new HEAD commit.
+async fn rebase_attempt(
+    pool: &SqlitePool,
+    git: &GitService,
+    task_attempt: &TaskAttempt,
+    params: RebaseParams<'_>,
+) -> Result<String, ApiError> {
+    let new_base_branch = params
+        .new_base_branch
+        .unwrap_or_else(|| task_attempt.base_branch.clone());
+    let result = git.rebase_branch(
+        params.repo_path,
+        params.worktree_path,
+        Some(&new_base_branch),
+        &task_attempt.base_branch,
+        params.github_token,
+        params.auto_stash,
+    );
+
+    // A stash that could not be restored still leaves the branch rebased
+    let rebased = matches!(result, Ok(_) | Err(GitServiceError::StashPopFailed(..)));
+    if rebased && new_base_branch != task_attempt.base_branch {
+        TaskAttempt::update_base_branch(pool, task_attempt.id, &new_base_branch).await?;
+    }
+    Ok(result?)
+}
+
 #[axum::debug_handler]
 pub async fn rebase_task_attempt(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
     request_body: Option<Json<RebaseTaskAttemptRequest>>,
-) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<String>>, ApiError> {
     // Extract new base branch from request body if provided
-    let new_base_branch = request_body.and_then(|body| body.new_base_branch.clone());
+    let new_base_branch = request_body
+        .as_ref()
+        .and_then(|body| body.new_base_branch.clone());
+    let auto_stash = request_body
+        .as_ref()
+        .and_then(|body| body.auto_stash)
+        .unwrap_or(false);
 
     let github_config = deployment.config().read().await.github.clone();
 
@@ -905,32 +1573,103 @@ pub async fn rebase_task_attempt(
         .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
     let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
 
-    // Use the stored base branch if no new base branch is provided
-    let effective_base_branch =
-        new_base_branch.or_else(|| Some(ctx.task_attempt.base_branch.clone()));
-
     let container_ref = deployment
         .container()
         .ensure_container_exists(&task_attempt)
         .await?;
     let worktree_path = std::path::Path::new(&container_ref);
 
-    let _new_base_commit = deployment.git().rebase_branch(
-        &ctx.project.git_repo_path,
-        worktree_path,
-        effective_base_branch.clone().as_deref(),
-        &ctx.task_attempt.base_branch.clone(),
-        github_config.token(),
-    )?;
+    let stash_message = format!(
+        "vibe-kanban: auto-stash before rebase of task attempt {}",
+        task_attempt.id
+    );
+    let new_base_branch = new_base_branch
+        .map(|branch| {
+            deployment
+                .git()
+                .resolve_branch_name(&ctx.project.git_repo_path, &branch)
+        })
+        .transpose()
+        .map_err(ApiError::from_branch_lookup)?;
+    let head = rebase_attempt(
+        pool,
+        deployment.git(),
+        &ctx.task_attempt,
+        RebaseParams {
+            repo_path: &ctx.project.git_repo_path,
+            worktree_path,
+            new_base_branch,
+            github_token: github_config.token(),
+            auto_stash: auto_stash.then_some(stash_message.as_str()),
+        },
+    )
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(head)))
+}
+
+/// Abort a rebase or merge left stopped in the attempt's worktree, e.g. by a
+/// server crash, and report which one was aborted
+pub async fn abort_task_attempt_git_operation(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<GitOperation>>, ApiError> {
+    let container_ref = deployment
+        .container()
+        .ensure_container_exists(&task_attempt)
+        .await?;
+    let worktree_path = std::path::Path::new(&container_ref);
 
-    if let Some(new_base_branch) = &effective_base_branch
-        && new_base_branch != &ctx.task_attempt.base_branch
+    match deployment
+        .git()
+        .abort_in_progress_operation(worktree_path)?
     {
-        TaskAttempt::update_base_branch(&deployment.db().pool, task_attempt.id, new_base_branch)
-            .await?;
+        Some(operation) => {
+            tracing::info!(
+                "Aborted {:?} in progress for task attempt {}",
+                operation,
+                task_attempt.id
+            );
+            Ok(ResponseJson(ApiResponse::success(operation)))
+        }
+        None => Err(ApiError::Conflict(
+            "No merge or rebase is in progress in this attempt's worktree".to_string(),
+        )),
     }
+}
 
-    Ok(ResponseJson(ApiResponse::success(())))
+/// Rename the attempt's branch and record the new name on the attempt
+pub async fn rename_task_attempt_branch(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(request): Json<RenameBranchRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let old_branch = task_attempt.branch.as_ref().ok_or_else(|| {
+        ApiError::TaskAttempt(TaskAttemptError::ValidationError(
+            "No branch found for task attempt".to_string(),
+        ))
+    })?;
+    let new_branch = request.new_branch_name.trim();
+    if new_branch == old_branch {
+        return Ok(ResponseJson(ApiResponse::success(task_attempt)));
+    }
+
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+
+    deployment
+        .git()
+        .rename_branch(&ctx.project.git_repo_path, old_branch, new_branch)?;
+    TaskAttempt::update_branch(pool, task_attempt.id, new_branch).await?;
+
+    let task_attempt = TaskAttempt::find_by_id(pool, task_attempt.id)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(task_attempt)))
 }
 
 #[derive(serde::Deserialize)]
@@ -1021,6 +1760,7 @@ pub async fn start_dev_server(
                 script: dev_server,
                 language: ScriptRequestLanguage::Bash,
                 context: ScriptContext::DevServer,
+                dry_run: false,
             }),
             None,
         );
@@ -1071,17 +1811,32 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_task_attempt))
         .route("/follow-up", post(follow_up))
+        .route("/retry", post(retry_task_attempt_execution))
         .route("/restore", post(restore_task_attempt))
         .route("/commit-info", get(get_commit_info))
         .route("/commit-compare", get(compare_commit_to_head))
         .route("/start-dev-server", post(start_dev_server))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/base-status", get(get_task_attempt_base_status))
+        .route("/graph", get(get_task_attempt_graph))
+        .route("/commits", get(get_task_attempt_commits))
         .route("/diff", get(get_task_attempt_diff))
+        .route("/diff/stream", get(stream_task_attempt_diff))
+        .route("/diff/file", get(get_task_attempt_file_diff))
+        .route("/diff/summary", get(get_task_attempt_diff_summary))
+        .route("/patch", get(get_task_attempt_patch))
+        .route("/status", get(get_task_attempt_worktree_status))
+        .route("/files", get(search_task_attempt_files))
+        .route("/conversation", get(get_task_attempt_conversation))
+        .route("/merge-preview", get(preview_task_attempt_merge))
         .route("/merge", post(merge_task_attempt))
         .route("/push", post(push_task_attempt_branch))
         .route("/rebase", post(rebase_task_attempt))
+        .route("/abort-git-op", post(abort_task_attempt_git_operation))
+        .route("/rename-branch", post(rename_task_attempt_branch))
         .route("/pr", post(create_github_pr))
         .route("/open-editor", post(open_task_attempt_in_editor))
+        .route("/recreate-worktree", post(recreate_task_attempt_worktree))
         .route("/delete-file", post(delete_task_attempt_file))
         .route("/children", get(get_task_attempt_children))
         .route("/stop", post(stop_task_attempt_execution))
@@ -1096,3 +1851,217 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     Router::new().nest("/task-attempts", task_attempts_router)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_support::{create_attempt, create_project, create_task, setup_pool};
+
+    /// Repo with `main`, a `develop` branch one commit ahead of it, and a
+    /// worktree on `feature` branched from `main` with a commit of its own
+    fn setup_repo(td: &TempDir) -> (PathBuf, PathBuf) {
+        let git = GitService::new();
+        let repo_path = td.path().join("repo");
+        git.initialize_repo_with_main_branch(&repo_path).unwrap();
+        git.configure_user(&repo_path, "Test User", "test@example.com")
+            .unwrap();
+        git.checkout_branch(&repo_path, "main").unwrap();
+        fs::write(repo_path.join("base.txt"), "base\n").unwrap();
+        git.commit(&repo_path, "base").unwrap();
+
+        git.create_branch(&repo_path, "develop").unwrap();
+        git.checkout_branch(&repo_path, "develop").unwrap();
+        fs::write(repo_path.join("develop.txt"), "develop\n").unwrap();
+        git.commit(&repo_path, "develop change").unwrap();
+        git.checkout_branch(&repo_path, "main").unwrap();
+
+        let worktree_path = td.path().join("wt_feature");
+        git.create_branch(&repo_path, "feature").unwrap();
+        git.add_worktree(&repo_path, &worktree_path, "feature", false)
+            .unwrap();
+        fs::write(worktree_path.join("feature.txt"), "feature\n").unwrap();
+        git.commit(&worktree_path, "feature change").unwrap();
+        (repo_path, worktree_path)
+    }
+
+    fn onto_develop<'a>(
+        repo_path: &'a std::path::Path,
+        worktree_path: &'a std::path::Path,
+    ) -> RebaseParams<'a> {
+        RebaseParams {
+            repo_path,
+            worktree_path,
+            new_base_branch: Some("develop".to_string()),
+            github_token: None,
+            auto_stash: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn rebase_onto_new_base_records_it_on_the_attempt() {
+        let td = TempDir::new().unwrap();
+        let (repo_path, worktree_path) = setup_repo(&td);
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, &repo_path).await;
+        let git = GitService::new();
+
+        let head = rebase_attempt(
+            &pool,
+            &git,
+            &attempt,
+            onto_develop(&repo_path, &worktree_path),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(git.get_head_info(&worktree_path).unwrap().oid, head);
+        assert!(worktree_path.join("develop.txt").exists());
+        assert!(worktree_path.join("feature.txt").exists());
+        let stored = TaskAttempt::find_by_id(&pool, attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.base_branch, "develop");
+    }
+
+    #[tokio::test]
+    async fn rebase_of_dirty_worktree_keeps_the_recorded_base() {
+        let td = TempDir::new().unwrap();
+        let (repo_path, worktree_path) = setup_repo(&td);
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, &repo_path).await;
+        let git = GitService::new();
+        let before = git.get_head_info(&worktree_path).unwrap().oid;
+        fs::write(worktree_path.join("feature.txt"), "uncommitted\n").unwrap();
+
+        let result = rebase_attempt(
+            &pool,
+            &git,
+            &attempt,
+            onto_develop(&repo_path, &worktree_path),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ApiError::GitService(GitServiceError::WorktreeDirty(..)))
+        ));
+        assert_eq!(git.get_head_info(&worktree_path).unwrap().oid, before);
+        let stored = TaskAttempt::find_by_id(&pool, attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.base_branch, "main");
+    }
+
+    #[tokio::test]
+    async fn rebase_conflicts_list_the_conflicted_files() {
+        let td = TempDir::new().unwrap();
+        let (repo_path, worktree_path) = setup_repo(&td);
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, &repo_path).await;
+        let git = GitService::new();
+        // `develop` adds the same file with other content
+        fs::write(worktree_path.join("develop.txt"), "feature\n").unwrap();
+        git.commit(&worktree_path, "clashing change").unwrap();
+
+        let result = rebase_attempt(
+            &pool,
+            &git,
+            &attempt,
+            onto_develop(&repo_path, &worktree_path),
+        )
+        .await;
+
+        let files = match result {
+            Err(ApiError::GitService(GitServiceError::MergeConflicts(_, files))) => files,
+            other => panic!("expected merge conflicts, got {other:?}"),
+        };
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["develop.txt"]);
+        let stored = TaskAttempt::find_by_id(&pool, attempt.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.base_branch, "main");
+    }
+
+    /// A task in a new project whose default executor profile is `project_default`
+    async fn task_with_project_default(
+        pool: &SqlitePool,
+        project_default: Option<ExecutorProfileId>,
+    ) -> Task {
+        let repo_path = PathBuf::from(format!("/tmp/test-project-{}", Uuid::new_v4()));
+        let project = create_project(pool, &repo_path).await;
+        sqlx::query("UPDATE projects SET executor_profile = $1 WHERE id = $2")
+            .bind(project_default.map(|profile| serde_json::to_string(&profile).unwrap()))
+            .bind(project.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        create_task(pool, project.id, "task").await
+    }
+
+    async fn resolve(
+        pool: &SqlitePool,
+        task: &Task,
+        requested: Option<ExecutorProfileId>,
+    ) -> Result<ExecutorProfileId, ApiError> {
+        let global_profile = ExecutorProfileId::new(BaseCodingAgent::ClaudeCode);
+        let configs = ExecutorConfigs::from_defaults();
+        resolve_executor_profile(pool, task.id, requested, global_profile, &configs)
+            .await
+            .map(|(_, profile)| profile)
+    }
+
+    fn gemini_flash() -> ExecutorProfileId {
+        ExecutorProfileId {
+            executor: BaseCodingAgent::Gemini,
+            variant: Some("FLASH".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn requested_profile_overrides_project_default() {
+        let pool = setup_pool().await;
+        let task = task_with_project_default(&pool, Some(gemini_flash())).await;
+        let requested = ExecutorProfileId::new(BaseCodingAgent::Codex);
+
+        let profile = resolve(&pool, &task, Some(requested.clone()))
+            .await
+            .unwrap();
+        assert_eq!(profile, requested);
+    }
+
+    #[tokio::test]
+    async fn project_default_overrides_global_config() {
+        let pool = setup_pool().await;
+        let task = task_with_project_default(&pool, Some(gemini_flash())).await;
+        assert_eq!(resolve(&pool, &task, None).await.unwrap(), gemini_flash());
+
+        let task = task_with_project_default(&pool, None).await;
+        assert_eq!(
+            resolve(&pool, &task, None).await.unwrap(),
+            ExecutorProfileId::new(BaseCodingAgent::ClaudeCode)
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_profile_is_rejected() {
+        let pool = setup_pool().await;
+        let unknown = ExecutorProfileId {
+            executor: BaseCodingAgent::Codex,
+            variant: Some("MISSING".to_string()),
+        };
+        let task = task_with_project_default(&pool, Some(unknown.clone())).await;
+
+        let result = resolve(&pool, &task, None).await;
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+        let result = resolve(&pool, &task, Some(unknown)).await;
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    }
+}
@@ -2,29 +2,42 @@ use std::path::PathBuf;
 
 use axum::{
     BoxError, Extension, Json, Router,
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     middleware::from_fn_with_state,
     response::{Json as ResponseJson, Sse, sse::KeepAlive},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use db::models::{
+    execution_process::ExecutionProcess,
     image::TaskImage,
     project::Project,
-    task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
+    task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     task_attempt::{CreateTaskAttempt, TaskAttempt},
 };
 use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
 use futures_util::TryStreamExt;
-use serde::Deserialize;
-use services::services::container::{
-    ContainerService, WorktreeCleanupData, cleanup_worktrees_direct,
+use git2::BranchType;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    container::{ContainerService, WorktreeCleanupData, cleanup_worktrees_direct},
+    git::DiffTarget,
+};
+use sqlx::{Error as SqlxError, SqlitePool};
+use ts_rs::TS;
+use utils::{
+    diff::{Diff, DiffSummary},
+    response::ApiResponse,
 };
-use sqlx::Error as SqlxError;
-use utils::response::ApiResponse;
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_task_middleware};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::load_task_middleware,
+    routes::task_attempts::{CreateFollowUpAttempt, start_follow_up},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct TaskQuery {
@@ -121,27 +134,33 @@ pub async fn create_task_and_start(
         )
         .await;
 
-    // use the default executor profile and the current branch for the task attempt
-    let executor_profile_id = deployment.config().read().await.executor_profile.clone();
+    // use the project's (else the configured) executor profile and the current branch
+    // for the task attempt
     let project = Project::find_by_id(&deployment.db().pool, payload.project_id)
         .await?
         .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
-    let branch = deployment
-        .git()
-        .get_current_branch(&project.git_repo_path)?;
+    let global_profile = deployment.config().read().await.executor_profile.clone();
+    let executor_profile_id = project.executor_profile_for(None, global_profile);
+    let branch = match project.base_branch_for(None) {
+        Some(branch) => branch,
+        None => deployment
+            .git()
+            .get_current_branch(&project.git_repo_path)?,
+    };
 
     let task_attempt = TaskAttempt::create(
         &deployment.db().pool,
         &CreateTaskAttempt {
             executor: executor_profile_id.executor,
             base_branch: branch,
+            env_overrides: None,
         },
         task.id,
     )
     .await?;
     let execution_process = deployment
         .container()
-        .start_attempt(&task_attempt, executor_profile_id.clone())
+        .start_attempt(&task_attempt, executor_profile_id.clone(), false, None)
         .await?;
     deployment
         .track_if_analytics_allowed(
@@ -167,15 +186,106 @@ pub async fn create_task_and_start(
         project_id: task.project_id,
         status: task.status,
         parent_task_attempt: task.parent_task_attempt,
+        estimate_minutes: task.estimate_minutes,
+        assignee: task.assignee,
+        is_pinned: task.is_pinned,
+        pin_order: task.pin_order,
+        actual_minutes: 0,
         created_at: task.created_at,
         updated_at: task.updated_at,
         has_in_progress_attempt: true,
         has_merged_attempt: false,
         last_attempt_failed: false,
         executor: task_attempt.executor,
+        dependency_ids: Vec::new(),
     })))
 }
 
+#[derive(Debug, Deserialize, TS)]
+pub struct AdoptBranchRequest {
+    /// Existing local branch to check out in the attempt worktree
+    pub branch: String,
+    /// Branch to compare and merge against; defaults to the project's default base
+    /// branch, then the repo's current branch
+    pub base_branch: Option<String>,
+    /// Executor used for follow-ups; defaults to the project's executor profile,
+    /// then the configured profile
+    pub executor_profile_id: Option<ExecutorProfileId>,
+}
+
+/// Record a task attempt for a branch created outside vibe-kanban, without
+/// running an agent
+pub async fn adopt_branch(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<AdoptBranchRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let project = task
+        .parent_project(&deployment.db().pool)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+
+    match deployment
+        .git()
+        .find_branch_type(&project.git_repo_path, &payload.branch)?
+    {
+        BranchType::Local => {}
+        BranchType::Remote => {
+            return Ok(ResponseJson(ApiResponse::error(
+                "Only local branches can be adopted; check out the remote branch locally first",
+            )));
+        }
+    }
+
+    // A branch can only be checked out in one worktree at a time
+    let current_branch = deployment
+        .git()
+        .get_current_branch(&project.git_repo_path)?;
+    if current_branch == payload.branch {
+        return Ok(ResponseJson(ApiResponse::error(&format!(
+            "Branch '{}' is checked out in the project repository; switch to another branch first",
+            payload.branch
+        ))));
+    }
+
+    let base_branch = project
+        .base_branch_for(payload.base_branch)
+        .unwrap_or(current_branch);
+    let global_profile = deployment.config().read().await.executor_profile.clone();
+    let executor_profile_id =
+        project.executor_profile_for(payload.executor_profile_id, global_profile);
+
+    let task_attempt = TaskAttempt::create(
+        &deployment.db().pool,
+        &CreateTaskAttempt {
+            executor: executor_profile_id.executor,
+            base_branch,
+            env_overrides: None,
+        },
+        task.id,
+    )
+    .await?;
+    deployment
+        .container()
+        .adopt_branch(&task_attempt, &payload.branch)
+        .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_branch_adopted",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    let task_attempt = TaskAttempt::find_by_id(&deployment.db().pool, task_attempt.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(task_attempt)))
+}
+
 pub async fn update_task(
     Extension(existing_task): Extension<Task>,
     State(deployment): State<DeploymentImpl>,
@@ -188,6 +298,16 @@ pub async fn update_task(
     let parent_task_attempt = payload
         .parent_task_attempt
         .or(existing_task.parent_task_attempt);
+    let estimate_minutes = payload.estimate_minutes.or(existing_task.estimate_minutes);
+    let assignee = match payload.assignee {
+        Some(assignee) => Some(assignee.trim().to_string()).filter(|a| !a.is_empty()),
+        None => existing_task.assignee,
+    };
+    if estimate_minutes.is_some_and(|minutes| minutes < 0) {
+        return Ok(ResponseJson(ApiResponse::error(
+            "Estimate must not be negative",
+        )));
+    }
 
     let task = Task::update(
         &deployment.db().pool,
@@ -197,6 +317,8 @@ pub async fn update_task(
         description,
         status,
         parent_task_attempt,
+        estimate_minutes,
+        assignee,
     )
     .await?;
 
@@ -249,7 +371,7 @@ pub async fn delete_task(
         })
         .collect();
 
-    // Delete task from database (FK CASCADE will handle task_attempts)
+    // Move the task to the trash; attempts stay until it is purged
     let rows_affected = Task::delete(&deployment.db().pool, task.id).await?;
 
     if rows_affected == 0 {
@@ -283,17 +405,322 @@ pub async fn delete_task(
     Ok((StatusCode::ACCEPTED, ResponseJson(ApiResponse::success(()))))
 }
 
+pub async fn restore_task(
+    Path(task_id): Path<Uuid>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    if Task::restore(pool, task_id).await? == 0 {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+
+    // Worktrees removed on deletion are recreated when the attempts are used again
+    let task = Task::find_by_id(pool, task_id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+/// Pin the task above the unpinned ones, or unpin it if it is pinned
+pub async fn toggle_task_pin(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Task>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Task::set_pinned(pool, task.id, !task.is_pinned).await?;
+
+    let task = Task::find_by_id(pool, task.id)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareAttemptsQuery {
+    pub a: Uuid,
+    pub b: Uuid,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct TaskAttemptComparison {
+    /// Changes on attempt `b`'s branch relative to attempt `a`'s branch
+    pub diffs: Vec<Diff>,
+    pub summary: DiffSummary,
+}
+
+/// Diff the committed branches of two attempts of the same task
+pub async fn compare_task_attempts(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<CompareAttemptsQuery>,
+) -> Result<ResponseJson<ApiResponse<TaskAttemptComparison>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let mut branches = Vec::with_capacity(2);
+    for attempt_id in [query.a, query.b] {
+        let attempt = TaskAttempt::find_by_id(pool, attempt_id)
+            .await?
+            .filter(|attempt| attempt.task_id == task.id)
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "Attempt {attempt_id} does not belong to task {}",
+                    task.id
+                ))
+            })?;
+        let branch = attempt.branch.ok_or_else(|| {
+            ApiError::BadRequest(format!("Attempt {attempt_id} has no branch yet"))
+        })?;
+        branches.push(branch);
+    }
+
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(ApiError::Database(SqlxError::RowNotFound))?;
+    let diffs = deployment.git().get_diffs(
+        DiffTarget::BranchToBranch {
+            repo_path: &project.git_repo_path,
+            branch_a: &branches[0],
+            branch_b: &branches[1],
+        },
+        None,
+    )?;
+    let summary = DiffSummary::from_diffs(&diffs);
+
+    Ok(ResponseJson(ApiResponse::success(TaskAttemptComparison {
+        diffs,
+        summary,
+    })))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct AddTaskDependency {
+    /// Task that must be done before this one starts
+    pub depends_on_task_id: Uuid,
+}
+
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let dependencies = task.dependencies(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+/// Make the task wait on another task of its project; 409 if that would
+/// create a cycle
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<AddTaskDependency>,
+) -> Result<ResponseJson<ApiResponse<Vec<Task>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    Task::add_dependency(pool, task.id, payload.depends_on_task_id).await?;
+    let dependencies = task.dependencies(pool).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, depends_on_task_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    Task::remove_dependency(&deployment.db().pool, task.id, depends_on_task_id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ReopenTask {
+    /// Follow-up prompt describing what still needs fixing
+    pub prompt: String,
+    pub variant: Option<String>,
+}
+
+/// The attempt whose agent session reopening `task` resumes: its newest one,
+/// provided the task was reviewed and that attempt has a session
+async fn attempt_to_reopen(pool: &SqlitePool, task: &Task) -> Result<TaskAttempt, ApiError> {
+    if !matches!(task.status, TaskStatus::InReview | TaskStatus::Done) {
+        return Err(ApiError::Conflict(
+            "Only tasks in review or done can be reopened".to_string(),
+        ));
+    }
+
+    let attempt = TaskAttempt::fetch_all(pool, Some(task.id))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ApiError::BadRequest("Task has no attempts to reopen".to_string()))?;
+    if ExecutionProcess::find_latest_session_id_by_task_attempt(pool, attempt.id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::BadRequest(
+            "The latest attempt has no agent session to resume; start a new attempt instead"
+                .to_string(),
+        ));
+    }
+    Ok(attempt)
+}
+
+/// Send a reviewed task back to its agent: resumes the latest attempt's
+/// session with `prompt`, which moves the task back to in progress
+pub async fn reopen_task(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ReopenTask>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let attempt = attempt_to_reopen(&deployment.db().pool, &task).await?;
+
+    let execution_process = start_follow_up(
+        &deployment,
+        &attempt,
+        CreateFollowUpAttempt {
+            prompt: payload.prompt,
+            variant: payload.variant,
+            image_ids: None,
+        },
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_reopened",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "attempt_id": attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_id_router = Router::new()
         .route("/", get(get_task).put(update_task).delete(delete_task))
+        .route("/adopt-branch", post(adopt_branch))
+        .route("/compare", get(compare_task_attempts))
+        .route("/reopen", post(reopen_task))
+        .route("/pin", post(toggle_task_pin))
+        .route(
+            "/dependencies",
+            get(get_task_dependencies).post(add_task_dependency),
+        )
+        .route(
+            "/dependencies/{depends_on_task_id}",
+            delete(remove_task_dependency),
+        )
         .layer(from_fn_with_state(deployment.clone(), load_task_middleware));
 
     let inner = Router::new()
         .route("/", get(get_tasks).post(create_task))
         .route("/stream", get(stream_tasks))
         .route("/create-and-start", post(create_task_and_start))
+        .route("/trash/{task_id}/restore", post(restore_task))
         .nest("/{task_id}", task_id_router);
 
     // mount under /projects/:project_id/tasks
     Router::new().nest("/tasks", inner)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use db::models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcessRunReason},
+        executor_session::{CreateExecutorSession, ExecutorSession},
+    };
+    use executors::{
+        actions::{
+            ExecutorAction, ExecutorActionType, coding_agent_initial::CodingAgentInitialRequest,
+        },
+        executors::BaseCodingAgent,
+    };
+
+    use super::*;
+    use crate::test_support::{create_attempt, setup_pool};
+
+    /// Record a coding agent run on `attempt`, with `session_id` if it got one
+    async fn run_agent(pool: &SqlitePool, attempt: &TaskAttempt, session_id: Option<&str>) {
+        let data = CreateExecutionProcess {
+            task_attempt_id: attempt.id,
+            executor_action: ExecutorAction::new(
+                ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                    prompt: "build it".to_string(),
+                    executor_profile_id: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+                }),
+                None,
+            ),
+            run_reason: ExecutionProcessRunReason::CodingAgent,
+        };
+        let process = ExecutionProcess::create(pool, &data, Uuid::new_v4())
+            .await
+            .unwrap();
+        ExecutorSession::create(
+            pool,
+            &CreateExecutorSession {
+                task_attempt_id: attempt.id,
+                execution_process_id: process.id,
+                prompt: Some("build it".to_string()),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        if let Some(session_id) = session_id {
+            ExecutorSession::update_session_id(pool, process.id, session_id)
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn task_with_status(
+        pool: &SqlitePool,
+        attempt: &TaskAttempt,
+        status: TaskStatus,
+    ) -> Task {
+        Task::update_status(pool, attempt.task_id, status)
+            .await
+            .unwrap();
+        Task::find_by_id(pool, attempt.task_id)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reviewed_task_reopens_its_latest_attempt() {
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, Path::new("/tmp/reopen-reviewed")).await;
+        run_agent(&pool, &attempt, Some("session-1")).await;
+
+        for status in [TaskStatus::InReview, TaskStatus::Done] {
+            let task = task_with_status(&pool, &attempt, status).await;
+            let reopened = attempt_to_reopen(&pool, &task).await.unwrap();
+            assert_eq!(reopened.id, attempt.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn only_reviewed_tasks_can_be_reopened() {
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, Path::new("/tmp/reopen-in-progress")).await;
+        run_agent(&pool, &attempt, Some("session-1")).await;
+
+        let task = task_with_status(&pool, &attempt, TaskStatus::InProgress).await;
+        let result = attempt_to_reopen(&pool, &task).await;
+        assert!(matches!(result, Err(ApiError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn attempt_without_session_cannot_be_reopened() {
+        let pool = setup_pool().await;
+        let attempt = create_attempt(&pool, Path::new("/tmp/reopen-no-session")).await;
+        run_agent(&pool, &attempt, None).await;
+
+        let task = task_with_status(&pool, &attempt, TaskStatus::InReview).await;
+        let result = attempt_to_reopen(&pool, &task).await;
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    }
+}
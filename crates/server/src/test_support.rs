@@ -0,0 +1,73 @@
+//! Database fixtures shared by the in-crate tests
+
+use std::path::Path;
+
+use db::models::{
+    project::Project,
+    task::{CreateTask, Task},
+    task_attempt::{CreateTaskAttempt, TaskAttempt},
+};
+use executors::executors::BaseCodingAgent;
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use uuid::Uuid;
+
+/// Open a migrated in-memory database
+pub async fn setup_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    sqlx::migrate!("../db/migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Insert a project pointing at `git_repo_path`, which must be unique
+pub async fn create_project(pool: &SqlitePool, git_repo_path: &Path) -> Project {
+    let project_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO projects (id, name, git_repo_path) VALUES ($1, $2, $3)")
+        .bind(project_id)
+        .bind("test project")
+        .bind(git_repo_path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .expect("insert project");
+    Project::find_by_id(pool, project_id)
+        .await
+        .expect("load project")
+        .expect("project exists")
+}
+
+pub async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Task {
+    let data = CreateTask {
+        project_id,
+        title: title.to_string(),
+        description: None,
+        parent_task_attempt: None,
+        image_ids: None,
+        assignee: None,
+    };
+    Task::create(pool, &data, Uuid::new_v4())
+        .await
+        .expect("create task")
+}
+
+/// Create a project for `repo_path` with one task and return an attempt on `main`
+pub async fn create_attempt(pool: &SqlitePool, repo_path: &Path) -> TaskAttempt {
+    let project = create_project(pool, repo_path).await;
+    let task = create_task(pool, project.id, "task").await;
+    TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: BaseCodingAgent::ClaudeCode,
+            base_branch: "main".to_string(),
+            env_overrides: None,
+        },
+        task.id,
+    )
+    .await
+    .expect("create attempt")
+}
@@ -7,6 +7,62 @@ use std::{
 use os_info;
 use serde_json::{Value, json};
 
+tokio::task_local! {
+    /// Whether the request being handled sent `DNT: 1`
+    static REQUEST_DO_NOT_TRACK: bool;
+}
+
+/// The user's answer to the analytics opt-in prompt, from `Config.analytics_enabled`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsConsent {
+    /// Not answered yet; treated as disabled
+    Unset,
+    Enabled,
+    Disabled,
+}
+
+impl AnalyticsConsent {
+    pub fn from_config(analytics_enabled: Option<bool>) -> Self {
+        match analytics_enabled {
+            None => Self::Unset,
+            Some(true) => Self::Enabled,
+            Some(false) => Self::Disabled,
+        }
+    }
+
+    /// Events are only sent after an explicit opt-in, and never under Do Not Track
+    pub fn allows_tracking(self, do_not_track: bool) -> bool {
+        self == Self::Enabled && !do_not_track
+    }
+}
+
+/// Whether analytics events may be sent, given `Config.analytics_enabled`
+pub fn analytics_allowed(analytics_enabled: Option<bool>) -> bool {
+    AnalyticsConsent::from_config(analytics_enabled).allows_tracking(do_not_track())
+}
+
+/// `DNT=1` is set in the environment, or the request being handled sent a
+/// `DNT: 1` header
+pub fn do_not_track() -> bool {
+    is_do_not_track(std::env::var("DNT").ok().as_deref()) || request_do_not_track()
+}
+
+/// Run `handler` with the request's `DNT` header value in scope; `1` disables
+/// analytics for the events it tracks
+pub async fn with_do_not_track_header<F: Future>(value: Option<&str>, handler: F) -> F::Output {
+    REQUEST_DO_NOT_TRACK
+        .scope(is_do_not_track(value), handler)
+        .await
+}
+
+fn request_do_not_track() -> bool {
+    REQUEST_DO_NOT_TRACK.try_with(|dnt| *dnt).unwrap_or(false)
+}
+
+fn is_do_not_track(value: Option<&str>) -> bool {
+    value.is_some_and(|value| value.trim() == "1")
+}
+
 #[derive(Debug, Clone)]
 pub struct AnalyticsContext {
     pub user_id: String,
@@ -191,6 +247,45 @@ mod tests {
         assert_eq!(id.len(), 25);
     }
 
+    #[test]
+    fn only_explicit_opt_in_allows_tracking() {
+        let unset = AnalyticsConsent::from_config(None);
+        let enabled = AnalyticsConsent::from_config(Some(true));
+        let disabled = AnalyticsConsent::from_config(Some(false));
+        assert_eq!(unset, AnalyticsConsent::Unset);
+        assert_eq!(enabled, AnalyticsConsent::Enabled);
+        assert_eq!(disabled, AnalyticsConsent::Disabled);
+
+        assert!(!unset.allows_tracking(false));
+        assert!(enabled.allows_tracking(false));
+        assert!(!disabled.allows_tracking(false));
+    }
+
+    #[tokio::test]
+    async fn do_not_track_overrides_opt_in() {
+        for consent in [
+            AnalyticsConsent::Unset,
+            AnalyticsConsent::Enabled,
+            AnalyticsConsent::Disabled,
+        ] {
+            assert!(!consent.allows_tracking(true), "{consent:?}");
+        }
+
+        assert!(is_do_not_track(Some("1")));
+        assert!(is_do_not_track(Some(" 1 ")));
+        for value in [None, Some("0"), Some(""), Some("unspecified")] {
+            assert!(!is_do_not_track(value), "{value:?}");
+        }
+
+        assert!(with_do_not_track_header(Some("1"), async { request_do_not_track() }).await);
+        assert!(
+            with_do_not_track_header(Some("1"), async { !analytics_allowed(Some(true)) }).await
+        );
+        assert!(!with_do_not_track_header(Some("0"), async { request_do_not_track() }).await);
+        // The header only applies to the request that sent it
+        assert!(!request_do_not_track());
+    }
+
     #[test]
     fn test_consistency() {
         let id1 = generate_user_id();
@@ -14,13 +14,19 @@ pub enum ConfigError {
     ValidationError(String),
 }
 
-pub type Config = versions::v6::Config;
-pub type NotificationConfig = versions::v6::NotificationConfig;
-pub type EditorConfig = versions::v6::EditorConfig;
-pub type ThemeMode = versions::v6::ThemeMode;
-pub type SoundFile = versions::v6::SoundFile;
-pub type EditorType = versions::v6::EditorType;
-pub type GitHubConfig = versions::v6::GitHubConfig;
+pub type Config = versions::v8::Config;
+pub type NotificationConfig = versions::v8::NotificationConfig;
+pub type NotificationEvents = versions::v8::NotificationEvents;
+pub type EditorConfig = versions::v8::EditorConfig;
+pub type ThemeMode = versions::v8::ThemeMode;
+pub type SoundFile = versions::v8::SoundFile;
+pub type EditorType = versions::v8::EditorType;
+pub type GitHubConfig = versions::v8::GitHubConfig;
+pub type LogRetentionConfig = versions::v8::LogRetentionConfig;
+pub type RateLimitConfig = versions::v8::RateLimitConfig;
+pub type WebhookConfig = versions::v8::WebhookConfig;
+pub type WebhookEvent = versions::v8::WebhookEvent;
+pub use versions::v8::DEFAULT_MERGE_COMMIT_TEMPLATE;
 
 /// Will always return config, trying old schemas or eventually returning default
 pub async fn load_config_from_file(config_path: &PathBuf) -> Config {
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+pub use v6::{EditorConfig, EditorType, NotificationConfig, SoundFile, ThemeMode};
+
+use crate::services::config::versions::v6;
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct Config {
+    pub config_version: String,
+    pub theme: ThemeMode,
+    pub executor_profile: ExecutorProfileId,
+    pub disclaimer_acknowledged: bool,
+    pub onboarding_acknowledged: bool,
+    pub github_login_acknowledged: bool,
+    pub telemetry_acknowledged: bool,
+    pub notifications: NotificationConfig,
+    pub editor: EditorConfig,
+    pub github: GitHubConfig,
+    pub analytics_enabled: Option<bool>,
+    pub workspace_dir: Option<String>,
+    pub last_app_version: Option<String>,
+    pub show_release_notes: bool,
+    /// Flag files whose changes are whitespace-only so the diff view can collapse them
+    pub diff_ignore_whitespace: bool,
+    /// Store the agent's final message on the task attempt when it finishes
+    pub auto_summarize_attempts: bool,
+    pub log_retention: LogRetentionConfig,
+    /// Only warn when an agent moves its worktree off the task branch, instead
+    /// of switching back to the task branch
+    pub allow_agent_branch_switch: bool,
+    /// Prune the least recently used worktrees of finished attempts beyond this many
+    pub max_worktree_count: Option<u32>,
+    /// Prune worktrees of finished attempts inactive for more than this many days
+    pub max_worktree_age_days: Option<u32>,
+    /// Report file paths from agents and diffs with `/` separators on Windows
+    pub forward_slash_paths: bool,
+}
+
+/// Limits on persisted execution logs; `None` keeps logs indefinitely.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, JsonSchema)]
+pub struct LogRetentionConfig {
+    /// Delete logs of attempts that finished more than this many days ago,
+    /// unless the attempt has an open pull request or its task is in review
+    pub max_age_days: Option<u32>,
+    /// Delete the oldest logs once the total size exceeds this many megabytes
+    pub max_total_size_mb: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct GitHubConfig {
+    pub pat: Option<String>,
+    pub oauth_token: Option<String>,
+    pub username: Option<String>,
+    pub primary_email: Option<String>,
+    pub default_pr_base: Option<String>,
+    /// Extra headers sent with every GitHub API request, e.g. for authenticating proxies
+    pub extra_headers: HashMap<String, String>,
+}
+
+impl From<v6::GitHubConfig> for GitHubConfig {
+    fn from(old: v6::GitHubConfig) -> Self {
+        Self {
+            pat: old.pat,
+            oauth_token: old.oauth_token,
+            username: old.username,
+            primary_email: old.primary_email,
+            default_pr_base: old.default_pr_base,
+            extra_headers: HashMap::new(),
+        }
+    }
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self::from(v6::GitHubConfig::default())
+    }
+}
+
+impl GitHubConfig {
+    pub fn token(&self) -> Option<String> {
+        self.pat
+            .as_deref()
+            .or(self.oauth_token.as_deref())
+            .map(|s| s.to_string())
+    }
+}
+
+impl Config {
+    pub fn from_previous_version(raw_config: &str) -> Result<Self, Error> {
+        let old_config = match serde_json::from_str::<v6::Config>(raw_config) {
+            Ok(cfg) => cfg,
+            // Configs older than v6 are brought up to v6 first
+            Err(e) => match v6::Config::from_previous_version(raw_config) {
+                Ok(cfg) => cfg,
+                Err(_) => {
+                    tracing::error!("❌ Failed to parse config: {}", e);
+                    tracing::error!("   at line {}, column {}", e.line(), e.column());
+                    return Err(e.into());
+                }
+            },
+        };
+
+        Ok(Self {
+            config_version: "v7".to_string(),
+            theme: old_config.theme,
+            executor_profile: old_config.executor_profile,
+            disclaimer_acknowledged: old_config.disclaimer_acknowledged,
+            onboarding_acknowledged: old_config.onboarding_acknowledged,
+            github_login_acknowledged: old_config.github_login_acknowledged,
+            telemetry_acknowledged: old_config.telemetry_acknowledged,
+            notifications: old_config.notifications,
+            editor: old_config.editor,
+            github: GitHubConfig::from(old_config.github),
+            analytics_enabled: old_config.analytics_enabled,
+            workspace_dir: old_config.workspace_dir,
+            last_app_version: old_config.last_app_version,
+            show_release_notes: old_config.show_release_notes,
+            diff_ignore_whitespace: false,
+            auto_summarize_attempts: false,
+            log_retention: LogRetentionConfig::default(),
+            allow_agent_branch_switch: true,
+            max_worktree_count: None,
+            max_worktree_age_days: None,
+            forward_slash_paths: true,
+        })
+    }
+}
+
+impl From<String> for Config {
+    fn from(raw_config: String) -> Self {
+        if let Ok(config) = serde_json::from_str::<Config>(&raw_config)
+            && config.config_version == "v7"
+        {
+            return config;
+        }
+
+        match Self::from_previous_version(&raw_config) {
+            Ok(config) => {
+                tracing::info!("Config upgraded to v7");
+                config
+            }
+            Err(e) => {
+                tracing::warn!("Config migration failed: {}, using default", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: "v7".to_string(),
+            theme: ThemeMode::System,
+            executor_profile: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            disclaimer_acknowledged: false,
+            onboarding_acknowledged: false,
+            github_login_acknowledged: false,
+            telemetry_acknowledged: false,
+            notifications: NotificationConfig::default(),
+            editor: EditorConfig::default(),
+            github: GitHubConfig::default(),
+            analytics_enabled: None,
+            workspace_dir: None,
+            last_app_version: None,
+            show_release_notes: false,
+            diff_ignore_whitespace: false,
+            auto_summarize_attempts: false,
+            log_retention: LogRetentionConfig::default(),
+            allow_agent_branch_switch: true,
+            max_worktree_count: None,
+            max_worktree_age_days: None,
+            forward_slash_paths: true,
+        }
+    }
+}
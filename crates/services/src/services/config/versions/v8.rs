@@ -0,0 +1,343 @@
+use anyhow::Error;
+use executors::{
+    executors::BaseCodingAgent, logs::utils::output::DEFAULT_OUTPUT_LIMIT_BYTES,
+    profile::ExecutorProfileId,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+pub use v7::{EditorConfig, EditorType, GitHubConfig, LogRetentionConfig, SoundFile, ThemeMode};
+
+use crate::services::config::versions::v7;
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS, JsonSchema)]
+pub struct Config {
+    pub config_version: String,
+    pub theme: ThemeMode,
+    pub executor_profile: ExecutorProfileId,
+    pub disclaimer_acknowledged: bool,
+    pub onboarding_acknowledged: bool,
+    pub github_login_acknowledged: bool,
+    /// The analytics opt-in prompt was answered, with `analytics_enabled`
+    pub telemetry_acknowledged: bool,
+    pub notifications: NotificationConfig,
+    pub editor: EditorConfig,
+    pub github: GitHubConfig,
+    /// Unset until the user answers the analytics opt-in prompt, and treated as
+    /// disabled until then. `DNT=1` disables analytics whatever the value.
+    pub analytics_enabled: Option<bool>,
+    pub workspace_dir: Option<String>,
+    pub last_app_version: Option<String>,
+    pub show_release_notes: bool,
+    /// Flag files whose changes are whitespace-only so the diff view can collapse them
+    pub diff_ignore_whitespace: bool,
+    /// Store the agent's final message on the task attempt when it finishes
+    pub auto_summarize_attempts: bool,
+    pub log_retention: LogRetentionConfig,
+    /// Only warn when an agent moves its worktree off the task branch, instead
+    /// of switching back to the task branch
+    pub allow_agent_branch_switch: bool,
+    /// Prune the least recently used worktrees of finished attempts beyond this many
+    pub max_worktree_count: Option<u32>,
+    /// Prune worktrees of finished attempts inactive for more than this many days
+    pub max_worktree_age_days: Option<u32>,
+    /// Report file paths from agents and diffs with `/` separators on Windows
+    pub forward_slash_paths: bool,
+    /// Kill an execution that produces no output for this many minutes and mark
+    /// it failed; unset or 0 disables the timeout
+    pub execution_timeout_minutes: Option<u32>,
+    /// Commit message for task merges; supports `{task_title}`,
+    /// `{task_description}`, `{task_id}`, `{task_id_short}`, `{attempt_id}` and
+    /// `{branch}` placeholders
+    #[serde(default = "default_merge_commit_template")]
+    pub merge_commit_template: String,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Shell that runs scripts and agents instead of the platform default
+    /// (`bash`/`sh`, or `cmd` on Windows)
+    pub shell_override: Option<String>,
+    /// Arguments passed to `shell_override` before the command; the platform's
+    /// usual `-c` or `/C` when empty
+    #[serde(default)]
+    pub shell_override_args: Vec<String>,
+    /// Run at most this many execution processes at once and queue the rest
+    /// until a slot frees up; unset or 0 runs everything immediately. Dev
+    /// servers are not counted
+    pub max_concurrent_executions: Option<u32>,
+    /// Keep at most this many bytes of a command's output in the conversation;
+    /// the rest can be fetched on demand. 0 keeps all output
+    #[serde(default = "default_command_output_limit_bytes")]
+    pub command_output_limit_bytes: u32,
+    /// Endpoints that are POSTed a signed JSON payload when task events happen
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+/// Per-client token bucket applied to API requests; streaming and health
+/// routes are exempt
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct RateLimitConfig {
+    /// Off by default, since the server usually only answers the local UI
+    pub enabled: bool,
+    /// Sustained request rate each client IP is allowed
+    pub requests_per_minute: u32,
+    /// Requests a client can make in a quick burst before being limited
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_minute: 1200,
+            burst: 300,
+        }
+    }
+}
+
+/// Task events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    TaskCreated,
+    TaskStatusChanged,
+    PrMerged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Key for the HMAC-SHA256 signature sent in the `X-Signature` header
+    pub secret: String,
+    /// Events delivered to this endpoint
+    pub events: Vec<WebhookEvent>,
+}
+
+pub const DEFAULT_MERGE_COMMIT_TEMPLATE: &str =
+    "{task_title} (vibe-kanban {task_id_short})\n\n{task_description}";
+
+fn default_merge_commit_template() -> String {
+    DEFAULT_MERGE_COMMIT_TEMPLATE.to_string()
+}
+
+fn default_command_output_limit_bytes() -> u32 {
+    DEFAULT_OUTPUT_LIMIT_BYTES as u32
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct NotificationConfig {
+    pub sound_enabled: bool,
+    pub push_enabled: bool,
+    pub sound_file: SoundFile,
+    /// Which events notify at all; sound and push then apply to each of them
+    pub events: NotificationEvents,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct NotificationEvents {
+    pub task_complete: bool,
+    pub pr_merged: bool,
+    pub execution_failed: bool,
+}
+
+impl Default for NotificationEvents {
+    fn default() -> Self {
+        Self {
+            task_complete: true,
+            pr_merged: true,
+            execution_failed: true,
+        }
+    }
+}
+
+impl From<v7::NotificationConfig> for NotificationConfig {
+    fn from(old: v7::NotificationConfig) -> Self {
+        Self {
+            sound_enabled: old.sound_enabled,
+            push_enabled: old.push_enabled,
+            sound_file: old.sound_file,
+            events: NotificationEvents::default(),
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self::from(v7::NotificationConfig::default())
+    }
+}
+
+impl Config {
+    pub fn from_previous_version(raw_config: &str) -> Result<Self, Error> {
+        let old_config = match serde_json::from_str::<v7::Config>(raw_config) {
+            Ok(cfg) => cfg,
+            // Configs older than v7 are brought up to v7 first
+            Err(e) => match v7::Config::from_previous_version(raw_config) {
+                Ok(cfg) => cfg,
+                Err(_) => {
+                    tracing::error!("❌ Failed to parse config: {}", e);
+                    tracing::error!("   at line {}, column {}", e.line(), e.column());
+                    return Err(e.into());
+                }
+            },
+        };
+
+        Ok(Self {
+            config_version: "v8".to_string(),
+            theme: old_config.theme,
+            executor_profile: old_config.executor_profile,
+            disclaimer_acknowledged: old_config.disclaimer_acknowledged,
+            onboarding_acknowledged: old_config.onboarding_acknowledged,
+            github_login_acknowledged: old_config.github_login_acknowledged,
+            telemetry_acknowledged: old_config.telemetry_acknowledged,
+            notifications: NotificationConfig::from(old_config.notifications),
+            editor: old_config.editor,
+            github: old_config.github,
+            analytics_enabled: old_config.analytics_enabled,
+            workspace_dir: old_config.workspace_dir,
+            last_app_version: old_config.last_app_version,
+            show_release_notes: old_config.show_release_notes,
+            diff_ignore_whitespace: old_config.diff_ignore_whitespace,
+            auto_summarize_attempts: old_config.auto_summarize_attempts,
+            log_retention: old_config.log_retention,
+            allow_agent_branch_switch: old_config.allow_agent_branch_switch,
+            max_worktree_count: old_config.max_worktree_count,
+            max_worktree_age_days: old_config.max_worktree_age_days,
+            forward_slash_paths: old_config.forward_slash_paths,
+            execution_timeout_minutes: None,
+            merge_commit_template: default_merge_commit_template(),
+            rate_limit: RateLimitConfig::default(),
+            shell_override: None,
+            shell_override_args: Vec::new(),
+            max_concurrent_executions: None,
+            command_output_limit_bytes: default_command_output_limit_bytes(),
+            webhooks: Vec::new(),
+        })
+    }
+}
+
+impl From<String> for Config {
+    fn from(raw_config: String) -> Self {
+        if let Ok(config) = serde_json::from_str::<Config>(&raw_config)
+            && config.config_version == "v8"
+        {
+            return config;
+        }
+
+        match Self::from_previous_version(&raw_config) {
+            Ok(config) => {
+                tracing::info!("Config upgraded to v8");
+                config
+            }
+            Err(e) => {
+                tracing::warn!("Config migration failed: {}, using default", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: "v8".to_string(),
+            theme: ThemeMode::System,
+            executor_profile: ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            disclaimer_acknowledged: false,
+            onboarding_acknowledged: false,
+            github_login_acknowledged: false,
+            telemetry_acknowledged: false,
+            notifications: NotificationConfig::default(),
+            editor: EditorConfig::default(),
+            github: GitHubConfig::default(),
+            analytics_enabled: None,
+            workspace_dir: None,
+            last_app_version: None,
+            show_release_notes: false,
+            diff_ignore_whitespace: false,
+            auto_summarize_attempts: false,
+            log_retention: LogRetentionConfig::default(),
+            allow_agent_branch_switch: true,
+            max_worktree_count: None,
+            max_worktree_age_days: None,
+            forward_slash_paths: true,
+            execution_timeout_minutes: None,
+            merge_commit_template: default_merge_commit_template(),
+            rate_limit: RateLimitConfig::default(),
+            shell_override: None,
+            shell_override_args: Vec::new(),
+            max_concurrent_executions: None,
+            command_output_limit_bytes: default_command_output_limit_bytes(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V5_CONFIG: &str = r#"{
+        "config_version": "v5",
+        "theme": "DARK",
+        "profile": { "profile": "gemini", "variant": null },
+        "disclaimer_acknowledged": true,
+        "onboarding_acknowledged": true,
+        "github_login_acknowledged": true,
+        "telemetry_acknowledged": true,
+        "notifications": {
+            "sound_enabled": false,
+            "push_enabled": true,
+            "sound_file": "ABSTRACT_SOUND1"
+        },
+        "editor": { "editor_type": "ZED", "custom_command": null },
+        "github": {
+            "pat": null,
+            "oauth_token": null,
+            "username": "octocat",
+            "primary_email": null,
+            "default_pr_base": "main"
+        },
+        "analytics_enabled": false,
+        "workspace_dir": null,
+        "last_app_version": "0.0.60",
+        "show_release_notes": false
+    }"#;
+
+    #[test]
+    fn upgrades_v5_config_with_all_events_enabled() {
+        let config = Config::from(V5_CONFIG.to_string());
+
+        assert_eq!(config.config_version, "v8");
+        assert!(matches!(config.theme, ThemeMode::Dark));
+        assert_eq!(
+            config.executor_profile,
+            ExecutorProfileId::new(BaseCodingAgent::Gemini)
+        );
+        assert_eq!(config.github.username.as_deref(), Some("octocat"));
+        assert_eq!(config.analytics_enabled, Some(false));
+        assert!(config.telemetry_acknowledged);
+
+        let notifications = &config.notifications;
+        assert!(!notifications.sound_enabled);
+        assert!(notifications.push_enabled);
+        assert!(matches!(
+            notifications.sound_file,
+            SoundFile::AbstractSound1
+        ));
+        assert!(notifications.events.task_complete);
+        assert!(notifications.events.pr_merged);
+        assert!(notifications.events.execution_failed);
+    }
+
+    #[test]
+    fn saved_config_loads_back_as_v8() {
+        let mut config = Config::from(V5_CONFIG.to_string());
+        config.notifications.events.execution_failed = false;
+
+        let reloaded = Config::from(serde_json::to_string_pretty(&config).unwrap());
+        assert_eq!(reloaded.config_version, "v8");
+        assert!(!reloaded.notifications.events.execution_failed);
+        assert!(reloaded.notifications.events.task_complete);
+    }
+}
@@ -19,6 +19,7 @@ use db::{
         },
         execution_process_logs::ExecutionProcessLogs,
         executor_session::{CreateExecutorSession, ExecutorSession},
+        project::Project,
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
     },
@@ -29,18 +30,29 @@ use executors::{
         coding_agent_initial::CodingAgentInitialRequest,
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
+    approvals::ApprovalDecision,
+    command::{describe_spawn_error, record_spawn_error},
     executors::{ExecutorError, StandardCodingAgentExecutor},
-    logs::utils::patch::ConversationPatch,
+    logs::{
+        ActionType, NormalizedEntry, NormalizedEntryType,
+        utils::{patch::ConversationPatch, replay},
+    },
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use futures::{StreamExt, TryStreamExt, future};
 use sqlx::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinHandle};
-use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use utils::{
+    diff::{Diff, DiffSummary},
+    log_msg::{LogMsg, LogStream},
+    msg_store::MsgStore,
+    shell::get_shell_command,
+};
 use uuid::Uuid;
 
 use crate::services::{
+    execution_slots::ExecutionSlots,
     git::{GitService, GitServiceError},
     image::ImageService,
     worktree_manager::{WorktreeError, WorktreeManager},
@@ -109,10 +121,20 @@ pub trait ContainerService {
 
     fn git(&self) -> &GitService;
 
+    fn execution_slots(&self) -> &ExecutionSlots;
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf;
 
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError>;
 
+    /// Like [`ContainerService::create`], but checks out an existing branch
+    /// instead of creating a new one from the base branch
+    async fn adopt_branch(
+        &self,
+        task_attempt: &TaskAttempt,
+        branch_name: &str,
+    ) -> Result<ContainerRef, ContainerError>;
+
     async fn delete(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
         self.try_stop(task_attempt).await;
         self.delete_inner(task_attempt).await
@@ -127,7 +149,10 @@ pub trait ContainerService {
                 ExecutionProcess::find_by_task_attempt_id(&self.db().pool, attempt.id).await
             {
                 for process in processes {
-                    if process.status == ExecutionProcessStatus::Running {
+                    if matches!(
+                        process.status,
+                        ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+                    ) {
                         return Ok(true);
                     }
                 }
@@ -154,7 +179,10 @@ pub trait ContainerService {
             ExecutionProcess::find_by_task_attempt_id(&self.db().pool, task_attempt.id).await
         {
             for process in processes {
-                if process.status == ExecutionProcessStatus::Running {
+                if matches!(
+                    process.status,
+                    ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
+                ) {
                     self.stop_execution(&process).await.unwrap_or_else(|e| {
                         tracing::debug!(
                             "Failed to stop execution process {} for task attempt {}: {}",
@@ -174,6 +202,15 @@ pub trait ContainerService {
         &self,
         task_attempt: &TaskAttempt,
     ) -> Result<ContainerRef, ContainerError>;
+
+    /// Rebuild the worktree of attempt `attempt_id` on its branch when it is
+    /// missing or damaged, e.g. after it was deleted outside of vibe-kanban
+    async fn recreate_worktree(&self, attempt_id: Uuid) -> Result<ContainerRef, ContainerError> {
+        let task_attempt = TaskAttempt::find_by_id(&self.db().pool, attempt_id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        self.ensure_container_exists(&task_attempt).await
+    }
     async fn is_container_clean(&self, task_attempt: &TaskAttempt) -> Result<bool, ContainerError>;
 
     async fn start_execution_inner(
@@ -188,6 +225,24 @@ pub trait ContainerService {
         execution_process: &ExecutionProcess,
     ) -> Result<(), ContainerError>;
 
+    /// Pass `decision` to a running execution blocked on approval `call_id`.
+    /// Returns false if that execution is not waiting on the call.
+    async fn answer_approval(
+        &self,
+        execution_process: &ExecutionProcess,
+        call_id: &str,
+        decision: ApprovalDecision,
+    ) -> Result<bool, ContainerError>;
+
+    /// Reattach to an execution that kept running (detached) while the server
+    /// was down. Returns false if there is no such process to follow.
+    async fn try_reattach(
+        &self,
+        _execution_process: &ExecutionProcess,
+    ) -> Result<bool, ContainerError> {
+        Ok(false)
+    }
+
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError>;
 
     async fn copy_project_files(
@@ -197,11 +252,34 @@ pub trait ContainerService {
         copy_files: &str,
     ) -> Result<(), ContainerError>;
 
+    /// `ignore_whitespace` overrides the configured diff whitespace handling
     async fn get_diff(
         &self,
         task_attempt: &TaskAttempt,
+        ignore_whitespace: Option<bool>,
+    ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>;
+
+    /// Snapshot of the attempt's diff that emits each file as soon as it is
+    /// computed, rather than after the whole diff is built.
+    async fn stream_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        ignore_whitespace: Option<bool>,
     ) -> Result<futures::stream::BoxStream<'static, Result<Event, std::io::Error>>, ContainerError>;
 
+    /// Diff a single path of the attempt, or `None` if the path is unchanged.
+    async fn get_file_diff(
+        &self,
+        task_attempt: &TaskAttempt,
+        path: &str,
+    ) -> Result<Option<Diff>, ContainerError>;
+
+    /// Files changed and lines added/removed across the attempt's diff
+    async fn get_diff_summary(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<DiffSummary, ContainerError>;
+
     /// Fetch the MsgStore for a given execution ID, panicking if missing.
     async fn get_msg_store_by_id(&self, uuid: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores().read().await;
@@ -290,6 +368,30 @@ pub trait ContainerService {
         }
     }
 
+    /// Raw output of an execution as plain text, from its in-memory store or,
+    /// once that has been dropped, from the logs persisted to the database
+    async fn raw_log_text(&self, id: &Uuid, stream: LogStream) -> Option<String> {
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            return Some(store.raw_output(stream));
+        }
+
+        let logs_record =
+            match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
+                Ok(record) => record?,
+                Err(e) => {
+                    tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
+                    return None;
+                }
+            };
+        match logs_record.parse_logs() {
+            Ok(messages) => Some(stream.collect_output(&messages)),
+            Err(e) => {
+                tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                None
+            }
+        }
+    }
+
     async fn stream_normalized_logs(
         &self,
         id: &Uuid,
@@ -305,112 +407,185 @@ pub trait ContainerService {
             )
         } else {
             // Fallback: load from DB and normalize
-            let logs_record =
-                match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
-                    Ok(Some(record)) => record,
-                    Ok(None) => return None, // No logs exist
-                    Err(e) => {
-                        tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
-                        return None;
-                    }
-                };
+            let temp_store = self.rebuild_normalized_store(id).await?;
+            Some(
+                temp_store
+                    .history_plus_stream()
+                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
+                    .map_ok(|m| m.to_sse_event())
+                    .chain(futures::stream::once(async {
+                        Ok::<_, std::io::Error>(LogMsg::Finished.to_sse_event())
+                    }))
+                    .boxed(),
+            )
+        }
+    }
 
-            let raw_messages = match logs_record.parse_logs() {
-                Ok(msgs) => msgs,
+    /// Rebuild the message store of a finished execution process from its persisted
+    /// logs, with the executor's normalizer running on it
+    async fn rebuild_normalized_store(&self, id: &Uuid) -> Option<Arc<MsgStore>> {
+        let logs_record =
+            match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
+                Ok(Some(record)) => record,
+                Ok(None) => return None, // No logs exist
                 Err(e) => {
-                    tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                    tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
                     return None;
                 }
             };
 
-            // Create temporary store and populate
-            let temp_store = Arc::new(MsgStore::new());
-            for msg in raw_messages {
-                if matches!(msg, LogMsg::Stdout(_) | LogMsg::Stderr(_)) {
-                    temp_store.push(msg);
-                }
+        let raw_messages = match logs_record.parse_logs() {
+            Ok(msgs) => msgs,
+            Err(e) => {
+                tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                return None;
             }
-            temp_store.push_finished();
+        };
 
-            let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
-                Ok(Some(process)) => process,
-                Ok(None) => {
-                    tracing::error!("No execution process found for ID: {}", id);
-                    return None;
-                }
-                Err(e) => {
-                    tracing::error!("Failed to fetch execution process {}: {}", id, e);
-                    return None;
-                }
-            };
+        let temp_store = replay::raw_log_store(raw_messages);
 
-            // Get the task attempt to determine correct directory
-            let task_attempt = match process.parent_task_attempt(&self.db().pool).await {
-                Ok(Some(task_attempt)) => task_attempt,
-                Ok(None) => {
-                    tracing::error!("No task attempt found for ID: {}", process.task_attempt_id);
-                    return None;
-                }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to fetch task attempt {}: {}",
-                        process.task_attempt_id,
-                        e
-                    );
-                    return None;
-                }
-            };
+        let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
+            Ok(Some(process)) => process,
+            Ok(None) => {
+                tracing::error!("No execution process found for ID: {}", id);
+                return None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to fetch execution process {}: {}", id, e);
+                return None;
+            }
+        };
 
-            if let Err(err) = self.ensure_container_exists(&task_attempt).await {
-                tracing::warn!(
-                    "Failed to recreate worktree before log normalization for task attempt {}: {}",
-                    task_attempt.id,
-                    err
+        // Get the task attempt to determine correct directory
+        let task_attempt = match process.parent_task_attempt(&self.db().pool).await {
+            Ok(Some(task_attempt)) => task_attempt,
+            Ok(None) => {
+                tracing::error!("No task attempt found for ID: {}", process.task_attempt_id);
+                return None;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to fetch task attempt {}: {}",
+                    process.task_attempt_id,
+                    e
                 );
+                return None;
             }
+        };
 
-            let current_dir = self.task_attempt_to_current_dir(&task_attempt);
+        if let Err(err) = self.ensure_container_exists(&task_attempt).await {
+            tracing::warn!(
+                "Failed to recreate worktree before log normalization for task attempt {}: {}",
+                task_attempt.id,
+                err
+            );
+        }
 
-            let executor_action = if let Ok(executor_action) = process.executor_action() {
-                executor_action
-            } else {
-                tracing::error!(
-                    "Failed to parse executor action: {:?}",
+        let current_dir = self.task_attempt_to_current_dir(&task_attempt);
+
+        let executor_action = if let Ok(executor_action) = process.executor_action() {
+            executor_action
+        } else {
+            tracing::error!(
+                "Failed to parse executor action: {:?}",
+                process.executor_action()
+            );
+            return None;
+        };
+
+        // Spawn normalizer on populated store
+        match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                let executor = ExecutorConfigs::get_cached()
+                    .get_coding_agent_or_default(&request.executor_profile_id);
+                executor.normalize_logs(temp_store.clone(), &current_dir);
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                let executor = ExecutorConfigs::get_cached()
+                    .get_coding_agent_or_default(&request.executor_profile_id);
+                executor.normalize_follow_up_logs(temp_store.clone(), &current_dir);
+            }
+            _ => {
+                tracing::debug!(
+                    "Executor action doesn't support log normalization: {:?}",
                     process.executor_action()
                 );
                 return None;
-            };
-
-            // Spawn normalizer on populated store
-            match executor_action.typ() {
-                ExecutorActionType::CodingAgentInitialRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
-                }
-                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
-                }
-                _ => {
-                    tracing::debug!(
-                        "Executor action doesn't support log normalization: {:?}",
-                        process.executor_action()
-                    );
-                    return None;
-                }
             }
-            Some(
-                temp_store
-                    .history_plus_stream()
-                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
-                    .map_ok(|m| m.to_sse_event())
-                    .chain(futures::stream::once(async {
-                        Ok::<_, std::io::Error>(LogMsg::Finished.to_sse_event())
-                    }))
-                    .boxed(),
+        }
+        Some(temp_store)
+    }
+
+    /// Conversation entries of an execution process, reconstructed by applying its
+    /// normalized JSON patches. Running processes yield the entries produced so far.
+    async fn normalized_entries(&self, id: &Uuid) -> Option<Vec<NormalizedEntry>> {
+        match self.get_msg_store_by_id(id).await {
+            Some(store) => Some(replay::conversation_entries(&store.get_history())),
+            None => self.renormalize_logs(id).await,
+        }
+    }
+
+    /// Run the executor's current normalizer over a process's persisted raw logs
+    /// and return the resulting conversation, ignoring any normalized output
+    /// recorded when it ran. `None` if no raw logs were persisted, or the process
+    /// is not a coding agent run.
+    ///
+    /// A finished process's in-memory store still holds the old conversation, so
+    /// it is dropped; `/normalized-logs` then rebuilds from the persisted logs
+    /// with the current normalizer too.
+    async fn renormalize_logs(&self, id: &Uuid) -> Option<Vec<NormalizedEntry>> {
+        let temp_store = self.rebuild_normalized_store(id).await?;
+        let entries = replay::collect_entries(temp_store).await;
+
+        let finished = matches!(
+            ExecutionProcess::find_by_id(&self.db().pool, *id).await,
+            Ok(Some(process)) if !matches!(
+                process.status,
+                ExecutionProcessStatus::Queued | ExecutionProcessStatus::Running
             )
+        );
+        if finished && let Some(store) = self.msg_stores().write().await.remove(id) {
+            store.push_finished();
+        }
+        Some(entries)
+    }
+
+    /// Complete output of tool call `call_id` in process `id`, where the
+    /// conversation only holds it truncated. `None` if that call's output was
+    /// not truncated.
+    async fn full_tool_output(&self, id: &Uuid, call_id: &str) -> Option<String> {
+        let truncated_in = |entries: &[NormalizedEntry]| {
+            entries.iter().any(|entry| {
+                matches!(
+                    &entry.entry_type,
+                    NormalizedEntryType::ToolUse {
+                        action_type: ActionType::CommandRun {
+                            result: Some(result),
+                            ..
+                        },
+                        ..
+                    } if result.full_output_id.as_deref() == Some(call_id)
+                )
+            })
+        };
+
+        if let Some(store) = self.get_msg_store_by_id(id).await {
+            let entries = replay::conversation_entries(&store.get_history());
+            return if truncated_in(&entries) {
+                store.full_outputs().get(call_id)
+            } else {
+                None
+            };
+        }
+        // The store is gone, e.g. after a restart; normalizing the persisted
+        // logs again records the full outputs
+        let temp_store = self.rebuild_normalized_store(id).await?;
+        let full_outputs = temp_store.full_outputs();
+        let entries = replay::collect_entries(temp_store).await;
+        if truncated_in(&entries) {
+            full_outputs.get(call_id)
+        } else {
+            None
         }
     }
 
@@ -488,10 +663,44 @@ pub trait ContainerService {
         })
     }
 
+    /// Actions chained after a coding agent run: the project's verify command,
+    /// then its cleanup script
+    fn post_agent_action(&self, project: &Project) -> Option<Box<ExecutorAction>> {
+        let cleanup_action = project.cleanup_script.clone().map(|script| {
+            Box::new(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script,
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::CleanupScript,
+                    dry_run: false,
+                }),
+                None,
+            ))
+        });
+
+        match project.verify_command.clone() {
+            Some(script) if !script.trim().is_empty() => Some(Box::new(ExecutorAction::new(
+                ExecutorActionType::ScriptRequest(ScriptRequest {
+                    script,
+                    language: ScriptRequestLanguage::Bash,
+                    context: ScriptContext::VerifyScript,
+                    dry_run: false,
+                }),
+                cleanup_action,
+            ))),
+            _ => cleanup_action,
+        }
+    }
+
+    /// Start the attempt's setup script, or its coding agent when the project has
+    /// none. With `dry_run` the setup script is only logged and the chain stops there.
+    /// The coding agent runs in `working_subdir` of the worktree when given.
     async fn start_attempt(
         &self,
         task_attempt: &TaskAttempt,
         executor_profile_id: ExecutorProfileId,
+        dry_run: bool,
+        working_subdir: Option<PathBuf>,
     ) -> Result<ExecutionProcess, ContainerError> {
         // Create container
         self.create(task_attempt).await?;
@@ -522,16 +731,7 @@ pub trait ContainerService {
         );
         let prompt = ImageService::canonicalise_image_paths(&task.to_prompt(), &worktree_path);
 
-        let cleanup_action = project.cleanup_script.map(|script| {
-            Box::new(ExecutorAction::new(
-                ExecutorActionType::ScriptRequest(ScriptRequest {
-                    script,
-                    language: ScriptRequestLanguage::Bash,
-                    context: ScriptContext::CleanupScript,
-                }),
-                None,
-            ))
-        });
+        let cleanup_action = self.post_agent_action(&project);
 
         // Choose whether to execute the setup_script or coding agent first
         let execution_process = if let Some(setup_script) = project.setup_script {
@@ -540,15 +740,19 @@ pub trait ContainerService {
                     script: setup_script,
                     language: ScriptRequestLanguage::Bash,
                     context: ScriptContext::SetupScript,
+                    dry_run,
                 }),
                 // once the setup script is done, run the initial coding agent request
-                Some(Box::new(ExecutorAction::new(
-                    ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
-                        prompt,
-                        executor_profile_id: executor_profile_id.clone(),
-                    }),
-                    cleanup_action,
-                ))),
+                Some(Box::new(
+                    ExecutorAction::new(
+                        ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+                            prompt,
+                            executor_profile_id: executor_profile_id.clone(),
+                        }),
+                        cleanup_action,
+                    )
+                    .with_working_subdir(working_subdir),
+                )),
             );
 
             self.start_execution(
@@ -564,7 +768,8 @@ pub trait ContainerService {
                     executor_profile_id: executor_profile_id.clone(),
                 }),
                 cleanup_action,
-            );
+            )
+            .with_working_subdir(working_subdir);
 
             self.start_execution(
                 &task_attempt,
@@ -582,6 +787,11 @@ pub trait ContainerService {
         executor_action: &ExecutorAction,
         run_reason: &ExecutionProcessRunReason,
     ) -> Result<ExecutionProcess, ContainerError> {
+        let dry_run_script = match executor_action.typ() {
+            ExecutorActionType::ScriptRequest(request) if request.dry_run => Some(request),
+            _ => None,
+        };
+
         // Update task status to InProgress when starting an attempt
         let task = task_attempt
             .parent_task(&self.db().pool)
@@ -589,6 +799,7 @@ pub trait ContainerService {
             .ok_or(SqlxError::RowNotFound)?;
         if task.status != TaskStatus::InProgress
             && run_reason != &ExecutionProcessRunReason::DevServer
+            && dry_run_script.is_none()
         {
             Task::update_status(&self.db().pool, task.id, TaskStatus::InProgress).await?;
         }
@@ -628,9 +839,107 @@ pub trait ContainerService {
             .await?;
         }
 
-        let _ = self
-            .start_execution_inner(task_attempt, &execution_process, executor_action)
-            .await?;
+        if let Some(request) = dry_run_script {
+            self.record_dry_run(&execution_process, request).await?;
+            self.spawn_stream_raw_logs_to_db(&execution_process.id);
+            return Ok(execution_process);
+        }
+
+        // Dev servers stay up indefinitely, so they are not counted against the
+        // limit. Processes queue behind any that are already waiting.
+        if run_reason != &ExecutionProcessRunReason::DevServer
+            && !self.try_claim_slot(execution_process.id).await?
+        {
+            ExecutionProcess::mark_queued(&self.db().pool, execution_process.id).await?;
+            tracing::info!(
+                "Queued execution process {} until an execution slot frees up",
+                execution_process.id
+            );
+            return Ok(ExecutionProcess {
+                status: ExecutionProcessStatus::Queued,
+                ..execution_process
+            });
+        }
+
+        if let Err(e) = self
+            .launch_execution(task_attempt, &execution_process, executor_action)
+            .await
+        {
+            // Let the next queued process have the slot this one gave up
+            if let Err(e) = self.start_queued_executions().await {
+                tracing::error!("Failed to start queued execution processes: {}", e);
+            }
+            return Err(e);
+        }
+        Ok(execution_process)
+    }
+
+    /// Take a free execution slot for `id`, unless older processes are queued
+    async fn try_claim_slot(&self, id: Uuid) -> Result<bool, ContainerError> {
+        if !self.execution_slots().is_limited() {
+            return Ok(true);
+        }
+        if !ExecutionProcess::find_queued(&self.db().pool)
+            .await?
+            .is_empty()
+        {
+            return Ok(false);
+        }
+        Ok(self.execution_slots().try_acquire(id))
+    }
+
+    /// Start queued processes, oldest first, while execution slots are free.
+    /// Called whenever a running process gives up its slot.
+    async fn start_queued_executions(&self) -> Result<(), ContainerError> {
+        for process in ExecutionProcess::find_queued(&self.db().pool).await? {
+            if !self.execution_slots().try_acquire(process.id) {
+                continue;
+            }
+            let task_attempt = match process.parent_task_attempt(&self.db().pool).await? {
+                Some(task_attempt) => task_attempt,
+                None => {
+                    self.execution_slots().release(&process.id);
+                    continue;
+                }
+            };
+            // Stopped while it was waiting
+            if !ExecutionProcess::mark_started(&self.db().pool, process.id).await? {
+                self.execution_slots().release(&process.id);
+                continue;
+            }
+            tracing::info!("Starting queued execution process {}", process.id);
+            let executor_action = process.executor_action()?.clone();
+            if let Err(e) = self
+                .launch_execution(&task_attempt, &process, &executor_action)
+                .await
+            {
+                tracing::error!(
+                    "Failed to start queued execution process {}: {}",
+                    process.id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawn a created process and start processing its logs. On failure the
+    /// process is marked failed and its execution slot freed.
+    async fn launch_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        execution_process: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<(), ContainerError> {
+        if let Err(e) = self
+            .start_execution_inner(task_attempt, execution_process, executor_action)
+            .await
+        {
+            self.record_spawn_failure(task_attempt, execution_process, &e)
+                .await;
+            self.execution_slots().release(&execution_process.id);
+            return Err(e);
+        }
 
         // Start processing normalised logs for executor requests and follow ups
         match executor_action.typ() {
@@ -656,7 +965,7 @@ pub trait ContainerService {
                     if let Some(executor) =
                         ExecutorConfigs::get_cached().get_coding_agent(&request.executor_profile_id)
                     {
-                        executor.normalize_logs(
+                        executor.normalize_follow_up_logs(
                             msg_store,
                             &self.task_attempt_to_current_dir(task_attempt),
                         );
@@ -672,6 +981,99 @@ pub trait ContainerService {
         };
 
         self.spawn_stream_raw_logs_to_db(&execution_process.id);
+        Ok(())
+    }
+
+    /// Complete a dry-run script process without spawning it; its logs only show
+    /// the script, and no next action is started
+    async fn record_dry_run(
+        &self,
+        execution_process: &ExecutionProcess,
+        request: &ScriptRequest,
+    ) -> Result<(), ContainerError> {
+        let store = Arc::new(MsgStore::new());
+        request.record_dry_run(&store);
+        self.msg_stores()
+            .write()
+            .await
+            .insert(execution_process.id, store);
+
+        ExecutionProcess::update_completion(
+            &self.db().pool,
+            execution_process.id,
+            ExecutionProcessStatus::Completed,
+            Some(0),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Explain in the conversation why an execution could not be started and
+    /// mark it failed, instead of leaving it running without output
+    async fn record_spawn_failure(
+        &self,
+        task_attempt: &TaskAttempt,
+        execution_process: &ExecutionProcess,
+        error: &ContainerError,
+    ) {
+        let io_error = match error {
+            ContainerError::ExecutorError(ExecutorError::SpawnError(e) | ExecutorError::Io(e))
+            | ContainerError::Io(e) => Some(e),
+            _ => None,
+        };
+        let message = match io_error {
+            Some(e) => describe_spawn_error(
+                &get_shell_command().0,
+                &self.task_attempt_to_current_dir(task_attempt),
+                e,
+            ),
+            None => error.to_string(),
+        };
+        tracing::error!(
+            "Failed to start execution process {}: {}",
+            execution_process.id,
+            message
+        );
+
+        let store = match self.get_msg_store_by_id(&execution_process.id).await {
+            Some(store) => store,
+            None => {
+                let store = Arc::new(MsgStore::new());
+                self.msg_stores()
+                    .write()
+                    .await
+                    .insert(execution_process.id, store.clone());
+                store
+            }
+        };
+        record_spawn_error(&store, &message);
+        self.spawn_stream_raw_logs_to_db(&execution_process.id);
+
+        if let Err(e) =
+            ExecutionProcess::mark_failed(&self.db().pool, execution_process.id, &message).await
+        {
+            tracing::error!(
+                "Failed to mark execution process {} failed: {}",
+                execution_process.id,
+                e
+            );
+        }
+    }
+
+    /// Re-run the action of a failed process against the attempt's existing
+    /// worktree, linking the new process back to the one it retries.
+    async fn retry_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        failed_process: &ExecutionProcess,
+    ) -> Result<ExecutionProcess, ContainerError> {
+        let executor_action = failed_process.executor_action()?.clone();
+        let mut execution_process = self
+            .start_execution(task_attempt, &executor_action, &failed_process.run_reason)
+            .await?;
+        ExecutionProcess::set_retry_of(&self.db().pool, execution_process.id, failed_process.id)
+            .await?;
+        execution_process.retry_of = Some(failed_process.id);
         Ok(execution_process)
     }
 
@@ -692,15 +1094,16 @@ pub trait ContainerService {
         };
 
         // Determine the run reason of the next action
-        let next_run_reason = match ctx.execution_process.run_reason {
-            ExecutionProcessRunReason::SetupScript => ExecutionProcessRunReason::CodingAgent,
-            ExecutionProcessRunReason::CodingAgent => ExecutionProcessRunReason::CleanupScript,
-            _ => {
-                tracing::warn!(
-                    "Unexpected run reason: {:?}, defaulting to current reason",
-                    ctx.execution_process.run_reason
-                );
-                ctx.execution_process.run_reason.clone()
+        let next_run_reason = match next_action.typ() {
+            ExecutorActionType::ScriptRequest(script) => match script.context {
+                ScriptContext::SetupScript => ExecutionProcessRunReason::SetupScript,
+                ScriptContext::CleanupScript => ExecutionProcessRunReason::CleanupScript,
+                ScriptContext::VerifyScript => ExecutionProcessRunReason::VerifyScript,
+                ScriptContext::DevServer => ExecutionProcessRunReason::DevServer,
+            },
+            ExecutorActionType::CodingAgentInitialRequest(_)
+            | ExecutorActionType::CodingAgentFollowUpRequest(_) => {
+                ExecutionProcessRunReason::CodingAgent
             }
         };
 
@@ -23,6 +23,8 @@ use ts_rs::TS;
 use utils::{log_msg::LogMsg, msg_store::MsgStore};
 use uuid::Uuid;
 
+use crate::services::metrics::metrics;
+
 #[derive(Debug, Error)]
 pub enum EventError {
     #[error(transparent)]
@@ -81,7 +83,6 @@ pub mod task_patch {
 pub struct EventService {
     msg_store: Arc<MsgStore>,
     db: DBService,
-    #[allow(dead_code)]
     entry_count: Arc<RwLock<usize>>,
 }
 
@@ -114,6 +115,11 @@ pub enum RecordTypes {
         rowid: i64,
         task_attempt_id: Option<Uuid>,
     },
+    /// Files changed in the worktree of a running attempt, so its diff is stale
+    DiffChanged {
+        task_attempt_id: Uuid,
+        paths: Vec<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -166,6 +172,7 @@ impl EventService {
                     let db = db_for_hook.clone();
 
                     if let Ok(table) = HookTables::from_str(hook.table) {
+                        metrics().event_emitted();
                         let rowid = hook.rowid;
                         runtime_handle.spawn(async move {
                             let record_type: RecordTypes = match (table, hook.operation.clone()) {
@@ -203,6 +210,14 @@ impl EventService {
                                 }
                                 (HookTables::Tasks, _) => {
                                     match Task::find_by_rowid(&db.pool, rowid).await {
+                                        // Soft-deleted tasks leave the board like hard deletes
+                                        Ok(Some(task)) if task.deleted_at.is_some() => {
+                                            RecordTypes::DeletedTask {
+                                                rowid,
+                                                project_id: Some(task.project_id),
+                                                task_id: Some(task.id),
+                                            }
+                                        }
                                         Ok(Some(task)) => RecordTypes::Task(task),
                                         Ok(None) => RecordTypes::DeletedTask {
                                             rowid,
@@ -273,8 +288,10 @@ impl EventService {
                                             SqliteOperation::Insert => {
                                                 task_patch::add(&task_with_status)
                                             }
+                                            // A restored task is not on the board yet, and
+                                            // `add` replaces existing entries anyway
                                             SqliteOperation::Update => {
-                                                task_patch::replace(&task_with_status)
+                                                task_patch::add(&task_with_status)
                                             }
                                             _ => task_patch::replace(&task_with_status), // fallback
                                         };
@@ -333,27 +350,8 @@ impl EventService {
                             }
 
                             // Fallback: use the old entries format for other record types
-                            let next_entry_count = {
-                                let mut entry_count = entry_count_for_hook.write().await;
-                                *entry_count += 1;
-                                *entry_count
-                            };
-
-                            let event_patch: EventPatch = EventPatch {
-                                op: "add".to_string(),
-                                path: format!("/entries/{next_entry_count}"),
-                                value: EventPatchInner {
-                                    db_op: db_op.to_string(),
-                                    record: record_type,
-                                },
-                            };
-
                             let patch =
-                                serde_json::from_value(json!([
-                                    serde_json::to_value(event_patch).unwrap()
-                                ]))
-                                .unwrap();
-
+                                Self::entry_patch(&entry_count_for_hook, db_op, record_type).await;
                             msg_store_for_hook.push_patch(patch);
                         });
                     }
@@ -364,10 +362,42 @@ impl EventService {
         }
     }
 
+    /// Patch appending `record` to the entries list of the event stream
+    async fn entry_patch(entry_count: &RwLock<usize>, db_op: &str, record: RecordTypes) -> Patch {
+        let next_entry_count = {
+            let mut entry_count = entry_count.write().await;
+            *entry_count += 1;
+            *entry_count
+        };
+
+        let event_patch: EventPatch = EventPatch {
+            op: "add".to_string(),
+            path: format!("/entries/{next_entry_count}"),
+            value: EventPatchInner {
+                db_op: db_op.to_string(),
+                record,
+            },
+        };
+
+        serde_json::from_value(json!([serde_json::to_value(event_patch).unwrap()])).unwrap()
+    }
+
     pub fn msg_store(&self) -> &Arc<MsgStore> {
         &self.msg_store
     }
 
+    /// Tell clients that `paths` changed in the worktree of `task_attempt_id`,
+    /// so they can refresh its diff. Only clients connected now are told; the
+    /// event is not kept in the history replayed to new ones.
+    pub async fn push_diff_changed(&self, task_attempt_id: Uuid, paths: Vec<String>) {
+        let record = RecordTypes::DiffChanged {
+            task_attempt_id,
+            paths,
+        };
+        let patch = Self::entry_patch(&self.entry_count, "update", record).await;
+        self.msg_store.broadcast_patch(patch);
+    }
+
     /// Stream tasks for a specific project with initial snapshot
     pub async fn stream_tasks_for_project(
         &self,
@@ -0,0 +1,101 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use uuid::Uuid;
+
+/// Caps how many execution processes run at once. Each started process holds a
+/// slot until it exits; processes that cannot get one wait as queued.
+#[derive(Clone, Default)]
+pub struct ExecutionSlots(Arc<Mutex<SlotState>>);
+
+#[derive(Default)]
+struct SlotState {
+    /// None when the number of running processes is not limited
+    limit: Option<usize>,
+    /// Tracked even without a limit, so one set later counts them
+    holders: HashSet<Uuid>,
+}
+
+impl ExecutionSlots {
+    /// Allow `limit` processes at once; unset or 0 allows any number
+    pub fn new(limit: Option<u32>) -> Self {
+        let slots = Self::default();
+        slots.set_limit(limit);
+        slots
+    }
+
+    /// Change the limit of a running server. Processes already holding a slot
+    /// keep it; a lower limit only holds back the next ones.
+    pub fn set_limit(&self, limit: Option<u32>) {
+        self.0.lock().unwrap().limit = limit.filter(|limit| *limit > 0).map(|limit| limit as usize);
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.0.lock().unwrap().limit.is_some()
+    }
+
+    /// Claim a slot for process `id`. False when every slot is taken, or `id`
+    /// already holds one.
+    pub fn try_acquire(&self, id: Uuid) -> bool {
+        let mut state = self.0.lock().unwrap();
+        match state.limit {
+            None => {
+                state.holders.insert(id);
+                true
+            }
+            Some(limit) => state.holders.len() < limit && state.holders.insert(id),
+        }
+    }
+
+    /// Free the slot held by process `id`, if any
+    pub fn release(&self, id: &Uuid) {
+        self.0.lock().unwrap().holders.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_process_waits_for_the_first_to_release() {
+        let slots = ExecutionSlots::new(Some(1));
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(slots.try_acquire(first));
+        assert!(!slots.try_acquire(second));
+        assert!(!slots.try_acquire(first));
+
+        slots.release(&first);
+        assert!(slots.try_acquire(second));
+        assert!(!slots.try_acquire(first));
+    }
+
+    #[test]
+    fn unset_or_zero_limit_never_queues() {
+        for slots in [ExecutionSlots::new(None), ExecutionSlots::new(Some(0))] {
+            assert!(!slots.is_limited());
+            for _ in 0..3 {
+                assert!(slots.try_acquire(Uuid::new_v4()));
+            }
+        }
+    }
+
+    #[test]
+    fn changed_limit_counts_processes_already_running() {
+        let slots = ExecutionSlots::new(None);
+        let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        assert!(slots.try_acquire(first));
+        assert!(slots.try_acquire(second));
+
+        slots.set_limit(Some(2));
+        assert!(!slots.try_acquire(third));
+        slots.release(&first);
+        assert!(slots.try_acquire(third));
+
+        slots.set_limit(Some(3));
+        assert!(slots.try_acquire(first));
+    }
+}
@@ -34,6 +34,17 @@ pub struct DirectoryEntry {
     pub last_modified: Option<u64>,
 }
 
+/// A file whose path fuzzy-matched a search query; higher scores rank first
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct FuzzyFileMatch {
+    /// Path relative to the searched root, with `/` separators
+    pub path: String,
+    pub score: i64,
+}
+
+/// Directories skipped by fuzzy file search even when not gitignored
+const FUZZY_SEARCH_SKIP_DIRS: &[&str] = &[".git", "node_modules", "target", "dist", "build"];
+
 impl Default for FilesystemService {
     fn default() -> Self {
         Self::new()
@@ -86,6 +97,52 @@ impl FilesystemService {
         Ok(git_repos)
     }
 
+    /// Files under `root` whose relative path contains the characters of `query`
+    /// in order, best matches first. Respects `.gitignore` and returns at most `limit`.
+    pub async fn fuzzy_find_files(
+        &self,
+        root: &Path,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<FuzzyFileMatch>, FilesystemError> {
+        Self::verify_directory(root)?;
+        let mut matches: Vec<FuzzyFileMatch> = WalkBuilder::new(root)
+            .follow_links(false)
+            .hidden(false)
+            .git_ignore(true)
+            .git_exclude(true)
+            .require_git(false)
+            .filter_entry(|entry| {
+                let name = entry.file_name().to_string_lossy();
+                !(entry.file_type().is_some_and(|t| t.is_dir())
+                    && FUZZY_SEARCH_SKIP_DIRS.contains(&name.as_ref()))
+            })
+            .build()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                if !entry.file_type()?.is_file() {
+                    return None;
+                }
+                let relative = entry.path().strip_prefix(root).ok()?;
+                let path = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let score = fuzzy_score(query, &path)?;
+                Some(FuzzyFileMatch { path, score })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.len().cmp(&b.path.len()))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
     fn get_home_directory() -> PathBuf {
         dirs::home_dir()
             .or_else(dirs::desktop_dir)
@@ -161,3 +218,104 @@ impl FilesystemService {
         })
     }
 }
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match, or
+/// `None` if the query characters do not all appear in order. Consecutive
+/// characters, matches at the start of a path segment or word, and matches in the
+/// file name score higher; the best-scoring alignment is used.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let chars: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    // Prefer shorter paths among otherwise equal matches
+    let length_penalty = chars.len() as i64;
+    if query.is_empty() {
+        return Some(-length_penalty);
+    }
+
+    let file_name_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+    let char_score = |j: usize| {
+        let mut score = 1;
+        if j == 0 || matches!(chars[j - 1], '/' | '_' | '-' | '.' | ' ') {
+            score += 8;
+        }
+        if j >= file_name_start {
+            score += 2;
+        }
+        score
+    };
+
+    // prev[j]: best score of the query so far with its last character matched at j
+    let mut prev: Vec<Option<i64>> = Vec::new();
+    for (i, &q) in query.iter().enumerate() {
+        let mut cur = vec![None; chars.len()];
+        // Best score of the previous query character matched before j - 1
+        let mut gapped: Option<i64> = None;
+        for (j, &c) in chars.iter().enumerate() {
+            if i > 0 && j >= 2 {
+                gapped = gapped.max(prev[j - 2]);
+            }
+            if c != q {
+                continue;
+            }
+            cur[j] = if i == 0 {
+                Some(char_score(j))
+            } else {
+                let consecutive = j.checked_sub(1).and_then(|k| prev[k]).map(|s| s + 5);
+                gapped.max(consecutive).map(|s| s + char_score(j))
+            };
+        }
+        prev = cur;
+    }
+    prev.into_iter()
+        .flatten()
+        .max()
+        .map(|score| score * 10 - length_penalty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    #[tokio::test]
+    async fn fuzzy_find_ranks_file_name_matches_first() {
+        let td = tempfile::TempDir::new().unwrap();
+        let root = td.path();
+        for rel in [
+            "src/routes/task_attempts.rs",
+            "src/services/attempt_store.rs",
+            "docs/tasks/attempts.md",
+            "src/main.rs",
+            "node_modules/task_attempts/index.js",
+            "ignored/task_attempts.rs",
+        ] {
+            write(root, rel);
+        }
+        fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+
+        let matches = FilesystemService::new()
+            .fuzzy_find_files(root, "tskatt", 50)
+            .await
+            .unwrap();
+        let paths: Vec<&str> = matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["src/routes/task_attempts.rs", "docs/tasks/attempts.md",]
+        );
+
+        let limited = FilesystemService::new()
+            .fuzzy_find_files(root, "rs", 1)
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+}
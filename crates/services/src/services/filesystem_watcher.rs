@@ -1,11 +1,13 @@
 use std::{
+    collections::HashMap,
+    future::Future,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
 use futures::{
-    SinkExt,
+    SinkExt, StreamExt,
     channel::mpsc::{Receiver, channel},
 };
 use ignore::{
@@ -17,6 +19,11 @@ use notify_debouncer_full::{
     DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
 };
 use thiserror::Error;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Window in which rapid edits to a watched worktree are coalesced into one change
+pub const WORKTREE_CHANGE_DEBOUNCE: Duration = Duration::from_millis(500);
 
 pub type WatcherComponents = (
     Debouncer<RecommendedWatcher, RecommendedCache>,
@@ -107,6 +114,13 @@ fn debounced_should_forward(event: &DebouncedEvent, gi: &Gitignore, canonical_ro
 }
 
 pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatcherError> {
+    async_watcher_with_debounce(root, Duration::from_millis(200))
+}
+
+pub fn async_watcher_with_debounce(
+    root: PathBuf,
+    debounce: Duration,
+) -> Result<WatcherComponents, FilesystemWatcherError> {
     let canonical_root = canonicalize_lossy(&root);
     let gi_set = Arc::new(build_gitignore_set(&canonical_root)?);
     let (mut tx, rx) = channel(64); // Increased capacity for error bursts
@@ -115,7 +129,7 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
     let root_clone = canonical_root.clone();
 
     let mut debouncer = new_debouncer(
-        Duration::from_millis(200),
+        debounce,
         None, // Use default config
         move |res: DebounceEventResult| {
             match res {
@@ -148,3 +162,122 @@ pub fn async_watcher(root: PathBuf) -> Result<WatcherComponents, FilesystemWatch
 
     Ok((debouncer, rx, canonical_root))
 }
+
+/// Paths touched by `events`, relative to the watched root and `/`-separated
+pub fn changed_paths(events: &[DebouncedEvent], canonical_root: &Path, root: &Path) -> Vec<String> {
+    events
+        .iter()
+        .flat_map(|event| &event.paths)
+        .filter_map(|path| {
+            path.strip_prefix(canonical_root)
+                .or_else(|_| path.strip_prefix(root))
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+        })
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Watches the worktrees of active task attempts, keyed by attempt id
+#[derive(Clone, Default)]
+pub struct WorktreeWatchers {
+    watchers: Arc<Mutex<HashMap<Uuid, JoinHandle<()>>>>,
+}
+
+impl WorktreeWatchers {
+    /// Watch `worktree_path` for `attempt_id`, handing `on_change` the paths
+    /// changed in each debounced batch. Does nothing if the attempt is already
+    /// watched.
+    pub fn subscribe<F, Fut>(
+        &self,
+        attempt_id: Uuid,
+        worktree_path: PathBuf,
+        on_change: F,
+    ) -> Result<(), FilesystemWatcherError>
+    where
+        F: Fn(Vec<String>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let mut watchers = self.watchers.lock().unwrap();
+        if watchers
+            .get(&attempt_id)
+            .is_some_and(|watcher| !watcher.is_finished())
+        {
+            return Ok(());
+        }
+
+        let (debouncer, mut rx, canonical_root) =
+            async_watcher_with_debounce(worktree_path.clone(), WORKTREE_CHANGE_DEBOUNCE)?;
+        let watcher = tokio::spawn(async move {
+            // The watch stops when the debouncer is dropped with this task
+            let _debouncer = debouncer;
+            while let Some(result) = rx.next().await {
+                match result {
+                    Ok(events) => {
+                        let mut paths = changed_paths(&events, &canonical_root, &worktree_path);
+                        paths.sort();
+                        paths.dedup();
+                        if !paths.is_empty() {
+                            on_change(paths).await;
+                        }
+                    }
+                    Err(errors) => tracing::warn!(
+                        "Worktree watcher error for attempt {}: {:?}",
+                        attempt_id,
+                        errors
+                    ),
+                }
+            }
+        });
+        watchers.insert(attempt_id, watcher);
+        Ok(())
+    }
+
+    /// Stop watching the worktree of `attempt_id`, if it is watched
+    pub fn unsubscribe(&self, attempt_id: &Uuid) {
+        if let Some(watcher) = self.watchers.lock().unwrap().remove(attempt_id) {
+            watcher.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rapid_edits_fire_one_debounced_change() {
+        let worktree = TempDir::new().unwrap();
+        let watchers = WorktreeWatchers::default();
+        let attempt_id = Uuid::new_v4();
+        let (tx, mut changes) = mpsc::unbounded_channel();
+        watchers
+            .subscribe(attempt_id, worktree.path().to_path_buf(), move |paths| {
+                let tx = tx.clone();
+                async move {
+                    tx.send(paths).unwrap();
+                }
+            })
+            .unwrap();
+
+        for content in ["one", "two", "three"] {
+            std::fs::write(worktree.path().join("notes.txt"), content).unwrap();
+        }
+        let paths = tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .expect("debounced change fires")
+            .unwrap();
+        assert_eq!(paths, ["notes.txt"]);
+        let extra = tokio::time::timeout(WORKTREE_CHANGE_DEBOUNCE * 2, changes.recv()).await;
+        assert!(extra.is_err(), "edits were not coalesced: {extra:?}");
+
+        // Nothing fires once unsubscribed
+        watchers.unsubscribe(&attempt_id);
+        std::fs::write(worktree.path().join("notes.txt"), "four").unwrap();
+        let after = tokio::time::timeout(WORKTREE_CHANGE_DEBOUNCE * 2, changes.recv()).await;
+        assert!(!matches!(after, Ok(Some(_))));
+    }
+}
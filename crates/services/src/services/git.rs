@@ -1,4 +1,9 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use chrono::{DateTime, Utc};
 use git2::{
@@ -6,16 +11,64 @@ use git2::{
     Remote, Repository, Sort, build::CheckoutBuilder,
 };
 use regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ts_rs::TS;
-use utils::diff::{Diff, DiffChangeKind, FileDiffDetails};
+use utils::{
+    diff::{Diff, DiffChangeKind, FileDiffDetails, content_similarity, count_line_changes},
+    path::to_display_path,
+};
 
 // Import for file ranking functionality
 use super::file_ranker::FileStat;
-use super::git_cli::{ChangeType, GitCli, StatusDiffEntry, StatusDiffOptions};
+use super::git_cli::{
+    ChangeType, GitCli, GitCliError, StashEntry, StatusDiffEntry, StatusDiffOptions,
+};
 use crate::services::github_service::GitHubRepoInfo;
 
+/// How long a fetch made by [`GitService::status_vs_remote_default`] is reused
+/// before the remote is contacted again
+const REMOTE_FETCH_TTL: Duration = Duration::from_secs(60);
+
+// Last fetch per repository and remote, shared by every GitService instance
+lazy_static::lazy_static! {
+    static ref LAST_REMOTE_FETCH: Mutex<HashMap<(PathBuf, String), Instant>> =
+        Mutex::new(HashMap::new());
+}
+
+/// How [`GitService::merge_changes`] lands a task branch on its base branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Always create one squash commit on the base branch
+    #[default]
+    Squash,
+    /// Move the base branch to the task branch head when it has not diverged,
+    /// and squash otherwise
+    FastForwardIfPossible,
+}
+
+/// Commit the base branch points at after a merge, and the strategy that was
+/// actually applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct MergeOutcome {
+    pub sha: String,
+    pub strategy: MergeStrategy,
+    /// Tag created on `sha`; `None` when none was asked for or tagging failed
+    pub tag: Option<String>,
+}
+
+/// Optional settings for [`GitService::merge_changes`]; the default is a squash
+/// merge committed with the repository identity
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// (name, email) to attribute the squash commit to
+    pub author: Option<(String, String)>,
+    pub strategy: MergeStrategy,
+    /// Annotated tag to create on the resulting commit, with the commit message
+    pub tag_name: Option<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum GitServiceError {
     #[error(transparent)]
@@ -26,8 +79,9 @@ pub enum GitServiceError {
     InvalidRepository(String),
     #[error("Branch not found: {0}")]
     BranchNotFound(String),
+    /// Carries the conflicted files when they could be read back
     #[error("Merge conflicts: {0}")]
-    MergeConflicts(String),
+    MergeConflicts(String, Vec<ConflictedFile>),
     #[error("Branches diverged: {0}")]
     BranchesDiverged(String),
     #[error("Invalid path: {0}")]
@@ -40,6 +94,40 @@ pub enum GitServiceError {
     TokenUnavailable,
     #[error("Rebase in progress; resolve or abort it before retrying")]
     RebaseInProgress,
+    #[error("Insufficient disk space: {0}")]
+    InsufficientDiskSpace(String),
+    #[error("Tag already exists: {0}")]
+    TagExists(String),
+    #[error("No repository found at {0}")]
+    RepoMissing(String),
+    #[error("Commit was already pushed to {0}; amending it would rewrite published history")]
+    CommitAlreadyPushed(String),
+    #[error("No commits on top of {0} to amend")]
+    NothingToAmend(String),
+    #[error("Invalid branch name: {0}")]
+    InvalidBranchName(String),
+    #[error("Branch already exists: {0}")]
+    BranchExists(String),
+    /// The rebase went through, but the changes stashed for it could not be restored
+    #[error(
+        "Rebased to {}, but restoring stashed changes failed: {1}; they are kept in stash {}",
+        .0.head,
+        .0.stash
+    )]
+    StashPopFailed(KeptStash, String),
+}
+
+impl GitServiceError {
+    /// Wrap a failed git CLI `operation` in `path`, reporting a full disk as
+    /// [`GitServiceError::InsufficientDiskSpace`]
+    fn from_cli(operation: &str, path: &Path, err: GitCliError) -> Self {
+        let message = err.to_string();
+        if utils::disk::is_storage_full_message(&message) {
+            GitServiceError::InsufficientDiskSpace(path.display().to_string())
+        } else {
+            GitServiceError::InvalidRepository(format!("{operation} failed: {message}"))
+        }
+    }
 }
 
 /// Service for managing Git operations in task execution workflows
@@ -55,12 +143,105 @@ pub struct GitBranch {
     pub last_commit_date: DateTime<Utc>,
 }
 
+/// A commit as listed in an attempt's history
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommitInfo {
+    pub sha: String,
+    /// First line of the commit message
+    pub subject: String,
+    pub author: String,
+    #[ts(type = "Date")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CommitInfo {
+    fn from_commit(commit: &git2::Commit) -> Self {
+        Self {
+            sha: commit.id().to_string(),
+            subject: commit.summary().unwrap_or("(no subject)").to_string(),
+            author: String::from_utf8_lossy(commit.author().name_bytes()).into_owned(),
+            timestamp: DateTime::from_timestamp(commit.time().seconds(), 0)
+                .unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A merge or rebase that stopped partway in a worktree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum GitOperation {
+    Merge,
+    Rebase,
+}
+
+/// A path left conflicted by a merge or rebase, with the content of each side.
+/// A side is `None` when the file does not exist there or is not UTF-8.
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ConflictedFile {
+    pub path: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+}
+
+/// Where changes auto-stashed for a rebase are, when they could not be restored
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct KeptStash {
+    /// HEAD commit after the rebase
+    pub head: String,
+    /// Commit id of the stash still holding the changes
+    pub stash: String,
+}
+
+/// Uncommitted changes in a worktree grouped like `git status`. A file staged
+/// and then edited again is listed as both staged and modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+pub struct WorktreeFileStatus {
+    /// Changes in the index that the next commit would include
+    pub staged: Vec<String>,
+    /// Changes to tracked files that are not staged
+    pub modified: Vec<String>,
+    /// Only filled in when untracked files were asked for
+    pub untracked: Vec<String>,
+    pub staged_count: usize,
+    pub modified_count: usize,
+    pub untracked_count: usize,
+}
+
+/// Result of a dry-run merge of a task branch into its base branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct MergePreview {
+    pub clean: bool,
+    /// Paths that would conflict; empty when `clean`
+    pub conflicted_files: Vec<String>,
+}
+
+/// Whether a repository or worktree can be used, as reported by
+/// [`GitService::repo_health`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoHealth {
+    Ok,
+    /// Nothing at the path, or a directory that is not a repository
+    Missing,
+    /// A `.git` is present but the repository, its HEAD or its index cannot be read
+    Corrupt,
+}
+
 #[derive(Debug, Clone)]
 pub struct HeadInfo {
     pub branch: String,
     pub oid: String,
 }
 
+/// Extra knobs for [`GitService::get_diffs_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffOptionsExt {
+    /// Ignore whitespace when comparing lines and report files whose changes
+    /// are whitespace-only as [`DiffChangeKind::WhitespaceChange`]
+    pub ignore_whitespace: bool,
+}
+
 /// Target for diff generation
 pub enum DiffTarget<'p> {
     /// Work-in-progress branch checked out in this worktree
@@ -80,6 +261,12 @@ pub enum DiffTarget<'p> {
         repo_path: &'p Path,
         commit_sha: &'p str,
     },
+    /// Committed state of `branch_b` vs `branch_a`
+    BranchToBranch {
+        repo_path: &'p Path,
+        branch_a: &'p str,
+        branch_b: &'p str,
+    },
 }
 
 impl Default for GitService {
@@ -96,7 +283,37 @@ impl GitService {
 
     /// Open the repository
     fn open_repo(&self, repo_path: &Path) -> Result<Repository, GitServiceError> {
-        Repository::open(repo_path).map_err(GitServiceError::from)
+        Repository::open(repo_path).map_err(|e| {
+            if self.repo_health(repo_path) == RepoHealth::Missing {
+                GitServiceError::RepoMissing(repo_path.display().to_string())
+            } else {
+                GitServiceError::from(e)
+            }
+        })
+    }
+
+    /// Tell a usable repository apart from one that was deleted out from under
+    /// us and one that is damaged
+    pub fn repo_health(&self, repo_path: &Path) -> RepoHealth {
+        if !repo_path.exists() {
+            return RepoHealth::Missing;
+        }
+        let repo = match Repository::open(repo_path) {
+            Ok(repo) => repo,
+            Err(e) if e.code() == git2::ErrorCode::NotFound && !repo_path.join(".git").exists() => {
+                return RepoHealth::Missing;
+            }
+            Err(_) => return RepoHealth::Corrupt,
+        };
+        let head_ok = match repo.head() {
+            Ok(head) => head.peel_to_commit().is_ok(),
+            Err(e) => e.code() == git2::ErrorCode::UnbornBranch,
+        };
+        if head_ok && repo.index().is_ok() {
+            RepoHealth::Ok
+        } else {
+            RepoHealth::Corrupt
+        }
     }
 
     /// Ensure local (repo-scoped) identity exists for CLI commits.
@@ -212,20 +429,129 @@ impl GitService {
         }
 
         git.add_all(path)
-            .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))?;
+            .map_err(|e| GitServiceError::from_cli("git add", path, e))?;
         // Only ensure identity once we know we're about to commit
         self.ensure_cli_commit_identity(path)?;
         git.commit(path, message)
-            .map_err(|e| GitServiceError::InvalidRepository(format!("git commit failed: {e}")))?;
+            .map_err(|e| GitServiceError::from_cli("git commit", path, e))?;
         Ok(true)
     }
 
+    /// Amend the last commit in `worktree_path`, replacing its message when
+    /// `new_message` is given and folding in all current changes when
+    /// `stage_all` is set. Refuses when the commit is already on `base_branch`,
+    /// and once it is on the branch's remote unless `force`. Returns the id of
+    /// the amended commit.
+    pub fn amend_commit(
+        &self,
+        worktree_path: &Path,
+        base_branch: &str,
+        new_message: Option<&str>,
+        stage_all: bool,
+        force: bool,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let head = repo.head()?.peel_to_commit()?;
+        let base_oid = Self::find_branch(&repo, base_branch)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        // Without commits of its own, HEAD is a commit of the base branch
+        if head.id() == base_oid || repo.graph_descendant_of(base_oid, head.id())? {
+            return Err(GitServiceError::NothingToAmend(base_branch.to_string()));
+        }
+        if !force && let Some(remote_branch) = self.pushed_to(&repo, head.id())? {
+            return Err(GitServiceError::CommitAlreadyPushed(remote_branch));
+        }
+
+        let git = GitCli::new();
+        if stage_all {
+            git.add_all(worktree_path)
+                .map_err(|e| GitServiceError::from_cli("git add", worktree_path, e))?;
+        }
+        self.ensure_cli_commit_identity(worktree_path)?;
+        git.commit_amend(worktree_path, new_message)
+            .map_err(|e| GitServiceError::from_cli("git commit --amend", worktree_path, e))?;
+        Ok(self.get_head_info(worktree_path)?.oid)
+    }
+
+    /// Remote branch of the checked out branch that already contains `oid`:
+    /// its upstream, or the same-named branch on the default remote
+    fn pushed_to(
+        &self,
+        repo: &Repository,
+        oid: git2::Oid,
+    ) -> Result<Option<String>, GitServiceError> {
+        let head = repo.head()?;
+        let Some(branch_name) = head.shorthand().filter(|_| head.is_branch()) else {
+            return Ok(None);
+        };
+        let remote_ref = match repo.find_branch(branch_name, BranchType::Local)?.upstream() {
+            Ok(upstream) => upstream.into_reference(),
+            Err(_) => match repo.find_reference(&format!(
+                "refs/remotes/{}/{branch_name}",
+                self.default_remote_name(repo)
+            )) {
+                Ok(reference) => reference,
+                Err(_) => return Ok(None),
+            },
+        };
+        let Some(remote_oid) = remote_ref.target() else {
+            return Ok(None);
+        };
+        let pushed = remote_oid == oid || repo.graph_descendant_of(remote_oid, oid)?;
+        Ok(pushed.then(|| remote_ref.shorthand().unwrap_or(branch_name).to_string()))
+    }
+
     /// Get diffs between branches or worktree changes
     pub fn get_diffs(
         &self,
         target: DiffTarget,
         path_filter: Option<&[&str]>,
     ) -> Result<Vec<Diff>, GitServiceError> {
+        self.get_diffs_with_options(target, path_filter, DiffOptionsExt::default())
+    }
+
+    /// Like [`GitService::get_diffs`], with extra control over how changes are reported
+    pub fn get_diffs_with_options(
+        &self,
+        target: DiffTarget,
+        path_filter: Option<&[&str]>,
+        opts: DiffOptionsExt,
+    ) -> Result<Vec<Diff>, GitServiceError> {
+        let mut diffs = Vec::new();
+        self.stream_diffs(target, path_filter, opts, &mut |diff| {
+            diffs.push(diff);
+            true
+        })?;
+        Ok(diffs)
+    }
+
+    /// Like [`GitService::get_diffs_with_options`], but hands each file's diff to
+    /// `on_diff` as soon as it is built instead of collecting them. Returning
+    /// `false` from `on_diff` stops the walk early.
+    pub fn stream_diffs(
+        &self,
+        target: DiffTarget,
+        path_filter: Option<&[&str]>,
+        opts: DiffOptionsExt,
+        on_diff: &mut dyn FnMut(Diff) -> bool,
+    ) -> Result<(), GitServiceError> {
+        self.compute_diffs(target, path_filter, opts, &mut |mut diff| {
+            if opts.ignore_whitespace {
+                Self::flag_whitespace_only_change(&mut diff);
+            }
+            on_diff(diff)
+        })
+    }
+
+    fn compute_diffs(
+        &self,
+        target: DiffTarget,
+        path_filter: Option<&[&str]>,
+        opts: DiffOptionsExt,
+        emit: &mut dyn FnMut(Diff) -> bool,
+    ) -> Result<(), GitServiceError> {
         match target {
             DiffTarget::Worktree {
                 worktree_path,
@@ -237,6 +563,8 @@ impl GitService {
                 let base_git_branch = GitService::find_branch(&repo, base_branch)?;
                 let base_tree = base_git_branch.get().peel_to_commit()?.tree()?;
 
+                // `-w` would not drop whitespace-only files from `--name-status`;
+                // `stream_diffs` flags them from their contents instead
                 let git = GitCli::new();
                 let cli_opts = StatusDiffOptions {
                     path_filter: path_filter.map(|fs| fs.iter().map(|s| s.to_string()).collect()),
@@ -246,15 +574,22 @@ impl GitService {
                     .map_err(|e| {
                         GitServiceError::InvalidRepository(format!("git diff failed: {e}"))
                     })?;
-                Ok(entries
-                    .into_iter()
-                    .map(|e| Self::status_entry_to_diff(&repo, &base_tree, e))
-                    .collect())
+                for entry in entries {
+                    if !emit(Self::status_entry_to_diff(&repo, &base_tree, entry)) {
+                        break;
+                    }
+                }
+                Ok(())
             }
             DiffTarget::Branch {
                 repo_path,
                 branch_name,
                 base_branch,
+            }
+            | DiffTarget::BranchToBranch {
+                repo_path,
+                branch_a: base_branch,
+                branch_b: branch_name,
             } => {
                 let repo = self.open_repo(repo_path)?;
                 let base_tree = Self::find_branch(&repo, base_branch)?
@@ -268,6 +603,7 @@ impl GitService {
 
                 let mut diff_opts = DiffOptions::new();
                 diff_opts.include_typechange(true);
+                diff_opts.ignore_whitespace(opts.ignore_whitespace);
 
                 // Add path filtering if specified
                 if let Some(paths) = path_filter {
@@ -286,7 +622,7 @@ impl GitService {
                 let mut find_opts = DiffFindOptions::new();
                 diff.find_similar(Some(&mut find_opts))?;
 
-                self.convert_diff_to_file_diffs(diff, &repo)
+                self.emit_file_diffs(diff, &repo, emit)
             }
             DiffTarget::Commit {
                 repo_path,
@@ -312,6 +648,7 @@ impl GitService {
                 // Diff options
                 let mut diff_opts = git2::DiffOptions::new();
                 diff_opts.include_typechange(true);
+                diff_opts.ignore_whitespace(opts.ignore_whitespace);
 
                 // Optional path filtering
                 if let Some(paths) = path_filter {
@@ -331,20 +668,42 @@ impl GitService {
                 let mut find_opts = git2::DiffFindOptions::new();
                 diff.find_similar(Some(&mut find_opts))?;
 
-                self.convert_diff_to_file_diffs(diff, &repo)
+                self.emit_file_diffs(diff, &repo, emit)
             }
         }
     }
 
-    /// Convert git2::Diff to our Diff structs
-    fn convert_diff_to_file_diffs(
+    /// Reclassify a modification whose content differs only in whitespace
+    fn flag_whitespace_only_change(diff: &mut Diff) {
+        if !matches!(diff.change, DiffChangeKind::Modified) {
+            return;
+        }
+        if let (Some(old), Some(new)) = (&diff.old_content, &diff.new_content)
+            && Self::is_whitespace_only_change(old, new)
+        {
+            diff.change = DiffChangeKind::WhitespaceChange;
+        }
+    }
+
+    /// True when `old` and `new` differ, but not once whitespace within each
+    /// line is ignored (the same notion as `git diff -w`)
+    fn is_whitespace_only_change(old: &str, new: &str) -> bool {
+        fn strip(line: &str) -> String {
+            line.chars().filter(|c| !c.is_whitespace()).collect()
+        }
+        old != new && old.lines().map(strip).eq(new.lines().map(strip))
+    }
+
+    /// Convert git2::Diff to our Diff structs, emitting each file as it is built
+    fn emit_file_diffs(
         &self,
         diff: git2::Diff,
         repo: &Repository,
-    ) -> Result<Vec<Diff>, GitServiceError> {
-        let mut file_diffs = Vec::new();
+        emit: &mut dyn FnMut(Diff) -> bool,
+    ) -> Result<(), GitServiceError> {
+        let mut stopped = false;
 
-        diff.foreach(
+        let walk = diff.foreach(
             &mut |delta, _| {
                 if delta.status() == Delta::Unreadable {
                     return true;
@@ -401,30 +760,67 @@ impl GitService {
                     }
                 }
 
-                file_diffs.push(Diff {
+                let (additions, deletions) = Self::line_stats(
+                    old_path.as_ref().map(|_| old_content.as_deref()),
+                    new_path.as_ref().map(|_| new_content.as_deref()),
+                );
+                // git2 does not expose the rename score, so estimate it from the contents
+                let similarity = if !matches!(status, Delta::Renamed | Delta::Copied) {
+                    None
+                } else if delta.old_file().id() == delta.new_file().id() {
+                    Some(100)
+                } else {
+                    old_content
+                        .as_deref()
+                        .zip(new_content.as_deref())
+                        .map(|(old, new)| content_similarity(old, new))
+                };
+                let keep_going = emit(Diff {
                     change,
                     old_path,
                     new_path,
                     old_content,
                     new_content,
+                    additions,
+                    deletions,
+                    similarity,
                 });
-
-                true
+                stopped = !keep_going;
+                keep_going
             },
             None,
             None,
             None,
-        )?;
+        );
 
-        Ok(file_diffs)
+        match walk {
+            // git2 reports a callback that stopped the walk as an error
+            Err(_) if stopped => Ok(()),
+            walk => Ok(walk?),
+        }
+    }
+
+    /// (additions, deletions) for a file diff. Each side is `None` when the
+    /// file does not exist on that side, and `Some(None)` when it exists but
+    /// its content could not be loaded (binary, too large, not UTF-8); such
+    /// files are not counted.
+    fn line_stats(old: Option<Option<&str>>, new: Option<Option<&str>>) -> (usize, usize) {
+        match (old, new) {
+            (Some(None), _) | (_, Some(None)) => (0, 0),
+            (old, new) => {
+                count_line_changes(old.flatten().unwrap_or(""), new.flatten().unwrap_or(""))
+            }
+        }
     }
 
     /// Extract file path from a Diff (for indexing and ConversationPatch)
     pub fn diff_path(diff: &Diff) -> String {
-        diff.new_path
-            .clone()
-            .or_else(|| diff.old_path.clone())
-            .unwrap_or_default()
+        let path = diff
+            .new_path
+            .as_deref()
+            .or(diff.old_path.as_deref())
+            .unwrap_or_default();
+        to_display_path(path)
     }
 
     /// Helper function to convert blob to string content
@@ -570,16 +966,28 @@ impl GitService {
             change = DiffChangeKind::PermissionChange;
         }
 
+        let (additions, deletions) = Self::line_stats(
+            old_path_opt.as_ref().map(|_| old_content.as_deref()),
+            new_path_opt.as_ref().map(|_| new_content.as_deref()),
+        );
+
         Diff {
             change,
             old_path: old_path_opt,
             new_path: new_path_opt,
             old_content,
             new_content,
+            additions,
+            deletions,
+            similarity: e.similarity,
         }
     }
 
-    /// Merge changes from a worktree branch back to the main repository
+    /// Merge changes from a worktree branch back to the main repository.
+    ///
+    /// With [`MergeStrategy::FastForwardIfPossible`] and a base branch that the
+    /// task branch already contains, the base branch is moved to the task branch
+    /// head instead and `commit_message` and the author are unused.
     pub fn merge_changes(
         &self,
         repo_path: &Path,
@@ -587,6 +995,169 @@ impl GitService {
         branch_name: &str,
         base_branch_name: &str,
         commit_message: &str,
+        options: &MergeOptions,
+    ) -> Result<MergeOutcome, GitServiceError> {
+        // Refuse before merging so a taken tag name does not leave an untagged merge
+        if let Some(tag_name) = &options.tag_name {
+            Self::ensure_tag_available(&self.open_repo(repo_path)?, tag_name)?;
+        }
+        let fast_forward = options.strategy == MergeStrategy::FastForwardIfPossible
+            && self.can_fast_forward(repo_path, branch_name, base_branch_name)?;
+        let sha = if fast_forward {
+            self.fast_forward_merge(repo_path, branch_name, base_branch_name)?
+        } else {
+            self.squash_merge_changes(
+                repo_path,
+                worktree_path,
+                branch_name,
+                base_branch_name,
+                commit_message,
+                options.author.clone(),
+            )?
+        };
+        // The merge has landed, so failing to tag it is reported rather than fatal
+        let tag = options.tag_name.as_ref().and_then(|tag_name| {
+            match self.create_tag(repo_path, tag_name, &sha, commit_message) {
+                Ok(()) => Some(tag_name.clone()),
+                Err(e) => {
+                    tracing::warn!("Merged {} but could not tag it {}: {}", sha, tag_name, e);
+                    None
+                }
+            }
+        });
+        Ok(MergeOutcome {
+            sha,
+            strategy: if fast_forward {
+                MergeStrategy::FastForwardIfPossible
+            } else {
+                MergeStrategy::Squash
+            },
+            tag,
+        })
+    }
+
+    /// Whether `base_branch_name` is `branch_name` or one of its ancestors, so
+    /// the base can be moved to the branch head without a new commit
+    fn can_fast_forward(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<bool, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_oid = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        let base_oid = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        Ok(branch_oid == base_oid || repo.graph_descendant_of(branch_oid, base_oid)?)
+    }
+
+    /// Move the base branch to the task branch head. When the main repository or
+    /// one of its worktrees has the base branch checked out, that working tree
+    /// follows via `git merge --ff-only`; otherwise only the ref moves.
+    fn fast_forward_merge(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        if let Some(checkout_path) = Self::checkout_of_branch(&repo, repo_path, base_branch_name) {
+            let git = GitCli::new();
+            if git.has_staged_changes(&checkout_path).map_err(|e| {
+                GitServiceError::InvalidRepository(format!("git diff --cached failed: {e}"))
+            })? {
+                return Err(GitServiceError::WorktreeDirty(
+                    base_branch_name.to_string(),
+                    "staged changes present".to_string(),
+                ));
+            }
+            return git
+                .merge_fast_forward(&checkout_path, branch_name)
+                .map_err(|e| GitServiceError::from_cli("git merge --ff-only", &checkout_path, e));
+        }
+
+        let branch_oid = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?
+            .id();
+        repo.reference(
+            &format!("refs/heads/{base_branch_name}"),
+            branch_oid,
+            true,
+            &format!("Fast-forward {base_branch_name} to {branch_name}"),
+        )?;
+        Ok(branch_oid.to_string())
+    }
+
+    /// Working tree that has `branch_name` checked out: the main one at
+    /// `repo_path` or one of its linked worktrees. Worktrees that can no longer
+    /// be opened are skipped.
+    fn checkout_of_branch(
+        repo: &Repository,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Option<PathBuf> {
+        let on_branch = |repo: &Repository| {
+            repo.head()
+                .is_ok_and(|h| h.shorthand() == Some(branch_name))
+        };
+        if on_branch(repo) {
+            return Some(repo_path.to_path_buf());
+        }
+        let names = repo.worktrees().ok()?;
+        names
+            .iter()
+            .flatten()
+            .filter_map(|name| repo.find_worktree(name).ok())
+            .find(|worktree| {
+                Repository::open_from_worktree(worktree).is_ok_and(|wt_repo| on_branch(&wt_repo))
+            })
+            .map(|worktree| worktree.path().to_path_buf())
+    }
+
+    /// Create an annotated tag `tag_name` on `target_sha`, tagged by the
+    /// repository identity or the fallback one
+    pub fn create_tag(
+        &self,
+        repo_path: &Path,
+        tag_name: &str,
+        target_sha: &str,
+        message: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        Self::ensure_tag_available(&repo, tag_name)?;
+        let target = repo.find_object(git2::Oid::from_str(target_sha)?, None)?;
+        let tagger = self.signature_with_fallback(&repo)?;
+        repo.tag(tag_name, &target, &tagger, message, false)?;
+        Ok(())
+    }
+
+    fn ensure_tag_available(repo: &Repository, tag_name: &str) -> Result<(), GitServiceError> {
+        let refname = format!("refs/tags/{tag_name}");
+        if !git2::Reference::is_valid_name(&refname) {
+            return Err(GitServiceError::Git(GitError::from_str(&format!(
+                "Invalid tag name: {tag_name}"
+            ))));
+        }
+        if repo.find_reference(&refname).is_ok() {
+            return Err(GitServiceError::TagExists(tag_name.to_string()));
+        }
+        Ok(())
+    }
+
+    fn squash_merge_changes(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+        commit_message: &str,
+        author: Option<(String, String)>,
     ) -> Result<String, GitServiceError> {
         // Open the repositories
         let worktree_repo = self.open_repo(worktree_path)?;
@@ -611,10 +1182,28 @@ impl GitService {
             // This path updates both ref and working tree safely (git will refuse if unsafe)
             // Ensure identity for the CLI commit
             self.ensure_cli_commit_identity(repo_path)?;
+            let author = author
+                .as_ref()
+                .map(|(name, email)| format!("{name} <{email}>"));
             let sha = git
-                .merge_squash_commit(repo_path, base_branch_name, branch_name, commit_message)
-                .map_err(|e| {
-                    GitServiceError::InvalidRepository(format!("git merge --squash failed: {e}"))
+                .merge_squash_commit(
+                    repo_path,
+                    base_branch_name,
+                    branch_name,
+                    commit_message,
+                    author.as_deref(),
+                )
+                .map_err(|e| match self.list_conflicts(repo_path) {
+                    Ok(conflicts) if !conflicts.is_empty() => GitServiceError::MergeConflicts(
+                        format!(
+                            "git merge --squash stopped on conflicts in {}",
+                            repo_path.display()
+                        ),
+                        conflicts,
+                    ),
+                    _ => GitServiceError::InvalidRepository(format!(
+                        "git merge --squash failed: {e}"
+                    )),
                 })?;
             // Also update task branch ref to merged commit for continuity
             let task_refname = format!("refs/heads/{branch_name}");
@@ -635,12 +1224,15 @@ impl GitService {
         let task_commit = task_branch.get().peel_to_commit()?;
 
         // Create the squash commit in-memory (no checkout) and update the base branch ref
-        let signature = self.signature_with_fallback(&worktree_repo)?;
+        let author = match author {
+            Some((name, email)) => git2::Signature::now(&name, &email)?,
+            None => self.signature_with_fallback(&worktree_repo)?,
+        };
         let squash_commit_id = self.perform_squash_merge(
             &worktree_repo,
             &base_commit,
             &task_commit,
-            &signature,
+            &author,
             commit_message,
             base_branch_name,
         )?;
@@ -692,6 +1284,96 @@ impl GitService {
         )
     }
 
+    fn branch_oid(repo: &Repository, branch_name: &str) -> Result<git2::Oid, GitServiceError> {
+        Self::find_branch(repo, branch_name)?
+            .get()
+            .target()
+            .ok_or_else(|| GitServiceError::BranchNotFound(branch_name.to_string()))
+    }
+
+    /// Best common ancestor of two commits, or None if they share no history
+    fn get_merge_base(
+        repo: &Repository,
+        a: git2::Oid,
+        b: git2::Oid,
+    ) -> Result<Option<git2::Oid>, GitServiceError> {
+        match repo.merge_base(a, b) {
+            Ok(oid) => Ok(Some(oid)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Commits reachable from `branch_oid` but not from `base_oid`, newest first
+    fn find_unique_commits(
+        repo: &Repository,
+        branch_oid: git2::Oid,
+        base_oid: git2::Oid,
+    ) -> Result<Vec<git2::Oid>, GitServiceError> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        revwalk.push(branch_oid)?;
+        revwalk.hide(base_oid)?;
+        revwalk
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(GitServiceError::from)
+    }
+
+    /// Merge-base sha of `branch_name` and `base_branch_name`, or None if the
+    /// branches have unrelated histories
+    pub fn merge_base(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_oid = Self::branch_oid(&repo, branch_name)?;
+        let base_oid = Self::branch_oid(&repo, base_branch_name)?;
+        Ok(Self::get_merge_base(&repo, branch_oid, base_oid)?.map(|oid| oid.to_string()))
+    }
+
+    /// Shas of commits on `branch_name` that are not on `base_branch_name`,
+    /// newest first. With unrelated histories this is every commit on the branch.
+    pub fn unique_commits(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_oid = Self::branch_oid(&repo, branch_name)?;
+        let base_oid = Self::branch_oid(&repo, base_branch_name)?;
+        Ok(Self::find_unique_commits(&repo, branch_oid, base_oid)?
+            .into_iter()
+            .map(|oid| oid.to_string())
+            .collect())
+    }
+
+    /// Up to `limit` commits on `branch_name` that are not on `base_branch_name`,
+    /// newest first
+    pub fn list_commits(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+        limit: usize,
+    ) -> Result<Vec<CommitInfo>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let branch_oid = Self::branch_oid(&repo, branch_name)?;
+        let base_oid = Self::branch_oid(&repo, base_branch_name)?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        revwalk.push(branch_oid)?;
+        revwalk.hide(base_oid)?;
+        revwalk
+            .take(limit)
+            .map(|oid| -> Result<_, GitServiceError> {
+                Ok(CommitInfo::from_commit(&repo.find_commit(oid?)?))
+            })
+            .collect()
+    }
+
     pub fn get_remote_branch_status(
         &self,
         repo_path: &Path,
@@ -714,6 +1396,42 @@ impl GitService {
         self.get_branch_status_inner(&repo, &branch_ref, &base_branch_ref)
     }
 
+    /// Ahead/behind counts of `branch_name` against the remote's default branch
+    /// (e.g. `origin/main`). The remote is fetched first unless it was fetched
+    /// within [`REMOTE_FETCH_TTL`].
+    pub fn status_vs_remote_default(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        github_token: &str,
+    ) -> Result<(usize, usize), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let remote_name = self.default_remote_name(&repo);
+        let remote = repo.find_remote(&remote_name)?;
+
+        let key = (repo.commondir().to_path_buf(), remote_name.clone());
+        let fetched_recently = LAST_REMOTE_FETCH
+            .lock()
+            .unwrap()
+            .get(&key)
+            .is_some_and(|at| at.elapsed() < REMOTE_FETCH_TTL);
+        if !fetched_recently {
+            self.fetch_from_remote(&repo, github_token, &remote)?;
+            LAST_REMOTE_FETCH
+                .lock()
+                .unwrap()
+                .insert(key, Instant::now());
+        }
+
+        let remote_head = format!("refs/remotes/{remote_name}/HEAD");
+        let default_ref = repo
+            .find_reference(&remote_head)
+            .and_then(|head| head.resolve())
+            .map_err(|_| GitServiceError::BranchNotFound(format!("{remote_name}/HEAD")))?;
+        let branch_ref = Self::find_branch(&repo, branch_name)?.into_reference();
+        self.get_branch_status_inner(&repo, &branch_ref, &default_ref)
+    }
+
     pub fn is_worktree_clean(&self, worktree_path: &Path) -> Result<bool, GitServiceError> {
         let repo = self.open_repo(worktree_path)?;
         match self.check_worktree_clean(&repo) {
@@ -723,6 +1441,71 @@ impl GitService {
         }
     }
 
+    /// Like [`GitService::is_worktree_clean`], but a dirty worktree is reported
+    /// as [`GitServiceError::WorktreeDirty`] carrying the modified tracked files
+    pub fn ensure_worktree_clean(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        self.check_worktree_clean(&repo)
+    }
+
+    /// Keep `relative_path`, a file vibe-kanban writes into a worktree, out of
+    /// commits by listing it in the repository's `info/exclude`
+    pub fn exclude_path(
+        &self,
+        worktree_path: &Path,
+        relative_path: &Path,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let pattern = format!("/{}", relative_path.to_string_lossy().replace('\\', "/"));
+        let exclude_path = repo.commondir().join("info").join("exclude");
+        let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+        if existing.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
+        }
+        if let Some(parent) = exclude_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let separator = if existing.is_empty() || existing.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        std::fs::write(&exclude_path, format!("{existing}{separator}{pattern}\n"))?;
+        Ok(())
+    }
+
+    /// Check that `worktree_path` still has `branch_name` checked out.
+    /// Returns the branch found instead (`HEAD` when detached), or `None` if on
+    /// the expected branch. With `reattach`, a clean worktree is switched back
+    /// to `branch_name`; a dirty one is reported as [`GitServiceError::WorktreeDirty`].
+    pub fn ensure_on_branch(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+        reattach: bool,
+    ) -> Result<Option<String>, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let head = repo.head()?;
+        let current = if head.is_branch() {
+            head.shorthand().unwrap_or("HEAD").to_string()
+        } else {
+            "HEAD".to_string()
+        };
+        if current == branch_name {
+            return Ok(None);
+        }
+
+        if reattach {
+            self.check_worktree_clean(&repo)?;
+            let refname = format!("refs/heads/{branch_name}");
+            let target = repo.revparse_single(&refname)?;
+            repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+            repo.set_head(&refname)?;
+        }
+
+        Ok(Some(current))
+    }
+
     /// Check if the worktree is clean (no uncommitted changes to tracked files)
     fn check_worktree_clean(&self, repo: &Repository) -> Result<(), GitServiceError> {
         let mut status_options = git2::StatusOptions::new();
@@ -830,6 +1613,18 @@ impl GitService {
         ))
     }
 
+    /// Subject, author and date of the given commit OID (hex)
+    pub fn get_commit_info(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+    ) -> Result<CommitInfo, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let oid = git2::Oid::from_str(commit_sha)
+            .map_err(|_| GitServiceError::InvalidRepository("Invalid commit SHA".into()))?;
+        Ok(CommitInfo::from_commit(&repo.find_commit(oid)?))
+    }
+
     /// Get the subject/summary line for a given commit OID
     pub fn get_commit_subject(
         &self,
@@ -843,6 +1638,19 @@ impl GitService {
         Ok(commit.summary().unwrap_or("(no subject)").to_string())
     }
 
+    /// Committer date of the given commit OID (hex)
+    pub fn get_commit_date(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+    ) -> Result<DateTime<Utc>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let oid = git2::Oid::from_str(commit_sha)
+            .map_err(|_| GitServiceError::InvalidRepository("Invalid commit SHA".into()))?;
+        let commit = repo.find_commit(oid)?;
+        Ok(DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(Utc::now))
+    }
+
     /// Compare two OIDs and return (ahead, behind) counts: how many commits
     /// `from_oid` is ahead of and behind `to_oid`.
     pub fn ahead_behind_commits_by_oid(
@@ -872,6 +1680,53 @@ impl GitService {
         Ok((st.uncommitted_tracked, st.untracked))
     }
 
+    /// Staged, modified and, with `include_untracked`, untracked files in
+    /// `worktree_path`. Ignored files are never listed.
+    pub fn worktree_status(
+        &self,
+        worktree_path: &Path,
+        include_untracked: bool,
+    ) -> Result<WorktreeFileStatus, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let mut status_options = git2::StatusOptions::new();
+        status_options
+            .include_untracked(include_untracked)
+            .recurse_untracked_dirs(include_untracked)
+            .include_ignored(false);
+
+        let mut status = WorktreeFileStatus::default();
+        for entry in repo.statuses(Some(&mut status_options))?.iter() {
+            let Some(path) = entry.path() else {
+                continue;
+            };
+            let flags = entry.status();
+            if flags.intersects(
+                git2::Status::INDEX_NEW
+                    | git2::Status::INDEX_MODIFIED
+                    | git2::Status::INDEX_DELETED
+                    | git2::Status::INDEX_RENAMED
+                    | git2::Status::INDEX_TYPECHANGE,
+            ) {
+                status.staged.push(path.to_string());
+            }
+            if flags.intersects(
+                git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE,
+            ) {
+                status.modified.push(path.to_string());
+            }
+            if flags.contains(git2::Status::WT_NEW) {
+                status.untracked.push(path.to_string());
+            }
+        }
+        status.staged_count = status.staged.len();
+        status.modified_count = status.modified.len();
+        status.untracked_count = status.untracked.len();
+        Ok(status)
+    }
+
     /// Expose full worktree status details (CLI porcelain parsing)
     pub fn get_worktree_status(
         &self,
@@ -905,6 +1760,57 @@ impl GitService {
         Ok(())
     }
 
+    /// Stash uncommitted changes to tracked files in the worktree under `message`.
+    /// Returns the stash commit id, or `None` if there was nothing to stash.
+    pub fn stash_worktree(
+        &self,
+        worktree_path: &Path,
+        message: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let cli = GitCli::new();
+        let map_err = |e| GitServiceError::InvalidRepository(format!("git stash failed: {e}"));
+        let top = |cli: &GitCli| -> Result<Option<String>, GitServiceError> {
+            Ok(cli
+                .stash_list(worktree_path)
+                .map_err(map_err)?
+                .into_iter()
+                .next()
+                .map(|entry| entry.sha))
+        };
+
+        self.ensure_cli_commit_identity(worktree_path)?;
+        let before = top(&cli)?;
+        cli.stash_push(worktree_path, message).map_err(map_err)?;
+        let after = top(&cli)?;
+        Ok(after.filter(|sha| before.as_ref() != Some(sha)))
+    }
+
+    /// Restore and drop a stash, given either its commit id or its `stash@{n}` reference.
+    pub fn pop_stash(&self, worktree_path: &Path, stash_ref: &str) -> Result<(), GitServiceError> {
+        let entry = self
+            .list_stashes(worktree_path)?
+            .into_iter()
+            .find(|entry| entry.sha == stash_ref || entry.reference == stash_ref)
+            .ok_or_else(|| {
+                GitServiceError::InvalidRepository(format!("stash {stash_ref} not found"))
+            })?;
+        GitCli::new()
+            .stash_pop(worktree_path, &entry.reference)
+            .map_err(|e| {
+                GitServiceError::InvalidRepository(format!(
+                    "git stash pop {} failed: {e}",
+                    entry.reference
+                ))
+            })
+    }
+
+    /// List the stashes of the worktree, most recent first
+    pub fn list_stashes(&self, worktree_path: &Path) -> Result<Vec<StashEntry>, GitServiceError> {
+        GitCli::new()
+            .stash_list(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git stash list failed: {e}")))
+    }
+
     /// Convenience: Get author of HEAD commit
     pub fn get_head_author(
         &self,
@@ -940,6 +1846,31 @@ impl GitService {
         Ok(())
     }
 
+    /// Rename local branch `old_name` to `new_name`. Worktrees that have the
+    /// branch checked out follow the rename. A pushed branch keeps tracking its
+    /// existing remote branch, so open pull requests and the check against
+    /// amending pushed commits still see it.
+    pub fn rename_branch(
+        &self,
+        repo_path: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), GitServiceError> {
+        if !git2::Branch::name_is_valid(new_name)? {
+            return Err(GitServiceError::InvalidBranchName(new_name.to_string()));
+        }
+        let repo = self.open_repo(repo_path)?;
+        if repo.find_branch(new_name, BranchType::Local).is_ok() {
+            return Err(GitServiceError::BranchExists(new_name.to_string()));
+        }
+        let mut branch = repo
+            .find_branch(old_name, BranchType::Local)
+            .map_err(|_| GitServiceError::BranchNotFound(old_name.to_string()))?;
+        // Moves the `branch.<name>.*` config, upstream included, along with it
+        branch.rename(new_name, false)?;
+        Ok(())
+    }
+
     /// Checkout a local branch in the given working tree
     pub fn checkout_branch(
         &self,
@@ -986,6 +1917,20 @@ impl GitService {
         Ok(())
     }
 
+    /// Changes committed on `branch_name` since it forked from `base_branch`,
+    /// as a single patch that `git apply` or `git am` accept
+    pub fn format_patch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch: &str,
+        context_lines: usize,
+    ) -> Result<String, GitServiceError> {
+        GitCli::new()
+            .format_patch(repo_path, base_branch, branch_name, context_lines)
+            .map_err(|e| GitServiceError::from_cli("git diff", repo_path, e))
+    }
+
     /// Stage a specific path (wrapper over git add)
     pub fn add_path(&self, repo_path: &Path, path: &str) -> Result<(), GitServiceError> {
         let git = GitCli::new();
@@ -1069,27 +2014,66 @@ impl GitService {
         Ok(branches)
     }
 
-    /// Perform a squash merge of task branch into base branch, but fail on conflicts
+    /// Merge `branch_name` into `base_branch_name` in memory and report whether
+    /// it would conflict. Neither branch nor any working tree is touched.
+    pub fn preview_merge(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<MergePreview, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let task_commit = Self::find_branch(&repo, branch_name)?
+            .get()
+            .peel_to_commit()?;
+
+        let index = Self::merge_in_memory(&repo, &base_commit, &task_commit)?;
+        let conflicted_files: Vec<String> = Self::conflicts_in_index(&repo, &index)?
+            .into_iter()
+            .map(|conflict| conflict.path)
+            .collect();
+        Ok(MergePreview {
+            clean: conflicted_files.is_empty(),
+            conflicted_files,
+        })
+    }
+
+    /// Merge `task_commit` into `base_commit` without touching the working tree.
+    /// Conflicts are recorded in the returned index rather than failing the merge.
+    fn merge_in_memory(
+        repo: &Repository,
+        base_commit: &git2::Commit,
+        task_commit: &git2::Commit,
+    ) -> Result<git2::Index, GitServiceError> {
+        let mut merge_opts = git2::MergeOptions::new();
+        // Safety and correctness options
+        merge_opts.find_renames(true); // improve rename handling
+        merge_opts.fail_on_conflict(false);
+        Ok(repo.merge_commits(base_commit, task_commit, Some(&merge_opts))?)
+    }
+
+    /// Perform a squash merge of task branch into base branch, but fail on conflicts.
+    /// The commit is authored by `author` and committed by the repository identity.
     fn perform_squash_merge(
         &self,
         repo: &Repository,
         base_commit: &git2::Commit,
         task_commit: &git2::Commit,
-        signature: &git2::Signature,
+        author: &git2::Signature,
         commit_message: &str,
         base_branch_name: &str,
     ) -> Result<git2::Oid, GitServiceError> {
         // In-memory merge to detect conflicts without touching the working tree
-        let mut merge_opts = git2::MergeOptions::new();
-        // Safety and correctness options
-        merge_opts.find_renames(true); // improve rename handling
-        merge_opts.fail_on_conflict(true); // bail out instead of generating conflicted index
-        let mut index = repo.merge_commits(base_commit, task_commit, Some(&merge_opts))?;
+        let mut index = Self::merge_in_memory(repo, base_commit, task_commit)?;
 
-        // If there are conflicts, return an error
+        // If there are conflicts, return them; the index only lives in memory
         if index.has_conflicts() {
             return Err(GitServiceError::MergeConflicts(
                 "Merge failed due to conflicts. Please resolve conflicts manually.".to_string(),
+                Self::conflicts_in_index(repo, &index)?,
             ));
         }
 
@@ -1097,11 +2081,13 @@ impl GitService {
         let tree_id = index.write_tree_to(repo)?;
         let tree = repo.find_tree(tree_id)?;
 
+        let committer = self.signature_with_fallback(repo)?;
+
         // Create a squash commit: use merged tree with base_commit as sole parent
         let squash_commit_id = repo.commit(
             None,           // Don't update any reference yet
-            signature,      // Author
-            signature,      // Committer
+            author,         // Author
+            &committer,     // Committer
             commit_message, // Custom message
             &tree,          // Merged tree content
             &[base_commit], // Single parent: base branch commit
@@ -1114,6 +2100,67 @@ impl GitService {
         Ok(squash_commit_id)
     }
 
+    /// Abort the rebase or merge left in progress in `worktree_path`, returning the
+    /// worktree to its state before the operation. None when neither is in progress.
+    pub fn abort_in_progress_operation(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Option<GitOperation>, GitServiceError> {
+        let git = GitCli::new();
+        let cli_err = |e| GitServiceError::from_cli("abort", worktree_path, e);
+        if git.is_rebase_in_progress(worktree_path).map_err(cli_err)? {
+            git.rebase_abort(worktree_path).map_err(cli_err)?;
+            Ok(Some(GitOperation::Rebase))
+        } else if git.is_merge_in_progress(worktree_path).map_err(cli_err)? {
+            git.merge_abort(worktree_path).map_err(cli_err)?;
+            Ok(Some(GitOperation::Merge))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Files with unresolved conflicts in the index of `worktree_path`, e.g. after
+    /// a rebase stopped on conflicts
+    pub fn list_conflicts(
+        &self,
+        worktree_path: &Path,
+    ) -> Result<Vec<ConflictedFile>, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        let index = repo.index()?;
+        Self::conflicts_in_index(&repo, &index)
+    }
+
+    fn conflicts_in_index(
+        repo: &Repository,
+        index: &git2::Index,
+    ) -> Result<Vec<ConflictedFile>, GitServiceError> {
+        let blob_text = |entry: Option<&git2::IndexEntry>| {
+            let entry = entry?;
+            let blob = repo.find_blob(entry.id).ok()?;
+            String::from_utf8(blob.content().to_vec()).ok()
+        };
+
+        let mut conflicts = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+                .into_iter()
+                .flatten()
+                .next()
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            else {
+                continue;
+            };
+            conflicts.push(ConflictedFile {
+                path,
+                base: blob_text(conflict.ancestor.as_ref()),
+                ours: blob_text(conflict.our.as_ref()),
+                theirs: blob_text(conflict.their.as_ref()),
+            });
+        }
+        Ok(conflicts)
+    }
+
     /// Rebase a worktree branch onto a new base
     pub fn rebase_branch(
         &self,
@@ -1122,14 +2169,78 @@ impl GitService {
         new_base_branch: Option<&str>,
         old_base_branch: &str,
         github_token: Option<String>,
+        auto_stash: Option<&str>,
     ) -> Result<String, GitServiceError> {
         let worktree_repo = Repository::open(worktree_path)?;
-        let main_repo = self.open_repo(repo_path)?;
 
         // Safety guard: never operate on a dirty worktree. This preserves any
         // uncommitted changes to tracked files by failing fast instead of
         // resetting or cherry-picking over them. Untracked files are allowed.
-        self.check_worktree_clean(&worktree_repo)?;
+        // With `auto_stash`, the changes are stashed under that message instead
+        // and restored once the rebase succeeds.
+        let git = GitCli::new();
+        if auto_stash.is_some() && git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+            return Err(GitServiceError::RebaseInProgress);
+        }
+        let stash = match (self.check_worktree_clean(&worktree_repo), auto_stash) {
+            (Ok(()), _) => None,
+            (Err(GitServiceError::WorktreeDirty(..)), Some(message)) => {
+                self.stash_worktree(worktree_path, message)?
+            }
+            (Err(e), _) => return Err(e),
+        };
+
+        let result = self.rebase_branch_inner(
+            repo_path,
+            worktree_path,
+            new_base_branch,
+            old_base_branch,
+            github_token,
+        );
+
+        let Some(stash_sha) = stash else {
+            return result;
+        };
+        if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+            // Popping now would mix the stash into the conflicted rebase
+            tracing::warn!(
+                "Rebase of {} stopped; stashed changes kept in stash {}",
+                worktree_path.display(),
+                stash_sha
+            );
+            return result.map_err(|e| match e {
+                GitServiceError::MergeConflicts(message, files) => GitServiceError::MergeConflicts(
+                    format!("{message}; uncommitted changes are kept in stash {stash_sha}"),
+                    files,
+                ),
+                e => e,
+            });
+        }
+        if let Err(e) = self.pop_stash(worktree_path, &stash_sha) {
+            return Err(match result {
+                Ok(head) => GitServiceError::StashPopFailed(
+                    KeptStash {
+                        head,
+                        stash: stash_sha,
+                    },
+                    e.to_string(),
+                ),
+                Err(_) => e,
+            });
+        }
+        result
+    }
+
+    fn rebase_branch_inner(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        new_base_branch: Option<&str>,
+        old_base_branch: &str,
+        github_token: Option<String>,
+    ) -> Result<String, GitServiceError> {
+        let worktree_repo = Repository::open(worktree_path)?;
+        let main_repo = self.open_repo(repo_path)?;
 
         // If a rebase is already in progress, refuse to proceed instead of
         // aborting (which might destroy user changes mid-rebase).
@@ -1159,35 +2270,131 @@ impl GitService {
         // Ensure identity for any commits produced by rebase
         self.ensure_cli_commit_identity(worktree_path)?;
         // Use git CLI rebase to carry out the operation safely
-        git.rebase_onto(worktree_path, &new_base_branch_name, old_base_branch)
-            .map_err(|e| {
-                GitServiceError::InvalidRepository(format!("git rebase --onto failed: {e}"))
-            })?;
+        if let Err(e) = git.rebase_onto(worktree_path, &new_base_branch_name, old_base_branch) {
+            if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+                return Err(GitServiceError::MergeConflicts(
+                    format!(
+                        "Rebase onto {new_base_branch_name} stopped on conflicts; resolve or abort it in {}",
+                        worktree_path.display()
+                    ),
+                    self.list_conflicts(worktree_path)?,
+                ));
+            }
+            return Err(GitServiceError::InvalidRepository(format!(
+                "git rebase --onto failed: {e}"
+            )));
+        }
 
         // Return resulting HEAD commit
         let final_commit = worktree_repo.head()?.peel_to_commit()?;
         Ok(final_commit.id().to_string())
     }
 
+    /// Rebase the worktree's commits since `old_base_branch` onto an arbitrary
+    /// commit instead of a branch tip. Returns the new HEAD commit.
+    pub fn rebase_onto_commit(
+        &self,
+        repo_path: &Path,
+        worktree_path: &Path,
+        target_sha: &str,
+        old_base_branch: &str,
+    ) -> Result<String, GitServiceError> {
+        let worktree_repo = Repository::open(worktree_path)?;
+        let main_repo = self.open_repo(repo_path)?;
+
+        // Same guards as `rebase_branch`: never abort a rebase the user is in
+        // the middle of and never touch uncommitted changes.
+        let git = GitCli::new();
+        if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+            return Err(GitServiceError::RebaseInProgress);
+        }
+        self.check_worktree_clean(&worktree_repo)?;
+
+        let target_oid = git2::Oid::from_str(target_sha).map_err(|_| {
+            GitServiceError::InvalidRepository(format!("Invalid commit SHA: {target_sha}"))
+        })?;
+        let target = main_repo.find_commit(target_oid).map_err(|_| {
+            GitServiceError::InvalidRepository(format!("Commit not found: {target_sha}"))
+        })?;
+        let target_sha = target.id().to_string();
+
+        self.ensure_cli_commit_identity(worktree_path)?;
+        if let Err(e) = git.rebase_onto(worktree_path, &target_sha, old_base_branch) {
+            if git.is_rebase_in_progress(worktree_path).unwrap_or(false) {
+                return Err(GitServiceError::MergeConflicts(
+                    format!(
+                        "Rebase onto {target_sha} stopped on conflicts; resolve or abort it in {}",
+                        worktree_path.display()
+                    ),
+                    self.list_conflicts(worktree_path)?,
+                ));
+            }
+            return Err(GitServiceError::from_cli(
+                "git rebase --onto",
+                worktree_path,
+                e,
+            ));
+        }
+
+        let final_commit = worktree_repo.head()?.peel_to_commit()?;
+        Ok(final_commit.id().to_string())
+    }
+
+    /// Apply a single commit, e.g. from another branch, on top of the
+    /// worktree's HEAD and return the new commit. Conflicts are detected in
+    /// memory first, so a conflicting pick leaves the worktree untouched.
+    pub fn cherry_pick(
+        &self,
+        worktree_path: &Path,
+        commit_sha: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(worktree_path)?;
+        self.check_worktree_clean(&repo)?;
+
+        let commit_oid = git2::Oid::from_str(commit_sha).map_err(|_| {
+            GitServiceError::InvalidRepository(format!("Invalid commit SHA: {commit_sha}"))
+        })?;
+        let commit = repo.find_commit(commit_oid).map_err(|_| {
+            GitServiceError::InvalidRepository(format!("Commit not found: {commit_sha}"))
+        })?;
+        if commit.parent_count() > 1 {
+            return Err(GitServiceError::InvalidRepository(format!(
+                "Cannot cherry-pick merge commit {commit_sha}"
+            )));
+        }
+
+        let head = repo.head()?.peel_to_commit()?;
+        let index = repo.cherrypick_commit(&commit, &head, 0, None)?;
+        if index.has_conflicts() {
+            return Err(GitServiceError::MergeConflicts(
+                format!("Cherry-picking {commit_sha} conflicts with the worktree's branch"),
+                Self::conflicts_in_index(&repo, &index)?,
+            ));
+        }
+
+        self.ensure_cli_commit_identity(worktree_path)?;
+        GitCli::new()
+            .cherry_pick(worktree_path, &commit.id().to_string())
+            .map_err(|e| GitServiceError::from_cli("git cherry-pick", worktree_path, e))?;
+
+        Ok(repo.head()?.peel_to_commit()?.id().to_string())
+    }
+
     pub fn find_branch_type(
         &self,
         repo_path: &Path,
         branch_name: &str,
     ) -> Result<BranchType, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
-        // Try to find the branch as a local branch first
-        match repo.find_branch(branch_name, BranchType::Local) {
-            Ok(_) => Ok(BranchType::Local),
-            Err(_) => {
-                // If not found, try to find it as a remote branch
-                match repo.find_branch(branch_name, BranchType::Remote) {
-                    Ok(_) => Ok(BranchType::Remote),
-                    Err(_) => Err(GitServiceError::BranchNotFound(branch_name.to_string())),
-                }
-            }
+        let branch = Self::find_branch(&repo, branch_name)?;
+        if branch.get().is_remote() {
+            Ok(BranchType::Remote)
+        } else {
+            Ok(BranchType::Local)
         }
     }
 
+    /// Look up a branch by its exact name, local first, then remote
     pub fn find_branch<'a>(
         repo: &'a Repository,
         branch_name: &str,
@@ -1205,6 +2412,83 @@ impl GitService {
         }
     }
 
+    /// Resolve a branch name typed by a user to an existing branch: an exact
+    /// local or remote match, else a unique fuzzy match (e.g. `feature` for
+    /// `origin/feature`). Ambiguous or missing names yield `BranchNotFound`
+    /// listing candidate branches. Internal lookups use [`GitService::find_branch`].
+    pub fn resolve_branch_name(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<String, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        if Self::find_branch(&repo, branch_name).is_ok() {
+            return Ok(branch_name.to_string());
+        }
+        if branch_name.is_empty() {
+            return Err(GitServiceError::BranchNotFound(branch_name.to_string()));
+        }
+
+        let mut branches = Vec::new();
+        for entry in repo.branches(None)? {
+            let (branch, branch_type) = entry?;
+            if let Ok(Some(name)) = branch.name()
+                && !name.ends_with("/HEAD")
+            {
+                branches.push((name.to_string(), branch_type));
+            }
+        }
+
+        // A match on whole path segments (`origin/feature`) wins over a looser
+        // prefix/suffix match (`feature-login`)
+        let segment_matches: Vec<_> = branches
+            .iter()
+            .filter(|(name, _)| name.ends_with(&format!("/{branch_name}")))
+            .collect();
+        let candidates = if segment_matches.is_empty() {
+            branches
+                .iter()
+                .filter(|(name, branch_type)| {
+                    // Remote branches are prefix-matched without their remote name
+                    let short = match branch_type {
+                        BranchType::Remote => {
+                            name.split_once('/').map_or(name.as_str(), |(_, rest)| rest)
+                        }
+                        BranchType::Local => name.as_str(),
+                    };
+                    short.starts_with(branch_name) || name.ends_with(branch_name)
+                })
+                .collect()
+        } else {
+            segment_matches
+        };
+
+        match candidates.as_slice() {
+            [(name, _)] => Ok(name.clone()),
+            [] => {
+                let needle = branch_name.to_lowercase();
+                let suggestions: Vec<_> = branches
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .filter(|name| name.to_lowercase().contains(&needle))
+                    .take(5)
+                    .collect();
+                Err(GitServiceError::BranchNotFound(if suggestions.is_empty() {
+                    branch_name.to_string()
+                } else {
+                    format!("{branch_name} (did you mean: {}?)", suggestions.join(", "))
+                }))
+            }
+            _ => {
+                let names: Vec<_> = candidates.iter().map(|(name, _)| name.as_str()).collect();
+                Err(GitServiceError::BranchNotFound(format!(
+                    "{branch_name} is ambiguous (candidates: {})",
+                    names.join(", ")
+                )))
+            }
+        }
+    }
+
     /// Delete a file from the repository and commit the change
     pub fn delete_file_and_commit(
         &self,
@@ -1449,6 +2733,20 @@ impl GitService {
         let refspec = format!("+refs/heads/*:refs/remotes/{remote_name}/*");
 
         let fetch_result = temp_remote.fetch(&[&refspec], Some(&mut fetch_opts), None);
+        // Record the remote's default branch as <remote>/HEAD, like `git remote set-head --auto`
+        if fetch_result.is_ok()
+            && let Ok(default_branch) = temp_remote.default_branch()
+            && let Some(branch) = default_branch
+                .as_str()
+                .and_then(|name| name.strip_prefix("refs/heads/"))
+        {
+            let _ = repo.reference_symbolic(
+                &format!("refs/remotes/{remote_name}/HEAD"),
+                &format!("refs/remotes/{remote_name}/{branch}"),
+                true,
+                "fetch: update remote HEAD",
+            );
+        }
         // Clean up the temporary remote
         let _ = repo.remote_delete(temp_remote_name);
 
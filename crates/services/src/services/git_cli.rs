@@ -56,6 +56,18 @@ pub struct StatusDiffEntry {
     pub change: ChangeType,
     pub path: String,
     pub old_path: Option<String>,
+    /// Similarity score of a rename or copy, e.g. 87 for `R087`
+    pub similarity: Option<u8>,
+}
+
+/// One entry from `git stash list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashEntry {
+    /// Reflog selector, e.g. `stash@{0}`
+    pub reference: String,
+    /// Commit id of the stash
+    pub sha: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -154,8 +166,8 @@ impl GitCli {
             "--cached".into(),
             "-M".into(),
             "--name-status".into(),
-            OsString::from(base_branch),
         ];
+        args.push(OsString::from(base_branch));
         if let Some(paths) = &opts.path_filter {
             let non_empty_paths: Vec<&str> = paths
                 .iter()
@@ -241,6 +253,19 @@ impl GitCli {
         Ok(())
     }
 
+    /// Fold staged changes into the last commit, replacing its message when given.
+    pub fn commit_amend(
+        &self,
+        worktree_path: &Path,
+        message: Option<&str>,
+    ) -> Result<(), GitCliError> {
+        match message {
+            Some(message) => self.git(worktree_path, ["commit", "--amend", "-m", message])?,
+            None => self.git(worktree_path, ["commit", "--amend", "--no-edit"])?,
+        };
+        Ok(())
+    }
+
     // Parse `git diff --name-status` output into structured entries.
     // Handles rename/copy scores like `R100` by matching the first letter.
     fn parse_name_status(output: &str) -> Vec<StatusDiffEntry> {
@@ -270,6 +295,7 @@ impl GitCli {
                             change,
                             path: newp.to_string(),
                             old_path: Some(old.to_string()),
+                            similarity: code.get(1..).and_then(|score| score.parse().ok()),
                         });
                     }
                 }
@@ -279,6 +305,7 @@ impl GitCli {
                             change,
                             path: p.to_string(),
                             old_path: None,
+                            similarity: None,
                         });
                     }
                 }
@@ -312,6 +339,69 @@ impl GitCli {
         }
     }
 
+    /// Return true if there is a merge stopped on conflicts in this worktree.
+    pub fn is_merge_in_progress(&self, worktree_path: &Path) -> Result<bool, GitCliError> {
+        match self.git(worktree_path, ["rev-parse", "--verify", "MERGE_HEAD"]) {
+            Ok(_) => Ok(true),
+            Err(GitCliError::CommandFailed(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Abort the rebase in progress, restoring the branch to where it was before.
+    pub fn rebase_abort(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["rebase", "--abort"])?;
+        Ok(())
+    }
+
+    /// Abort the merge in progress, restoring the pre-merge state.
+    pub fn merge_abort(&self, worktree_path: &Path) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["merge", "--abort"])?;
+        Ok(())
+    }
+
+    /// Apply `commit_sha` on top of HEAD as a new commit. A pick that cannot be
+    /// completed is aborted so the worktree is not left mid-cherry-pick.
+    pub fn cherry_pick(&self, worktree_path: &Path, commit_sha: &str) -> Result<(), GitCliError> {
+        if let Err(e) = self.git(worktree_path, ["cherry-pick", commit_sha]) {
+            let _ = self.git(worktree_path, ["cherry-pick", "--abort"]);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Stash uncommitted changes to tracked files with the given message.
+    pub fn stash_push(&self, worktree_path: &Path, message: &str) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["stash", "push", "-m", message])?;
+        Ok(())
+    }
+
+    /// List stashes, most recent first.
+    pub fn stash_list(&self, worktree_path: &Path) -> Result<Vec<StashEntry>, GitCliError> {
+        let out = self.git(
+            worktree_path,
+            ["stash", "list", "--format=%gd%x00%H%x00%gs"],
+        )?;
+        Ok(out
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\0');
+                Some(StashEntry {
+                    reference: parts.next()?.to_string(),
+                    sha: parts.next()?.to_string(),
+                    message: parts.next().unwrap_or("").to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Apply the given stash (e.g. `stash@{0}`) and drop it. On conflicts git keeps
+    /// the stash and this returns an error.
+    pub fn stash_pop(&self, worktree_path: &Path, reference: &str) -> Result<(), GitCliError> {
+        self.git(worktree_path, ["stash", "pop", reference])?;
+        Ok(())
+    }
+
     /// Return true if there are staged changes (index differs from HEAD)
     pub fn has_staged_changes(&self, repo_path: &Path) -> Result<bool, GitCliError> {
         // `git diff --cached --quiet` returns exit code 1 if there are differences
@@ -337,18 +427,40 @@ impl GitCli {
         self.git(repo_path, ["reset"]).map(|_| ())
     }
 
-    /// Checkout base branch, squash-merge from_branch, and commit with message. Returns new HEAD sha.
+    /// Checkout base branch, squash-merge from_branch, and commit with message, attributed to
+    /// `author` ("Name <email>") when given. Returns new HEAD sha.
     pub fn merge_squash_commit(
         &self,
         repo_path: &Path,
         base_branch: &str,
         from_branch: &str,
         message: &str,
+        author: Option<&str>,
     ) -> Result<String, GitCliError> {
         self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
         self.git(repo_path, ["merge", "--squash", "--no-commit", from_branch])
             .map(|_| ())?;
-        self.git(repo_path, ["commit", "-m", message]).map(|_| ())?;
+        let mut args = vec!["commit", "-m", message];
+        if let Some(author) = author {
+            args.extend(["--author", author]);
+        }
+        self.git(repo_path, args).map(|_| ())?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Fast-forward the branch checked out in `repo_path` to from_branch; git
+    /// refuses if the branches diverged. Returns new HEAD sha.
+    pub fn merge_fast_forward(
+        &self,
+        repo_path: &Path,
+        from_branch: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["merge", "--ff-only", from_branch])
+            .map(|_| ())?;
         let sha = self
             .git(repo_path, ["rev-parse", "HEAD"])?
             .trim()
@@ -366,6 +478,69 @@ impl GitCli {
         self.git(repo_path, ["update-ref", refname, sha])
             .map(|_| ())
     }
+
+    /// Render the changes on `branch` since it forked from `base_branch` as one
+    /// `git format-patch`-style patch: a mail header from the branch head commit,
+    /// a diffstat, then the unified diff with `context_lines` of context around
+    /// each change. Binary files are reported as differing rather than inlined.
+    pub fn format_patch(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        branch: &str,
+        context_lines: usize,
+    ) -> Result<String, GitCliError> {
+        let merge_base = self
+            .git(repo_path, ["merge-base", base_branch, branch])?
+            .trim()
+            .to_string();
+        let head = self.git(
+            repo_path,
+            ["log", "-1", "--format=%H%n%an%n%ae%n%aD%n%s", branch],
+        )?;
+        let mut head = head.lines();
+        let mut field = || head.next().unwrap_or_default().to_string();
+        let (sha, name, email, date, subject) = (field(), field(), field(), field(), field());
+
+        let range = format!("{merge_base}..{branch}");
+        let context = format!("-U{context_lines}");
+        let commits = self.git(
+            repo_path,
+            ["log", "--reverse", "--format=%h %s", range.as_str()],
+        )?;
+        let diff = self.git(
+            repo_path,
+            [
+                "-c",
+                "core.quotepath=false",
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "-M",
+                "--stat",
+                "--summary",
+                "--patch",
+                context.as_str(),
+                merge_base.as_str(),
+                branch,
+            ],
+        )?;
+
+        let mut patch = format!(
+            "From {sha} Mon Sep 17 00:00:00 2001\nFrom: {name} <{email}>\nDate: {date}\nSubject: [PATCH] {subject}\n\n"
+        );
+        // Several commits are squashed into one patch; keep their subjects
+        if commits.lines().count() > 1 {
+            patch.push_str("Commits:\n");
+            for line in commits.lines() {
+                patch.push_str(&format!("  {line}\n"));
+            }
+            patch.push('\n');
+        }
+        patch.push_str("---\n");
+        patch.push_str(&diff);
+        Ok(patch)
+    }
 }
 
 // Private methods
@@ -1,8 +1,9 @@
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use backon::{ExponentialBuilder, Retryable};
 use db::models::merge::{MergeStatus, PullRequestInfo};
 use octocrab::{Octocrab, OctocrabBuilder};
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::info;
@@ -139,13 +140,26 @@ pub struct GitHubService {
 }
 
 impl GitHubService {
-    /// Create a new GitHub service with authentication
-    pub fn new(github_token: &str) -> Result<Self, GitHubServiceError> {
-        let client = OctocrabBuilder::new()
-            .personal_token(github_token.to_string())
-            .build()?;
+    /// Create a new GitHub service with authentication, sending `extra_headers`
+    /// with every request
+    pub fn new(
+        github_token: &str,
+        extra_headers: &HashMap<String, String>,
+    ) -> Result<Self, GitHubServiceError> {
+        let mut builder = OctocrabBuilder::new().personal_token(github_token.to_string());
+        for (name, value) in extra_headers {
+            // Header values may carry credentials, so only the name is ever logged
+            match HeaderName::from_bytes(name.trim().as_bytes()) {
+                Ok(header) if HeaderValue::from_str(value).is_ok() => {
+                    builder = builder.add_header(header, value.clone());
+                }
+                _ => tracing::warn!("Ignoring invalid GitHub API header {name:?}"),
+            }
+        }
 
-        Ok(Self { client })
+        Ok(Self {
+            client: builder.build()?,
+        })
     }
 
     pub async fn check_token(&self) -> Result<(), GitHubServiceError> {
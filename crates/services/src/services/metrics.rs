@@ -0,0 +1,153 @@
+//! Lightweight in-process metrics, exported in the Prometheus text format.
+
+use std::{
+    fmt::Write,
+    sync::{
+        OnceLock,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+};
+
+/// Set to `1` or `true` to serve `GET /metrics`
+pub const METRICS_ENV_VAR: &str = "VIBE_KANBAN_METRICS";
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Process-wide metrics registry
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::default)
+}
+
+/// Whether the metrics endpoint is enabled for this process
+pub fn metrics_enabled() -> bool {
+    std::env::var(METRICS_ENV_VAR)
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    active_executions: AtomicI64,
+    executions_started: AtomicU64,
+    executions_finished: AtomicU64,
+    events_emitted: AtomicU64,
+    pr_polls_succeeded: AtomicU64,
+    pr_polls_failed: AtomicU64,
+}
+
+/// Values read at scrape time rather than tracked by the registry
+#[derive(Debug, Default)]
+pub struct MetricsSnapshot {
+    pub tasks_by_status: Vec<(String, i64)>,
+    pub db_pool_size: u32,
+    pub db_pool_idle: usize,
+}
+
+impl Metrics {
+    pub fn execution_started(&self) {
+        self.active_executions.fetch_add(1, Ordering::Relaxed);
+        self.executions_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn execution_finished(&self) {
+        self.active_executions.fetch_sub(1, Ordering::Relaxed);
+        self.executions_finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn event_emitted(&self) {
+        self.events_emitted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn pr_poll(&self, success: bool) {
+        let counter = if success {
+            &self.pr_polls_succeeded
+        } else {
+            &self.pr_polls_failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self, snapshot: &MetricsSnapshot) -> String {
+        let mut out = String::new();
+        let load = |c: &AtomicU64| c.load(Ordering::Relaxed) as f64;
+
+        write_metric(
+            &mut out,
+            "vibe_kanban_active_executions",
+            "Execution processes currently running",
+            "gauge",
+            &[("", self.active_executions.load(Ordering::Relaxed) as f64)],
+        );
+        write_metric(
+            &mut out,
+            "vibe_kanban_executions_started_total",
+            "Execution processes started",
+            "counter",
+            &[("", load(&self.executions_started))],
+        );
+        write_metric(
+            &mut out,
+            "vibe_kanban_executions_finished_total",
+            "Execution processes that exited",
+            "counter",
+            &[("", load(&self.executions_finished))],
+        );
+
+        let tasks: Vec<(String, f64)> = snapshot
+            .tasks_by_status
+            .iter()
+            .map(|(status, count)| (format!("status=\"{status}\""), *count as f64))
+            .collect();
+        let tasks: Vec<(&str, f64)> = tasks.iter().map(|(l, v)| (l.as_str(), *v)).collect();
+        write_metric(
+            &mut out,
+            "vibe_kanban_tasks",
+            "Tasks by status",
+            "gauge",
+            &tasks,
+        );
+
+        write_metric(
+            &mut out,
+            "vibe_kanban_db_pool_connections",
+            "Database pool connections",
+            "gauge",
+            &[
+                ("state=\"open\"", snapshot.db_pool_size as f64),
+                ("state=\"idle\"", snapshot.db_pool_idle as f64),
+            ],
+        );
+        write_metric(
+            &mut out,
+            "vibe_kanban_events_emitted_total",
+            "Database change events emitted to clients",
+            "counter",
+            &[("", load(&self.events_emitted))],
+        );
+        write_metric(
+            &mut out,
+            "vibe_kanban_pr_polls_total",
+            "GitHub PR status checks",
+            "counter",
+            &[
+                ("result=\"success\"", load(&self.pr_polls_succeeded)),
+                ("result=\"failure\"", load(&self.pr_polls_failed)),
+            ],
+        );
+
+        out
+    }
+}
+
+fn write_metric(out: &mut String, name: &str, help: &str, kind: &str, samples: &[(&str, f64)]) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (labels, value) in samples {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{name} {value}");
+        } else {
+            let _ = writeln!(out, "{name}{{{labels}}} {value}");
+        }
+    }
+}
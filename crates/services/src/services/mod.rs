@@ -3,6 +3,7 @@ pub mod auth;
 pub mod config;
 pub mod container;
 pub mod events;
+pub mod execution_slots;
 pub mod file_ranker;
 pub mod file_search_cache;
 pub mod filesystem;
@@ -11,7 +12,9 @@ pub mod git;
 pub mod git_cli;
 pub mod github_service;
 pub mod image;
+pub mod metrics;
 pub mod notification;
 pub mod pr_monitor;
 pub mod sentry;
+pub mod webhook;
 pub mod worktree_manager;
@@ -1,5 +1,6 @@
 use std::sync::OnceLock;
 
+use async_trait::async_trait;
 use db::models::execution_process::{ExecutionContext, ExecutionProcessStatus};
 use utils;
 
@@ -13,6 +14,20 @@ use crate::services::config::NotificationConfig;
 /// Cache for WSL root path from PowerShell
 static WSL_ROOT_PATH_CACHE: OnceLock<Option<String>> = OnceLock::new();
 
+/// Delivers notifications; lets background services be exercised without
+/// playing sounds or showing toasts
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(&self, config: NotificationConfig, title: &str, message: &str);
+}
+
+#[async_trait]
+impl Notifier for NotificationService {
+    async fn send(&self, config: NotificationConfig, title: &str, message: &str) {
+        Self::notify(config, title, message).await;
+    }
+}
+
 impl NotificationService {
     pub async fn notify_execution_halted(mut config: NotificationConfig, ctx: &ExecutionContext) {
         // If the process was intentionally killed by user, suppress sound
@@ -20,6 +35,15 @@ impl NotificationService {
             config.sound_enabled = false;
         }
 
+        let event_enabled = match ctx.execution_process.status {
+            ExecutionProcessStatus::Completed => config.events.task_complete,
+            ExecutionProcessStatus::Failed => config.events.execution_failed,
+            _ => true,
+        };
+        if !event_enabled {
+            return;
+        }
+
         let title = format!("Task Complete: {}", ctx.task.title);
         let message = match ctx.execution_process.status {
             ExecutionProcessStatus::Completed => format!(
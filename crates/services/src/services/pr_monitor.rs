@@ -1,4 +1,8 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use db::{
     DBService,
@@ -12,10 +16,13 @@ use sqlx::error::Error as SqlxError;
 use thiserror::Error;
 use tokio::{sync::RwLock, time::interval};
 use tracing::{debug, error, info};
+use uuid::Uuid;
 
 use crate::services::{
-    config::Config,
+    config::{Config, NotificationConfig},
     github_service::{GitHubRepoInfo, GitHubService, GitHubServiceError},
+    metrics::metrics,
+    notification::{NotificationService, Notifier},
 };
 
 #[derive(Debug, Error)]
@@ -35,6 +42,39 @@ pub struct PrMonitorService {
     db: DBService,
     config: Arc<RwLock<Config>>,
     poll_interval: Duration,
+    merge_notifications: MergeNotifications,
+}
+
+/// Announces each merged PR at most once
+struct MergeNotifications {
+    notifier: Arc<dyn Notifier>,
+    notified: Mutex<HashSet<Uuid>>,
+}
+
+impl MergeNotifications {
+    fn new(notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            notifier,
+            notified: Mutex::default(),
+        }
+    }
+
+    async fn pr_merged(&self, config: NotificationConfig, pr_merge: &PrMerge, task_title: &str) {
+        if !config.events.pr_merged {
+            return;
+        }
+        if !self.notified.lock().unwrap().insert(pr_merge.id) {
+            debug!("Already notified about PR #{}", pr_merge.pr_info.number);
+            return;
+        }
+
+        let title = format!("PR Merged: {task_title}");
+        let message = format!(
+            "🎉 PR #{} for '{}' was merged into {}",
+            pr_merge.pr_info.number, task_title, pr_merge.target_branch_name
+        );
+        self.notifier.send(config, &title, &message).await;
+    }
 }
 
 impl PrMonitorService {
@@ -43,6 +83,7 @@ impl PrMonitorService {
             db,
             config,
             poll_interval: Duration::from_secs(60), // Check every minute
+            merge_notifications: MergeNotifications::new(Arc::new(NotificationService {})),
         };
         tokio::spawn(async move {
             service.start().await;
@@ -77,7 +118,9 @@ impl PrMonitorService {
         info!("Checking {} open PRs", open_prs.len());
 
         for pr_merge in open_prs {
-            if let Err(e) = self.check_pr_status(&pr_merge).await {
+            let result = self.check_pr_status(&pr_merge).await;
+            metrics().pr_poll(result.is_ok());
+            if let Err(e) = result {
                 error!(
                     "Error checking PR #{} for attempt {}: {}",
                     pr_merge.pr_info.number, pr_merge.task_attempt_id, e
@@ -92,7 +135,7 @@ impl PrMonitorService {
         let github_config = self.config.read().await.github.clone();
         let github_token = github_config.token().ok_or(PrMonitorError::NoGitHubToken)?;
 
-        let github_service = GitHubService::new(&github_token)?;
+        let github_service = GitHubService::new(&github_token, &github_config.extra_headers)?;
 
         let repo_info = GitHubRepoInfo::from_pr_url(&pr_merge.pr_info.url)?;
 
@@ -126,9 +169,90 @@ impl PrMonitorService {
                     pr_merge.pr_info.number, task_attempt.task_id
                 );
                 Task::update_status(&self.db.pool, task_attempt.task_id, TaskStatus::Done).await?;
+
+                if let Some(task) = Task::find_by_id(&self.db.pool, task_attempt.task_id).await? {
+                    let notifications = self.config.read().await.notifications.clone();
+                    self.merge_notifications
+                        .pr_merged(notifications, pr_merge, &task.title)
+                        .await;
+                }
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use db::models::merge::PullRequestInfo;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingNotifier {
+        sent: AtomicUsize,
+        last_message: Mutex<Option<String>>,
+    }
+
+    #[async_trait]
+    impl Notifier for CountingNotifier {
+        async fn send(&self, _config: NotificationConfig, _title: &str, message: &str) {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            *self.last_message.lock().unwrap() = Some(message.to_string());
+        }
+    }
+
+    fn merged_pr() -> PrMerge {
+        PrMerge {
+            id: Uuid::new_v4(),
+            task_attempt_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            target_branch_name: "main".to_string(),
+            pr_info: PullRequestInfo {
+                number: 42,
+                url: "https://github.com/owner/repo/pull/42".to_string(),
+                status: MergeStatus::Merged,
+                merged_at: Some(Utc::now()),
+                merge_commit_sha: Some("abc123".to_string()),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn merged_pr_notifies_once() {
+        let notifier = Arc::new(CountingNotifier::default());
+        let notifications = MergeNotifications::new(notifier.clone());
+        let pr = merged_pr();
+
+        // The same merge can be observed by more than one poll
+        for _ in 0..2 {
+            notifications
+                .pr_merged(NotificationConfig::default(), &pr, "Fix login")
+                .await;
+        }
+
+        assert_eq!(notifier.sent.load(Ordering::SeqCst), 1);
+        let message = notifier.last_message.lock().unwrap().clone().unwrap();
+        assert!(message.contains("PR #42"));
+        assert!(message.contains("Fix login"));
+    }
+
+    #[tokio::test]
+    async fn merged_pr_respects_event_setting() {
+        let notifier = Arc::new(CountingNotifier::default());
+        let notifications = MergeNotifications::new(notifier.clone());
+        let mut config = NotificationConfig::default();
+        config.events.pr_merged = false;
+
+        notifications
+            .pr_merged(config, &merged_pr(), "Fix login")
+            .await;
+
+        assert_eq!(notifier.sent.load(Ordering::SeqCst), 0);
+    }
+}
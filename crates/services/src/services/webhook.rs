@@ -0,0 +1,384 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+        project::Project,
+        task::{Task, TaskStatus, TaskWithAttemptStatus},
+        task_attempt::TaskAttempt,
+    },
+};
+use hmac::{Hmac, Mac};
+use json_patch::{Patch, PatchOperation};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::{
+    RwLock,
+    broadcast::{Receiver, error::RecvError},
+};
+use tracing::{debug, error, warn};
+use utils::log_msg::LogMsg;
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, WebhookConfig, WebhookEvent},
+    events::EventService,
+};
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Give up on a single delivery attempt after this long
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to webhook endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub timestamp: DateTime<Utc>,
+    pub task: TaskWithAttemptStatus,
+    /// Status before a `task_status_changed` event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<TaskStatus>,
+    /// The merged pull request of a `pr_merged` event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull_request: Option<PullRequestInfo>,
+}
+
+impl WebhookPayload {
+    fn new(event: WebhookEvent, task: &TaskWithAttemptStatus) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now(),
+            task: task.clone(),
+            previous_status: None,
+            pull_request: None,
+        }
+    }
+}
+
+/// `sha256=`-prefixed hex HMAC-SHA256 of `body`, keyed with `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Watches task patches on the event stream and POSTs them to the configured
+/// webhooks
+pub struct WebhookService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    client: reqwest::Client,
+    /// Last status seen for each task, to tell creations and status changes apart
+    statuses: HashMap<Uuid, TaskStatus>,
+    /// PR merges already delivered
+    announced_prs: HashSet<Uuid>,
+}
+
+impl WebhookService {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        events: &EventService,
+    ) -> tokio::task::JoinHandle<()> {
+        // Subscribe before loading existing tasks so no change is missed
+        let receiver = events.msg_store().get_receiver();
+        let mut service = Self::new(db, config);
+        tokio::spawn(async move {
+            if let Err(e) = service.load_statuses().await {
+                error!("Failed to load task statuses for webhooks: {}", e);
+            }
+            service.run(receiver).await;
+        })
+    }
+
+    fn new(db: DBService, config: Arc<RwLock<Config>>) -> Self {
+        Self {
+            db,
+            config,
+            client: reqwest::Client::builder()
+                .timeout(DELIVERY_TIMEOUT)
+                .build()
+                .expect("Failed to build webhook client"),
+            statuses: HashMap::new(),
+            announced_prs: HashSet::new(),
+        }
+    }
+
+    /// Record the current status of every task, so only later changes fire
+    async fn load_statuses(&mut self) -> Result<(), sqlx::Error> {
+        for project in Project::find_all(&self.db.pool).await? {
+            for task in Task::find_by_project_id(&self.db.pool, project.id).await? {
+                self.statuses.insert(task.id, task.status);
+            }
+        }
+        Ok(())
+    }
+
+    async fn run(mut self, mut receiver: Receiver<LogMsg>) {
+        loop {
+            match receiver.recv().await {
+                Ok(LogMsg::JsonPatch(patch)) => self.handle_patch(patch).await,
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Webhook service missed {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn handle_patch(&mut self, patch: Patch) {
+        for operation in patch.0 {
+            let value = match operation {
+                PatchOperation::Add(op) if op.path.as_str().starts_with("/tasks/") => op.value,
+                PatchOperation::Replace(op) if op.path.as_str().starts_with("/tasks/") => op.value,
+                // Removed tasks keep their status, so restoring one is not a creation
+                _ => continue,
+            };
+            let Ok(task) = serde_json::from_value::<TaskWithAttemptStatus>(value) else {
+                continue;
+            };
+            for payload in self.payloads_for(&task).await {
+                self.dispatch(payload).await;
+            }
+        }
+    }
+
+    /// Events fired by `task`'s latest state
+    async fn payloads_for(&mut self, task: &TaskWithAttemptStatus) -> Vec<WebhookPayload> {
+        let previous = self.statuses.insert(task.id, task.status.clone());
+        let mut payloads = Vec::new();
+        match previous {
+            None => payloads.push(WebhookPayload::new(WebhookEvent::TaskCreated, task)),
+            Some(previous) if previous != task.status => {
+                payloads.push(WebhookPayload {
+                    previous_status: Some(previous),
+                    ..WebhookPayload::new(WebhookEvent::TaskStatusChanged, task)
+                });
+                // The PR monitor marks tasks done once their PR merges
+                if task.status == TaskStatus::Done
+                    && let Some(pr_merge) = self.merged_pr(task.id).await
+                {
+                    payloads.push(WebhookPayload {
+                        pull_request: Some(pr_merge.pr_info),
+                        ..WebhookPayload::new(WebhookEvent::PrMerged, task)
+                    });
+                }
+            }
+            Some(_) => {}
+        }
+        payloads
+    }
+
+    /// A merged PR of `task_id` that has not been announced yet
+    async fn merged_pr(&mut self, task_id: Uuid) -> Option<PrMerge> {
+        let attempts = match TaskAttempt::fetch_all(&self.db.pool, Some(task_id)).await {
+            Ok(attempts) => attempts,
+            Err(e) => {
+                error!("Failed to load attempts of task {}: {}", task_id, e);
+                return None;
+            }
+        };
+        for attempt in attempts {
+            let Ok(merges) = Merge::find_by_task_attempt_id(&self.db.pool, attempt.id).await else {
+                continue;
+            };
+            for merge in merges.into_iter().rev() {
+                if let Merge::Pr(pr_merge) = merge
+                    && matches!(pr_merge.pr_info.status, MergeStatus::Merged)
+                    && self.announced_prs.insert(pr_merge.id)
+                {
+                    return Some(pr_merge);
+                }
+            }
+        }
+        None
+    }
+
+    /// Send `payload` to every webhook subscribed to its event
+    async fn dispatch(&self, payload: WebhookPayload) {
+        let webhooks: Vec<WebhookConfig> = self
+            .config
+            .read()
+            .await
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.events.contains(&payload.event))
+            .cloned()
+            .collect();
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+        for webhook in webhooks {
+            let client = self.client.clone();
+            let body = body.clone();
+            // Slow endpoints must not hold up the event stream
+            tokio::spawn(async move {
+                match deliver(&client, &webhook, body).await {
+                    Ok(()) => debug!("Delivered webhook to {}", webhook.url),
+                    Err(e) => error!("Webhook delivery to {} failed: {}", webhook.url, e),
+                }
+            });
+        }
+    }
+}
+
+/// POST `body` to `webhook`, retrying failed deliveries with backoff
+async fn deliver(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    body: String,
+) -> Result<(), reqwest::Error> {
+    let signature = sign(&webhook.secret, body.as_bytes());
+    (|| async {
+        client
+            .post(&webhook.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    })
+    .retry(
+        &ExponentialBuilder::default()
+            .with_min_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(30))
+            .with_max_times(3)
+            .with_jitter(),
+    )
+    .notify(|err: &reqwest::Error, dur: Duration| {
+        warn!(
+            "Webhook delivery to {} failed, retrying after {:.2}s: {}",
+            webhook.url,
+            dur.as_secs_f64(),
+            err
+        );
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, extract::State, http::HeaderMap, routing::post};
+    use serde_json::Value;
+    use sqlx::SqlitePool;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::services::events::task_patch;
+
+    fn task(status: TaskStatus) -> TaskWithAttemptStatus {
+        TaskWithAttemptStatus {
+            id: Uuid::nil(),
+            project_id: Uuid::nil(),
+            title: "Fix login".to_string(),
+            description: None,
+            status,
+            parent_task_attempt: None,
+            estimate_minutes: None,
+            assignee: None,
+            is_pinned: false,
+            pin_order: None,
+            actual_minutes: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            has_in_progress_attempt: false,
+            has_merged_attempt: false,
+            last_attempt_failed: false,
+            executor: "CLAUDE_CODE".to_string(),
+            dependency_ids: Vec::new(),
+        }
+    }
+
+    /// Serve a local endpoint that forwards each request's headers and body
+    async fn mock_endpoint() -> (String, mpsc::UnboundedReceiver<(HeaderMap, String)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State(tx): State<mpsc::UnboundedSender<(HeaderMap, String)>>,
+                     headers: HeaderMap,
+                     body: String| async move {
+                        tx.send((headers, body)).unwrap();
+                    },
+                ),
+            )
+            .with_state(tx);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, rx)
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn status_change_posts_signed_payload() {
+        let (url, mut requests) = mock_endpoint().await;
+        let config = Config {
+            webhooks: vec![WebhookConfig {
+                url,
+                secret: "s3cret".to_string(),
+                events: vec![WebhookEvent::TaskStatusChanged],
+            }],
+            ..Config::default()
+        };
+        let db = DBService {
+            pool: SqlitePool::connect("sqlite::memory:").await.unwrap(),
+        };
+        let mut service = WebhookService::new(db, Arc::new(RwLock::new(config)));
+
+        // Creation is not subscribed to, so only the status change is sent
+        service
+            .handle_patch(task_patch::add(&task(TaskStatus::Todo)))
+            .await;
+        service
+            .handle_patch(task_patch::replace(&task(TaskStatus::InProgress)))
+            .await;
+
+        let (headers, body) = tokio::time::timeout(Duration::from_secs(5), requests.recv())
+            .await
+            .expect("webhook delivered")
+            .unwrap();
+        assert_eq!(headers[CONTENT_TYPE], "application/json");
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            sign("s3cret", body.as_bytes())
+        );
+        let payload: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["event"], "task_status_changed");
+        assert_eq!(payload["previous_status"], "todo");
+        assert_eq!(payload["task"]["status"], "inprogress");
+        assert_eq!(payload["task"]["title"], "Fix login");
+        assert!(payload.get("pull_request").is_none());
+        assert!(requests.try_recv().is_err());
+    }
+}
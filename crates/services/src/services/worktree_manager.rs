@@ -4,10 +4,11 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use chrono::{DateTime, Duration, Utc};
 use git2::{Error as GitError, Repository};
 use thiserror::Error;
 use tracing::{debug, info};
-use utils::shell::get_shell_command;
+use utils::{disk, shell::get_shell_command};
 
 use super::{
     git::{GitService, GitServiceError},
@@ -38,8 +39,24 @@ pub enum WorktreeError {
     BranchNotFound(String),
     #[error("Repository error: {0}")]
     Repository(String),
+    #[error("Insufficient disk space: {0}")]
+    InsufficientDiskSpace(String),
 }
 
+impl WorktreeError {
+    /// Wrap an IO error on `path`, reporting a full disk as [`WorktreeError::InsufficientDiskSpace`]
+    fn from_io(err: std::io::Error, path: &Path) -> Self {
+        if disk::is_storage_full(&err) {
+            WorktreeError::InsufficientDiskSpace(path.display().to_string())
+        } else {
+            WorktreeError::Io(err)
+        }
+    }
+}
+
+/// Free space required on the worktree filesystem before creating a worktree
+const MIN_FREE_SPACE_FOR_WORKTREE: u64 = 100 * 1024 * 1024;
+
 pub struct WorktreeManager;
 
 impl WorktreeManager {
@@ -142,12 +159,17 @@ impl WorktreeManager {
         // Step 2: Ensure parent directory exists (non-blocking)
         if let Some(parent) = worktree_path_owned.parent() {
             let parent_path = parent.to_path_buf();
-            tokio::task::spawn_blocking(move || std::fs::create_dir_all(&parent_path))
-                .await
-                .map_err(|e| WorktreeError::TaskJoin(format!("Task join error: {e}")))?
-                .map_err(WorktreeError::Io)?;
+            tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&parent_path)
+                    .map_err(|e| WorktreeError::from_io(e, &parent_path))
+            })
+            .await
+            .map_err(|e| WorktreeError::TaskJoin(format!("Task join error: {e}")))??;
         }
 
+        // Fail early with a clear error instead of a half-written checkout
+        Self::ensure_free_space(&worktree_path_owned)?;
+
         // Step 3: Create the worktree with retry logic for metadata conflicts (non-blocking)
         Self::create_worktree_with_retry(
             git_repo_path,
@@ -326,7 +348,11 @@ impl WorktreeManager {
                         git.worktree_add(&git_repo_path, &worktree_path, &branch_name, false)
                     {
                         debug!("Retry of git worktree add failed: {}", e2);
-                        return Err(WorktreeError::GitCli(e2.to_string()));
+                        let message = e2.to_string();
+                        if disk::is_storage_full_message(&message) {
+                            return Err(WorktreeError::InsufficientDiskSpace(path_str));
+                        }
+                        return Err(WorktreeError::GitCli(message));
                     }
                     if !worktree_path.exists() {
                         return Err(WorktreeError::Repository(format!(
@@ -436,11 +462,12 @@ impl WorktreeManager {
         let worktree_path_owned = worktree_path.to_path_buf();
 
         tokio::task::spawn_blocking(move || {
-            let (shell_cmd, shell_arg) = get_shell_command();
+            let (shell_cmd, shell_args) = get_shell_command();
             let git_command = "git rev-parse --git-common-dir";
 
             let output = std::process::Command::new(shell_cmd)
-                .args([shell_arg, git_command])
+                .args(shell_args)
+                .arg(git_command)
                 .current_dir(&worktree_path_owned)
                 .output()
                 .ok()?;
@@ -484,8 +511,122 @@ impl WorktreeManager {
         .map_err(|e| WorktreeError::TaskJoin(format!("{e}")))?
     }
 
+    /// Check that the filesystem holding `worktree_path` has room for a new worktree
+    pub fn ensure_free_space(worktree_path: &Path) -> Result<(), WorktreeError> {
+        match disk::available_space(worktree_path) {
+            Some(available) if available < MIN_FREE_SPACE_FOR_WORKTREE => {
+                Err(WorktreeError::InsufficientDiskSpace(format!(
+                    "{} ({} MB free, {} MB required)",
+                    worktree_path.display(),
+                    available / (1024 * 1024),
+                    MIN_FREE_SPACE_FOR_WORKTREE / (1024 * 1024)
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Get the base directory for vibe-kanban worktrees
     pub fn get_worktree_base_dir() -> std::path::PathBuf {
         utils::path::get_vibe_kanban_temp_dir().join("worktrees")
     }
+
+    /// Pick which worktrees to prune given `candidates` ordered from least to most
+    /// recently active. Candidates older than `max_age_days` are always selected; the
+    /// oldest remaining ones are selected while more than `max_count` worktrees
+    /// (out of `live_count`) would remain on disk.
+    pub fn select_worktrees_to_prune<T: Copy>(
+        candidates: &[(T, DateTime<Utc>)],
+        live_count: usize,
+        max_count: Option<u32>,
+        max_age_days: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> Vec<T> {
+        let age_cutoff = max_age_days.map(|days| now - Duration::days(days as i64));
+        let mut remaining = live_count;
+        candidates
+            .iter()
+            .filter(|(_, last_activity)| {
+                let too_many = max_count.is_some_and(|max| remaining > max as usize);
+                let too_old = age_cutoff.is_some_and(|cutoff| *last_activity < cutoff);
+                if too_many || too_old {
+                    remaining = remaining.saturating_sub(1);
+                    true
+                } else {
+                    false
+                }
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stale_candidates(now: DateTime<Utc>) -> Vec<(u32, DateTime<Utc>)> {
+        vec![
+            (1, now - Duration::days(30)),
+            (2, now - Duration::days(10)),
+            (3, now - Duration::days(5)),
+            (4, now - Duration::days(1)),
+        ]
+    }
+
+    #[test]
+    fn test_no_limits_prunes_nothing() {
+        let now = Utc::now();
+        let pruned =
+            WorktreeManager::select_worktrees_to_prune(&stale_candidates(now), 4, None, None, now);
+        assert!(pruned.is_empty());
+    }
+
+    #[test]
+    fn test_count_limit_prunes_oldest_first() {
+        let now = Utc::now();
+        // Six worktrees on disk, two of which belong to running attempts
+        let pruned = WorktreeManager::select_worktrees_to_prune(
+            &stale_candidates(now),
+            6,
+            Some(3),
+            None,
+            now,
+        );
+        assert_eq!(pruned, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_age_limit_prunes_stale_worktrees() {
+        let now = Utc::now();
+        let pruned = WorktreeManager::select_worktrees_to_prune(
+            &stale_candidates(now),
+            4,
+            None,
+            Some(7),
+            now,
+        );
+        assert_eq!(pruned, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_count_and_age_limits_combine() {
+        let now = Utc::now();
+        let pruned = WorktreeManager::select_worktrees_to_prune(
+            &stale_candidates(now),
+            4,
+            Some(3),
+            Some(20),
+            now,
+        );
+        assert_eq!(pruned, vec![1]);
+        let pruned = WorktreeManager::select_worktrees_to_prune(
+            &stale_candidates(now),
+            4,
+            Some(1),
+            Some(20),
+            now,
+        );
+        assert_eq!(pruned, vec![1, 2, 3]);
+    }
 }
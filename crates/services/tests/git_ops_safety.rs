@@ -5,8 +5,10 @@ use std::{
 };
 
 use git2::{Repository, build::CheckoutBuilder};
-use services::services::git::GitService;
-use services::services::git_cli::GitCli; // used only to set up sparse-checkout
+use services::services::git::{
+    GitOperation, GitService, GitServiceError, MergeOptions, MergeOutcome,
+};
+use services::services::git_cli::GitCli; // used only to set up sparse-checkout and manual merges
 use tempfile::TempDir;
 // Avoid direct git CLI usage in tests; exercise GitService instead.
 
@@ -233,6 +235,7 @@ fn rebase_preserves_untracked_files() {
         Some("new-base"),
         "old-base",
         None,
+        None,
     );
     assert!(res.is_ok(), "rebase should succeed: {res:?}");
 
@@ -255,6 +258,7 @@ fn rebase_aborts_on_uncommitted_tracked_changes() {
         Some("new-base"),
         "old-base",
         None,
+        None,
     );
     assert!(res.is_err(), "rebase should fail on dirty worktree");
 
@@ -262,6 +266,99 @@ fn rebase_aborts_on_uncommitted_tracked_changes() {
     assert_eq!(edited, "feat change (edited)\n");
 }
 
+#[test]
+fn stash_and_pop_restores_tracked_changes() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    let service = GitService::new();
+    assert_eq!(
+        service
+            .stash_worktree(&worktree_path, "nothing to stash")
+            .unwrap(),
+        None
+    );
+
+    write_file(&worktree_path, "feat.txt", "feat change (edited)\n");
+    let stash_sha = service
+        .stash_worktree(&worktree_path, "attempt 1234 before rebase")
+        .unwrap()
+        .expect("changes should be stashed");
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("feat.txt")).unwrap(),
+        "feat change\n"
+    );
+
+    let stashes = service.list_stashes(&worktree_path).unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert_eq!(stashes[0].sha, stash_sha);
+    assert!(stashes[0].message.contains("attempt 1234 before rebase"));
+
+    service.pop_stash(&worktree_path, &stash_sha).unwrap();
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("feat.txt")).unwrap(),
+        "feat change (edited)\n"
+    );
+    assert!(service.list_stashes(&worktree_path).unwrap().is_empty());
+}
+
+#[test]
+fn rebase_auto_stashes_uncommitted_tracked_changes() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    write_file(&worktree_path, "feat.txt", "feat change (edited)\n");
+
+    let service = GitService::new();
+    let res = service.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        Some("new-base"),
+        "old-base",
+        None,
+        Some("auto-stash for task attempt 1234"),
+    );
+    assert!(
+        res.is_ok(),
+        "rebase should succeed with auto-stash: {res:?}"
+    );
+
+    // Rebased onto new-base and the edit is restored
+    let base = fs::read_to_string(worktree_path.join("base.txt")).unwrap();
+    assert_eq!(base, "from new-base\n");
+    let edited = fs::read_to_string(worktree_path.join("feat.txt")).unwrap();
+    assert_eq!(edited, "feat change (edited)\n");
+    assert!(service.list_stashes(&worktree_path).unwrap().is_empty());
+}
+
+#[test]
+fn rebase_reports_head_and_stash_when_changes_cannot_be_restored() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+
+    // new-base changes the same line, so the stash no longer applies cleanly
+    write_file(&worktree_path, "base.txt", "my uncommitted edit\n");
+
+    let service = GitService::new();
+    let res = service.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        Some("new-base"),
+        "old-base",
+        None,
+        Some("auto-stash for task attempt 1234"),
+    );
+    let Err(GitServiceError::StashPopFailed(kept, _)) = res else {
+        panic!("expected the stash to be kept: {res:?}");
+    };
+
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    let head = wt_repo.head().unwrap().peel_to_commit().unwrap().id();
+    assert_eq!(kept.head, head.to_string());
+    let stashes = service.list_stashes(&worktree_path).unwrap();
+    assert!(stashes.iter().any(|entry| entry.sha == kept.stash));
+}
+
 #[test]
 fn rebase_aborts_if_untracked_would_be_overwritten_by_base() {
     let td = TempDir::new().unwrap();
@@ -276,6 +373,7 @@ fn rebase_aborts_if_untracked_would_be_overwritten_by_base() {
         Some("new-base"),
         "old-base",
         None,
+        None,
     );
     assert!(
         res.is_err(),
@@ -306,6 +404,7 @@ fn merge_does_not_overwrite_main_repo_untracked_files() {
         "feature",
         "main",
         "squash merge",
+        &MergeOptions::default(),
     );
     assert!(
         res.is_err(),
@@ -349,6 +448,7 @@ fn merge_does_not_touch_tracked_uncommitted_changes_in_base_worktree() {
         "feature",
         "main",
         "squash merge",
+        &MergeOptions::default(),
     );
     assert!(
         res.is_ok(),
@@ -378,7 +478,14 @@ fn merge_refuses_with_staged_changes_on_base() {
     // main has staged change
     write_file(&repo_path, "staged.txt", "staged\n");
     s.add_path(&repo_path, "staged.txt").unwrap();
-    let res = s.merge_changes(&repo_path, &worktree_path, "feature", "main", "squash");
+    let res = s.merge_changes(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        "squash",
+        &MergeOptions::default(),
+    );
     assert!(res.is_err(), "should refuse merge due to staged changes");
     // staged file remains
     let content = std::fs::read_to_string(repo_path.join("staged.txt")).unwrap();
@@ -402,8 +509,16 @@ fn merge_preserves_unstaged_changes_on_base() {
     let wt_repo = Repository::open(&worktree_path).unwrap();
     commit_all(&wt_repo, "feature merged");
     let _sha = s
-        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
-        .unwrap();
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .unwrap()
+        .sha;
     // local edit preserved
     let loc = std::fs::read_to_string(repo_path.join("local.txt")).unwrap();
     assert_eq!(loc, "local edited\n");
@@ -427,8 +542,16 @@ fn update_ref_does_not_destroy_feature_worktree_dirty_state() {
     write_file(&worktree_path, "dirty.txt", "unstaged\n");
     // merge from feature into main (CLI path updates task ref via update-ref)
     let sha = s
-        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
-        .unwrap();
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .unwrap()
+        .sha;
     // uncommitted change in feature worktree preserved
     let dirty = std::fs::read_to_string(worktree_path.join("dirty.txt")).unwrap();
     assert_eq!(dirty, "unstaged\n");
@@ -455,8 +578,16 @@ fn libgit2_merge_updates_base_ref_in_both_repos() {
 
     // Perform merge (squash) while main repo is NOT on base branch (libgit2 path)
     let sha = s
-        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
-        .expect("merge should succeed via libgit2 path");
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .expect("merge should succeed via libgit2 path")
+        .sha;
 
     // Base branch ref advanced in both main and worktree repositories
     let after_main_repo = s.get_branch_oid(&repo_path, "main").unwrap();
@@ -477,8 +608,16 @@ fn libgit2_merge_updates_task_ref_and_feature_head_preserves_dirty() {
 
     // Perform merge (squash) from feature into main; this path uses libgit2
     let sha = s
-        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
-        .expect("merge should succeed via libgit2 path");
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .expect("merge should succeed via libgit2 path")
+        .sha;
 
     // Dirty file preserved in worktree
     let dirty = std::fs::read_to_string(worktree_path.join("dirty2.txt")).unwrap();
@@ -510,6 +649,7 @@ fn rebase_refuses_to_abort_existing_rebase() {
             Some("new-base"),
             "old-base",
             None,
+            None,
         )
         .expect_err("first rebase should error and leave in-progress state");
 
@@ -521,11 +661,75 @@ fn rebase_refuses_to_abort_existing_rebase() {
         Some("new-base"),
         "old-base",
         None,
+        None,
     );
     assert!(res.is_err(), "should error because rebase is in progress");
     // Note: We do not auto-abort; user should resolve or abort explicitly
 }
 
+#[test]
+fn abort_restores_worktree_after_stopped_rebase() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let before = svc.get_head_info(&worktree_path).unwrap();
+
+    // Nothing to abort yet
+    assert_eq!(
+        svc.abort_in_progress_operation(&worktree_path).unwrap(),
+        None
+    );
+
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            Some("new-base"),
+            "old-base",
+            None,
+            None,
+        )
+        .expect_err("conflicting rebase should stop");
+    assert!(!svc.list_conflicts(&worktree_path).unwrap().is_empty());
+
+    assert_eq!(
+        svc.abort_in_progress_operation(&worktree_path).unwrap(),
+        Some(GitOperation::Rebase)
+    );
+    let after = svc.get_head_info(&worktree_path).unwrap();
+    assert_eq!(after.branch, "feature");
+    assert_eq!(after.oid, before.oid);
+    assert!(svc.is_worktree_clean(&worktree_path).unwrap());
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("conflict.txt")).unwrap(),
+        "feature version\n"
+    );
+    assert_eq!(
+        svc.abort_in_progress_operation(&worktree_path).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn abort_restores_worktree_after_stopped_merge() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let svc = GitService::new();
+    let before = svc.get_head_info(&worktree_path).unwrap();
+
+    // Simulate a merge run by hand in the worktree that stopped on conflicts
+    GitCli::new()
+        .git(&worktree_path, ["merge", "new-base"])
+        .expect_err("conflicting merge should stop");
+
+    assert_eq!(
+        svc.abort_in_progress_operation(&worktree_path).unwrap(),
+        Some(GitOperation::Merge)
+    );
+    assert_eq!(svc.get_head_info(&worktree_path).unwrap().oid, before.oid);
+    assert!(svc.is_worktree_clean(&worktree_path).unwrap());
+}
+
 #[test]
 fn rebase_fast_forwards_when_no_unique_commits() {
     let td = TempDir::new().unwrap();
@@ -541,6 +745,7 @@ fn rebase_fast_forwards_when_no_unique_commits() {
             Some("new-base"),
             "old-base",
             None,
+            None,
         )
         .expect("rebase should succeed");
     let after_oid = g.get_head_info(&worktree_path).unwrap().oid;
@@ -572,6 +777,7 @@ fn rebase_applies_multiple_commits_onto_ahead_base() {
             Some("new-base"),
             "old-base",
             None,
+            None,
         )
         .expect("rebase should succeed");
 
@@ -582,6 +788,103 @@ fn rebase_applies_multiple_commits_onto_ahead_base() {
     assert_eq!(feat2, "second change\n");
 }
 
+#[test]
+fn rebase_onto_mid_branch_commit() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    // Grow new-base by two commits and target the first of them
+    checkout_branch(&repo, "new-base");
+    write_file(&repo_path, "mid.txt", "mid\n");
+    commit_all(&repo, "new-base mid commit");
+    let g = GitService::new();
+    let mid_oid = g.get_branch_oid(&repo_path, "new-base").unwrap();
+    write_file(&repo_path, "tip.txt", "tip\n");
+    commit_all(&repo, "new-base tip commit");
+
+    let head = g
+        .rebase_onto_commit(&repo_path, &worktree_path, &mid_oid, "old-base")
+        .expect("rebase onto commit should succeed");
+    assert_eq!(head, g.get_head_info(&worktree_path).unwrap().oid);
+
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    let head_commit = wt_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head_commit.parent_id(0).unwrap().to_string(), mid_oid);
+    assert!(worktree_path.join("feat.txt").exists());
+    assert!(worktree_path.join("mid.txt").exists());
+    assert!(!worktree_path.join("tip.txt").exists());
+}
+
+#[test]
+fn rebase_onto_commit_rejects_bad_targets_and_reports_conflicts() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    let g = GitService::new();
+
+    let res = g.rebase_onto_commit(&repo_path, &worktree_path, "not-a-sha", "old-base");
+    assert!(matches!(res, Err(GitServiceError::InvalidRepository(_))));
+    let missing = "0123456789abcdef0123456789abcdef01234567";
+    let res = g.rebase_onto_commit(&repo_path, &worktree_path, missing, "old-base");
+    assert!(matches!(res, Err(GitServiceError::InvalidRepository(_))));
+
+    let new_base_oid = g.get_branch_oid(&repo_path, "new-base").unwrap();
+    let res = g.rebase_onto_commit(&repo_path, &worktree_path, &new_base_oid, "old-base");
+    assert!(
+        matches!(res, Err(GitServiceError::MergeConflicts(..))),
+        "conflicting rebase should halt: {res:?}"
+    );
+    // The halted rebase is left for the user and blocks further rebases
+    let res = g.rebase_onto_commit(&repo_path, &worktree_path, &new_base_oid, "old-base");
+    assert!(matches!(res, Err(GitServiceError::RebaseInProgress)));
+}
+
+#[test]
+fn conflicting_merge_and_rebase_report_conflicted_files() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+    // Keep the main repo off the target branch so the merge runs in memory
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+
+    let g = GitService::new();
+    let res = g.merge_changes(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "new-base",
+        "squash merge",
+        &MergeOptions::default(),
+    );
+    let Err(GitServiceError::MergeConflicts(_, conflicts)) = res else {
+        panic!("conflicting merge should report conflicts: {res:?}");
+    };
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].path, "conflict.txt");
+    assert_eq!(conflicts[0].base.as_deref(), Some("base\n"));
+    assert_eq!(conflicts[0].ours.as_deref(), Some("new-base version\n"));
+    assert_eq!(conflicts[0].theirs.as_deref(), Some("feature version\n"));
+
+    // A rebase that stops leaves the conflict in the worktree index
+    let res = g.rebase_branch(
+        &repo_path,
+        &worktree_path,
+        Some("new-base"),
+        "old-base",
+        None,
+        None,
+    );
+    let Err(GitServiceError::MergeConflicts(_, conflicts)) = res else {
+        panic!("conflicting rebase should report conflicts: {res:?}");
+    };
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].ours.as_deref(), Some("new-base version\n"));
+    assert_eq!(conflicts[0].theirs.as_deref(), Some("feature version\n"));
+
+    let listed = g.list_conflicts(&worktree_path).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].path, "conflict.txt");
+}
+
 #[test]
 fn merge_when_base_ahead_and_feature_ahead_succeeds() {
     let td = TempDir::new().unwrap();
@@ -608,8 +911,10 @@ fn merge_when_base_ahead_and_feature_ahead_succeeds() {
             "feature",
             "main",
             "squash merge",
+            &MergeOptions::default(),
         )
-        .expect("merge should succeed");
+        .expect("merge should succeed")
+        .sha;
 
     let after_main = g.get_branch_oid(&repo_path, "main").unwrap();
     assert_ne!(before_main, after_main, "main should advance");
@@ -653,6 +958,7 @@ fn merge_conflict_does_not_move_base_ref() {
         "feature",
         "main",
         "squash merge",
+        &MergeOptions::default(),
     );
 
     assert!(res.is_err(), "conflicting merge should fail");
@@ -694,6 +1000,7 @@ fn merge_delete_vs_modify_conflict_behaves_safely() {
         "feature",
         "main",
         "squash merge",
+        &MergeOptions::default(),
     );
     match res {
         Err(_) => {
@@ -701,7 +1008,7 @@ fn merge_delete_vs_modify_conflict_behaves_safely() {
             let after = g.get_branch_oid(&repo_path, "main").unwrap();
             assert_eq!(before, after, "main ref must remain unchanged on failure");
         }
-        Ok(merge_sha) => {
+        Ok(MergeOutcome { sha: merge_sha, .. }) => {
             // On success, verify the resulting commit exists and the working tree was not touched
             let after_oid = g.get_branch_oid(&repo_path, "main").unwrap();
             assert_eq!(after_oid, merge_sha);
@@ -739,6 +1046,7 @@ fn rebase_preserves_rename_changes() {
             Some("new-base"),
             "old-base",
             None,
+            None,
         )
         .expect("rebase should succeed");
 
@@ -771,8 +1079,16 @@ fn merge_refreshes_main_worktree_when_on_base() {
 
     // Merge into main (squash) and ensure main worktree is updated since it is on base
     let merge_sha = s
-        .merge_changes(&repo_path, &wt, "feature", "main", "squash")
-        .unwrap();
+        .merge_changes(
+            &repo_path,
+            &wt,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .unwrap()
+        .sha;
     // Since main is on base branch and we use safe CLI merge, both working tree
     // and ref should reflect the merged content.
     let content = std::fs::read_to_string(repo_path.join("file.txt")).unwrap();
@@ -896,7 +1212,14 @@ fn merge_binary_conflict_does_not_move_ref() {
     let _ = s.commit(&repo_path, "main bin").unwrap();
 
     let before = s.get_branch_oid(&repo_path, "main").unwrap();
-    let res = s.merge_changes(&repo_path, &worktree_path, "feature", "main", "merge bin");
+    let res = s.merge_changes(
+        &repo_path,
+        &worktree_path,
+        "feature",
+        "main",
+        "merge bin",
+        &MergeOptions::default(),
+    );
     assert!(res.is_err(), "binary conflict should fail");
     let after = s.get_branch_oid(&repo_path, "main").unwrap();
     assert_eq!(before, after, "main ref unchanged on conflict");
@@ -934,13 +1257,14 @@ fn merge_rename_vs_modify_conflict_does_not_move_ref() {
         "feature",
         "main",
         "merge rename",
+        &MergeOptions::default(),
     );
     match res {
         Err(_) => {
             let after = s.get_branch_oid(&repo_path, "main").unwrap();
             assert_eq!(before, after, "main unchanged on conflict");
         }
-        Ok(sha) => {
+        Ok(MergeOutcome { sha, .. }) => {
             // ensure main advanced and result contains either renamed or modified content
             let after = s.get_branch_oid(&repo_path, "main").unwrap();
             assert_eq!(after, sha);
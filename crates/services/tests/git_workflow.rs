@@ -4,9 +4,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use services::services::git::{DiffTarget, GitService};
+use services::services::git::{
+    DiffOptionsExt, DiffTarget, GitService, GitServiceError, MergeOptions, MergeStrategy,
+    RepoHealth,
+};
 use tempfile::TempDir;
-use utils::diff::DiffChangeKind;
+use utils::diff::{DiffChangeKind, DiffSummary};
 
 fn write_file<P: AsRef<Path>>(base: P, rel: &str, content: &str) {
     let path = base.as_ref().join(rel);
@@ -160,6 +163,52 @@ fn worktree_clean_detects_staged_deleted_and_renamed() {
     assert!(!s.is_worktree_clean(&repo_path).unwrap());
 }
 
+#[test]
+fn worktree_status_categorizes_mixed_changes() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "staged.txt", "1\n");
+    write_file(&repo_path, "both.txt", "1\n");
+    write_file(&repo_path, "edited.txt", "1\n");
+    write_file(&repo_path, "gone.txt", "1\n");
+    write_file(&repo_path, ".gitignore", "*.log\n");
+    let _ = s.commit(&repo_path, "seed").unwrap();
+
+    let worktree_path = td.path().join("wt_status");
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+
+    write_file(&worktree_path, "staged.txt", "2\n");
+    s.add_path(&worktree_path, "staged.txt").unwrap();
+    write_file(&worktree_path, "both.txt", "2\n");
+    s.add_path(&worktree_path, "both.txt").unwrap();
+    write_file(&worktree_path, "both.txt", "3\n");
+    write_file(&worktree_path, "edited.txt", "2\n");
+    fs::remove_file(worktree_path.join("gone.txt")).unwrap();
+    write_file(&worktree_path, "new/untracked.txt", "new\n");
+    write_file(&worktree_path, "debug.log", "ignored\n");
+
+    let status = s.worktree_status(&worktree_path, false).unwrap();
+    assert_eq!(status.staged, ["both.txt", "staged.txt"]);
+    assert_eq!(status.modified, ["both.txt", "edited.txt", "gone.txt"]);
+    assert!(status.untracked.is_empty());
+    assert_eq!(
+        (
+            status.staged_count,
+            status.modified_count,
+            status.untracked_count
+        ),
+        (2, 3, 0)
+    );
+
+    let status = s.worktree_status(&worktree_path, true).unwrap();
+    assert_eq!(status.untracked, ["new/untracked.txt"]);
+    assert_eq!(status.untracked_count, 1);
+    assert_eq!(status.staged, ["both.txt", "staged.txt"]);
+}
+
 #[test]
 fn diff_added_binary_file_has_no_content() {
     // ensure binary file content is not loaded (null byte guard)
@@ -194,6 +243,391 @@ fn diff_added_binary_file_has_no_content() {
     assert!(bin.new_content.is_none());
 }
 
+#[test]
+fn diff_line_counts_and_summary() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "1\n2\n3\n");
+    write_file(&repo_path, "gone.txt", "x\ny\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "1\ntwo\n3\n4\n");
+    fs::remove_file(repo_path.join("gone.txt")).unwrap();
+    write_file(&repo_path, "new.txt", "n\n");
+    let mut f = fs::File::create(repo_path.join("bin.dat")).unwrap();
+    f.write_all(&[0u8, 1, 2, 3]).unwrap();
+    let _ = s.commit(&repo_path, "feature changes").unwrap();
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+    let counts = |path: &str| {
+        let d = diffs
+            .iter()
+            .find(|d| d.new_path.as_deref().or(d.old_path.as_deref()) == Some(path))
+            .unwrap_or_else(|| panic!("diff for {path}"));
+        (d.additions, d.deletions)
+    };
+    assert_eq!(counts("a.txt"), (2, 1));
+    assert_eq!(counts("gone.txt"), (0, 2));
+    assert_eq!(counts("new.txt"), (1, 0));
+    assert_eq!(counts("bin.dat"), (0, 0));
+    assert_eq!(
+        DiffSummary::from_diffs(&diffs),
+        DiffSummary {
+            files_changed: 4,
+            additions: 3,
+            deletions: 3,
+        }
+    );
+
+    // Uncommitted worktree changes are counted the same way
+    write_file(&repo_path, "new.txt", "n\nm\n");
+    let diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            Some(&["new.txt"]),
+        )
+        .unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!((diffs[0].additions, diffs[0].deletions), (2, 0));
+}
+
+#[test]
+fn format_patch_has_mail_header_and_hunks() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "1\n2\n3\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "1\ntwo\n3\n");
+    let _ = s.commit(&repo_path, "edit a").unwrap();
+    let mut f = fs::File::create(repo_path.join("bin.dat")).unwrap();
+    f.write_all(&[0u8, 1, 2, 3]).unwrap();
+    let _ = s.commit(&repo_path, "add binary").unwrap();
+
+    let patch = s.format_patch(&repo_path, "feature", "main", 3).unwrap();
+    let head = s.get_head_info(&repo_path).unwrap().oid;
+
+    assert!(patch.starts_with(&format!("From {head} Mon Sep 17 00:00:00 2001\n")));
+    assert!(patch.contains("\nFrom: Test User <test@example.com>\n"));
+    assert!(patch.contains("\nDate: "));
+    assert!(patch.contains("\nSubject: [PATCH] add binary\n"));
+    // Both squashed commits are listed, oldest first, before the diffstat
+    let commits = &patch[patch.find("\nCommits:\n").unwrap()..patch.find("\n---\n").unwrap()];
+    let edit = commits.find(" edit a\n").expect("first commit listed");
+    let binary = commits.find(" add binary\n").expect("second commit listed");
+    assert!(edit < binary);
+    assert!(patch.contains(" 2 files changed"));
+
+    assert!(patch.contains("diff --git a/a.txt b/a.txt\n"));
+    assert!(patch.contains("--- a/a.txt\n+++ b/a.txt\n"));
+    assert!(patch.contains("@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n"));
+    assert!(patch.contains("Binary files /dev/null and b/bin.dat differ"));
+    assert!(!patch.contains("GIT binary patch"));
+}
+
+#[test]
+fn format_patch_honours_context_lines() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let lines: String = (1..=9).map(|n| format!("{n}\n")).collect();
+    write_file(&repo_path, "a.txt", &lines);
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", &lines.replace("5\n", "five\n"));
+    let _ = s.commit(&repo_path, "edit a").unwrap();
+
+    let default = s.format_patch(&repo_path, "feature", "main", 3).unwrap();
+    assert!(default.contains("@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"));
+    let narrow = s.format_patch(&repo_path, "feature", "main", 1).unwrap();
+    assert!(narrow.contains("@@ -4,3 +4,3 @@\n 4\n-5\n+five\n 6\n"));
+    let none = s.format_patch(&repo_path, "feature", "main", 0).unwrap();
+    assert!(none.contains("@@ -5 +5 @@\n-5\n+five\n"));
+}
+
+/// Repo with `a.txt` on main, a worktree on `feature` and a commit on `other`
+/// that edits `a.txt` and adds `b.txt`
+fn cherry_pick_setup(td: &TempDir) -> (PathBuf, PathBuf, String) {
+    let repo_path = init_repo_main(td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "one\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "other").unwrap();
+    s.checkout_branch(&repo_path, "other").unwrap();
+    write_file(&repo_path, "a.txt", "one from other\n");
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "other change").unwrap();
+    let other_sha = s.get_head_info(&repo_path).unwrap().oid;
+    s.checkout_branch(&repo_path, "main").unwrap();
+
+    let worktree_path = td.path().join("wt_feature");
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    (repo_path, worktree_path, other_sha)
+}
+
+#[test]
+fn cherry_pick_applies_single_commit() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path, other_sha) = cherry_pick_setup(&td);
+    let s = GitService::new();
+    let before = s.get_head_info(&worktree_path).unwrap().oid;
+
+    // Uncommitted edits to tracked files block the pick
+    write_file(&worktree_path, "a.txt", "dirty\n");
+    assert!(matches!(
+        s.cherry_pick(&worktree_path, &other_sha),
+        Err(GitServiceError::WorktreeDirty(..))
+    ));
+    write_file(&worktree_path, "a.txt", "one\n");
+
+    let picked = s.cherry_pick(&worktree_path, &other_sha).unwrap();
+    assert_ne!(picked, other_sha);
+    assert_eq!(s.get_head_info(&worktree_path).unwrap().oid, picked);
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("a.txt")).unwrap(),
+        "one from other\n"
+    );
+    assert!(worktree_path.join("b.txt").exists());
+
+    let repo = git2::Repository::open(&worktree_path).unwrap();
+    let commit = repo
+        .find_commit(git2::Oid::from_str(&picked).unwrap())
+        .unwrap();
+    assert_eq!(commit.parent_id(0).unwrap().to_string(), before);
+    assert_eq!(commit.summary(), Some("other change"));
+}
+
+#[test]
+fn cherry_pick_reports_conflicts_without_touching_worktree() {
+    let td = TempDir::new().unwrap();
+    let (_repo_path, worktree_path, other_sha) = cherry_pick_setup(&td);
+    let s = GitService::new();
+    write_file(&worktree_path, "a.txt", "one from feature\n");
+    s.commit(&worktree_path, "feature change").unwrap();
+    let before = s.get_head_info(&worktree_path).unwrap().oid;
+
+    match s.cherry_pick(&worktree_path, &other_sha) {
+        Err(GitServiceError::MergeConflicts(_, files)) => {
+            let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+            assert_eq!(paths, ["a.txt"]);
+        }
+        other => panic!("expected conflicts, got {other:?}"),
+    }
+    assert_eq!(s.get_head_info(&worktree_path).unwrap().oid, before);
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("a.txt")).unwrap(),
+        "one from feature\n"
+    );
+    assert!(!worktree_path.join("b.txt").exists());
+    assert!(s.is_worktree_clean(&worktree_path).unwrap());
+}
+
+/// Repo with `a.txt` edited on `feature`, checked out on `main`
+fn merge_preview_setup(td: &TempDir) -> PathBuf {
+    let repo_path = init_repo_main(td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "one\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "one from feature\n");
+    let _ = s.commit(&repo_path, "feature change").unwrap();
+    s.checkout_branch(&repo_path, "main").unwrap();
+    repo_path
+}
+
+#[test]
+fn merge_preview_is_clean_for_unrelated_changes() {
+    let td = TempDir::new().unwrap();
+    let repo_path = merge_preview_setup(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "main change").unwrap();
+    let main_before = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    let preview = s.preview_merge(&repo_path, "feature", "main").unwrap();
+    assert!(preview.clean);
+    assert!(preview.conflicted_files.is_empty());
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+}
+
+#[test]
+fn merge_preview_lists_conflicts_without_touching_branches() {
+    let td = TempDir::new().unwrap();
+    let repo_path = merge_preview_setup(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "one from main\n");
+    let _ = s.commit(&repo_path, "main change").unwrap();
+    let main_before = s.get_branch_oid(&repo_path, "main").unwrap();
+    let feature_before = s.get_branch_oid(&repo_path, "feature").unwrap();
+
+    let preview = s.preview_merge(&repo_path, "feature", "main").unwrap();
+    assert!(!preview.clean);
+    assert_eq!(preview.conflicted_files, ["a.txt"]);
+
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), main_before);
+    assert_eq!(
+        s.get_branch_oid(&repo_path, "feature").unwrap(),
+        feature_before
+    );
+    assert_eq!(
+        fs::read_to_string(repo_path.join("a.txt")).unwrap(),
+        "one from main\n"
+    );
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+}
+
+#[test]
+fn branch_to_branch_diff_compares_two_attempts() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "shared.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    // Two attempts branch off main and edit the same file differently
+    for (branch, content, extra) in [
+        ("attempt-a", "from a\n", "only-a.txt"),
+        ("attempt-b", "from b\nmore b\n", "only-b.txt"),
+    ] {
+        let wt = td.path().join(branch);
+        s.create_branch(&repo_path, branch).unwrap();
+        s.add_worktree(&repo_path, &wt, branch, false).unwrap();
+        write_file(&wt, "shared.txt", content);
+        write_file(&wt, extra, &format!("{branch}\n"));
+        s.commit(&wt, branch).unwrap();
+    }
+
+    let diffs = s
+        .get_diffs(
+            DiffTarget::BranchToBranch {
+                repo_path: Path::new(&repo_path),
+                branch_a: "attempt-a",
+                branch_b: "attempt-b",
+            },
+            None,
+        )
+        .unwrap();
+    let find = |path: &str| {
+        diffs
+            .iter()
+            .find(|d| d.new_path.as_deref().or(d.old_path.as_deref()) == Some(path))
+            .unwrap_or_else(|| panic!("diff for {path}"))
+    };
+
+    let shared = find("shared.txt");
+    assert_eq!(shared.old_content.as_deref(), Some("from a\n"));
+    assert_eq!(shared.new_content.as_deref(), Some("from b\nmore b\n"));
+    assert!(matches!(find("only-a.txt").change, DiffChangeKind::Deleted));
+    assert!(matches!(find("only-b.txt").change, DiffChangeKind::Added));
+    assert_eq!(
+        DiffSummary::from_diffs(&diffs),
+        DiffSummary {
+            files_changed: 3,
+            additions: 3,
+            deletions: 2,
+        }
+    );
+}
+
+#[test]
+fn stream_diffs_emits_each_file_of_worktree_change_set() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "keep.txt", "keep\n");
+    write_file(&repo_path, "edit.txt", "before\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    let wt = td.path().join("wt-stream");
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &wt, "feature", false).unwrap();
+    write_file(&wt, "edit.txt", "after\n");
+    write_file(&wt, "src/new.rs", "fn main() {}\n");
+    s.commit(&wt, "committed change").unwrap();
+    // Uncommitted, oversized and binary files are streamed too
+    write_file(&wt, "notes.md", "draft\n");
+    write_file(&wt, "big.txt", &"x".repeat(2 * 1024 * 1024));
+    fs::write(wt.join("blob.bin"), [0u8, 159, 146, 150]).unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let producer = {
+        let wt = wt.clone();
+        std::thread::spawn(move || {
+            GitService::new().stream_diffs(
+                DiffTarget::Worktree {
+                    worktree_path: &wt,
+                    branch_name: "feature",
+                    base_branch: "main",
+                },
+                None,
+                DiffOptionsExt::default(),
+                &mut |diff| tx.send(diff).is_ok(),
+            )
+        })
+    };
+    let streamed: Vec<_> = rx.iter().collect();
+    producer.join().unwrap().unwrap();
+
+    let mut paths: Vec<_> = streamed.iter().map(GitService::diff_path).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["big.txt", "blob.bin", "edit.txt", "notes.md", "src/new.rs"]
+    );
+    // The size and binary guards still apply to streamed entries
+    for skipped in ["big.txt", "blob.bin"] {
+        let diff = streamed
+            .iter()
+            .find(|d| GitService::diff_path(d) == skipped)
+            .unwrap();
+        assert!(diff.new_content.is_none(), "{skipped} content was loaded");
+    }
+
+    // Returning false stops the walk after the first file
+    let mut seen = 0;
+    s.stream_diffs(
+        DiffTarget::Branch {
+            repo_path: Path::new(&repo_path),
+            branch_name: "feature",
+            base_branch: "main",
+        },
+        None,
+        DiffOptionsExt::default(),
+        &mut |_| {
+            seen += 1;
+            false
+        },
+    )
+    .unwrap();
+    assert_eq!(seen, 1);
+}
+
 #[test]
 fn initialize_and_default_branch_and_head_info() {
     let td = TempDir::new().unwrap();
@@ -290,6 +724,178 @@ fn branch_status_ahead_and_behind() {
     assert_eq!((ahead2, behind2), (2, 1));
 }
 
+#[test]
+fn merge_base_and_unique_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let fork_point = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "feature.txt", "f1\n");
+    let _ = s.commit(&repo_path, "f1").unwrap();
+    let f1 = s.get_head_info(&repo_path).unwrap().oid;
+    write_file(&repo_path, "feature2.txt", "f2\n");
+    let _ = s.commit(&repo_path, "f2").unwrap();
+    let f2 = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "main.txt", "m1\n");
+    let _ = s.commit(&repo_path, "m1").unwrap();
+
+    assert_eq!(
+        s.merge_base(&repo_path, "feature", "main").unwrap(),
+        Some(fork_point)
+    );
+    assert_eq!(
+        s.unique_commits(&repo_path, "feature", "main").unwrap(),
+        vec![f2, f1]
+    );
+}
+
+#[test]
+fn list_commits_excludes_base_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    for (file, message) in [("a.txt", "add a"), ("b.txt", "add b"), ("c.txt", "add c")] {
+        write_file(&repo_path, file, "content\n");
+        let _ = s
+            .commit(&repo_path, &format!("{message}\n\nlonger body"))
+            .unwrap();
+    }
+    let tip = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "main.txt", "m1\n");
+    let _ = s.commit(&repo_path, "m1").unwrap();
+
+    let commits = s.list_commits(&repo_path, "feature", "main", 10).unwrap();
+    let subjects: Vec<_> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, ["add c", "add b", "add a"]);
+    assert_eq!(commits[0].sha, tip);
+    assert!(commits.iter().all(|c| c.author == "Test User"));
+
+    let limited = s.list_commits(&repo_path, "feature", "main", 2).unwrap();
+    assert_eq!(limited.len(), 2);
+    assert_eq!(limited[0].sha, tip);
+}
+
+#[test]
+fn base_branch_tip_date_and_missing_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    let before = chrono::Utc::now().timestamp();
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+    let tip = s.get_branch_oid(&repo_path, "main").unwrap();
+    let date = s.get_commit_date(&repo_path, &tip).unwrap();
+    // Commit times have second precision
+    assert!(date.timestamp() >= before - 1);
+    assert!(date.timestamp() <= chrono::Utc::now().timestamp());
+
+    assert!(matches!(
+        s.get_branch_oid(&repo_path, "deleted-base"),
+        Err(GitServiceError::BranchNotFound(_))
+    ));
+}
+
+#[test]
+fn resolve_branch_name_prefers_exact_then_fuzzy_names() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "base.txt", "base\n");
+    let _ = s.commit(&repo_path, "base").unwrap();
+
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("feature-login", &head, false).unwrap();
+    repo.branch("feature-signup", &head, false).unwrap();
+    repo.branch("release", &head, false).unwrap();
+    for remote_branch in ["feature", "release", "hotfix-42"] {
+        repo.reference(
+            &format!("refs/remotes/origin/{remote_branch}"),
+            head.id(),
+            true,
+            "test remote branch",
+        )
+        .unwrap();
+    }
+
+    let name_of = |name: &str| s.resolve_branch_name(&repo_path, name);
+
+    // Exact local wins over the remote of the same name
+    assert_eq!(name_of("release").unwrap(), "release");
+    // Exact remote
+    assert_eq!(name_of("origin/feature").unwrap(), "origin/feature");
+    // Whole-segment match beats the looser `feature-*` prefix matches
+    assert_eq!(name_of("feature").unwrap(), "origin/feature");
+    // Unique prefix and suffix matches
+    assert_eq!(name_of("hotfix").unwrap(), "origin/hotfix-42");
+    assert_eq!(name_of("signup").unwrap(), "feature-signup");
+    // Internal lookups stay exact
+    assert!(GitService::find_branch(&repo, "hotfix").is_err());
+    assert!(matches!(
+        s.find_branch_type(&repo_path, "hotfix"),
+        Err(GitServiceError::BranchNotFound(_))
+    ));
+
+    // Ambiguous and missing names list candidates
+    match name_of("feature-") {
+        Err(GitServiceError::BranchNotFound(msg)) => {
+            assert!(msg.contains("feature-login") && msg.contains("feature-signup"));
+        }
+        other => panic!("expected ambiguity error, got {other:?}"),
+    }
+    match name_of("LOGIN-page") {
+        Err(GitServiceError::BranchNotFound(msg)) => assert_eq!(msg, "LOGIN-page"),
+        other => panic!("expected not found, got {other:?}"),
+    }
+    match name_of("eatur") {
+        Err(GitServiceError::BranchNotFound(msg)) => {
+            assert!(msg.contains("did you mean") && msg.contains("origin/feature"));
+        }
+        other => panic!("expected suggestions, got {other:?}"),
+    }
+}
+
+#[test]
+fn merge_base_is_none_for_unrelated_histories() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    // Build a root commit with no parents and point "orphan" at it
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+    let tree_id = repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let orphan = repo
+        .commit(None, &sig, &sig, "orphan root", &tree, &[])
+        .unwrap();
+    repo.branch("orphan", &repo.find_commit(orphan).unwrap(), false)
+        .unwrap();
+
+    assert_eq!(s.merge_base(&repo_path, "orphan", "main").unwrap(), None);
+    assert_eq!(
+        s.unique_commits(&repo_path, "orphan", "main").unwrap(),
+        vec![orphan.to_string()]
+    );
+}
+
 #[test]
 fn get_all_branches_lists_current_and_others() {
     let td = TempDir::new().unwrap();
@@ -409,13 +1015,198 @@ fn worktree_diff_respects_path_filter() {
 }
 
 #[test]
-fn get_branch_oid_nonexistent_errors() {
+fn worktree_diff_flags_whitespace_only_changes_when_enabled() {
     let td = TempDir::new().unwrap();
     let repo_path = init_repo_main(&td);
     let s = GitService::new();
-    let res = s.get_branch_oid(&repo_path, "no-such-branch");
-    assert!(res.is_err());
-}
+    write_file(&repo_path, "indent.rs", "fn main() {\n    run();\n}\n");
+    write_file(&repo_path, "edit.rs", "fn main() {\n    run();\n}\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+
+    // Re-indent one file with tabs, really change the other
+    write_file(&repo_path, "indent.rs", "fn main() {\n\trun();\n}\n");
+    write_file(&repo_path, "edit.rs", "fn main() {\n    walk();\n}\n");
+
+    let change_of = |opts: DiffOptionsExt, path: &str| {
+        s.get_diffs_with_options(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+            opts,
+        )
+        .unwrap()
+        .into_iter()
+        .find(|d| d.new_path.as_deref() == Some(path))
+        .map(|d| d.change)
+        .unwrap()
+    };
+
+    let default = DiffOptionsExt::default();
+    let ignore_ws = DiffOptionsExt {
+        ignore_whitespace: true,
+    };
+    assert!(matches!(
+        change_of(default, "indent.rs"),
+        DiffChangeKind::Modified
+    ));
+    assert!(matches!(
+        change_of(ignore_ws, "indent.rs"),
+        DiffChangeKind::WhitespaceChange
+    ));
+    assert!(matches!(
+        change_of(ignore_ws, "edit.rs"),
+        DiffChangeKind::Modified
+    ));
+}
+
+#[test]
+fn branch_diff_flags_whitespace_only_commits_when_enabled() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "indent.rs", "fn main() {\n    run();\n}\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "indent.rs", "fn main() {\n\trun();  \n}\n");
+    let _ = s.commit(&repo_path, "reindent").unwrap();
+
+    let diff_with = |ignore_whitespace: bool| {
+        s.get_diffs_with_options(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+            DiffOptionsExt { ignore_whitespace },
+        )
+        .unwrap()
+    };
+
+    let shown = diff_with(false);
+    assert_eq!(shown.len(), 1);
+    assert!(matches!(shown[0].change, DiffChangeKind::Modified));
+
+    let suppressed = diff_with(true);
+    assert!(
+        suppressed
+            .iter()
+            .all(|d| matches!(d.change, DiffChangeKind::WhitespaceChange))
+    );
+}
+
+#[test]
+fn worktree_diff_flags_committed_whitespace_only_changes_when_enabled() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "committed.rs", "fn main() {\n    run();\n}\n");
+    write_file(&repo_path, "pending.rs", "fn main() {\n    run();\n}\n");
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+
+    // One re-indent committed on the branch, one left in the worktree
+    write_file(&repo_path, "committed.rs", "fn main() {\n\trun();\n}\n");
+    let _ = s.commit(&repo_path, "reindent").unwrap();
+    write_file(&repo_path, "pending.rs", "fn main() {\n  run();  \n}\n");
+
+    let diff_with = |ignore_whitespace: bool| {
+        s.get_diffs_with_options(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+            DiffOptionsExt { ignore_whitespace },
+        )
+        .unwrap()
+    };
+
+    let shown = diff_with(false);
+    assert_eq!(shown.len(), 2);
+    assert!(
+        shown
+            .iter()
+            .all(|d| matches!(d.change, DiffChangeKind::Modified))
+    );
+
+    let suppressed = diff_with(true);
+    assert_eq!(suppressed.len(), 2);
+    assert!(
+        suppressed
+            .iter()
+            .all(|d| matches!(d.change, DiffChangeKind::WhitespaceChange))
+    );
+}
+
+#[test]
+fn renamed_file_with_edits_reports_similarity() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let body: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+    write_file(&repo_path, "old_name.txt", &body);
+    let _ = s.commit(&repo_path, "baseline").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+
+    // Rename with a one-line edit
+    fs::remove_file(repo_path.join("old_name.txt")).unwrap();
+    write_file(
+        &repo_path,
+        "new_name.txt",
+        &body.replace("line 10\n", "line ten\n"),
+    );
+
+    let worktree_diffs = s
+        .get_diffs(
+            DiffTarget::Worktree {
+                worktree_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+    let _ = s.commit(&repo_path, "rename").unwrap();
+    let branch_diffs = s
+        .get_diffs(
+            DiffTarget::Branch {
+                repo_path: Path::new(&repo_path),
+                branch_name: "feature",
+                base_branch: "main",
+            },
+            None,
+        )
+        .unwrap();
+
+    for diffs in [worktree_diffs, branch_diffs] {
+        assert_eq!(diffs.len(), 1);
+        let d = &diffs[0];
+        assert!(matches!(d.change, DiffChangeKind::Renamed));
+        assert_eq!(d.old_path.as_deref(), Some("old_name.txt"));
+        assert_eq!(d.new_path.as_deref(), Some("new_name.txt"));
+        let similarity = d.similarity.expect("renames carry a similarity");
+        assert!((50..100).contains(&similarity), "{similarity}");
+        assert_ne!(d.old_content, d.new_content);
+        assert_eq!((d.additions, d.deletions), (1, 1));
+    }
+}
+
+#[test]
+fn get_branch_oid_nonexistent_errors() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let res = s.get_branch_oid(&repo_path, "no-such-branch");
+    assert!(res.is_err());
+}
 
 #[test]
 fn create_unicode_branch_and_list() {
@@ -580,8 +1371,16 @@ fn squash_merge_libgit2_sets_author_without_user() {
 
     // Merge feature -> main (libgit2 squash)
     let merge_sha = s
-        .merge_changes(&repo_path, &worktree_path, "feature", "main", "squash")
-        .unwrap();
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash",
+            &MergeOptions::default(),
+        )
+        .unwrap()
+        .sha;
 
     // The squash commit author should not be the feature commit's author, and must be present.
     let (name, email) = s.get_commit_author(&repo_path, &merge_sha).unwrap();
@@ -594,3 +1393,635 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn squash_merge_uses_supplied_author() {
+    let author = || Some(("Jane Doe".to_string(), "jane@example.com".to_string()));
+
+    // main repo on another branch: libgit2 path; on the base branch: CLI path
+    for base_checked_out in [false, true] {
+        let td = TempDir::new().unwrap();
+        let repo_path = init_repo_main(&td);
+        let worktree_path = td.path().join("wt_feature");
+        let s = GitService::new();
+
+        s.create_branch(&repo_path, "feature").unwrap();
+        s.add_worktree(&repo_path, &worktree_path, "feature", false)
+            .unwrap();
+        write_file(&worktree_path, "f.txt", "feat\n");
+        s.commit(&worktree_path, "feat").unwrap();
+
+        if !base_checked_out {
+            s.create_branch(&repo_path, "dev").unwrap();
+            s.checkout_branch(&repo_path, "dev").unwrap();
+        }
+
+        let merge_sha = s
+            .merge_changes(
+                &repo_path,
+                &worktree_path,
+                "feature",
+                "main",
+                "squash",
+                &MergeOptions {
+                    author: author(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .sha;
+
+        let (name, email) = s.get_commit_author(&repo_path, &merge_sha).unwrap();
+        assert_eq!(name.as_deref(), Some("Jane Doe"));
+        assert_eq!(email.as_deref(), Some("jane@example.com"));
+    }
+}
+
+#[test]
+fn squash_merge_with_tag_annotates_merge_commit() {
+    // main repo on another branch: libgit2 path; on the base branch: CLI path
+    for base_checked_out in [false, true] {
+        let td = TempDir::new().unwrap();
+        let repo_path = init_repo_main(&td);
+        let worktree_path = td.path().join("wt_feature");
+        let s = GitService::new();
+
+        s.create_branch(&repo_path, "feature").unwrap();
+        s.add_worktree(&repo_path, &worktree_path, "feature", false)
+            .unwrap();
+        write_file(&worktree_path, "f.txt", "feat\n");
+        s.commit(&worktree_path, "feat").unwrap();
+
+        if !base_checked_out {
+            s.create_branch(&repo_path, "dev").unwrap();
+            s.checkout_branch(&repo_path, "dev").unwrap();
+        }
+
+        let tagged_merge = MergeOptions {
+            tag_name: Some("v1.0.0".to_string()),
+            ..Default::default()
+        };
+        let outcome = s
+            .merge_changes(
+                &repo_path,
+                &worktree_path,
+                "feature",
+                "main",
+                "squash",
+                &tagged_merge,
+            )
+            .unwrap();
+        assert_eq!(outcome.tag.as_deref(), Some("v1.0.0"));
+
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let tagged = repo.revparse_single("v1.0.0").unwrap();
+        let tag = tagged.as_tag().expect("tag should be annotated");
+        assert_eq!(tag.message().map(str::trim), Some("squash"));
+        assert_eq!(
+            tagged.peel_to_commit().unwrap().id().to_string(),
+            outcome.sha
+        );
+
+        // A taken tag name is refused before anything is merged
+        write_file(&worktree_path, "g.txt", "more\n");
+        s.commit(&worktree_path, "more").unwrap();
+        let res = s.merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash again",
+            &tagged_merge,
+        );
+        assert!(matches!(res, Err(GitServiceError::TagExists(name)) if name == "v1.0.0"));
+        assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), outcome.sha);
+    }
+}
+
+#[test]
+fn fast_forward_merge_moves_base_to_branch_head() {
+    // main repo on another branch: ref update; on the base branch: git merge --ff-only
+    for base_checked_out in [false, true] {
+        let td = TempDir::new().unwrap();
+        let repo_path = init_repo_main(&td);
+        let worktree_path = td.path().join("wt_feature");
+        let s = GitService::new();
+
+        s.create_branch(&repo_path, "feature").unwrap();
+        s.add_worktree(&repo_path, &worktree_path, "feature", false)
+            .unwrap();
+        write_file(&worktree_path, "f.txt", "feat\n");
+        s.commit(&worktree_path, "feat").unwrap();
+        let feature_head = s.get_branch_oid(&repo_path, "feature").unwrap();
+
+        if !base_checked_out {
+            s.create_branch(&repo_path, "dev").unwrap();
+            s.checkout_branch(&repo_path, "dev").unwrap();
+        }
+
+        let outcome = s
+            .merge_changes(
+                &repo_path,
+                &worktree_path,
+                "feature",
+                "main",
+                "unused",
+                &MergeOptions {
+                    strategy: MergeStrategy::FastForwardIfPossible,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(outcome.strategy, MergeStrategy::FastForwardIfPossible);
+        assert_eq!(outcome.sha, feature_head);
+        assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), feature_head);
+        if base_checked_out {
+            let merged = fs::read_to_string(repo_path.join("f.txt")).unwrap();
+            assert_eq!(merged, "feat\n");
+        }
+    }
+}
+
+#[test]
+fn fast_forward_merge_updates_worktree_with_base_checked_out() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+
+    // The main repository moves off the base, which stays checked out in a worktree
+    s.create_branch(&repo_path, "dev").unwrap();
+    s.checkout_branch(&repo_path, "dev").unwrap();
+    let base_worktree = td.path().join("wt_main");
+    s.add_worktree(&repo_path, &base_worktree, "main", false)
+        .unwrap();
+
+    let worktree_path = td.path().join("wt_feature");
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    write_file(&worktree_path, "f.txt", "feat\n");
+    s.commit(&worktree_path, "feat").unwrap();
+
+    let outcome = s
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "unused",
+            &MergeOptions {
+                strategy: MergeStrategy::FastForwardIfPossible,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(outcome.strategy, MergeStrategy::FastForwardIfPossible);
+    let merged = fs::read_to_string(base_worktree.join("f.txt")).unwrap();
+    assert_eq!(merged, "feat\n");
+    assert!(s.is_worktree_clean(&base_worktree).unwrap());
+}
+
+#[test]
+fn fast_forward_merge_falls_back_to_squash_when_diverged() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let worktree_path = td.path().join("wt_feature");
+    let s = GitService::new();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    write_file(&worktree_path, "f.txt", "feat\n");
+    s.commit(&worktree_path, "feat").unwrap();
+    let feature_head = s.get_branch_oid(&repo_path, "feature").unwrap();
+
+    // main moves on independently
+    write_file(&repo_path, "m.txt", "main\n");
+    s.commit(&repo_path, "main change").unwrap();
+    let main_before = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    let outcome = s
+        .merge_changes(
+            &repo_path,
+            &worktree_path,
+            "feature",
+            "main",
+            "squash feat",
+            &MergeOptions {
+                strategy: MergeStrategy::FastForwardIfPossible,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(outcome.strategy, MergeStrategy::Squash);
+    assert_ne!(outcome.sha, feature_head);
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), outcome.sha);
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let merge_commit = repo
+        .find_commit(git2::Oid::from_str(&outcome.sha).unwrap())
+        .unwrap();
+    assert_eq!(merge_commit.parent_count(), 1);
+    assert_eq!(merge_commit.parent_id(0).unwrap().to_string(), main_before);
+    assert_eq!(merge_commit.message().map(str::trim), Some("squash feat"));
+}
+
+#[test]
+fn repo_health_reports_healthy_and_deleted_worktrees() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let worktree_path = td.path().join("wt_feature");
+    let s = GitService::new();
+
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    assert_eq!(s.repo_health(&repo_path), RepoHealth::Ok);
+    assert_eq!(s.repo_health(&worktree_path), RepoHealth::Ok);
+
+    // Deleted out from under us: reported as missing, not as a raw git2 error
+    fs::remove_dir_all(&worktree_path).unwrap();
+    assert_eq!(s.repo_health(&worktree_path), RepoHealth::Missing);
+    let res = s.get_head_info(&worktree_path);
+    assert!(matches!(res, Err(GitServiceError::RepoMissing(_))));
+
+    // A plain directory is not a repository either
+    fs::create_dir_all(&worktree_path).unwrap();
+    assert_eq!(s.repo_health(&worktree_path), RepoHealth::Missing);
+}
+
+#[test]
+fn repo_health_reports_corrupt_head() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    s.commit(&repo_path, "seed").unwrap();
+
+    fs::write(repo_path.join(".git/HEAD"), "garbage\n").unwrap();
+    assert_eq!(s.repo_health(&repo_path), RepoHealth::Corrupt);
+}
+
+#[test]
+fn create_tag_rejects_existing_name() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    let head = s.get_head_info(&repo_path).unwrap().oid;
+
+    s.create_tag(&repo_path, "release", &head, "first").unwrap();
+    let res = s.create_tag(&repo_path, "release", &head, "second");
+    assert!(matches!(res, Err(GitServiceError::TagExists(name)) if name == "release"));
+}
+
+#[test]
+fn ensure_on_branch_detects_and_reattaches_switched_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "seed").unwrap();
+
+    s.create_branch(&repo_path, "task").unwrap();
+    s.checkout_branch(&repo_path, "task").unwrap();
+    assert_eq!(s.ensure_on_branch(&repo_path, "task", false).unwrap(), None);
+
+    // Agent switches to its own branch
+    s.create_branch(&repo_path, "agent-branch").unwrap();
+    s.checkout_branch(&repo_path, "agent-branch").unwrap();
+    assert_eq!(
+        s.ensure_on_branch(&repo_path, "task", false).unwrap(),
+        Some("agent-branch".to_string())
+    );
+    assert_eq!(s.get_current_branch(&repo_path).unwrap(), "agent-branch");
+
+    // A dirty worktree is never switched
+    write_file(&repo_path, "a.txt", "changed\n");
+    assert!(matches!(
+        s.ensure_on_branch(&repo_path, "task", true),
+        Err(GitServiceError::WorktreeDirty(_, _))
+    ));
+    write_file(&repo_path, "a.txt", "a\n");
+
+    assert_eq!(
+        s.ensure_on_branch(&repo_path, "task", true).unwrap(),
+        Some("agent-branch".to_string())
+    );
+    assert_eq!(s.get_current_branch(&repo_path).unwrap(), "task");
+}
+
+#[test]
+fn amend_commit_rewrites_local_commit() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let original = s.get_head_info(&repo_path).unwrap().oid;
+
+    let amended = s
+        .amend_commit(&repo_path, "main", Some("Add a.txt"), false, false)
+        .unwrap();
+    assert_ne!(amended, original);
+    assert_eq!(s.get_head_info(&repo_path).unwrap().oid, amended);
+    assert_eq!(
+        s.get_commit_subject(&repo_path, &amended).unwrap(),
+        "Add a.txt"
+    );
+
+    // A forgotten file is folded in, keeping the message
+    write_file(&repo_path, "b.txt", "b\n");
+    let amended = s
+        .amend_commit(&repo_path, "main", None, true, false)
+        .unwrap();
+    assert_eq!(
+        s.get_commit_subject(&repo_path, &amended).unwrap(),
+        "Add a.txt"
+    );
+    assert!(s.is_worktree_clean(&repo_path).unwrap());
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let tree = repo
+        .find_commit(git2::Oid::from_str(&amended).unwrap())
+        .unwrap()
+        .tree()
+        .unwrap();
+    assert!(tree.get_name("a.txt").is_some() && tree.get_name("b.txt").is_some());
+}
+
+#[test]
+fn amend_commit_refuses_pushed_commit_unless_forced() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let pushed = s.get_head_info(&repo_path).unwrap().oid;
+
+    // As if pushed: the remote branch points at the commit
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    repo.reference(
+        "refs/remotes/origin/feature",
+        git2::Oid::from_str(&pushed).unwrap(),
+        true,
+        "test push",
+    )
+    .unwrap();
+
+    match s.amend_commit(&repo_path, "main", Some("reworded"), false, false) {
+        Err(GitServiceError::CommitAlreadyPushed(remote_branch)) => {
+            assert_eq!(remote_branch, "origin/feature");
+        }
+        other => panic!("expected CommitAlreadyPushed, got {other:?}"),
+    }
+    assert_eq!(s.get_head_info(&repo_path).unwrap().oid, pushed);
+
+    // Commits made after the push are still local
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "add b").unwrap();
+    let amended = s
+        .amend_commit(&repo_path, "main", Some("Add b.txt"), false, false)
+        .unwrap();
+    assert_eq!(
+        s.get_commit_subject(&repo_path, &amended).unwrap(),
+        "Add b.txt"
+    );
+
+    // Forcing rewrites even pushed history
+    s.reset_worktree_to_commit(&repo_path, &pushed, true)
+        .unwrap();
+    let forced = s
+        .amend_commit(&repo_path, "main", Some("reworded"), false, true)
+        .unwrap();
+    assert_eq!(
+        s.get_commit_subject(&repo_path, &forced).unwrap(),
+        "reworded"
+    );
+}
+
+#[test]
+fn amend_commit_refuses_commits_of_the_base_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    let base = s.get_head_info(&repo_path).unwrap().oid;
+
+    // The branch has no commits of its own yet, not even when forced
+    for force in [false, true] {
+        match s.amend_commit(&repo_path, "main", Some("reworded"), false, force) {
+            Err(GitServiceError::NothingToAmend(base_branch)) => {
+                assert_eq!(base_branch, "main");
+            }
+            other => panic!("expected NothingToAmend, got {other:?}"),
+        }
+    }
+    assert_eq!(s.get_head_info(&repo_path).unwrap().oid, base);
+
+    // Once the base moves on, its older commits are still refused
+    s.checkout_branch(&repo_path, "main").unwrap();
+    write_file(&repo_path, "b.txt", "b\n");
+    let _ = s.commit(&repo_path, "add b").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    assert!(matches!(
+        s.amend_commit(&repo_path, "main", Some("reworded"), false, false),
+        Err(GitServiceError::NothingToAmend(_))
+    ));
+}
+
+#[test]
+fn rename_branch_moves_unpushed_branch_and_its_worktree() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    s.create_branch(&repo_path, "vk-1a2b-task").unwrap();
+    let wt_path = td.path().join("wt");
+    s.add_worktree(&repo_path, &wt_path, "vk-1a2b-task", false)
+        .unwrap();
+    let tip = s.get_branch_oid(&repo_path, "vk-1a2b-task").unwrap();
+
+    s.rename_branch(&repo_path, "vk-1a2b-task", "fix-login-redirect")
+        .unwrap();
+
+    assert_eq!(
+        s.get_branch_oid(&repo_path, "fix-login-redirect").unwrap(),
+        tip
+    );
+    assert!(s.get_branch_oid(&repo_path, "vk-1a2b-task").is_err());
+    assert_eq!(
+        s.get_current_branch(&wt_path).unwrap(),
+        "fix-login-redirect"
+    );
+}
+
+#[test]
+fn rename_branch_keeps_the_upstream_of_a_pushed_branch() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    s.set_remote(&repo_path, "origin", "https://github.com/foo/bar.git")
+        .unwrap();
+    s.create_branch(&repo_path, "vk-1a2b-task").unwrap();
+    s.checkout_branch(&repo_path, "vk-1a2b-task").unwrap();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    let pushed = s.get_head_info(&repo_path).unwrap().oid;
+
+    // As if pushed: the remote branch points at the commit and is tracked
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    repo.reference(
+        "refs/remotes/origin/vk-1a2b-task",
+        git2::Oid::from_str(&pushed).unwrap(),
+        true,
+        "test push",
+    )
+    .unwrap();
+    repo.find_branch("vk-1a2b-task", git2::BranchType::Local)
+        .unwrap()
+        .set_upstream(Some("origin/vk-1a2b-task"))
+        .unwrap();
+
+    s.rename_branch(&repo_path, "vk-1a2b-task", "fix-login-redirect")
+        .unwrap();
+
+    let renamed = repo
+        .find_branch("fix-login-redirect", git2::BranchType::Local)
+        .unwrap();
+    assert_eq!(
+        renamed.upstream().unwrap().name().unwrap(),
+        Some("origin/vk-1a2b-task")
+    );
+    // The pushed commit is still protected from amending
+    match s.amend_commit(&repo_path, "main", Some("reworded"), false, false) {
+        Err(GitServiceError::CommitAlreadyPushed(remote_branch)) => {
+            assert_eq!(remote_branch, "origin/vk-1a2b-task");
+        }
+        other => panic!("expected CommitAlreadyPushed, got {other:?}"),
+    }
+}
+
+#[test]
+fn rename_branch_rejects_invalid_names_and_collisions() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+    s.create_branch(&repo_path, "feature").unwrap();
+
+    for bad in ["bad..name", "has space", "ends.lock", "-leading-dash", ""] {
+        assert!(
+            matches!(
+                s.rename_branch(&repo_path, "feature", bad),
+                Err(GitServiceError::InvalidBranchName(_))
+            ),
+            "{bad:?} should be rejected"
+        );
+    }
+    assert!(matches!(
+        s.rename_branch(&repo_path, "feature", "main"),
+        Err(GitServiceError::BranchExists(_))
+    ));
+    assert!(s.get_branch_oid(&repo_path, "feature").is_ok());
+}
+
+#[test]
+fn status_vs_remote_default_counts_against_fetched_remote_head() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "a.txt", "a\n");
+    let _ = s.commit(&repo_path, "add a").unwrap();
+
+    // Publish main to a local bare remote
+    let remote_path = td.path().join("remote.git");
+    let bare = git2::Repository::init_bare(&remote_path).unwrap();
+    s.set_remote(&repo_path, "origin", remote_path.to_str().unwrap())
+        .unwrap();
+    let push_main = |path: &Path| {
+        git2::Repository::open(path)
+            .unwrap()
+            .find_remote("origin")
+            .unwrap()
+            .push(&["refs/heads/main:refs/heads/main"], None)
+            .unwrap();
+    };
+    push_main(&repo_path);
+    bare.set_head("refs/heads/main").unwrap();
+
+    // Two commits on the attempt branch
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.checkout_branch(&repo_path, "feature").unwrap();
+    for name in ["f1.txt", "f2.txt"] {
+        write_file(&repo_path, name, "f\n");
+        let _ = s.commit(&repo_path, name).unwrap();
+    }
+
+    // Someone else lands a commit on the remote's main
+    let other_path = td.path().join("other");
+    git2::Repository::clone(remote_path.to_str().unwrap(), &other_path).unwrap();
+    s.configure_user(&other_path, "Other User", "other@example.com")
+        .unwrap();
+    write_file(&other_path, "upstream.txt", "u\n");
+    let _ = s.commit(&other_path, "upstream change").unwrap();
+    push_main(&other_path);
+
+    assert_eq!(
+        s.get_branch_status(&repo_path, "feature", "main").unwrap(),
+        (2, 0)
+    );
+    assert_eq!(
+        s.status_vs_remote_default(&repo_path, "feature", "unused-token")
+            .unwrap(),
+        (2, 1)
+    );
+
+    // A newer remote commit is not seen while the last fetch is still fresh
+    write_file(&other_path, "upstream2.txt", "u\n");
+    let _ = s.commit(&other_path, "another upstream change").unwrap();
+    push_main(&other_path);
+    assert_eq!(
+        s.status_vs_remote_default(&repo_path, "feature", "unused-token")
+            .unwrap(),
+        (2, 1)
+    );
+}
+
+#[test]
+fn excluded_worktree_files_stay_out_of_commits() {
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let s = GitService::new();
+    write_file(&repo_path, "README.md", "hello\n");
+    s.commit(&repo_path, "seed").unwrap();
+
+    let worktree_path = td.path().join("wt_exclude");
+    s.create_branch(&repo_path, "feature").unwrap();
+    s.add_worktree(&repo_path, &worktree_path, "feature", false)
+        .unwrap();
+    write_file(&worktree_path, ".opencode/config.json", "{}\n");
+
+    let config = Path::new(".opencode/config.json");
+    s.exclude_path(&worktree_path, config).unwrap();
+    s.exclude_path(&worktree_path, config).unwrap();
+
+    assert!(!s.commit(&worktree_path, "agent changes").unwrap());
+    let exclude = fs::read_to_string(repo_path.join(".git/info/exclude")).unwrap();
+    assert_eq!(
+        exclude
+            .lines()
+            .filter(|line| *line == "/.opencode/config.json")
+            .count(),
+        1
+    );
+}
@@ -20,6 +20,32 @@ pub struct Diff {
     pub new_path: Option<String>,
     pub old_content: Option<String>,
     pub new_content: Option<String>,
+    /// Lines added; zero for binary or unreadable files
+    pub additions: usize,
+    /// Lines removed; zero for binary or unreadable files
+    pub deletions: usize,
+    /// Rename or copy similarity in percent (100 means unchanged content);
+    /// `None` for other changes
+    pub similarity: Option<u8>,
+}
+
+/// Totals across a set of [`Diff`]s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSummary {
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+impl DiffSummary {
+    pub fn from_diffs(diffs: &[Diff]) -> Self {
+        diffs.iter().fold(Self::default(), |acc, diff| Self {
+            files_changed: acc.files_changed + 1,
+            additions: acc.additions + diff.additions,
+            deletions: acc.deletions + diff.deletions,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -32,15 +58,22 @@ pub enum DiffChangeKind {
     Renamed,
     Copied,
     PermissionChange,
+    WhitespaceChange,
 }
 
 // ==============================
 // Unified diff utility functions
 // ==============================
 
-/// Converts a replace diff to a unified diff hunk without the hunk header.
-/// The hunk returned will have valid hunk, and diff lines.
-pub fn create_unified_diff_hunk(old: &str, new: &str) -> String {
+/// Context lines around each change when the caller has no preference,
+/// matching `git diff`
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
+/// Converts a replace diff to unified diff hunks, keeping `context_lines`
+/// unchanged lines around each change. Changes further apart than twice the
+/// context end up in separate hunks. Returns an empty string when nothing
+/// changed.
+pub fn create_unified_diff_hunk(old: &str, new: &str, context_lines: usize) -> String {
     // normalize ending line feed to optimize diff output
     let mut old = old.to_string();
     let mut new = new.to_string();
@@ -54,33 +87,42 @@ pub fn create_unified_diff_hunk(old: &str, new: &str) -> String {
     let diff = TextDiff::from_lines(&old, &new);
 
     let mut out = String::new();
-
-    // We need a valud hunk header. assume lines are 0. but - + count will be correct.
-
-    let old_count = diff.old_slices().len();
-    let new_count = diff.new_slices().len();
-
-    out.push_str(&format!("@@ -1,{old_count} +1,{new_count} @@\n"));
-
-    for change in diff.iter_all_changes() {
-        let sign = match change.tag() {
-            ChangeTag::Equal => ' ',
-            ChangeTag::Delete => '-',
-            ChangeTag::Insert => '+',
-        };
-        let val = change.value();
-        out.push(sign);
-        out.push_str(val);
+    for hunk in diff
+        .unified_diff()
+        .context_radius(context_lines)
+        .missing_newline_hint(false)
+        .iter_hunks()
+    {
+        out.push_str(&hunk.to_string());
     }
-
     out
 }
 
+/// Counts (added, removed) lines between two versions of a file.
+/// Pass an empty string for the missing side of an added or deleted file.
+pub fn count_line_changes(old: &str, new: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+/// Percentage of lines shared by two versions of a file, as an estimate of
+/// git's rename similarity when the score itself is not available. Only
+/// identical contents score 100.
+pub fn content_similarity(old: &str, new: &str) -> u8 {
+    let score = (TextDiff::from_lines(old, new).ratio() * 100.0).round() as u8;
+    if old == new { score } else { score.min(99) }
+}
+
 /// Creates a full unified diff with the file path in the header.
-pub fn create_unified_diff(file_path: &str, old: &str, new: &str) -> String {
+pub fn create_unified_diff(file_path: &str, old: &str, new: &str, context_lines: usize) -> String {
     let mut out = String::new();
     out.push_str(format!("--- a/{file_path}\n+++ b/{file_path}\n").as_str());
-    out.push_str(&create_unified_diff_hunk(old, new));
+    out.push_str(&create_unified_diff_hunk(old, new, context_lines));
     out
 }
 
@@ -222,3 +264,67 @@ pub fn concatenate_diff_hunks(file_path: &str, hunks: &[String]) -> String {
 
     unified_diff
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_lines(count: usize) -> String {
+        (1..=count).map(|n| format!("line {n}\n")).collect()
+    }
+
+    #[test]
+    fn hunk_keeps_only_the_requested_context() {
+        let old = numbered_lines(10);
+        let new = old.replace("line 5\n", "line five\n");
+
+        assert_eq!(
+            create_unified_diff_hunk(&old, &new, DEFAULT_CONTEXT_LINES),
+            "@@ -2,7 +2,7 @@\n line 2\n line 3\n line 4\n-line 5\n+line five\n line 6\n line 7\n line 8\n"
+        );
+        assert_eq!(
+            create_unified_diff_hunk(&old, &new, 1),
+            "@@ -4,3 +4,3 @@\n line 4\n-line 5\n+line five\n line 6\n"
+        );
+        assert_eq!(
+            create_unified_diff_hunk(&old, &new, 0),
+            "@@ -5 +5 @@\n-line 5\n+line five\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_split_into_hunks_with_small_context() {
+        let old = numbered_lines(20);
+        let new = old
+            .replace("line 3\n", "line three\n")
+            .replace("line 17\n", "line seventeen\n");
+
+        let narrow = create_unified_diff_hunk(&old, &new, 1);
+        assert_eq!(narrow.matches("@@ -").count(), 2);
+        assert!(narrow.starts_with("@@ -2,3 +2,3 @@\n"));
+        assert!(narrow.contains("@@ -16,3 +16,3 @@\n"));
+
+        // Enough context to bridge the gap merges them into one hunk
+        let wide = create_unified_diff_hunk(&old, &new, 7);
+        assert_eq!(wide.matches("@@ -").count(), 1);
+        assert!(wide.starts_with("@@ -1,20 +1,20 @@\n"));
+    }
+
+    #[test]
+    fn unified_diff_has_file_headers_and_tolerates_missing_newline() {
+        let diff = create_unified_diff("src/lib.rs", "a\nb", "a\nc", 0);
+        assert_eq!(
+            diff,
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -2 +2 @@\n-b\n+c\n"
+        );
+        assert_eq!(create_unified_diff_hunk("same\n", "same\n", 3), "");
+    }
+
+    #[test]
+    fn changed_content_is_never_fully_similar() {
+        let old = numbered_lines(500);
+        let new = old.replace("line 250\n", "line 250 changed\n");
+        assert_eq!(content_similarity(&old, &new), 99);
+        assert_eq!(content_similarity(&old, &old), 100);
+    }
+}
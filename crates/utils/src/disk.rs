@@ -0,0 +1,85 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Messages the OS (and tools like git relaying them) use for a full disk
+const STORAGE_FULL_MESSAGES: &[&str] = &[
+    "No space left on device",
+    "There is not enough space on the disk",
+    "Disk quota exceeded",
+];
+
+/// Whether `err` was caused by the filesystem running out of space
+pub fn is_storage_full(err: &io::Error) -> bool {
+    if err.kind() == io::ErrorKind::StorageFull {
+        return true;
+    }
+    match err.raw_os_error() {
+        #[cfg(unix)]
+        Some(code) => code == libc::ENOSPC || code == libc::EDQUOT,
+        // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+        #[cfg(windows)]
+        Some(code) => code == 39 || code == 112,
+        _ => is_storage_full_message(&err.to_string()),
+    }
+}
+
+/// Whether an error message, e.g. git's stderr, reports a full disk
+pub fn is_storage_full_message(message: &str) -> bool {
+    STORAGE_FULL_MESSAGES
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
+/// Bytes available to the current user on the filesystem holding `path`, or the
+/// closest existing ancestor when `path` does not exist yet. `None` when unknown.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let existing: PathBuf = path.ancestors().find(|p| p.exists())?.to_path_buf();
+    available_space_at(&existing)
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // statvfs field widths differ between platforms
+fn available_space_at(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_space_at(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_storage_full() {
+        assert!(is_storage_full(&io::Error::from(
+            io::ErrorKind::StorageFull
+        )));
+        #[cfg(unix)]
+        assert!(is_storage_full(&io::Error::from_raw_os_error(libc::ENOSPC)));
+        assert!(!is_storage_full(&io::Error::from(io::ErrorKind::NotFound)));
+        assert!(is_storage_full_message(
+            "fatal: write error: No space left on device"
+        ));
+        assert!(!is_storage_full_message("fatal: not a git repository"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_available_space_uses_existing_ancestor() {
+        let dir = std::env::temp_dir();
+        assert!(available_space(&dir.join("does/not/exist/yet")).is_some());
+    }
+}
@@ -5,6 +5,7 @@ use directories::ProjectDirs;
 pub mod assets;
 pub mod browser;
 pub mod diff;
+pub mod disk;
 pub mod log_msg;
 pub mod msg_store;
 pub mod path;
@@ -13,6 +14,7 @@ pub mod response;
 pub mod sentry;
 pub mod shell;
 pub mod stream_lines;
+pub mod test_summary;
 pub mod text;
 pub mod version;
 
@@ -8,6 +8,35 @@ pub const EV_JSON_PATCH: &str = "json_patch";
 pub const EV_SESSION_ID: &str = "session_id";
 pub const EV_FINISHED: &str = "finished";
 
+/// Which process output to include when reading raw logs back
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+    #[default]
+    Both,
+}
+
+impl LogStream {
+    /// The output text of `msg` if it belongs to this stream
+    pub fn output<'a>(&self, msg: &'a LogMsg) -> Option<&'a str> {
+        match (self, msg) {
+            (LogStream::Stdout | LogStream::Both, LogMsg::Stdout(s))
+            | (LogStream::Stderr | LogStream::Both, LogMsg::Stderr(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Concatenate the output of `messages` on this stream, in order
+    pub fn collect_output<'a>(&self, messages: impl IntoIterator<Item = &'a LogMsg>) -> String {
+        messages
+            .into_iter()
+            .filter_map(|msg| self.output(msg))
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum LogMsg {
     Stdout(String),
@@ -1,5 +1,5 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     sync::{Arc, RwLock},
 };
 
@@ -8,7 +8,10 @@ use futures::{StreamExt, TryStreamExt, future};
 use tokio::{sync::broadcast, task::JoinHandle};
 use tokio_stream::wrappers::BroadcastStream;
 
-use crate::{log_msg::LogMsg, stream_lines::LinesStreamExt};
+use crate::{
+    log_msg::{LogMsg, LogStream},
+    stream_lines::{LineBufferConfig, LinesStreamExt},
+};
 
 // 100 MB Limit
 const HISTORY_BYTES: usize = 100000 * 1024;
@@ -24,9 +27,29 @@ struct Inner {
     total_bytes: usize,
 }
 
+/// Complete text of command outputs that the conversation only holds
+/// truncated, by tool call id. Call ids are only unique within a run, so each
+/// execution's store keeps its own.
+#[derive(Clone, Default)]
+pub struct FullOutputs(Arc<RwLock<HashMap<String, String>>>);
+
+impl FullOutputs {
+    pub fn insert(&self, call_id: &str, output: &str) {
+        self.0
+            .write()
+            .unwrap()
+            .insert(call_id.to_string(), output.to_string());
+    }
+
+    pub fn get(&self, call_id: &str) -> Option<String> {
+        self.0.read().unwrap().get(call_id).cloned()
+    }
+}
+
 pub struct MsgStore {
     inner: RwLock<Inner>,
     sender: broadcast::Sender<LogMsg>,
+    full_outputs: FullOutputs,
 }
 
 impl Default for MsgStore {
@@ -44,6 +67,7 @@ impl MsgStore {
                 total_bytes: 0,
             }),
             sender,
+            full_outputs: FullOutputs::default(),
         }
     }
 
@@ -74,6 +98,11 @@ impl MsgStore {
         self.push(LogMsg::JsonPatch(patch));
     }
 
+    /// Send `patch` to live listeners only, without keeping it in the history
+    pub fn broadcast_patch(&self, patch: json_patch::Patch) {
+        let _ = self.sender.send(LogMsg::JsonPatch(patch));
+    }
+
     pub fn push_session_id(&self, session_id: String) {
         self.push(LogMsg::SessionId(session_id));
     }
@@ -95,6 +124,17 @@ impl MsgStore {
             .collect()
     }
 
+    /// Full outputs of the commands this execution ran; a handle that outlives
+    /// the store
+    pub fn full_outputs(&self) -> FullOutputs {
+        self.full_outputs.clone()
+    }
+
+    /// Raw output recorded so far on `stream`, concatenated in order
+    pub fn raw_output(&self, stream: LogStream) -> String {
+        stream.collect_output(self.inner.read().unwrap().history.iter().map(|s| &s.msg))
+    }
+
     /// History then live, as `LogMsg`.
     pub fn history_plus_stream(
         &self,
@@ -122,10 +162,19 @@ impl MsgStore {
             .boxed()
     }
 
+    /// Lines of stdout, split according to [`LineBufferConfig::EXECUTOR_OUTPUT`]
     pub fn stdout_lines_stream(
         &self,
     ) -> futures::stream::BoxStream<'static, std::io::Result<String>> {
-        self.stdout_chunked_stream().lines()
+        self.stdout_lines_stream_with_config(LineBufferConfig::EXECUTOR_OUTPUT)
+    }
+
+    /// Like [`Self::stdout_lines_stream`], but splits lines according to `config`.
+    pub fn stdout_lines_stream_with_config(
+        &self,
+        config: LineBufferConfig,
+    ) -> futures::stream::BoxStream<'static, std::io::Result<String>> {
+        self.stdout_chunked_stream().lines_with_config(config)
     }
 
     pub fn stderr_chunked_stream(
@@ -148,6 +197,15 @@ impl MsgStore {
         self.stderr_chunked_stream().lines()
     }
 
+    /// Like [`Self::stderr_lines_stream`], but surfaces long or stalled partial
+    /// lines according to `config`.
+    pub fn stderr_lines_stream_with_config(
+        &self,
+        config: LineBufferConfig,
+    ) -> futures::stream::BoxStream<'static, std::io::Result<String>> {
+        self.stderr_chunked_stream().lines_with_config(config)
+    }
+
     /// Same stream but mapped to `Event` for SSE handlers.
     pub fn sse_stream(&self) -> futures::stream::BoxStream<'static, Result<Event, std::io::Error>> {
         self.history_plus_stream()
@@ -173,3 +231,59 @@ impl MsgStore {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_output_filters_by_stream_in_order() {
+        let store = MsgStore::new();
+        store.push_stdout("compiling\n");
+        store.push_stderr("warning: unused\n");
+        store.push_session_id("session".to_string());
+        store.push_stdout("done\n");
+        store.push_stderr("error: boom\n");
+        store.push_finished();
+
+        assert_eq!(store.raw_output(LogStream::Stdout), "compiling\ndone\n");
+        assert_eq!(
+            store.raw_output(LogStream::Stderr),
+            "warning: unused\nerror: boom\n"
+        );
+        assert_eq!(
+            store.raw_output(LogStream::Both),
+            "compiling\nwarning: unused\ndone\nerror: boom\n"
+        );
+    }
+
+    #[test]
+    fn broadcast_patches_are_not_replayed() {
+        let store = MsgStore::new();
+        let mut live = store.get_receiver();
+        let patch: json_patch::Patch =
+            serde_json::from_value(serde_json::json!([{"op": "add", "path": "/a", "value": 1}]))
+                .unwrap();
+
+        store.broadcast_patch(patch.clone());
+
+        assert!(matches!(live.try_recv(), Ok(LogMsg::JsonPatch(p)) if p == patch));
+        assert!(store.get_history().is_empty());
+    }
+
+    #[tokio::test]
+    async fn stdout_lines_flush_a_long_unterminated_line() {
+        let store = MsgStore::new();
+        let long_line = "x".repeat(100_000);
+        store.push_stdout(long_line.clone());
+
+        // Nothing finishes the store, so only the flush timeout surfaces the line
+        let mut lines = store.stdout_lines_stream();
+        let line = tokio::time::timeout(std::time::Duration::from_secs(10), lines.next())
+            .await
+            .expect("partial stdout line was not flushed")
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, long_line);
+    }
+}
@@ -1,11 +1,41 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 /// Directory name for storing images in worktrees
 pub const VIBE_IMAGES_DIR: &str = ".vibe-images";
 
+/// Whether reported paths use `/` separators on Windows; mirrors the user config
+static FORWARD_SLASH_PATHS: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable reporting paths with forward slashes on Windows
+pub fn set_forward_slash_paths(enabled: bool) {
+    FORWARD_SLASH_PATHS.store(enabled, Ordering::Relaxed);
+}
+
+/// Path as reported to the frontend, with `\` replaced by `/` on Windows when
+/// forward-slash paths are enabled. Other platforms keep the path unchanged,
+/// since `\` is a valid file name character there.
+pub fn to_display_path(path: &str) -> String {
+    normalize_separators(path, FORWARD_SLASH_PATHS.load(Ordering::Relaxed))
+}
+
+fn normalize_separators(path: &str, forward_slashes: bool) -> String {
+    if cfg!(windows) && forward_slashes {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    }
+}
+
 /// Convert absolute paths to relative paths based on worktree path
 /// This is a robust implementation that handles symlinks and edge cases
 pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
+    to_display_path(&relative_native_path(path, worktree_path))
+}
+
+fn relative_native_path(path: &str, worktree_path: &str) -> String {
     let path_obj = Path::new(path);
     let worktree_path_obj = Path::new(worktree_path);
 
@@ -28,16 +58,12 @@ pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
         return result;
     }
 
-    if !path_obj.exists() || !worktree_path_obj.exists() {
-        return path.to_string();
-    }
-
-    // canonicalize may fail if paths don't exist
-    let canonical_path = std::fs::canonicalize(&path_obj);
-    let canonical_worktree = std::fs::canonicalize(&worktree_path_obj);
+    // Resolve symlinks on both sides, e.g. /var -> /private/var for macOS temp dirs
+    let canonical_path = canonicalize_existing_prefix(&path_obj);
+    let canonical_worktree = canonicalize_existing_prefix(&worktree_path_obj);
 
     match (canonical_path, canonical_worktree) {
-        (Ok(canon_path), Ok(canon_worktree)) => {
+        (Some(canon_path), Some(canon_worktree)) => {
             tracing::debug!(
                 "Trying canonical path resolution: '{}' -> '{}', '{}' -> '{}'",
                 path,
@@ -72,7 +98,7 @@ pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
         }
         _ => {
             tracing::debug!(
-                "Could not canonicalize paths (paths may not exist): '{}', '{}', returning original",
+                "Could not canonicalize paths: '{}', '{}', returning original",
                 path,
                 worktree_path
             );
@@ -81,6 +107,26 @@ pub fn make_path_relative(path: &str, worktree_path: &str) -> String {
     }
 }
 
+/// Canonicalize the deepest existing ancestor of `path` and re-append the
+/// remaining components, so files that were deleted or not yet created still
+/// resolve through symlinked parent directories. `None` if no ancestor resolves.
+fn canonicalize_existing_prefix(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = path;
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(current) {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)),
+            );
+        }
+        missing.push(current.file_name()?);
+        current = current.parent()?;
+    }
+}
+
 /// Normalize macOS prefix /private/var/ and /private/tmp/ to their public aliases without resolving paths.
 /// This allows prefix normalization to work when the full paths don't exist.
 fn normalize_macos_private_alias<P: AsRef<Path>>(p: P) -> PathBuf {
@@ -153,6 +199,73 @@ mod tests {
         );
     }
 
+    #[cfg(windows)]
+    #[test]
+    fn test_make_path_relative_uses_forward_slashes_on_windows() {
+        assert_eq!(
+            make_path_relative(r"C:\work\tree\src\main.rs", r"C:\work\tree"),
+            "src/main.rs"
+        );
+        assert_eq!(
+            make_path_relative(r"src\nested/lib.rs", r"C:\work\tree"),
+            "src/nested/lib.rs"
+        );
+        assert_eq!(
+            make_path_relative(r"D:\other\file.js", r"C:\work\tree"),
+            "D:/other/file.js"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_normalize_separators_can_be_disabled_on_windows() {
+        assert_eq!(normalize_separators(r"src\main.rs", true), "src/main.rs");
+        assert_eq!(normalize_separators(r"src\main.rs", false), r"src\main.rs");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_normalize_separators_keeps_backslashes_elsewhere() {
+        assert_eq!(normalize_separators(r"odd\name.rs", true), r"odd\name.rs");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_make_path_relative_through_symlinked_root() {
+        // Mirror macOS, where /var is a symlink to /private/var: the worktree is
+        // reported through the link while agents report the resolved path
+        let root = std::env::temp_dir().join(format!("vk-path-test-{}", uuid::Uuid::new_v4()));
+        let real = root.join("private").join("var");
+        let worktree_real = real.join("worktrees").join("vk-test");
+        std::fs::create_dir_all(worktree_real.join("src")).unwrap();
+        let link = root.join("var");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let worktree_link = link.join("worktrees").join("vk-test");
+
+        let existing = worktree_real.join("src").join("main.rs");
+        std::fs::write(&existing, "fn main() {}").unwrap();
+        assert_eq!(
+            make_path_relative(existing.to_str().unwrap(), worktree_link.to_str().unwrap()),
+            "src/main.rs"
+        );
+
+        // Files that no longer exist resolve through their parent directory
+        let deleted = worktree_link.join("src").join("deleted.rs");
+        assert_eq!(
+            make_path_relative(deleted.to_str().unwrap(), worktree_real.to_str().unwrap()),
+            "src/deleted.rs"
+        );
+
+        // Paths outside the worktree keep their original form
+        let outside = root.join("elsewhere.rs");
+        assert_eq!(
+            make_path_relative(outside.to_str().unwrap(), worktree_link.to_str().unwrap()),
+            outside.to_str().unwrap()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
     #[cfg(target_os = "macos")]
     #[test]
     fn test_make_path_relative_macos_private_alias() {
@@ -29,6 +29,15 @@ impl<T, E> ApiResponse<T, E> {
             error_data: None,
         }
     }
+    /// Creates an error response, with `message` and arbitrary `error_data`.
+    pub fn error_with_message_and_data(message: &str, data: E) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            error_data: Some(data),
+            message: Some(message.to_string()),
+        }
+    }
     /// Creates an error response, with no `data`, no `message`, but with arbitrary `error_data`.
     pub fn error_with_data(data: E) -> Self {
         ApiResponse {
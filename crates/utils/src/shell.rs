@@ -1,16 +1,66 @@
 //! Cross-platform shell command utilities
 
-/// Returns the appropriate shell command and argument for the current platform.
+use std::{path::Path, sync::RwLock};
+
+/// Shell chosen in the config, used in place of the platform default
+static SHELL_OVERRIDE: RwLock<Option<ShellOverride>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ShellOverride {
+    program: String,
+    args: Vec<String>,
+}
+
+/// Run scripts and agents with `program` instead of the platform default shell.
+/// `args` go before the command string; when empty, the platform's usual flag
+/// (`-c`, or `/C` on Windows) is used. `None` or a blank program restores the
+/// default. Warns if the program cannot be found, but applies it regardless.
+pub fn set_shell_override(program: Option<&str>, args: &[String]) {
+    let shell = program
+        .map(str::trim)
+        .filter(|program| !program.is_empty())
+        .map(|program| {
+            if !shell_exists(program) {
+                tracing::warn!(
+                    "Configured shell `{}` was not found; scripts may fail to start",
+                    program
+                );
+            }
+            ShellOverride {
+                program: program.to_string(),
+                args: args.to_vec(),
+            }
+        });
+    *SHELL_OVERRIDE.write().unwrap() = shell;
+}
+
+/// Returns the shell program and the arguments that precede the command string.
 ///
-/// Returns (shell_program, shell_arg) where:
-/// - Windows: ("cmd", "/C")
-/// - Unix-like: ("sh", "-c") or ("bash", "-c") if available
-pub fn get_shell_command() -> (&'static str, &'static str) {
+/// Uses the configured override (see [`set_shell_override`]) when there is one,
+/// otherwise the platform default:
+/// - Windows: ("cmd", ["/C"])
+/// - Unix-like: ("sh", ["-c"]) or ("bash", ["-c"]) if available
+pub fn get_shell_command() -> (String, Vec<String>) {
+    resolve_shell_command(SHELL_OVERRIDE.read().unwrap().as_ref())
+}
+
+fn resolve_shell_command(shell: Option<&ShellOverride>) -> (String, Vec<String>) {
+    let (default_program, default_arg) = default_shell_command();
+    match shell {
+        Some(shell) if shell.args.is_empty() => {
+            (shell.program.clone(), vec![default_arg.to_string()])
+        }
+        Some(shell) => (shell.program.clone(), shell.args.clone()),
+        None => (default_program.to_string(), vec![default_arg.to_string()]),
+    }
+}
+
+fn default_shell_command() -> (&'static str, &'static str) {
     if cfg!(windows) {
         ("cmd", "/C")
     } else {
         // Prefer bash if available, fallback to sh
-        if std::path::Path::new("/bin/bash").exists() {
+        if Path::new("/bin/bash").exists() {
             ("bash", "-c")
         } else {
             ("sh", "-c")
@@ -18,6 +68,55 @@ pub fn get_shell_command() -> (&'static str, &'static str) {
     }
 }
 
+/// Quote `arg` so the platform shell passes it to the program as one argument.
+/// Arguments made only of characters no shell treats specially are returned
+/// as is. Meant for arguments already free of command separators and
+/// substitutions; on Windows they must also be free of `%`, which `cmd`
+/// expands even inside quotes.
+pub fn quote_shell_arg(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_=.,/:@+".contains(c));
+    if plain {
+        arg.to_string()
+    } else if cfg!(windows) {
+        quote_windows_arg(arg)
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Quote `arg` the way the Microsoft C runtime splits a command line: quotes
+/// are escaped with a backslash, and backslashes only need doubling where
+/// they precede a quote
+fn quote_windows_arg(arg: &str) -> String {
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let escapes = if c == '"' {
+            backslashes * 2 + 1
+        } else {
+            backslashes
+        };
+        quoted.extend(std::iter::repeat_n('\\', escapes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Whether `program` is an existing path or an executable on PATH
+fn shell_exists(program: &str) -> bool {
+    Path::new(program).is_file() || resolve_executable_path(program).is_some()
+}
+
 /// Resolves the full path of an executable using the system's PATH environment variable.
 /// Note: On Windows, resolving the executable path can be necessary before passing
 /// it to `std::process::Command::new`, as the latter has been deficient in finding executables.
@@ -26,3 +125,65 @@ pub fn resolve_executable_path(executable: &str) -> Option<String> {
         .ok()
         .map(|p| p.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(program: &str, args: &[&str]) -> ShellOverride {
+        ShellOverride {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn override_is_used_when_set() {
+        assert_eq!(
+            resolve_shell_command(Some(&shell("/usr/bin/zsh", &["-l", "-c"]))),
+            ("/usr/bin/zsh".to_string(), vec!["-l".into(), "-c".into()])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_default_and_override_without_args() {
+        let (program, args) = resolve_shell_command(None);
+        assert!(program == "bash" || program == "sh", "{program}");
+        assert_eq!(args, ["-c"]);
+
+        let (program, args) = resolve_shell_command(Some(&shell("fish", &[])));
+        assert_eq!((program.as_str(), args), ("fish", vec!["-c".to_string()]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn quote_shell_arg_keeps_plain_args_and_single_quotes_the_rest() {
+        assert_eq!(quote_shell_arg("--model=sonnet"), "--model=sonnet");
+        assert_eq!(quote_shell_arg("two words"), "'two words'");
+        assert_eq!(quote_shell_arg("it's"), "'it'\\''s'");
+        assert_eq!(quote_shell_arg(""), "''");
+    }
+
+    #[test]
+    fn windows_args_are_quoted_for_the_c_runtime() {
+        assert_eq!(quote_windows_arg("two words"), r#""two words""#);
+        assert_eq!(quote_windows_arg(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote_windows_arg(r"C:\dir\"), r#""C:\dir\\""#);
+        assert_eq!(quote_windows_arg(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(quote_windows_arg(""), r#""""#);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_default_and_override_without_args() {
+        assert_eq!(
+            resolve_shell_command(None),
+            ("cmd".to_string(), vec!["/C".to_string()])
+        );
+
+        let custom = r"C:\tools\cmd.exe";
+        let (program, args) = resolve_shell_command(Some(&shell(custom, &[])));
+        assert_eq!((program.as_str(), args), (custom, vec!["/C".to_string()]));
+    }
+}
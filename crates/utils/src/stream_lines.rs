@@ -1,14 +1,45 @@
+use std::{collections::VecDeque, time::Duration};
+
 use bytes::Bytes;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt, stream::BoxStream};
 use tokio_util::{
     codec::{FramedRead, LinesCodec},
     io::StreamReader,
 };
 
+/// Controls how a chunked stream is split into lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineBufferConfig {
+    /// Emit the buffered text as a line once it reaches this many bytes, even
+    /// without a newline.
+    pub max_line_length: Option<usize>,
+    /// Emit a partial line if no new output arrives within this duration.
+    pub flush_timeout: Option<Duration>,
+}
+
+impl LineBufferConfig {
+    /// Default for agent output: caps how much of a line is held back and
+    /// surfaces a line that stalls before its newline.
+    pub const EXECUTOR_OUTPUT: Self = Self {
+        max_line_length: Some(1024 * 1024),
+        flush_timeout: Some(Duration::from_secs(2)),
+    };
+
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length.max(1));
+        self
+    }
+
+    pub fn with_flush_timeout(mut self, flush_timeout: Duration) -> Self {
+        self.flush_timeout = Some(flush_timeout);
+        self
+    }
+}
+
 /// Extension trait for converting chunked string streams to line streams.
 pub trait LinesStreamExt: Stream<Item = Result<String, std::io::Error>> + Sized {
     /// Convert a chunked string stream to a line stream.
-    fn lines(self) -> futures::stream::BoxStream<'static, std::io::Result<String>>
+    fn lines(self) -> BoxStream<'static, std::io::Result<String>>
     where
         Self: Send + 'static,
     {
@@ -17,6 +48,150 @@ pub trait LinesStreamExt: Stream<Item = Result<String, std::io::Error>> + Sized
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
             .boxed()
     }
+
+    /// Convert a chunked string stream to a line stream, surfacing partial
+    /// lines according to `config`.
+    fn lines_with_config(
+        self,
+        config: LineBufferConfig,
+    ) -> BoxStream<'static, std::io::Result<String>>
+    where
+        Self: Send + 'static,
+    {
+        if config == LineBufferConfig::default() {
+            return self.lines();
+        }
+
+        let state = LineBuffer {
+            inner: self.boxed(),
+            config,
+            buffer: String::new(),
+            ready: VecDeque::new(),
+            finished: false,
+        };
+
+        futures::stream::unfold(state, |mut state| async move {
+            state.next_line().await.map(|item| (item, state))
+        })
+        .boxed()
+    }
 }
 
 impl<S> LinesStreamExt for S where S: Stream<Item = Result<String, std::io::Error>> {}
+
+struct LineBuffer {
+    inner: BoxStream<'static, std::io::Result<String>>,
+    config: LineBufferConfig,
+    buffer: String,
+    ready: VecDeque<String>,
+    finished: bool,
+}
+
+impl LineBuffer {
+    async fn next_line(&mut self) -> Option<std::io::Result<String>> {
+        loop {
+            if let Some(line) = self.ready.pop_front() {
+                return Some(Ok(line));
+            }
+            if self.finished {
+                return (!self.buffer.is_empty()).then(|| Ok(self.take_partial()));
+            }
+
+            let next = match self.config.flush_timeout {
+                Some(timeout) if !self.buffer.is_empty() => {
+                    match tokio::time::timeout(timeout, self.inner.next()).await {
+                        Ok(next) => next,
+                        Err(_) => return Some(Ok(self.take_partial())),
+                    }
+                }
+                _ => self.inner.next().await,
+            };
+
+            match next {
+                Some(Ok(chunk)) => self.push_chunk(&chunk),
+                Some(Err(e)) => return Some(Err(e)),
+                None => self.finished = true,
+            }
+        }
+    }
+
+    fn push_chunk(&mut self, chunk: &str) {
+        self.buffer.push_str(chunk);
+
+        while let Some(pos) = self.buffer.find('\n') {
+            let mut line: String = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            self.ready.push_back(line);
+        }
+
+        if let Some(max) = self.config.max_line_length {
+            while self.buffer.len() >= max {
+                let split = floor_char_boundary(&self.buffer, max);
+                self.ready.push_back(self.buffer.drain(..split).collect());
+            }
+        }
+    }
+
+    fn take_partial(&mut self) -> String {
+        let mut line = std::mem::take(&mut self.buffer);
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        line
+    }
+}
+
+/// Largest char boundary <= `index`, or the end of the first character when
+/// `index` falls inside it.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    match (1..=index).rev().find(|&i| s.is_char_boundary(i)) {
+        Some(i) => i,
+        None => s.chars().next().map_or(0, char::len_utf8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flushes_long_partial_line_after_timeout() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<std::io::Result<String>>();
+        let chunks = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+        let long_line = "x".repeat(10_000);
+        tx.send(Ok(long_line.clone())).unwrap();
+
+        let mut lines = chunks.lines_with_config(
+            LineBufferConfig::default().with_flush_timeout(Duration::from_millis(50)),
+        );
+
+        // The sender is still open and no newline was written, so the line
+        // only surfaces because of the flush timeout.
+        let line = tokio::time::timeout(Duration::from_secs(5), lines.next())
+            .await
+            .expect("partial line was not flushed")
+            .unwrap()
+            .unwrap();
+        assert_eq!(line, long_line);
+
+        tx.send(Ok("tail\r\nnext".to_string())).unwrap();
+        drop(tx);
+        let rest: Vec<String> = lines.map(|l| l.unwrap()).collect().await;
+        assert_eq!(rest, vec!["tail".to_string(), "next".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn splits_unterminated_text_at_max_length() {
+        let chunks =
+            futures::stream::iter(vec![Ok("done\nabcde".to_string()), Ok("éé".to_string())]);
+        let lines: Vec<String> = chunks
+            .lines_with_config(LineBufferConfig::default().with_max_line_length(2))
+            .map(|l| l.unwrap())
+            .collect()
+            .await;
+        assert_eq!(lines, vec!["done", "ab", "cd", "e", "é", "é"]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fmt, sync::OnceLock};
+
+use regex::Regex;
+
+/// Pass/fail counts pulled from the summary lines of a test run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TestSummary {
+    pub passed: u32,
+    pub failed: u32,
+}
+
+impl fmt::Display for TestSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} passed, {} failed", self.passed, self.failed)
+    }
+}
+
+fn summary_line_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // cargo: "test result: ok. 3 passed; 0 failed; ..."
+    // jest/vitest: "Tests:  1 failed, 4 passed, 5 total"
+    // pytest: "===== 1 failed, 4 passed in 0.12s ====="
+    RE.get_or_init(|| {
+        Regex::new(r"^(?:test result:|Tests:|=+ .* in [\d.]+m?s\b)").expect("valid regex")
+    })
+}
+
+fn count_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(\d+) (passed|failed)").expect("valid regex"))
+}
+
+/// Parse the summary lines printed by common test runners (cargo, pytest,
+/// jest/vitest) and sum their counts. Returns `None` when no summary line is found.
+pub fn parse_test_summary(output: &str) -> Option<TestSummary> {
+    let mut summary: Option<TestSummary> = None;
+    for line in output.lines().map(str::trim) {
+        if !summary_line_re().is_match(line) {
+            continue;
+        }
+        let totals = summary.get_or_insert_with(TestSummary::default);
+        for caps in count_re().captures_iter(line) {
+            let n: u32 = caps[1].parse().unwrap_or(0);
+            match &caps[2] {
+                "passed" => totals.passed += n,
+                _ => totals.failed += n,
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_cargo_test_results() {
+        let out = "running 3 tests\n\
+                   test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured\n\
+                   running 2 tests\n\
+                   test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured\n";
+        assert_eq!(
+            parse_test_summary(out),
+            Some(TestSummary {
+                passed: 4,
+                failed: 1
+            })
+        );
+    }
+
+    #[test]
+    fn parses_pytest_and_jest_summaries() {
+        let pytest = "tests/test_a.py ..F\n========= 1 failed, 2 passed in 0.31s =========";
+        assert_eq!(
+            parse_test_summary(pytest),
+            Some(TestSummary {
+                passed: 2,
+                failed: 1
+            })
+        );
+
+        let jest = "Test Suites: 1 passed, 1 total\nTests:       5 passed, 5 total\n";
+        assert_eq!(
+            parse_test_summary(jest),
+            Some(TestSummary {
+                passed: 5,
+                failed: 0
+            })
+        );
+    }
+
+    #[test]
+    fn returns_none_without_summary_line() {
+        assert_eq!(parse_test_summary("eslint: 2 problems found"), None);
+    }
+}
@@ -22,3 +22,51 @@ pub fn short_uuid(u: &Uuid) -> String {
     let full = u.simple().to_string();
     full.chars().take(4).collect() // grab the first 4 chars
 }
+
+/// Fill `{name}` placeholders in `template` from `values`. A known placeholder
+/// without a value renders empty; unknown placeholders are left as written.
+pub fn render_template(template: &str, values: &[(&str, Option<&str>)]) -> String {
+    let re = Regex::new(r"\{([A-Za-z0-9_]+)\}").unwrap();
+    re.replace_all(template, |caps: &regex::Captures| {
+        let name = &caps[1];
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => value.unwrap_or_default().to_string(),
+            None => {
+                tracing::debug!("Leaving unknown template placeholder {{{}}} as is", name);
+                caps[0].to_string()
+            }
+        }
+    })
+    .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_template_fills_all_placeholders() {
+        let rendered = render_template(
+            "{task_title} ({task_id}) on {branch} by {attempt_id}",
+            &[
+                ("task_title", Some("Fix login")),
+                ("task_id", Some("1234")),
+                ("attempt_id", Some("abcd")),
+                ("branch", Some("vk/1234-fix-login")),
+            ],
+        );
+        assert_eq!(rendered, "Fix login (1234) on vk/1234-fix-login by abcd");
+    }
+
+    #[test]
+    fn render_template_handles_missing_and_unknown_values() {
+        let rendered = render_template(
+            "{task_title}: {task_description} {unknown}",
+            &[
+                ("task_title", Some("Fix login")),
+                ("task_description", None),
+            ],
+        );
+        assert_eq!(rendered, "Fix login:  {unknown}");
+    }
+}
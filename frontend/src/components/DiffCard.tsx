@@ -37,6 +37,8 @@ function labelAndIcon(diff: Diff) {
   if (c === 'copied') return { label: 'Copied', Icon: Copy };
   if (c === 'permissionChange')
     return { label: 'Permission Changed', Icon: Key };
+  if (c === 'whitespaceChange')
+    return { label: 'Whitespace Only', Icon: PencilLine };
   return { label: undefined as string | undefined, Icon: PencilLine };
 }
 
@@ -18,7 +18,8 @@ type ExecutorType =
   | 'CURSOR'
   | 'OPENCODE'
   | 'QWEN_CODE'
-  | 'WARP_CLI';
+  | 'WARP_CLI'
+  | 'AIDER';
 
 interface ExecutorConfigFormProps {
   executor: ExecutorType;
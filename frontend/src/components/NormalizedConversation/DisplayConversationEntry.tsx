@@ -1,6 +1,10 @@
+import { useState } from 'react';
 import MarkdownRenderer from '@/components/ui/markdown-renderer.tsx';
+import { Button } from '@/components/ui/button';
+import { executionProcessesApi } from '@/lib/api';
 import {
   ActionType,
+  ApprovalDecision,
   NormalizedEntry,
   type NormalizedEntryType,
 } from 'shared/types.ts';
@@ -10,6 +14,7 @@ import { renderJson } from './ToolDetails';
 import { useExpandable } from '@/stores/useExpandableStore';
 import {
   AlertCircle,
+  AlertTriangle,
   Bot,
   Brain,
   CheckSquare,
@@ -21,6 +26,7 @@ import {
   Plus,
   Search,
   Settings,
+  ShieldQuestion,
   Terminal,
   User,
 } from 'lucide-react';
@@ -30,6 +36,8 @@ type Props = {
   entry: NormalizedEntry | ProcessStartPayload;
   expansionKey: string;
   diffDeletable?: boolean;
+  // Needed to answer pending approval requests
+  executionProcessId?: string;
 };
 
 type FileEditAction = Extract<ActionType, { action: 'file_edit' }>;
@@ -51,6 +59,12 @@ const getEntryIcon = (entryType: NormalizedEntryType) => {
   if (entryType.type === 'error_message') {
     return <AlertCircle className={iconSize} />;
   }
+  if (entryType.type === 'warning') {
+    return <AlertTriangle className={iconSize} />;
+  }
+  if (entryType.type === 'pending_approval') {
+    return <ShieldQuestion className={iconSize} />;
+  }
   if (entryType.type === 'tool_use') {
     const { action_type, tool_name } = entryType;
 
@@ -130,6 +144,7 @@ const getStatusIndicator = (entryType: NormalizedEntryType) => {
 const shouldRenderMarkdown = (entryType: NormalizedEntryType) =>
   entryType.type === 'assistant_message' ||
   entryType.type === 'system_message' ||
+  entryType.type === 'pending_approval' ||
   entryType.type === 'thinking' ||
   entryType.type === 'tool_use';
 
@@ -145,6 +160,9 @@ const getContentClassName = (entryType: NormalizedEntryType) => {
   if (entryType.type === 'error_message')
     return `${base} font-mono text-destructive`;
 
+  if (entryType.type === 'warning')
+    return `${base} font-mono text-amber-700 dark:text-amber-300`;
+
   if (entryType.type === 'thinking') return `${base} opacity-60`;
 
   if (
@@ -170,7 +188,7 @@ const getContentClassName = (entryType: NormalizedEntryType) => {
  * Unified card      *
  *********************/
 
-type CardVariant = 'system' | 'error';
+type CardVariant = 'system' | 'warning' | 'error';
 
 const MessageCard: React.FC<{
   children: React.ReactNode;
@@ -181,14 +199,20 @@ const MessageCard: React.FC<{
   const frameBase =
     'border px-3 py-2 w-full cursor-pointer  bg-[hsl(var(--card))] border-[hsl(var(--border))]';
   const systemTheme = 'border-400/40 text-zinc-500';
+  const warningTheme =
+    'border-amber-400/40 bg-amber-50 dark:bg-[hsl(var(--card))] text-[hsl(var(--foreground))]';
   const errorTheme =
     'border-red-400/40 bg-red-50 dark:bg-[hsl(var(--card))] text-[hsl(var(--foreground))]';
+  const theme =
+    variant === 'system'
+      ? systemTheme
+      : variant === 'warning'
+        ? warningTheme
+        : errorTheme;
 
   return (
     <div
-      className={`${frameBase} ${
-        variant === 'system' ? systemTheme : errorTheme
-      }`}
+      className={`${frameBase} ${theme}`}
       onClick={onToggle}
     >
       <div className="flex items-center gap-1.5">
@@ -209,7 +233,7 @@ const MessageCard: React.FC<{
  * Collapsible container *
  ************************/
 
-type CollapsibleVariant = 'system' | 'error';
+type CollapsibleVariant = 'system' | 'warning' | 'error';
 
 const ExpandChevron: React.FC<{
   expanded: boolean;
@@ -219,7 +243,9 @@ const ExpandChevron: React.FC<{
   const color =
     variant === 'system'
       ? 'text-700 dark:text-300'
-      : 'text-red-700 dark:text-red-300';
+      : variant === 'warning'
+        ? 'text-amber-700 dark:text-amber-300'
+        : 'text-red-700 dark:text-red-300';
 
   return (
     <ChevronDown
@@ -333,9 +359,19 @@ const ToolCallCard: React.FC<{
   expansionKey: string;
   content?: string;
   entryContent?: string;
-}> = ({ entryType, action, expansionKey, content, entryContent }) => {
+  // Needed to fetch command output that was truncated
+  executionProcessId?: string;
+}> = ({
+  entryType,
+  action,
+  expansionKey,
+  content,
+  entryContent,
+  executionProcessId,
+}) => {
   const at: any = entryType?.action_type || action;
   const [expanded, toggle] = useExpandable(`tool-entry:${expansionKey}`, false);
+  const [fullOutput, setFullOutput] = useState<string | null>(null);
 
   const label =
     at?.action === 'command_run'
@@ -351,7 +387,26 @@ const ToolCallCard: React.FC<{
   const hasArgs = at?.action === 'tool' && !!at?.arguments;
   const hasResult = at?.action === 'tool' && !!at?.result;
 
-  const output: string | null = isCommand ? (at?.result?.output ?? null) : null;
+  const output: string | null = isCommand
+    ? (fullOutput ?? at?.result?.output ?? null)
+    : null;
+  const fullOutputId: string | null = isCommand
+    ? (at?.result?.full_output_id ?? null)
+    : null;
+
+  const loadFullOutput = async () => {
+    if (!executionProcessId || !fullOutputId) return;
+    try {
+      setFullOutput(
+        await executionProcessesApi.getToolOutput(
+          executionProcessId,
+          fullOutputId
+        )
+      );
+    } catch (err) {
+      console.error('Failed to load full command output:', err);
+    }
+  };
   let argsText: string | null = null;
   if (isCommand) {
     const fromArgs =
@@ -424,6 +479,17 @@ const ToolCallCard: React.FC<{
                   <div className="px-2 py-1">
                     <RawLogText content={output} />
                   </div>
+                  {fullOutputId && executionProcessId && !fullOutput && (
+                    <div className="px-2 py-1">
+                      <Button
+                        size="sm"
+                        variant="outline"
+                        onClick={loadFullOutput}
+                      >
+                        Show full output
+                      </Button>
+                    </div>
+                  )}
                 </>
               )}
             </>
@@ -464,7 +530,64 @@ const ToolCallCard: React.FC<{
  * Main component  *
  *******************/
 
-function DisplayConversationEntry({ entry, expansionKey }: Props) {
+const PendingApprovalCard: React.FC<{
+  content: string;
+  callId: string;
+  executionProcessId?: string;
+}> = ({ content, callId, executionProcessId }) => {
+  const [decision, setDecision] = useState<ApprovalDecision | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const decide = async (choice: ApprovalDecision) => {
+    if (!executionProcessId) return;
+    setError(null);
+    try {
+      await executionProcessesApi.approve(executionProcessId, {
+        call_id: callId,
+        decision: choice,
+      });
+      setDecision(choice);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to send decision');
+    }
+  };
+
+  return (
+    <div className="border px-3 py-2 w-full bg-[hsl(var(--card))] border-amber-400/60">
+      <MarkdownRenderer
+        content={content}
+        className="whitespace-pre-wrap break-words flex flex-col gap-1 font-light"
+      />
+      {decision ? (
+        <div className="mt-2 text-xs text-muted-foreground">
+          {decision === 'approved' ? 'Approved' : 'Denied'}
+        </div>
+      ) : (
+        executionProcessId && (
+          <div className="mt-2 flex gap-2">
+            <Button size="sm" onClick={() => decide('approved')}>
+              Approve
+            </Button>
+            <Button
+              size="sm"
+              variant="outline"
+              onClick={() => decide('denied')}
+            >
+              Deny
+            </Button>
+          </div>
+        )
+      )}
+      {error && <div className="mt-2 text-xs text-destructive">{error}</div>}
+    </div>
+  );
+};
+
+function DisplayConversationEntry({
+  entry,
+  expansionKey,
+  executionProcessId,
+}: Props) {
   const isNormalizedEntry = (
     entry: NormalizedEntry | ProcessStartPayload
   ): entry is NormalizedEntry => 'entry_type' in entry;
@@ -487,18 +610,25 @@ function DisplayConversationEntry({ entry, expansionKey }: Props) {
   // Handle NormalizedEntry
   const entryType = entry.entry_type;
   const isSystem = entryType.type === 'system_message';
+  const isWarning = entryType.type === 'warning';
   const isError = entryType.type === 'error_message';
   const isToolUse = entryType.type === 'tool_use';
   const isFileEdit = (a: ActionType): a is FileEditAction =>
     a.action === 'file_edit';
   return (
     <>
-      {isSystem || isError ? (
+      {entryType.type === 'pending_approval' ? (
+        <PendingApprovalCard
+          content={isNormalizedEntry(entry) ? entry.content : ''}
+          callId={entryType.call_id}
+          executionProcessId={executionProcessId}
+        />
+      ) : isSystem || isWarning || isError ? (
         <CollapsibleEntry
           content={isNormalizedEntry(entry) ? entry.content : ''}
           markdown={shouldRenderMarkdown(entryType)}
           expansionKey={expansionKey}
-          variant={isSystem ? 'system' : 'error'}
+          variant={isSystem ? 'system' : isWarning ? 'warning' : 'error'}
           contentClassName={getContentClassName(entryType)}
         />
       ) : isToolUse && isFileEdit(entryType.action_type) ? (
@@ -524,6 +654,7 @@ function DisplayConversationEntry({ entry, expansionKey }: Props) {
           entryType={entryType}
           expansionKey={expansionKey}
           entryContent={isNormalizedEntry(entry) ? entry.content : ''}
+          executionProcessId={executionProcessId}
         />
       ) : (
         <div className={getContentClassName(entryType)}>
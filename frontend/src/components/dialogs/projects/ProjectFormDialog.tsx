@@ -87,6 +87,14 @@ export const ProjectFormDialog = NiceModal.create<ProjectFormDialogProps>(
           dev_script: null,
           cleanup_script: null,
           copy_files: null,
+          require_clean_before_followup: null,
+          commit_setup_changes: null,
+          auto_assign_users: null,
+          verify_command: null,
+          keep_in_progress_on_verify_failure: null,
+          default_base_branch: null,
+          mcp_servers: null,
+          executor_profile: null,
         };
 
         await projectsApi.create(createData);
@@ -126,6 +134,14 @@ export const ProjectFormDialog = NiceModal.create<ProjectFormDialogProps>(
             dev_script: devScript.trim() || null,
             cleanup_script: cleanupScript.trim() || null,
             copy_files: copyFiles.trim() || null,
+            require_clean_before_followup: null,
+            commit_setup_changes: null,
+            auto_assign_users: null,
+            verify_command: null,
+            keep_in_progress_on_verify_failure: null,
+            default_base_branch: null,
+            mcp_servers: null,
+            executor_profile: null,
           };
 
           await projectsApi.update(project!.id, updateData);
@@ -139,6 +155,14 @@ export const ProjectFormDialog = NiceModal.create<ProjectFormDialogProps>(
             dev_script: null,
             cleanup_script: null,
             copy_files: null,
+            require_clean_before_followup: null,
+            commit_setup_changes: null,
+            auto_assign_users: null,
+            verify_command: null,
+            keep_in_progress_on_verify_failure: null,
+            default_base_branch: null,
+            mcp_servers: null,
+            executor_profile: null,
           };
 
           await projectsApi.create(createData);
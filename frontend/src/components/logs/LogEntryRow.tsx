@@ -29,6 +29,7 @@ function LogEntryRow({ entry, index }: LogEntryRowProps) {
             entry={entry.payload as NormalizedEntry}
             expansionKey={`${entry.processId}:${index}`}
             diffDeletable={false}
+            executionProcessId={entry.processId}
           />
         </div>
       );
@@ -48,6 +48,8 @@ function ProcessStartCard({
         return 'Setup Script';
       case PROCESS_RUN_REASONS.CLEANUP_SCRIPT:
         return 'Cleanup Script';
+      case PROCESS_RUN_REASONS.VERIFY_SCRIPT:
+        return 'Verify Script';
       case PROCESS_RUN_REASONS.DEV_SERVER:
         return 'Dev Server';
       default:
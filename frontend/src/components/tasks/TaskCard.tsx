@@ -13,10 +13,13 @@ import {
   Edit,
   Loader2,
   MoreHorizontal,
+  Pin,
+  PinOff,
   Trash2,
   XCircle,
 } from 'lucide-react';
 import type { TaskWithAttemptStatus } from 'shared/types';
+import { tasksApi } from '@/lib/api';
 
 type Task = TaskWithAttemptStatus;
 
@@ -66,6 +69,15 @@ export function TaskCard({
     onViewDetails(task);
   }, [task, onViewDetails]);
 
+  const handleTogglePin = useCallback(async () => {
+    try {
+      // The task stream picks up the new order
+      await tasksApi.togglePin(task.id);
+    } catch (error) {
+      console.error('Failed to toggle task pin:', error);
+    }
+  }, [task.id]);
+
   return (
     <KanbanCard
       key={task.id}
@@ -83,6 +95,8 @@ export function TaskCard({
           {task.title}
         </h4>
         <div className="flex items-center space-x-1">
+          {/* Pinned Indicator */}
+          {task.is_pinned && <Pin className="h-3 w-3 text-muted-foreground" />}
           {/* In Progress Spinner */}
           {task.has_in_progress_attempt && (
             <Loader2 className="h-3 w-3 animate-spin text-blue-500" />
@@ -117,6 +131,14 @@ export function TaskCard({
                   <Edit className="h-4 w-4 mr-2" />
                   Edit
                 </DropdownMenuItem>
+                <DropdownMenuItem onClick={handleTogglePin}>
+                  {task.is_pinned ? (
+                    <PinOff className="h-4 w-4 mr-2" />
+                  ) : (
+                    <Pin className="h-4 w-4 mr-2" />
+                  )}
+                  {task.is_pinned ? 'Unpin' : 'Pin'}
+                </DropdownMenuItem>
                 {onDuplicate && (
                   <DropdownMenuItem onClick={() => onDuplicate(task)}>
                     <Copy className="h-4 w-4 mr-2" />
@@ -33,6 +33,7 @@ function DiffTab({ selectedAttempt }: DiffTabProps) {
       'renamed',
       'copied',
       'permissionChange',
+      'whitespaceChange',
     ]);
     const initial = new Set(
       diffs
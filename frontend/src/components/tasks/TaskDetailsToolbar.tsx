@@ -15,6 +15,7 @@ import { useTaskStopping } from '@/stores/useTaskDetailsUiStore';
 import CreateAttempt from '@/components/tasks/Toolbar/CreateAttempt.tsx';
 import CurrentAttempt from '@/components/tasks/Toolbar/CurrentAttempt.tsx';
 import { useUserSystem } from '@/components/config-provider';
+import { useProject } from '@/contexts/project-context';
 import { Card } from '../ui/card';
 
 // UI State Management
@@ -97,6 +98,7 @@ function TaskDetailsToolbar({
     useState<ExecutorProfileId | null>(null);
   // const { attemptId: urlAttemptId } = useParams<{ attemptId?: string }>();
   const { system, profiles } = useUserSystem();
+  const { project } = useProject();
 
   // Memoize latest attempt calculation
   const latestAttempt = useMemo(() => {
@@ -141,12 +143,9 @@ function TaskDetailsToolbar({
     fetchProjectBranches();
   }, [fetchProjectBranches]);
 
-  // Set default executor from config
-  useEffect(() => {
-    if (system.config?.executor_profile) {
-      setSelectedProfile(system.config.executor_profile);
-    }
-  }, [system.config?.executor_profile]);
+  // Shown until a profile is picked; the server resolves the same default
+  const defaultProfile =
+    project?.executor_profile ?? system.config?.executor_profile ?? null;
 
   // Simplified - hooks handle data fetching and navigation
   // const fetchTaskAttempts = useCallback(() => {
@@ -212,6 +211,7 @@ function TaskDetailsToolbar({
             createAttemptBranch={createAttemptBranch}
             selectedBranch={selectedBranch}
             selectedProfile={selectedProfile}
+            defaultProfile={defaultProfile}
             taskAttempts={taskAttempts}
             branches={branches}
             setCreateAttemptBranch={setCreateAttemptBranch}
@@ -24,6 +24,7 @@ type Props = {
   taskAttempts: TaskAttempt[];
   createAttemptBranch: string | null;
   selectedProfile: ExecutorProfileId | null;
+  defaultProfile: ExecutorProfileId | null;
   selectedBranch: string | null;
   setIsInCreateAttemptMode: Dispatch<SetStateAction<boolean>>;
   setCreateAttemptBranch: Dispatch<SetStateAction<string | null>>;
@@ -38,6 +39,7 @@ function CreateAttempt({
   taskAttempts,
   createAttemptBranch,
   selectedProfile,
+  defaultProfile,
   selectedBranch,
   setIsInCreateAttemptMode,
   setCreateAttemptBranch,
@@ -47,10 +49,13 @@ function CreateAttempt({
 }: Props) {
   const { isAttemptRunning } = useAttemptExecution(selectedAttempt?.id);
   const { createAttempt, isCreating } = useAttemptCreation(task.id);
+  // Only a profile the user picked is sent; otherwise the server applies the
+  // project's default, then the configured one
+  const shownProfile = selectedProfile ?? defaultProfile;
 
   // Create attempt logic
   const actuallyCreateAttempt = useCallback(
-    async (profile: ExecutorProfileId, baseBranch?: string) => {
+    async (profile: ExecutorProfileId | null, baseBranch?: string) => {
       const effectiveBaseBranch = baseBranch || selectedBranch;
 
       if (!effectiveBaseBranch) {
@@ -68,7 +73,7 @@ function CreateAttempt({
   // Handler for Enter key or Start button
   const onCreateNewAttempt = useCallback(
     async (
-      profile: ExecutorProfileId,
+      profile: ExecutorProfileId | null,
       baseBranch?: string,
       isKeyTriggered?: boolean
     ) => {
@@ -101,7 +106,7 @@ function CreateAttempt({
   // Keyboard shortcuts
   useKeyboardShortcuts({
     onEnter: () => {
-      if (!selectedProfile) {
+      if (!shownProfile) {
         return;
       }
       onCreateNewAttempt(
@@ -119,7 +124,7 @@ function CreateAttempt({
   };
 
   const handleCreateAttempt = () => {
-    if (!selectedProfile) {
+    if (!shownProfile) {
       return;
     }
     onCreateNewAttempt(selectedProfile, createAttemptBranch || undefined);
@@ -184,7 +189,7 @@ function CreateAttempt({
                     <div className="flex items-center gap-1.5">
                       <Settings2 className="h-3 w-3" />
                       <span className="truncate">
-                        {selectedProfile?.executor || 'Select profile'}
+                        {shownProfile?.executor || 'Select profile'}
                       </span>
                     </div>
                     <ArrowDown className="h-3 w-3" />
@@ -204,7 +209,7 @@ function CreateAttempt({
                             });
                           }}
                           className={
-                            selectedProfile?.executor === profileKey
+                            shownProfile?.executor === profileKey
                               ? 'bg-accent'
                               : ''
                           }
@@ -226,7 +231,7 @@ function CreateAttempt({
             </div>
             {(() => {
               const currentProfile =
-                availableProfiles?.[selectedProfile?.executor || ''];
+                availableProfiles?.[shownProfile?.executor || ''];
               const hasVariants =
                 currentProfile && Object.keys(currentProfile).length > 0;
 
@@ -240,7 +245,7 @@ function CreateAttempt({
                         className="w-full px-2 flex items-center justify-between text-xs"
                       >
                         <span className="truncate flex-1 text-left">
-                          {selectedProfile?.variant || 'DEFAULT'}
+                          {shownProfile?.variant || 'DEFAULT'}
                         </span>
                         <ArrowDown className="h-3 w-3 ml-1 flex-shrink-0" />
                       </Button>
@@ -250,15 +255,15 @@ function CreateAttempt({
                         <DropdownMenuItem
                           key={variantLabel}
                           onClick={() => {
-                            if (selectedProfile) {
+                            if (shownProfile) {
                               setSelectedProfile({
-                                ...selectedProfile,
+                                ...shownProfile,
                                 variant: variantLabel,
                               });
                             }
                           }}
                           className={
-                            selectedProfile?.variant === variantLabel
+                            shownProfile?.variant === variantLabel
                               ? 'bg-accent'
                               : ''
                           }
@@ -300,7 +305,7 @@ function CreateAttempt({
             <Button
               onClick={handleCreateAttempt}
               disabled={
-                !selectedProfile ||
+                !shownProfile ||
                 !createAttemptBranch ||
                 isAttemptRunning ||
                 isCreating
@@ -312,7 +317,7 @@ function CreateAttempt({
               title={
                 !createAttemptBranch
                   ? 'Base branch is required'
-                  : !selectedProfile
+                  : !shownProfile
                     ? 'Coding agent is required'
                     : undefined
               }
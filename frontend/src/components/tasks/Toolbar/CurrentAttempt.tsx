@@ -477,6 +477,17 @@ function CurrentAttempt({
         </div>
       </div>
 
+      {selectedAttempt.summary && (
+        <div>
+          <div className="text-xs font-medium text-muted-foreground uppercase tracking-wide mb-1">
+            Summary
+          </div>
+          <p className="text-sm whitespace-pre-wrap line-clamp-4">
+            {selectedAttempt.summary}
+          </p>
+        </div>
+      )}
+
       <div>
         <div className="flex items-center gap-1.5 mb-1">
           <div className="text-xs font-medium text-muted-foreground uppercase tracking-wide mb-1 pt-1">
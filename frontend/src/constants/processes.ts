@@ -8,12 +8,14 @@ import type {
 export const PROCESS_RUN_REASONS = {
   SETUP_SCRIPT: 'setupscript' as ExecutionProcessRunReason,
   CLEANUP_SCRIPT: 'cleanupscript' as ExecutionProcessRunReason,
+  VERIFY_SCRIPT: 'verifyscript' as ExecutionProcessRunReason,
   CODING_AGENT: 'codingagent' as ExecutionProcessRunReason,
   DEV_SERVER: 'devserver' as ExecutionProcessRunReason,
 } as const;
 
 // Process statuses
 export const PROCESS_STATUSES = {
+  QUEUED: 'queued' as ExecutionProcessStatus,
   RUNNING: 'running' as ExecutionProcessStatus,
   COMPLETED: 'completed' as ExecutionProcessStatus,
   FAILED: 'failed' as ExecutionProcessStatus,
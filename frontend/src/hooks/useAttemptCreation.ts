@@ -14,13 +14,16 @@ export function useAttemptCreation(taskId: string) {
       profile,
       baseBranch,
     }: {
-      profile: ExecutorProfileId;
+      profile: ExecutorProfileId | null;
       baseBranch: string;
     }) =>
       attemptsApi.create({
         task_id: taskId,
         executor_profile_id: profile,
         base_branch: baseBranch,
+        env_overrides: null,
+        dry_run: null,
+        working_subdir: null,
       }),
     onSuccess: (newAttempt: TaskAttempt) => {
       // Optimistically add to cache to prevent UI flicker
@@ -1,5 +1,6 @@
 import { useCallback } from 'react';
 import type { ProcessStartPayload } from '@/types/logs';
+import type { TokenUsage } from 'shared/types';
 import type { Operation } from 'rfc6902';
 import { useJsonPatchStream } from './useJsonPatchStream';
 
@@ -9,10 +10,12 @@ interface ProcessConversationData {
   executor_type: string;
   prompt: string | null;
   summary: string | null;
+  token_usage: TokenUsage | null;
 }
 
 interface UseProcessConversationResult {
   entries: any[]; // Mixed types like the original
+  tokenUsage: TokenUsage | null;
   isConnected: boolean;
   error: string | null;
 }
@@ -32,6 +35,7 @@ export const useProcessConversation = (
       executor_type: '',
       prompt: null,
       summary: null,
+      token_usage: null,
     }),
     []
   );
@@ -87,6 +91,7 @@ export const useProcessConversation = (
   );
 
   const entries = data?.entries || [];
+  const tokenUsage = data?.token_usage ?? null;
 
-  return { entries, isConnected, error };
+  return { entries, tokenUsage, isConnected, error };
 };
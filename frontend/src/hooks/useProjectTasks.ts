@@ -35,11 +35,17 @@ export const useProjectTasks = (
   );
 
   const tasksById = data?.tasks ?? {};
-  const tasks = Object.values(tasksById).sort(
-    (a, b) =>
+  // Pinned tasks first in pin order, then newest first
+  const tasks = Object.values(tasksById).sort((a, b) => {
+    if (a.is_pinned !== b.is_pinned) return a.is_pinned ? -1 : 1;
+    if (a.is_pinned && a.pin_order !== b.pin_order) {
+      return (a.pin_order ?? 0) - (b.pin_order ?? 0);
+    }
+    return (
       new Date(b.created_at as unknown as string).getTime() -
       new Date(a.created_at as unknown as string).getTime()
-  );
+    );
+  });
   const isLoading = !data && !error; // until first snapshot
 
   return { tasks, tasksById, isLoading, isConnected, error };
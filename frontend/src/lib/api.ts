@@ -1,11 +1,15 @@
 // Import all necessary types from shared types
 
 import {
+  AddTaskDependency,
   ApiResponse,
+  ApproveExecutionRequest,
+  BaseBranchStatus,
   BranchStatus,
   CheckTokenResponse,
   Config,
   CommitInfo,
+  ConflictedFile,
   CreateFollowUpAttempt,
   CreateGitHubPrRequest,
   CreateTask,
@@ -17,14 +21,26 @@ import {
   DirectoryEntry,
   EditorType,
   ExecutionProcess,
+  FuzzyFileMatch,
   GitBranch,
+  GitOperation,
+  MergeOutcome,
+  MergePreview,
+  MergeStrategy,
+  NormalizedEntry,
   Project,
+  ProjectExport,
+  ProjectImportSummary,
   CreateProject,
   RebaseTaskAttemptRequest,
+  RenameBranchRequest,
+  ReopenTask,
+  RepoHealth,
   RepositoryInfo,
   SearchResult,
   Task,
   TaskAttempt,
+  TaskAttemptComparison,
   TaskTemplate,
   TaskWithAttemptStatus,
   UpdateProject,
@@ -38,6 +54,7 @@ import {
   ImageResponse,
   RestoreAttemptRequest,
   RestoreAttemptResult,
+  WorktreeFileStatus,
 } from 'shared/types';
 
 // Re-export types for convenience
@@ -90,12 +107,15 @@ const handleApiResponseAsResult = async <T, E>(
   if (!response.ok) {
     // HTTP error - no structured error data
     let errorMessage = `Request failed with status ${response.status}`;
+    let errorData: E | undefined;
 
     try {
-      const errorData = await response.json();
-      if (errorData.message) {
-        errorMessage = errorData.message;
+      const body = await response.json();
+      if (body.message) {
+        errorMessage = body.message;
       }
+      // Structured errors, e.g. the files of a merge conflict
+      errorData = body.error_data ?? undefined;
     } catch {
       errorMessage = response.statusText || errorMessage;
     }
@@ -123,12 +143,15 @@ const handleApiResponseAsResult = async <T, E>(
 const handleApiResponse = async <T, E = T>(response: Response): Promise<T> => {
   if (!response.ok) {
     let errorMessage = `Request failed with status ${response.status}`;
+    let errorData: E | undefined;
 
     try {
-      const errorData = await response.json();
-      if (errorData.message) {
-        errorMessage = errorData.message;
+      const body = await response.json();
+      if (body.message) {
+        errorMessage = body.message;
       }
+      // Structured errors, e.g. the files of a merge conflict
+      errorData = body.error_data ?? undefined;
     } catch {
       // Fallback to status text if JSON parsing fails
       errorMessage = response.statusText || errorMessage;
@@ -141,7 +164,12 @@ const handleApiResponse = async <T, E = T>(response: Response): Promise<T> => {
       endpoint: response.url,
       timestamp: new Date().toISOString(),
     });
-    throw new ApiError<E>(errorMessage, response.status, response);
+    throw new ApiError<E>(
+      errorMessage,
+      response.status,
+      response,
+      errorData
+    );
   }
 
   const result: ApiResponse<T, E> = await response.json();
@@ -218,6 +246,22 @@ export const projectsApi = {
     return handleApiResponse<void>(response);
   },
 
+  exportBoard: async (id: string): Promise<ProjectExport> => {
+    const response = await makeRequest(`/api/projects/${id}/export`);
+    return handleApiResponse<ProjectExport>(response);
+  },
+
+  importBoard: async (
+    id: string,
+    data: ProjectExport
+  ): Promise<ProjectImportSummary> => {
+    const response = await makeRequest(`/api/projects/${id}/import`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ProjectImportSummary>(response);
+  },
+
   openEditor: async (id: string, editorType?: EditorType): Promise<void> => {
     const requestBody: any = {};
     if (editorType) requestBody.editor_type = editorType;
@@ -293,6 +337,62 @@ export const tasksApi = {
     });
     return handleApiResponse<void>(response);
   },
+
+  togglePin: async (taskId: string): Promise<Task> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/pin`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Task>(response);
+  },
+
+  compareAttempts: async (
+    taskId: string,
+    attemptA: string,
+    attemptB: string
+  ): Promise<TaskAttemptComparison> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/compare?a=${attemptA}&b=${attemptB}`
+    );
+    return handleApiResponse<TaskAttemptComparison>(response);
+  },
+
+  getDependencies: async (taskId: string): Promise<Task[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`);
+    return handleApiResponse<Task[]>(response);
+  },
+
+  addDependency: async (
+    taskId: string,
+    data: AddTaskDependency
+  ): Promise<Task[]> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/dependencies`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<Task[]>(response);
+  },
+
+  removeDependency: async (
+    taskId: string,
+    dependsOnTaskId: string
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/tasks/${taskId}/dependencies/${dependsOnTaskId}`,
+      { method: 'DELETE' }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  reopen: async (
+    taskId: string,
+    data: ReopenTask
+  ): Promise<ExecutionProcess> => {
+    const response = await makeRequest(`/api/tasks/${taskId}/reopen`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+    return handleApiResponse<ExecutionProcess>(response);
+  },
 };
 
 // Task Attempts APIs
@@ -324,6 +424,16 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  retry: async (attemptId: string): Promise<ExecutionProcess> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/retry`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<ExecutionProcess>(response);
+  },
+
   restore: async (
     attemptId: string,
     processId: string,
@@ -358,6 +468,16 @@ export const attemptsApi = {
     return handleApiResponse<void>(response);
   },
 
+  searchFiles: async (
+    attemptId: string,
+    query: string
+  ): Promise<FuzzyFileMatch[]> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/files?q=${encodeURIComponent(query)}`
+    );
+    return handleApiResponse<FuzzyFileMatch[]>(response);
+  },
+
   deleteFile: async (
     attemptId: string,
     fileToDelete: string
@@ -401,14 +521,62 @@ export const attemptsApi = {
     return handleApiResponse<BranchStatus>(response);
   },
 
-  merge: async (attemptId: string): Promise<void> => {
+  getCommits: async (attemptId: string): Promise<CommitInfo[]> => {
     const response = await makeRequest(
-      `/api/task-attempts/${attemptId}/merge`,
+      `/api/task-attempts/${attemptId}/commits`
+    );
+    return handleApiResponse<CommitInfo[]>(response);
+  },
+
+  getBaseStatus: async (attemptId: string): Promise<BaseBranchStatus> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/base-status`
+    );
+    return handleApiResponse<BaseBranchStatus>(response);
+  },
+
+  getWorktreeStatus: async (
+    attemptId: string,
+    includeUntracked = false
+  ): Promise<WorktreeFileStatus> => {
+    const query = includeUntracked ? '?include_untracked=true' : '';
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/status${query}`
+    );
+    return handleApiResponse<WorktreeFileStatus>(response);
+  },
+
+  previewMerge: async (attemptId: string): Promise<MergePreview> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge-preview`
+    );
+    return handleApiResponse<MergePreview>(response);
+  },
+
+  merge: async (
+    attemptId: string,
+    tagName?: string,
+    strategy?: MergeStrategy
+  ): Promise<MergeOutcome> => {
+    const params = new URLSearchParams();
+    if (tagName) params.set('tag_name', tagName);
+    if (strategy) params.set('strategy', strategy);
+    const query = params.toString() ? `?${params}` : '';
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/merge${query}`,
       {
         method: 'POST',
       }
     );
-    return handleApiResponse<void>(response);
+    return handleApiResponse<MergeOutcome, ConflictedFile[]>(response);
+  },
+
+  recreateWorktree: async (attemptId: string): Promise<RepoHealth> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/recreate-worktree`,
+      { method: 'POST' }
+    );
+    return handleApiResponse<RepoHealth>(response);
   },
 
   push: async (attemptId: string): Promise<void> => {
@@ -421,7 +589,7 @@ export const attemptsApi = {
   rebase: async (
     attemptId: string,
     data: RebaseTaskAttemptRequest
-  ): Promise<void> => {
+  ): Promise<string> => {
     const response = await makeRequest(
       `/api/task-attempts/${attemptId}/rebase`,
       {
@@ -429,7 +597,31 @@ export const attemptsApi = {
         body: JSON.stringify(data),
       }
     );
-    return handleApiResponse<void>(response);
+    return handleApiResponse<string, ConflictedFile[]>(response);
+  },
+
+  renameBranch: async (
+    attemptId: string,
+    data: RenameBranchRequest
+  ): Promise<TaskAttempt> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/rename-branch`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<TaskAttempt>(response);
+  },
+
+  abortGitOperation: async (attemptId: string): Promise<GitOperation> => {
+    const response = await makeRequest(
+      `/api/task-attempts/${attemptId}/abort-git-op`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<GitOperation>(response);
   },
 
   createPR: async (
@@ -508,6 +700,40 @@ export const executionProcessesApi = {
     );
     return handleApiResponse<void>(response);
   },
+
+  approve: async (
+    processId: string,
+    data: ApproveExecutionRequest
+  ): Promise<void> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/approve`,
+      {
+        method: 'POST',
+        body: JSON.stringify(data),
+      }
+    );
+    return handleApiResponse<void>(response);
+  },
+
+  renormalize: async (processId: string): Promise<NormalizedEntry[]> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/renormalize`,
+      {
+        method: 'POST',
+      }
+    );
+    return handleApiResponse<NormalizedEntry[]>(response);
+  },
+
+  getToolOutput: async (
+    processId: string,
+    callId: string
+  ): Promise<string> => {
+    const response = await makeRequest(
+      `/api/execution-processes/${processId}/tool-output/${encodeURIComponent(callId)}`
+    );
+    return handleApiResponse<string>(response);
+  },
 };
 
 // File System APIs
@@ -542,6 +768,10 @@ export const configApi = {
     });
     return handleApiResponse<Config>(response);
   },
+  getSchema: async (): Promise<Record<string, unknown>> => {
+    const response = await makeRequest('/api/config/schema');
+    return handleApiResponse<Record<string, unknown>>(response);
+  },
 };
 
 // GitHub Device Auth APIs
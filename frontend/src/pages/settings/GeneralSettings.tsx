@@ -486,6 +486,69 @@ export function GeneralSettings() {
               </p>
             </div>
           </div>
+          <div className="space-y-2">
+            <Label>Notify On</Label>
+            {(
+              [
+                ['task_complete', 'Task complete'],
+                ['execution_failed', 'Execution failed'],
+                ['pr_merged', 'Pull request merged'],
+              ] as const
+            ).map(([event, label]) => (
+              <div key={event} className="flex items-center space-x-2">
+                <Checkbox
+                  id={`notify-${event}`}
+                  checked={config.notifications.events[event]}
+                  onCheckedChange={(checked: boolean) =>
+                    updateConfig({
+                      notifications: {
+                        ...config.notifications,
+                        events: {
+                          ...config.notifications.events,
+                          [event]: checked,
+                        },
+                      },
+                    })
+                  }
+                />
+                <Label htmlFor={`notify-${event}`} className="cursor-pointer">
+                  {label}
+                </Label>
+              </div>
+            ))}
+          </div>
+        </CardContent>
+      </Card>
+
+      <Card>
+        <CardHeader>
+          <CardTitle>Task Attempts</CardTitle>
+          <CardDescription>
+            Configure what is recorded when task attempts finish.
+          </CardDescription>
+        </CardHeader>
+        <CardContent className="space-y-4">
+          <div className="flex items-center space-x-2">
+            <Checkbox
+              id="auto-summarize-attempts"
+              checked={config.auto_summarize_attempts}
+              onCheckedChange={(checked: boolean) =>
+                updateConfig({ auto_summarize_attempts: checked })
+              }
+            />
+            <div className="space-y-0.5">
+              <Label
+                htmlFor="auto-summarize-attempts"
+                className="cursor-pointer"
+              >
+                Save Attempt Summaries
+              </Label>
+              <p className="text-sm text-muted-foreground">
+                Store the agent's final message as a summary on the task
+                attempt when it finishes.
+              </p>
+            </div>
+          </div>
         </CardContent>
       </Card>
 
@@ -8,13 +8,56 @@ export type DirectoryEntry = { name: string, path: string, is_directory: boolean
 
 export type DirectoryListResponse = { entries: Array<DirectoryEntry>, current_path: string, };
 
-export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, created_at: Date, updated_at: Date, };
+export type FuzzyFileMatch = { 
+/**
+ * Path relative to the searched root, with `/` separators
+ */
+path: string, score: bigint, };
 
-export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, current_branch: string | null, created_at: Date, updated_at: Date, };
+export type Project = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, 
+/**
+ * Reject follow-ups while the attempt worktree has uncommitted changes
+ */
+require_clean_before_followup: boolean, 
+/**
+ * Commit worktree changes made by the setup script before the coding agent runs
+ */
+commit_setup_changes: boolean, 
+/**
+ * Comma-separated users that new tasks are assigned to in round-robin order;
+ * unset or empty leaves new tasks unassigned
+ */
+auto_assign_users: string | null, 
+/**
+ * Command run after the coding agent to verify its changes (e.g. tests or lint);
+ * its result is recorded on the attempt
+ */
+verify_command: string | null, 
+/**
+ * Keep the task in progress, needing a fix, instead of moving it to review
+ * when the verify command fails
+ */
+keep_in_progress_on_verify_failure: boolean, 
+/**
+ * Base branch for new attempts that do not specify one; falls back to the
+ * repository's default branch
+ */
+default_base_branch: string | null, 
+/**
+ * Extra MCP servers written into the coding agent's MCP config, next to vibe-kanban
+ */
+mcp_servers: Array<McpServerDefinition> | null, 
+/**
+ * Executor profile for new attempts that do not specify one, overriding the
+ * global default
+ */
+executor_profile: ExecutorProfileId | null, created_at: Date, updated_at: Date, };
+
+export type ProjectWithBranch = { id: string, name: string, git_repo_path: string, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, require_clean_before_followup: boolean, commit_setup_changes: boolean, auto_assign_users: string | null, verify_command: string | null, keep_in_progress_on_verify_failure: boolean, default_base_branch: string | null, mcp_servers: Array<McpServerDefinition> | null, executor_profile: ExecutorProfileId | null, current_branch: string | null, created_at: Date, updated_at: Date, };
 
-export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type CreateProject = { name: string, git_repo_path: string, use_existing_repo: boolean, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, require_clean_before_followup: boolean | null, commit_setup_changes: boolean | null, auto_assign_users: string | null, verify_command: string | null, keep_in_progress_on_verify_failure: boolean | null, default_base_branch: string | null, mcp_servers: Array<McpServerDefinition> | null, executor_profile: ExecutorProfileId | null, };
 
-export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, };
+export type UpdateProject = { name: string | null, git_repo_path: string | null, setup_script: string | null, dev_script: string | null, cleanup_script: string | null, copy_files: string | null, require_clean_before_followup: boolean | null, commit_setup_changes: boolean | null, auto_assign_users: string | null, verify_command: string | null, keep_in_progress_on_verify_failure: boolean | null, default_base_branch: string | null, mcp_servers: Array<McpServerDefinition> | null, executor_profile: ExecutorProfileId | null, };
 
 export type SearchResult = { path: string, is_file: boolean, match_type: SearchMatchType, };
 
@@ -22,21 +65,35 @@ export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
 export type SearchMode = "taskform" | "settings";
 
-export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, };
+export type ExecutorAction = { typ: ExecutorActionType, next_action: ExecutorAction | null, 
+/**
+ * Directory relative to the worktree to run this action in, instead of the
+ * worktree root
+ */
+working_subdir?: string | null, };
 
 export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path: Array<string>, template: JsonValue, vibe_kanban: JsonValue, is_toml_config: boolean, };
 
+/**
+ * An additional MCP server a project registers with its coding agents
+ */
+export type McpServerDefinition = { name: string, command: string, args: Array<string>, env: { [key in string]?: string }, };
+
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest;
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "VerifyScript" | "DevServer";
 
-export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, };
+export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, 
+/**
+ * Log the script instead of running it, so it can be previewed
+ */
+dry_run: boolean, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR = "CURSOR", QWEN_CODE = "QWEN_CODE", WARP_CLI = "WARP_CLI" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR = "CURSOR", QWEN_CODE = "QWEN_CODE", WARP_CLI = "WARP_CLI", AIDER = "AIDER" }
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR": Cursor } | { "QWEN_CODE": QwenCode } | { "WARP_CLI": WarpCli };
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR": Cursor } | { "QWEN_CODE": QwenCode } | { "WARP_CLI": WarpCli } | { "AIDER": Aider };
 
 export type TaskTemplate = { id: string, project_id: string | null, title: string, description: string | null, template_name: string, created_at: string, updated_at: string, };
 
@@ -46,13 +103,37 @@ export type UpdateTaskTemplate = { title: string | null, description: string | n
 
 export type TaskStatus = "todo" | "inprogress" | "inreview" | "done" | "cancelled";
 
-export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, };
+export type Task = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, estimate_minutes: number | null, assignee: string | null, 
+/**
+ * Pinned tasks are listed before the others
+ */
+is_pinned: boolean, 
+/**
+ * Position among the project's pinned tasks, lowest first; unset when unpinned
+ */
+pin_order: number | null, created_at: string, updated_at: string, deleted_at: string | null, };
 
-export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, created_at: string, updated_at: string, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, };
+export type TaskWithAttemptStatus = { id: string, project_id: string, title: string, description: string | null, status: TaskStatus, parent_task_attempt: string | null, estimate_minutes: number | null, assignee: string | null, is_pinned: boolean, pin_order: number | null, 
+/**
+ * Wall-clock minutes spent in the task's setup, agent and cleanup processes
+ */
+actual_minutes: number, created_at: string, updated_at: string, has_in_progress_attempt: boolean, has_merged_attempt: boolean, last_attempt_failed: boolean, executor: string, 
+/**
+ * Tasks that must be done before this one starts
+ */
+dependency_ids: Array<string>, };
 
-export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, image_ids: Array<string> | null, };
+export type CreateTask = { project_id: string, title: string, description: string | null, parent_task_attempt: string | null, image_ids: Array<string> | null, 
+/**
+ * Left unset, the project's auto-assignment policy picks the assignee
+ */
+assignee?: string | null, };
 
-export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, image_ids: Array<string> | null, };
+export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, image_ids: Array<string> | null, estimate_minutes?: number | null, 
+/**
+ * An empty string unassigns the task
+ */
+assignee?: string | null, };
 
 export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
@@ -74,6 +155,32 @@ export type UpdateMcpServersBody = { servers: { [key in string]?: JsonValue }, }
 
 export type GetMcpServerResponse = { mcp_config: McpConfig, config_path: string, };
 
+export type AgentAvailability = { available: boolean, 
+/**
+ * Installed vs recommended CLI version, when the executor supports version checks
+ */
+version: AgentVersionInfo | null, };
+
+export type ExecutorHealth = { 
+/**
+ * Availability and detected version per executor
+ */
+executors: { [key in string]?: AgentAvailability }, checked_at: string, };
+
+export type AgentVersionInfo = { 
+/**
+ * Version reported by the installed CLI, if it could be determined
+ */
+detected: string | null, 
+/**
+ * Minimum version we recommend
+ */
+recommended: string, 
+/**
+ * True when the detected version is older than the recommended one
+ */
+update_recommended: boolean, };
+
 export type CreateFollowUpAttempt = { prompt: string, variant: string | null, image_ids: Array<string> | null, };
 
 export type CreateGitHubPrRequest = { title: string, body: string | null, base_branch: string | null, };
@@ -82,9 +189,85 @@ export type ImageResponse = { id: string, file_path: string, original_name: stri
 
 export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS" }
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, github_login_acknowledged: boolean, 
+/**
+ * The analytics opt-in prompt was answered, with `analytics_enabled`
+ */
+telemetry_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, 
+/**
+ * Unset until the user answers the analytics opt-in prompt, and treated as
+ * disabled until then. `DNT=1` disables analytics whatever the value.
+ */
+analytics_enabled: boolean | null, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, 
+/**
+ * Flag files whose changes are whitespace-only so the diff view can collapse them
+ */
+diff_ignore_whitespace: boolean, 
+/**
+ * Store the agent's final message on the task attempt when it finishes
+ */
+auto_summarize_attempts: boolean, log_retention: LogRetentionConfig, 
+/**
+ * Only warn when an agent moves its worktree off the task branch, instead
+ * of switching back to the task branch
+ */
+allow_agent_branch_switch: boolean, 
+/**
+ * Prune the least recently used worktrees of finished attempts beyond this many
+ */
+max_worktree_count: number | null, 
+/**
+ * Prune worktrees of finished attempts inactive for more than this many days
+ */
+max_worktree_age_days: number | null, 
+/**
+ * Report file paths from agents and diffs with `/` separators on Windows
+ */
+forward_slash_paths: boolean, 
+/**
+ * Kill an execution that produces no output for this many minutes and mark
+ * it failed; unset or 0 disables the timeout
+ */
+execution_timeout_minutes: number | null, 
+/**
+ * Commit message for task merges; supports `{task_title}`,
+ * `{task_description}`, `{task_id}`, `{task_id_short}`, `{attempt_id}` and
+ * `{branch}` placeholders
+ */
+merge_commit_template: string, rate_limit: RateLimitConfig, 
+/**
+ * Shell that runs scripts and agents instead of the platform default
+ * (`bash`/`sh`, or `cmd` on Windows)
+ */
+shell_override: string | null, 
+/**
+ * Arguments passed to `shell_override` before the command; the platform's
+ * usual `-c` or `/C` when empty
+ */
+shell_override_args: Array<string>, 
+/**
+ * Run at most this many execution processes at once and queue the rest
+ * until a slot frees up; unset or 0 runs everything immediately. Dev
+ * servers are not counted
+ */
+max_concurrent_executions: number | null, 
+/**
+ * Keep at most this many bytes of a command's output in the conversation;
+ * the rest can be fetched on demand. 0 keeps all output
+ */
+command_output_limit_bytes: number, 
+/**
+ * Endpoints that are POSTed a signed JSON payload when task events happen
+ */
+webhooks: Array<WebhookConfig>, };
 
-export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
+export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, 
+/**
+ * Which events notify at all; sound and push then apply to each of them
+ */
+events: NotificationEvents, };
+
+export type NotificationEvents = { task_complete: boolean, pr_merged: boolean, execution_failed: boolean, };
 
 export enum ThemeMode { LIGHT = "LIGHT", DARK = "DARK", SYSTEM = "SYSTEM", PURPLE = "PURPLE", GREEN = "GREEN", BLUE = "BLUE", ORANGE = "ORANGE", RED = "RED" }
 
@@ -92,10 +275,61 @@ export type EditorConfig = { editor_type: EditorType, custom_command: string | n
 
 export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WINDSURF", INTELLI_J = "INTELLI_J", ZED = "ZED", XCODE = "XCODE", CUSTOM = "CUSTOM" }
 
-export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
+export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, 
+/**
+ * Extra headers sent with every GitHub API request, e.g. for authenticating proxies
+ */
+extra_headers: { [key in string]?: string }, };
 
 export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
 
+/**
+ * Limits on persisted execution logs; `None` keeps logs indefinitely.
+ */
+export type LogRetentionConfig = { 
+/**
+ * Delete logs of attempts that finished more than this many days ago,
+ * unless the attempt has an open pull request or its task is in review
+ */
+max_age_days: number | null, 
+/**
+ * Delete the oldest logs once the total size exceeds this many megabytes
+ */
+max_total_size_mb: number | null, };
+
+/**
+ * Per-client token bucket applied to API requests; streaming and health
+ * routes are exempt
+ */
+export type RateLimitConfig = { 
+/**
+ * Off by default, since the server usually only answers the local UI
+ */
+enabled: boolean, 
+/**
+ * Sustained request rate each client IP is allowed
+ */
+requests_per_minute: number, 
+/**
+ * Requests a client can make in a quick burst before being limited
+ */
+burst: number, };
+
+/**
+ * Task events a webhook can subscribe to
+ */
+export type WebhookEvent = "task_created" | "task_status_changed" | "pr_merged";
+
+export type WebhookConfig = { url: string, 
+/**
+ * Key for the HMAC-SHA256 signature sent in the `X-Signature` header
+ */
+secret: string, 
+/**
+ * Events delivered to this endpoint
+ */
+events: Array<WebhookEvent>, };
+
 export type DeviceFlowStartResponse = { user_code: string, verification_uri: string, expires_in: number, interval: number, };
 
 export enum DevicePollStatus { SLOW_DOWN = "SLOW_DOWN", AUTHORIZATION_PENDING = "AUTHORIZATION_PENDING", SUCCESS = "SUCCESS" }
@@ -104,9 +338,87 @@ export enum CheckTokenResponse { VALID = "VALID", INVALID = "INVALID" }
 
 export type GitBranch = { name: string, is_current: boolean, is_remote: boolean, last_commit_date: Date, };
 
-export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, };
+/**
+ * A path left conflicted by a merge or rebase, with the content of each side.
+ * A side is `None` when the file does not exist there or is not UTF-8.
+ */
+export type ConflictedFile = { path: string, base: string | null, ours: string | null, theirs: string | null, };
 
-export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
+/**
+ * Where changes auto-stashed for a rebase are, when they could not be restored
+ */
+export type KeptStash = { 
+/**
+ * HEAD commit after the rebase
+ */
+head: string, 
+/**
+ * Commit id of the stash still holding the changes
+ */
+stash: string, };
+
+export type MergeStrategy = "squash" | "fast_forward_if_possible";
+
+export type MergeOutcome = { sha: string, strategy: MergeStrategy, 
+/**
+ * Tag created on `sha`; `None` when none was asked for or tagging failed
+ */
+tag: string | null, };
+
+export type MergePreview = { clean: boolean, 
+/**
+ * Paths that would conflict; empty when `clean`
+ */
+conflicted_files: Array<string>, };
+
+export type WorktreeFileStatus = { 
+/**
+ * Changes in the index that the next commit would include
+ */
+staged: Array<string>, 
+/**
+ * Changes to tracked files that are not staged
+ */
+modified: Array<string>, 
+/**
+ * Only filled in when untracked files were asked for
+ */
+untracked: Array<string>, staged_count: number, modified_count: number, untracked_count: number, };
+
+export type RepoHealth = "ok" | "missing" | "corrupt";
+
+/**
+ * A merge or rebase that stopped partway in a worktree
+ */
+export type GitOperation = "merge" | "rebase";
+
+/**
+ * A commit as listed in an attempt's history
+ */
+export type CommitInfo = { sha: string, 
+/**
+ * First line of the commit message
+ */
+subject: string, author: string, timestamp: Date, };
+
+export type Diff = { change: DiffChangeKind, oldPath: string | null, newPath: string | null, oldContent: string | null, newContent: string | null, 
+/**
+ * Lines added; zero for binary or unreadable files
+ */
+additions: number, 
+/**
+ * Lines removed; zero for binary or unreadable files
+ */
+deletions: number, 
+/**
+ * Rename or copy similarity in percent (100 means unchanged content);
+ * `None` for other changes
+ */
+similarity: number | null, };
+
+export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange" | "whitespaceChange";
+
+export type DiffSummary = { filesChanged: number, additions: number, deletions: number, };
 
 export type FileDiffDetails = { fileName: string | null, content: string | null, };
 
@@ -132,34 +444,60 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR": Cursor } | { "QWEN_CODE": QwenCode } | { "WARP_CLI": WarpCli } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR": Cursor } | { "QWEN_CODE": QwenCode } | { "WARP_CLI": WarpCli } | { "AIDER": Aider } };
 
 export type BaseAgentCapability = "RESTORE_CHECKPOINT";
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, claude_code_router?: boolean | null, plan?: boolean | null, dangerously_skip_permissions?: boolean | null, 
+/**
+ * Environment variables set to the worktree path at spawn, keyed by name with a
+ * subdirectory relative to the worktree as value (empty for the worktree root)
+ */
+working_dir_env?: { [key in string]?: string } | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Gemini = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, model: GeminiModel, yolo?: boolean | null, 
+/**
+ * Request `--output-format stream-json` and parse tool calls from it
+ */
+stream_json?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
 export type GeminiModel = "default" | "flash";
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Amp = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, approval?: ApprovalPolicy | null, oss?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Codex = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, sandbox?: SandboxMode | null, approval?: ApprovalPolicy | null, oss?: boolean | null, model?: string | null, reasoning_effort?: ReasoningEffort | null, 
+/**
+ * Hide background events containing any of these substrings (defaults to retry notices)
+ */
+ignored_background_events?: Array<string> | null, 
+/**
+ * Environment variables set to the worktree path at spawn, keyed by name with a
+ * subdirectory relative to the worktree as value (empty for the worktree root)
+ */
+working_dir_env?: { [key in string]?: string } | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
 export type SandboxMode = "read-only" | "workspace-write" | "danger-full-access";
 
 export type ApprovalPolicy = "untrusted" | "on-failure" | "on-request" | "never";
 
-export type Cursor = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type ReasoningEffort = "low" | "medium" | "high";
+
+export type ApprovalDecision = "approved" | "denied";
 
-export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Cursor = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Opencode = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
-export type WarpCli = { append_prompt: AppendPrompt, profile?: string | null, mcp_servers?: Array<string>, extra_flags?: Array<string>, binary?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type QwenCode = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
+
+export type WarpCli = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, profile?: string | null, mcp_servers?: Array<string>, extra_flags?: Array<string>, binary?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
+
+export type Aider = { append_prompt: AppendPrompt, append_prompt_file: AppendPromptFile, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, raw_additional_params?: boolean | null, detached?: boolean | null, mcp_config_path_override?: string | null, };
 
 export type AppendPrompt = string | null;
 
+export type AppendPromptFile = string | null;
+
 export type CodingAgentInitialRequest = { prompt: string, 
 /**
  * Executor profile specification
@@ -174,11 +512,68 @@ executor_profile_id: ExecutorProfileId, };
 
 export type CreateTaskAttemptBody = { task_id: string, 
 /**
- * Executor profile specification
+ * Defaults to the project's executor profile, then the configured profile
+ */
+executor_profile_id: ExecutorProfileId | null, 
+/**
+ * Defaults to the project's default base branch, then the repository's default branch
+ */
+base_branch: string | null, 
+/**
+ * Environment variables for the attempt's agent and scripts; names must match `[A-Z_][A-Z0-9_]*`
+ */
+env_overrides: { [key in string]?: string } | null, 
+/**
+ * Only log the project's setup script instead of running it and the agent
  */
-executor_profile_id: ExecutorProfileId, base_branch: string, };
+dry_run: boolean | null, 
+/**
+ * Run the coding agent in this directory relative to the worktree, e.g. a
+ * package of a monorepo
+ */
+working_subdir: string | null, };
+
+export type AdoptBranchRequest = { 
+/**
+ * Existing local branch to check out in the attempt worktree
+ */
+branch: string, 
+/**
+ * Branch to compare and merge against; defaults to the project's default base
+ * branch, then the repo's current branch
+ */
+base_branch: string | null, 
+/**
+ * Executor used for follow-ups; defaults to the project's executor profile,
+ * then the configured profile
+ */
+executor_profile_id: ExecutorProfileId | null, };
+
+export type TaskAttemptComparison = { 
+/**
+ * Changes on attempt `b`'s branch relative to attempt `a`'s branch
+ */
+diffs: Array<Diff>, summary: DiffSummary, };
+
+export type AddTaskDependency = { 
+/**
+ * Task that must be done before this one starts
+ */
+depends_on_task_id: string, };
+
+export type ReopenTask = { 
+/**
+ * Follow-up prompt describing what still needs fixing
+ */
+prompt: string, variant: string | null, };
+
+export type RebaseTaskAttemptRequest = { new_base_branch: string | null, 
+/**
+ * Stash uncommitted changes before rebasing and restore them afterwards
+ */
+auto_stash?: boolean | null, };
 
-export type RebaseTaskAttemptRequest = { new_base_branch: string | null, };
+export type RenameBranchRequest = { new_branch_name: string, };
 
 export type RestoreAttemptRequest = { 
 /**
@@ -196,13 +591,56 @@ perform_git_reset: boolean | null, };
 
 export type RestoreAttemptResult = { had_later_processes: boolean, git_reset_needed: boolean, git_reset_applied: boolean, target_after_oid: string | null, };
 
-export type CommitInfo = { sha: string, subject: string, };
-
 export type CommitCompareResult = { head_oid: string, target_oid: string, ahead_from_head: number, behind_from_head: number, is_linear: boolean, };
 
 export type BranchStatus = { commits_behind: number | null, commits_ahead: number | null, has_uncommitted_changes: boolean | null, head_oid: string | null, uncommitted_count: number | null, untracked_count: number | null, base_branch_name: string, remote_commits_behind: number | null, remote_commits_ahead: number | null, merges: Array<Merge>, };
 
-export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string | null, base_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
+export type BaseBranchStatus = { base_branch_name: string, 
+/**
+ * Current tip of the base branch
+ */
+base_oid: string, base_commit_date: string, 
+/**
+ * Base commit the attempt branch forked from, if the branch exists yet
+ */
+fork_point_oid: string | null, 
+/**
+ * Commits the base branch gained since the fork point
+ */
+base_commits_since_fork: number | null, };
+
+export type BranchGraph = { base_branch_name: string, 
+/**
+ * None when the attempt branch and base branch share no history
+ */
+merge_base: string | null, commits_ahead: number, commits_behind: number, 
+/**
+ * Commits on the attempt branch that are not on the base branch, newest first
+ */
+unique_commits: Array<string>, };
+
+export type ProjectExport = { version: number, exported_at: string, project: Project, 
+/**
+ * Oldest first, so re-importing keeps the board order
+ */
+tasks: Array<ExportedTask>, templates: Array<TaskTemplate>, };
+
+export type ExportedTask = { task: Task, attempts: Array<ExportedTaskAttempt>, };
+
+export type ExportedTaskAttempt = { branch: string | null, base_branch: string, executor: string, summary: string | null, created_at: string, };
+
+export type ProjectImportSummary = { tasks_imported: number, templates_imported: number, 
+/**
+ * Templates whose name the project already uses
+ */
+templates_skipped: number, };
+
+export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string | null, base_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, summary: string | null, verify_passed: boolean | null, verify_summary: string | null, 
+/**
+ * Environment variables set for the attempt's coding agent and scripts.
+ * Only their names are serialized, since the values may be secrets.
+ */
+env_override_names: Array<string> | null, created_at: string, updated_at: string, };
 
 export type ExecutionProcess = { id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
 /**
@@ -214,11 +652,22 @@ after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: big
  * history view (due to restore/trimming). Hidden from logs/timeline;
  * still listed in the Processes tab.
  */
-dropped: boolean, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+dropped: boolean, 
+/**
+ * The failed process this one re-runs, when started as a retry
+ */
+retry_of: string | null, 
+/**
+ * Why vibe-kanban failed the process itself rather than it exiting on its
+ * own, e.g. after an inactivity timeout
+ */
+failure_reason: string | null, started_at: string, completed_at: string | null, created_at: string, updated_at: string, };
+
+export type ExecutionProcessStatus = "queued" | "running" | "completed" | "failed" | "killed";
 
-export type ExecutionProcessStatus = "running" | "completed" | "failed" | "killed";
+export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "verifyscript" | "codingagent" | "devserver";
 
-export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
+export type ApproveExecutionRequest = { call_id: string, decision: ApprovalDecision, };
 
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
@@ -234,17 +683,31 @@ export type EventPatch = { op: string, path: string, value: EventPatchInner, };
 
 export type EventPatchInner = { db_op: string, record: RecordTypes, };
 
-export type RecordTypes = { "type": "TASK", "data": Task } | { "type": "TASK_ATTEMPT", "data": TaskAttempt } | { "type": "EXECUTION_PROCESS", "data": ExecutionProcess } | { "type": "DELETED_TASK", "data": { rowid: bigint, project_id: string | null, task_id: string | null, } } | { "type": "DELETED_TASK_ATTEMPT", "data": { rowid: bigint, task_id: string | null, } } | { "type": "DELETED_EXECUTION_PROCESS", "data": { rowid: bigint, task_attempt_id: string | null, } };
+export type RecordTypes = { "type": "TASK", "data": Task } | { "type": "TASK_ATTEMPT", "data": TaskAttempt } | { "type": "EXECUTION_PROCESS", "data": ExecutionProcess } | { "type": "DELETED_TASK", "data": { rowid: bigint, project_id: string | null, task_id: string | null, } } | { "type": "DELETED_TASK_ATTEMPT", "data": { rowid: bigint, task_id: string | null, } } | { "type": "DELETED_EXECUTION_PROCESS", "data": { rowid: bigint, task_attempt_id: string | null, } } | { "type": "DIFF_CHANGED", "data": { task_attempt_id: string, paths: Array<string>, } };
 
 export type CommandExitStatus = { "type": "exit_code", code: number, } | { "type": "success", success: boolean, };
 
-export type CommandRunResult = { exit_status: CommandExitStatus | null, output: string | null, };
+export type CommandRunResult = { exit_status: CommandExitStatus | null, output: string | null, 
+/**
+ * Tool call id to fetch the complete output with when `output` was
+ * truncated
+ */
+full_output_id: string | null, };
 
-export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, };
+export type NormalizedConversation = { entries: Array<NormalizedEntry>, session_id: string | null, executor_type: string, prompt: string | null, summary: string | null, 
+/**
+ * Latest token usage reported by the agent for the current turn
+ */
+token_usage: TokenUsage | null, };
+
+/**
+ * Token counts reported by an agent
+ */
+export type TokenUsage = { input_tokens: bigint, output_tokens: bigint, cached_input_tokens: bigint, total_tokens: bigint, };
 
 export type NormalizedEntry = { timestamp: string | null, entry_type: NormalizedEntryType, content: string, };
 
-export type NormalizedEntryType = { "type": "user_message" } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, } | { "type": "system_message" } | { "type": "error_message" } | { "type": "thinking" };
+export type NormalizedEntryType = { "type": "user_message" } | { "type": "assistant_message" } | { "type": "tool_use", tool_name: string, action_type: ActionType, } | { "type": "system_message" } | { "type": "error_message" } | { "type": "warning" } | { "type": "thinking" } | { "type": "pending_approval", call_id: string, };
 
 export type FileChange = { "action": "write", content: string, } | { "action": "delete" } | { "action": "rename", new_path: string, } | { "action": "edit", 
 /**